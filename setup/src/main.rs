@@ -6,7 +6,7 @@
 
 use anyhow::{anyhow, Context, Result};
 use clap::Parser;
-use symposium_cli_agent_util::{detect_cli_agents, McpServer};
+use symposium_cli_agent_util::{detect_cli_agents, format_command, McpServer};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -25,6 +25,7 @@ Examples:
   cargo setup --mcp --restart          # Build/install MCP servers and restart daemon
   cargo setup --app                    # Build macOS app only
   cargo setup --app --open             # Build macOS app and launch it
+  cargo setup --all --dry-run          # Print what would be run without changing anything
 
 For CI builds, use: cargo ci check / cargo ci test
 
@@ -32,7 +33,7 @@ Prerequisites:
   - Rust and Cargo (https://rustup.rs/)
   - Node.js and npm (for VSCode extension)
   - VSCode with 'code' command available (for development setup)
-  - Q CLI or Claude Code (for MCP server setup)
+  - Q CLI, Claude Code, Cursor, or Windsurf (for MCP server setup)
 "#
 )]
 struct Args {
@@ -59,6 +60,10 @@ struct Args {
     /// Restart MCP daemon after building (requires --mcp)
     #[arg(long)]
     restart: bool,
+
+    /// Print each command that would be run, with its working directory, without running it
+    #[arg(long)]
+    dry_run: bool,
 }
 
 fn main() -> Result<()> {
@@ -85,6 +90,9 @@ fn main() -> Result<()> {
 
     println!("🐚 Symposium Development Setup");
     println!("{}", "=".repeat(35));
+    if args.dry_run {
+        println!("🔍 Dry run: commands are printed, not executed");
+    }
 
     // Check prerequisites based on what we're building
     check_rust()?;
@@ -99,35 +107,44 @@ fn main() -> Result<()> {
     // Build components
     let mut binary_path = None;
     if build_mcp {
-        binary_path = Some(build_and_install_rust_server()?);
-        build_and_install_sparkle_cli()?;
+        binary_path = Some(build_and_install_rust_server(args.dry_run)?);
+        build_and_install_sparkle_cli(args.dry_run)?;
     }
     if build_vscode {
-        build_and_install_extension()?;
+        build_and_install_extension(args.dry_run)?;
     }
 
     if build_app {
-        build_macos_app()?;
+        build_macos_app(args.dry_run)?;
     }
 
     // Post-build actions
     if args.restart && build_mcp {
-        cleanup_existing_daemon()?;
+        cleanup_existing_daemon(args.dry_run)?;
     }
 
     if let Some(ref binary_path) = binary_path {
-        setup_mcp_servers(binary_path)?;
+        setup_mcp_servers(binary_path, args.dry_run)?;
     }
 
-    print_completion_message(build_vscode, build_mcp, build_app)?;
+    if args.dry_run {
+        println!("\n🔍 Dry run complete, nothing was changed");
+    } else {
+        print_completion_message(build_vscode, build_mcp, build_app)?;
+    }
 
     if args.open && build_app {
-        open_macos_app()?;
+        open_macos_app(args.dry_run)?;
     }
 
     Ok(())
 }
 
+/// Print a command that a dry run skips
+fn print_planned(cmd: &Command) {
+    println!("   🔍 Would run: {}", format_command(cmd));
+}
+
 fn show_help() {
     println!("🎭 Symposium Development Setup");
     println!("{}", "=".repeat(35));
@@ -171,13 +188,13 @@ fn check_cli_tools() -> Result<()> {
     
     if agents.is_empty() {
         return Err(anyhow!(
-            "❌ No supported CLI tools found. Please install Q CLI or Claude Code.\n   Q CLI: https://docs.aws.amazon.com/amazonq/latest/qdeveloper-ug/q-cli.html\n   Claude Code: https://claude.ai/code"
+            "❌ No supported CLI tools found. Please install Q CLI, Claude Code, Cursor, or Windsurf.\n   Q CLI: https://docs.aws.amazon.com/amazonq/latest/qdeveloper-ug/q-cli.html\n   Claude Code: https://claude.ai/code"
         ));
     }
     Ok(())
 }
 
-fn setup_mcp_servers(binary_path: &Path) -> Result<()> {
+fn setup_mcp_servers(binary_path: &Path, dry_run: bool) -> Result<()> {
     let agents = detect_cli_agents();
     
     if agents.is_empty() {
@@ -203,6 +220,15 @@ fn setup_mcp_servers(binary_path: &Path) -> Result<()> {
         env: vec![],
     };
 
+    if dry_run {
+        for agent in &agents {
+            println!("🔧 Registering MCP servers with {}...", agent.name());
+            println!("   🔍 Would {}", agent.planned_install(&symposium_server));
+            println!("   🔍 Would {}", agent.planned_install(&sparkle_server));
+        }
+        return Ok(());
+    }
+
     let mut success = true;
     for agent in &agents {
         println!("🔧 Registering Symposium MCP server with {}...", agent.name());
@@ -212,7 +238,7 @@ fn setup_mcp_servers(binary_path: &Path) -> Result<()> {
         match agent.install_mcp(&symposium_server) {
             Ok(result) => success &= result,
             Err(e) => {
-                println!("❌ Failed to setup Symposium MCP for {}: {}", agent.name(), e);
+                println!("❌ Failed to setup Symposium MCP for {}: {:#}", agent.name(), e);
                 success = false;
             }
         }
@@ -223,7 +249,7 @@ fn setup_mcp_servers(binary_path: &Path) -> Result<()> {
         match agent.install_mcp(&sparkle_server) {
             Ok(result) => success &= result,
             Err(e) => {
-                println!("❌ Failed to setup Sparkle MCP for {}: {}", agent.name(), e);
+                println!("❌ Failed to setup Sparkle MCP for {}: {:#}", agent.name(), e);
                 success = false;
             }
         }
@@ -289,19 +315,28 @@ fn get_repo_root() -> Result<PathBuf> {
     Ok(manifest_path)
 }
 
-fn build_and_install_rust_server() -> Result<PathBuf> {
+fn build_and_install_rust_server(dry_run: bool) -> Result<PathBuf> {
     let repo_root = get_repo_root()?;
     let server_dir = repo_root.join("symposium/mcp-server");
 
     println!("📦 Installing Rust MCP server...");
     println!("   Installing from: {}", server_dir.display());
 
+    // Full path to the installed binary
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    let binary_path = PathBuf::from(home).join(".cargo/bin/symposium-mcp");
+
     // Use cargo install --force to always update the binary
-    let output = Command::new("cargo")
-        .args(["install", "--path", ".", "--force"])
-        .current_dir(&server_dir)
-        .output()
-        .context("Failed to execute cargo install")?;
+    let mut cmd = Command::new("cargo");
+    cmd.args(["install", "--path", ".", "--force"])
+        .current_dir(&server_dir);
+
+    if dry_run {
+        print_planned(&cmd);
+        return Ok(binary_path);
+    }
+
+    let output = cmd.output().context("Failed to execute cargo install")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -312,25 +347,29 @@ fn build_and_install_rust_server() -> Result<PathBuf> {
     }
 
     println!("✅ Rust server installed successfully!");
-    
-    // Return full path to the installed binary
-    let home = std::env::var("HOME").context("HOME environment variable not set")?;
-    Ok(PathBuf::from(home).join(".cargo/bin/symposium-mcp"))
+    Ok(binary_path)
 }
 
-fn build_and_install_sparkle_cli() -> Result<()> {
+fn build_and_install_sparkle_cli(dry_run: bool) -> Result<()> {
     println!("✨ Installing Sparkle MCP server...");
     println!("   Installing from: https://github.com/symposium-dev/sparkle.git");
 
     // Use cargo install --git to install Sparkle MCP server
-    let output = Command::new("cargo")
-        .args([
-            "install",
-            "--git",
-            "https://github.com/symposium-dev/sparkle.git",
-            "sparkle-mcp",
-            "--force"
-        ])
+    let mut cmd = Command::new("cargo");
+    cmd.args([
+        "install",
+        "--git",
+        "https://github.com/symposium-dev/sparkle.git",
+        "sparkle-mcp",
+        "--force"
+    ]);
+
+    if dry_run {
+        print_planned(&cmd);
+        return Ok(());
+    }
+
+    let output = cmd
         .output()
         .context("Failed to execute cargo install for Sparkle")?;
 
@@ -346,17 +385,22 @@ fn build_and_install_sparkle_cli() -> Result<()> {
     Ok(())
 }
 
-fn build_macos_app() -> Result<()> {
+fn build_macos_app(dry_run: bool) -> Result<()> {
     let repo_root = get_repo_root()?;
     let app_dir = repo_root.join("symposium").join("macos-app");
 
     println!("\n🍎 Building macOS application...");
     println!("   Building in: {}", app_dir.display());
 
-    let output = Command::new("./build-app.sh")
-        .current_dir(&app_dir)
-        .output()
-        .context("Failed to execute build-app.sh")?;
+    let mut cmd = Command::new("./build-app.sh");
+    cmd.current_dir(&app_dir);
+
+    if dry_run {
+        print_planned(&cmd);
+        return Ok(());
+    }
+
+    let output = cmd.output().context("Failed to execute build-app.sh")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -372,7 +416,7 @@ fn build_macos_app() -> Result<()> {
     Ok(())
 }
 
-fn open_macos_app() -> Result<()> {
+fn open_macos_app(dry_run: bool) -> Result<()> {
     let repo_root = get_repo_root()?;
     let app_path = repo_root
         .join("symposium")
@@ -384,10 +428,15 @@ fn open_macos_app() -> Result<()> {
 
     println!("\n🚀 Opening Symposium app...");
 
-    let output = Command::new("open")
-        .arg(&app_path)
-        .output()
-        .context("Failed to execute open command")?;
+    let mut cmd = Command::new("open");
+    cmd.arg(&app_path);
+
+    if dry_run {
+        print_planned(&cmd);
+        return Ok(());
+    }
+
+    let output = cmd.output().context("Failed to execute open command")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -401,12 +450,32 @@ fn open_macos_app() -> Result<()> {
     Ok(())
 }
 
-fn build_and_install_extension() -> Result<()> {
+fn build_and_install_extension(dry_run: bool) -> Result<()> {
     let repo_root = get_repo_root()?;
     let extension_dir = repo_root.join("symposium/vscode-extension");
 
     println!("\n📦 Building VSCode extension...");
 
+    if dry_run {
+        for args in [
+            &["install"][..],
+            &["run", "webpack-dev"],
+        ] {
+            let mut cmd = Command::new("npm");
+            cmd.args(args).current_dir(&extension_dir);
+            print_planned(&cmd);
+        }
+        let mut cmd = Command::new("npx");
+        cmd.args(["vsce", "package", "--no-dependencies"])
+            .current_dir(&extension_dir);
+        print_planned(&cmd);
+        let mut cmd = Command::new("code");
+        cmd.args(["--install-extension", "<generated .vsix>"])
+            .current_dir(&extension_dir);
+        print_planned(&cmd);
+        return Ok(());
+    }
+
     // Install dependencies
     println!("📥 Installing extension dependencies...");
     let output = Command::new("npm")
@@ -494,9 +563,15 @@ fn build_and_install_extension() -> Result<()> {
 }
 
 /// Clean up existing daemon process and stale socket files
-fn cleanup_existing_daemon() -> Result<()> {
+fn cleanup_existing_daemon(dry_run: bool) -> Result<()> {
     println!("🧹 Cleaning up existing daemon...");
 
+    if dry_run {
+        println!("   🔍 Would send SIGTERM to each 'symposium-mcp daemon' process listed by 'ps ux'");
+        println!("   🔍 Would remove stale socket file /tmp/symposium-daemon.sock");
+        return Ok(());
+    }
+
     // Find symposium-mcp daemon processes directly
     let ps_output = Command::new("ps")
         .args(["ux"])
@@ -509,7 +584,7 @@ fn cleanup_existing_daemon() -> Result<()> {
     }
 
     let ps_stdout = String::from_utf8_lossy(&ps_output.stdout);
-    let mut killed_any = false;
+    let mut signalled_pids = Vec::new();
 
     for line in ps_stdout.lines() {
         if line.contains("symposium-mcp daemon") {
@@ -526,7 +601,7 @@ fn cleanup_existing_daemon() -> Result<()> {
                     match kill_result {
                         Ok(output) if output.status.success() => {
                             println!("   ✅ Sent SIGTERM to daemon PID {}", pid);
-                            killed_any = true;
+                            signalled_pids.push(pid);
                         }
                         Ok(_) => {
                             println!("   ⚠️  Failed to kill daemon PID {}", pid);
@@ -540,9 +615,10 @@ fn cleanup_existing_daemon() -> Result<()> {
         }
     }
 
-    if killed_any {
-        // Give daemons time to shut down gracefully and send reload signals
-        std::thread::sleep(std::time::Duration::from_millis(500));
+    if !signalled_pids.is_empty() {
+        // The daemon broadcasts reload signals and removes its socket on SIGTERM;
+        // wait for it to actually exit rather than guessing how long that takes
+        wait_for_daemon_exit(&signalled_pids, std::time::Duration::from_secs(5));
     } else {
         println!("   ℹ️  No existing daemon processes found");
     }
@@ -560,3 +636,25 @@ fn cleanup_existing_daemon() -> Result<()> {
     println!("   🎯 Environment ready for fresh daemon");
     Ok(())
 }
+
+/// Poll until every process in `pids` has exited, or `timeout` elapses
+fn wait_for_daemon_exit(pids: &[u32], timeout: std::time::Duration) {
+    let deadline = std::time::Instant::now() + timeout;
+    let is_running = |pid: &u32| {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    };
+
+    while pids.iter().any(is_running) {
+        if std::time::Instant::now() >= deadline {
+            println!("   ⚠️  Daemon did not exit within {}s", timeout.as_secs());
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    println!("   ✅ Daemon shut down cleanly");
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use serde_json::{Map, Value, json};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Represents an MCP server configuration
@@ -8,13 +9,204 @@ pub struct McpServer {
     pub name: String,
     pub binary_path: PathBuf,
     pub args: Vec<String>,
+    /// Environment for the server; values may reference the installer's
+    /// environment as `${VAR}`, expanded at install time
     pub env: Vec<(String, String)>,
 }
 
+impl McpServer {
+    /// This server with every `${VAR}` in its env values replaced from the current
+    /// process environment. Fails if a referenced variable is not set.
+    pub fn with_expanded_env(&self) -> Result<McpServer> {
+        let env = self
+            .env
+            .iter()
+            .map(|(key, value)| {
+                let value = expand_env_vars(value)
+                    .with_context(|| format!("Failed to expand env entry {}", key))?;
+                Ok((key.clone(), value))
+            })
+            .collect::<Result<_>>()?;
+        Ok(McpServer { env, ..self.clone() })
+    }
+}
+
+/// Expand `${VAR}` references in `value` from the current process environment.
+///
+/// Text without references is returned unchanged, including a `$` not followed by
+/// `{`. References may nest (`${LOG_${PROFILE}}` looks up `PROFILE` first), but
+/// substituted values are not expanded again.
+pub fn expand_env_vars(value: &str) -> Result<String> {
+    expand_with(value, &|name: &str| std::env::var(name).ok())
+}
+
+fn expand_with(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        // Find the matching `}`, allowing nested `${...}` in the name
+        let mut depth = 0;
+        let mut end = None;
+        for (index, c) in after.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => {
+                    end = Some(index);
+                    break;
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        let end = end.with_context(|| format!("Unterminated `${{` in {:?}", value))?;
+
+        let name = expand_with(&after[..end], lookup)?;
+        let resolved = lookup(&name)
+            .with_context(|| format!("Environment variable {} is not set", name))?;
+        expanded.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Check `mcp list` output for a server named `name` that runs `binary_path`.
+///
+/// A line matches when one of its tokens is exactly the server name (so `symposium`
+/// does not match `symposium-dev`) and the line mentions the binary path.
+fn is_registered(list_output: &str, name: &str, binary_path: &Path) -> bool {
+    let binary_path = binary_path.to_string_lossy();
+    list_output.lines().any(|line| {
+        let names_server = line
+            .split_whitespace()
+            .map(|token| token.trim_matches(|c: char| matches!(c, ':' | '•' | '-' | ',')))
+            .any(|token| token == name);
+        names_server && line.contains(binary_path.as_ref())
+    })
+}
+
+/// Run `<agent> mcp list`, returning its stdout if the command succeeded
+fn mcp_list(executable_path: &Path) -> Option<String> {
+    let output = Command::new(executable_path)
+        .args(["mcp", "list"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Render a command as a shell-like line, including its working directory if set
+pub fn format_command(cmd: &Command) -> String {
+    let mut line = cmd.get_program().to_string_lossy().into_owned();
+    for arg in cmd.get_args() {
+        let arg = arg.to_string_lossy();
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            line.push_str(&format!(" '{}'", arg));
+        } else {
+            line.push(' ');
+            line.push_str(&arg);
+        }
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        line.push_str(&format!("  (in {})", dir.display()));
+    }
+    line
+}
+
+/// The JSON entry for `mcp` in an `mcpServers` config map
+fn mcp_config_entry(mcp: &McpServer) -> Value {
+    let env: Map<String, Value> = mcp
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+        .collect();
+    json!({
+        "command": mcp.binary_path.to_string_lossy(),
+        "args": mcp.args,
+        "env": env,
+    })
+}
+
+/// Merge `mcp` into the `mcpServers` map of the JSON config at `config_path`.
+///
+/// Creates the file (and its directory) if needed and leaves every other key
+/// untouched. Returns `false` without writing when the entry is already up to date.
+fn merge_mcp_config(config_path: &Path, mcp: &McpServer) -> Result<bool> {
+    let mut config: Value = match std::fs::read_to_string(config_path) {
+        Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", config_path.display()))?,
+        Ok(_) => json!({}),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => json!({}),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", config_path.display()));
+        }
+    };
+
+    let servers = config
+        .as_object_mut()
+        .with_context(|| format!("{} is not a JSON object", config_path.display()))?
+        .entry("mcpServers")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .with_context(|| format!("`mcpServers` in {} is not an object", config_path.display()))?;
+
+    let entry = mcp_config_entry(mcp);
+    if servers.get(&mcp.name) == Some(&entry) {
+        return Ok(false);
+    }
+    servers.insert(mcp.name.clone(), entry);
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(config_path, serde_json::to_string_pretty(&config)? + "\n")
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+    Ok(true)
+}
+
+/// Install `mcp` into a JSON-configured agent, reporting the outcome like the CLI agents do
+fn install_into_config(agent_name: &str, config_path: &Path, mcp: &McpServer) -> Result<bool> {
+    match merge_mcp_config(config_path, mcp) {
+        Ok(false) => {
+            println!("✅ MCP server '{}' already configured with correct path", mcp.name);
+            Ok(true)
+        }
+        Ok(true) => {
+            println!(
+                "✅ MCP server '{}' registered successfully with {} ({})",
+                mcp.name,
+                agent_name,
+                config_path.display()
+            );
+            Ok(true)
+        }
+        Err(e) => {
+            println!("❌ Failed to register MCP server with {}:", agent_name);
+            println!("   Error: {:#}", e);
+            Ok(false)
+        }
+    }
+}
+
+/// `$HOME`-relative path, if `$HOME` is set
+fn home_path(relative: &str) -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(relative))
+}
+
 /// Trait for CLI agents that can have MCP servers installed
 pub trait CLIAgent: Send + Sync {
     fn name(&self) -> String;
+    /// Register `mcp`, expanding `${VAR}` references in its env first
     fn install_mcp(&self, mcp: &McpServer) -> Result<bool>;
+    /// What `install_mcp` would do to register the server, for dry runs
+    fn planned_install(&self, mcp: &McpServer) -> String;
 }
 
 /// Q CLI agent implementation
@@ -35,6 +227,40 @@ impl CLIAgent for QCLI {
     }
 
     fn install_mcp(&self, mcp: &McpServer) -> Result<bool> {
+        let mcp = &mcp.with_expanded_env()?;
+
+        // Skip re-registering when the server already points at this binary
+        if let Some(list_stdout) = mcp_list(&self.executable_path)
+            && is_registered(&list_stdout, &mcp.name, &mcp.binary_path)
+        {
+            println!("✅ MCP server '{}' already configured with correct path", mcp.name);
+            return Ok(true);
+        }
+
+        let output = self
+            .add_command(mcp)
+            .output()
+            .context("Failed to execute q mcp add")?;
+
+        if output.status.success() {
+            println!("✅ MCP server '{}' registered successfully with Q CLI!", mcp.name);
+            Ok(true)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            println!("❌ Failed to register MCP server with Q CLI:");
+            println!("   Error: {}", stderr.trim());
+            Ok(false)
+        }
+    }
+
+    fn planned_install(&self, mcp: &McpServer) -> String {
+        format!("run: {}", format_command(&self.add_command(mcp)))
+    }
+}
+
+impl QCLI {
+    /// The command `install_mcp` runs to register the server
+    pub fn add_command(&self, mcp: &McpServer) -> Command {
         let mut cmd = Command::new(&self.executable_path);
 
         cmd.args([
@@ -44,7 +270,7 @@ impl CLIAgent for QCLI {
             &mcp.name,
             "--command",
             &mcp.binary_path.to_string_lossy(),
-            "--force", // Always overwrite existing configuration
+            "--force", // Overwrite an existing configuration with a different path
         ]);
 
         // Add arguments
@@ -57,17 +283,7 @@ impl CLIAgent for QCLI {
             cmd.args(["--env", &format!("{}={}", key, value)]);
         }
 
-        let output = cmd.output().context("Failed to execute q mcp add")?;
-
-        if output.status.success() {
-            println!("✅ MCP server '{}' registered successfully with Q CLI!", mcp.name);
-            Ok(true)
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            println!("❌ Failed to register MCP server with Q CLI:");
-            println!("   Error: {}", stderr.trim());
-            Ok(false)
-        }
+        cmd
     }
 }
 
@@ -97,26 +313,18 @@ impl CLIAgent for ClaudeCode {
     }
 
     fn install_mcp(&self, mcp: &McpServer) -> Result<bool> {
+        let mcp = &mcp.with_expanded_env()?;
+
         if !self.is_available() {
             println!("❌ Claude Code MCP functionality not available");
             return Ok(false);
         }
 
         // Check if server already exists with correct path
-        let list_output = Command::new(&self.executable_path)
-            .args(["mcp", "list"])
-            .output()
-            .context("Failed to execute claude mcp list")?;
-
-        if list_output.status.success() {
-            let list_stdout = String::from_utf8_lossy(&list_output.stdout);
-            let desired_binary_str = mcp.binary_path.to_string_lossy();
-
-            for line in list_stdout.lines() {
-                if line.contains(&mcp.name) && line.contains(desired_binary_str.as_ref()) {
-                    println!("✅ MCP server '{}' already configured with correct path", mcp.name);
-                    return Ok(true);
-                }
+        if let Some(list_stdout) = mcp_list(&self.executable_path) {
+            if is_registered(&list_stdout, &mcp.name, &mcp.binary_path) {
+                println!("✅ MCP server '{}' already configured with correct path", mcp.name);
+                return Ok(true);
             }
 
             // Remove existing if it exists with wrong path
@@ -128,6 +336,30 @@ impl CLIAgent for ClaudeCode {
         }
 
         // Add the server
+        let output = self
+            .add_command(mcp)
+            .output()
+            .context("Failed to execute claude mcp add")?;
+
+        if output.status.success() {
+            println!("✅ MCP server '{}' registered successfully with Claude Code!", mcp.name);
+            Ok(true)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            println!("❌ Failed to register MCP server with Claude Code:");
+            println!("   Error: {}", stderr.trim());
+            Ok(false)
+        }
+    }
+
+    fn planned_install(&self, mcp: &McpServer) -> String {
+        format!("run: {}", format_command(&self.add_command(mcp)))
+    }
+}
+
+impl ClaudeCode {
+    /// The command `install_mcp` runs to register the server
+    pub fn add_command(&self, mcp: &McpServer) -> Command {
         let mut cmd = Command::new(&self.executable_path);
         cmd.args([
             "mcp",
@@ -146,17 +378,69 @@ impl CLIAgent for ClaudeCode {
             cmd.args(["-e", &format!("{}={}", key, value)]);
         }
 
-        let output = cmd.output().context("Failed to execute claude mcp add")?;
+        cmd
+    }
+}
 
-        if output.status.success() {
-            println!("✅ MCP server '{}' registered successfully with Claude Code!", mcp.name);
-            Ok(true)
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            println!("❌ Failed to register MCP server with Claude Code:");
-            println!("   Error: {}", stderr.trim());
-            Ok(false)
+/// Cursor agent implementation, configured through `~/.cursor/mcp.json`
+pub struct Cursor {
+    config_path: PathBuf,
+}
+
+impl Cursor {
+    pub fn detect() -> Result<Box<Self>> {
+        let config_dir = home_path(".cursor")?;
+        if which::which("cursor").is_err() && !config_dir.is_dir() {
+            anyhow::bail!("Cursor not found in PATH or at {}", config_dir.display());
         }
+        Ok(Box::new(Cursor {
+            config_path: config_dir.join("mcp.json"),
+        }))
+    }
+}
+
+impl CLIAgent for Cursor {
+    fn name(&self) -> String {
+        "Cursor".to_string()
+    }
+
+    fn install_mcp(&self, mcp: &McpServer) -> Result<bool> {
+        install_into_config(&self.name(), &self.config_path, &mcp.with_expanded_env()?)
+    }
+
+    fn planned_install(&self, mcp: &McpServer) -> String {
+        format!("add '{}' to {}", mcp.name, self.config_path.display())
+    }
+}
+
+/// Windsurf agent implementation, configured through `~/.codeium/windsurf/mcp_config.json`
+pub struct Windsurf {
+    config_path: PathBuf,
+}
+
+impl Windsurf {
+    pub fn detect() -> Result<Box<Self>> {
+        let config_dir = home_path(".codeium/windsurf")?;
+        if which::which("windsurf").is_err() && !config_dir.is_dir() {
+            anyhow::bail!("Windsurf not found in PATH or at {}", config_dir.display());
+        }
+        Ok(Box::new(Windsurf {
+            config_path: config_dir.join("mcp_config.json"),
+        }))
+    }
+}
+
+impl CLIAgent for Windsurf {
+    fn name(&self) -> String {
+        "Windsurf".to_string()
+    }
+
+    fn install_mcp(&self, mcp: &McpServer) -> Result<bool> {
+        install_into_config(&self.name(), &self.config_path, &mcp.with_expanded_env()?)
+    }
+
+    fn planned_install(&self, mcp: &McpServer) -> String {
+        format!("add '{}' to {}", mcp.name, self.config_path.display())
     }
 }
 
@@ -172,5 +456,122 @@ pub fn detect_cli_agents() -> Vec<Box<dyn CLIAgent>> {
         result.push(agent);
     }
 
+    if let Ok(agent) = Cursor::detect() {
+        result.push(agent);
+    }
+
+    if let Ok(agent) = Windsurf::detect() {
+        result.push(agent);
+    }
+
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_registered() {
+        let binary = Path::new("/home/user/.cargo/bin/symposium-mcp");
+
+        // Claude Code style
+        let claude = "symposium: /home/user/.cargo/bin/symposium-mcp  - ✓ Connected\n";
+        assert!(is_registered(claude, "symposium", binary));
+
+        // Q CLI style
+        let q = "📄 global:\n    • symposium    /home/user/.cargo/bin/symposium-mcp\n";
+        assert!(is_registered(q, "symposium", binary));
+
+        // Different binary, or a server whose name merely contains ours
+        assert!(!is_registered("symposium: /usr/bin/symposium-mcp\n", "symposium", binary));
+        assert!(!is_registered(
+            "symposium-dev: /home/user/.cargo/bin/symposium-mcp\n",
+            "symposium",
+            binary
+        ));
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let lookup = |name: &str| match name {
+            "LEVEL" => Some("debug".to_string()),
+            "PROFILE" => Some("DEV".to_string()),
+            "LOG_DEV" => Some("symposium_mcp=trace".to_string()),
+            "EMPTY" => Some(String::new()),
+            "TEMPLATE" => Some("${LEVEL}".to_string()),
+            _ => None,
+        };
+        let expand = |value: &str| expand_with(value, &lookup);
+
+        // Literal values are untouched, including stray `$`
+        assert_eq!(expand("symposium_mcp=debug").unwrap(), "symposium_mcp=debug");
+        assert_eq!(expand("cost: $5 {ok}").unwrap(), "cost: $5 {ok}");
+
+        // Defined variables, alone or inside other text
+        assert_eq!(expand("${LEVEL}").unwrap(), "debug");
+        assert_eq!(expand("symposium_mcp=${LEVEL},${EMPTY}rmcp=info").unwrap(), "symposium_mcp=debug,rmcp=info");
+
+        // Nested references resolve the inner name first; values are not re-expanded
+        assert_eq!(expand("${LOG_${PROFILE}}").unwrap(), "symposium_mcp=trace");
+        assert_eq!(expand("${TEMPLATE}").unwrap(), "${LEVEL}");
+
+        // Undefined variables and unterminated references are errors
+        let error = expand("symposium_mcp=${MISSING}").unwrap_err();
+        assert_eq!(error.to_string(), "Environment variable MISSING is not set");
+        assert!(expand("${LOG_${PROFILE}").is_err());
+    }
+
+    #[test]
+    fn test_merge_mcp_config() {
+        let dir = std::env::temp_dir().join(format!("cli-agent-util-test-{}", std::process::id()));
+        let config_path = dir.join("nested").join("mcp.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &config_path,
+            r#"{"theme": "dark", "mcpServers": {"other": {"command": "/bin/other"}}}"#,
+        )
+        .unwrap();
+
+        let mcp = McpServer {
+            name: "symposium".to_string(),
+            binary_path: PathBuf::from("/home/user/.cargo/bin/symposium-mcp"),
+            args: vec!["--dev-log".to_string()],
+            env: vec![("RUST_LOG".to_string(), "symposium_mcp=debug".to_string())],
+        };
+
+        // First install writes the entry and keeps everything else
+        assert!(merge_mcp_config(&config_path, &mcp).unwrap());
+        let config: Value =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["theme"], "dark");
+        assert_eq!(config["mcpServers"]["other"]["command"], "/bin/other");
+        assert_eq!(
+            config["mcpServers"]["symposium"],
+            json!({
+                "command": "/home/user/.cargo/bin/symposium-mcp",
+                "args": ["--dev-log"],
+                "env": {"RUST_LOG": "symposium_mcp=debug"},
+            })
+        );
+
+        // Second install is a no-op
+        assert!(!merge_mcp_config(&config_path, &mcp).unwrap());
+
+        // A changed binary path is rewritten
+        let moved = McpServer {
+            binary_path: PathBuf::from("/usr/local/bin/symposium-mcp"),
+            ..mcp
+        };
+        assert!(merge_mcp_config(&config_path, &moved).unwrap());
+
+        // Missing files are created
+        let fresh_path = dir.join("fresh").join("mcp_config.json");
+        assert!(merge_mcp_config(&fresh_path, &moved).unwrap());
+        assert!(fresh_path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -40,11 +40,12 @@ struct TaskspaceStateRequest: Codable {
     let name: String?        // None = don't update, Some = set new value
     let description: String? // None = don't update, Some = set new value
     let collaborator: String? // None = don't update, Some = set new value
+    let tags: [String]?       // None = don't update, Some = replace the tags
     
     private enum CodingKeys: String, CodingKey {
         case projectPath = "project_path"
         case taskspaceUuid = "taskspace_uuid"
-        case name, description, collaborator
+        case name, description, collaborator, tags
     }
 }
 
@@ -55,12 +56,13 @@ struct TaskspaceStateResponse: Codable {
     let initialPrompt: String? // LLM task description (cleared after updates)
     let agentCommand: [String] // Command to launch the appropriate agent
     let collaborator: String? // Collaborator for this taskspace
+    let tags: [String]        // Tags grouping this taskspace with related ones
     
     private enum CodingKeys: String, CodingKey {
         case name, description
         case initialPrompt = "initial_prompt"
         case agentCommand = "agent_command"
-        case collaborator
+        case collaborator, tags
     }
 }
 
@@ -72,6 +74,7 @@ struct SpawnTaskspacePayload: Codable {
     let taskDescription: String
     let initialPrompt: String
     let collaborator: String?
+    let tags: [String]?  // Omitted when the new taskspace has no tags
 
     private enum CodingKeys: String, CodingKey {
         case projectPath = "project_path"
@@ -79,7 +82,7 @@ struct SpawnTaskspacePayload: Codable {
         case name
         case taskDescription = "task_description"
         case initialPrompt = "initial_prompt"
-        case collaborator
+        case collaborator, tags
     }
 }
 
@@ -139,11 +142,14 @@ struct SignalUserPayload: Codable {
     let projectPath: String
     let taskspaceUuid: String
     let message: String
+    /// "low", "normal", or "urgent"; absent from older MCP servers
+    let priority: String?
 
     private enum CodingKeys: String, CodingKey {
         case projectPath = "project_path"
         case taskspaceUuid = "taskspace_uuid"
         case message
+        case priority
     }
 }
 
@@ -680,7 +680,7 @@ class ProjectManager: ObservableObject, IpcMessageDelegate {
     }
 
     /// Create a new taskspace with specified values
-    func createTaskspace(name: String, description: String, initialPrompt: String, collaborator: String? = nil) throws {
+    func createTaskspace(name: String, description: String, initialPrompt: String, collaborator: String? = nil, tags: [String] = []) throws {
         guard let project = currentProject else {
             throw ProjectError.noCurrentProject
         }
@@ -700,7 +700,8 @@ class ProjectManager: ObservableObject, IpcMessageDelegate {
             name: name,
             description: description,
             initialPrompt: initialPrompt,
-            collaborator: collaborator
+            collaborator: collaborator,
+            tags: tags
         )
 
         // STAGE 1: Create taskspace directory
@@ -1664,6 +1665,12 @@ extension ProjectManager {
             Logger.shared.log("ProjectManager: Updated taskspace collaborator to: \(newCollaborator)")
         }
 
+        if let newTags = payload.tags {
+            taskspace.tags = newTags
+            hasUpdates = true
+            Logger.shared.log("ProjectManager: Updated taskspace tags to: \(newTags)")
+        }
+
         // Determine initial_prompt based on operation type
         let initialPrompt: String?
         if hasUpdates {
@@ -1706,7 +1713,8 @@ extension ProjectManager {
             description: taskspace.description,
             initialPrompt: initialPrompt,
             agentCommand: agentCommand,
-            collaborator: taskspace.collaborator
+            collaborator: taskspace.collaborator,
+            tags: taskspace.tags
         )
 
         Logger.shared.log(
@@ -1744,7 +1752,8 @@ extension ProjectManager {
                 name: payload.name,
                 description: payload.taskDescription,
                 initialPrompt: comprehensivePrompt,
-                collaborator: payload.collaborator
+                collaborator: payload.collaborator,
+                tags: payload.tags ?? []
             )
 
             // Get the newly created taskspace (it will be the last one added)
@@ -1853,7 +1862,7 @@ extension ProjectManager {
         }
 
         Logger.shared.log(
-            "ProjectManager: Signaling user for taskspace \(payload.taskspaceUuid): \(payload.message)"
+            "ProjectManager: Signaling user for taskspace \(payload.taskspaceUuid) (\(payload.priority ?? "normal")): \(payload.message)"
         )
 
         do {
@@ -11,6 +11,8 @@ struct Taskspace: Codable, Identifiable {
     let createdAt: Date
     var lastActivatedAt: Date
     var collaborator: String?
+    /// Tags grouping this taskspace with related ones (e.g., a shared feature effort)
+    var tags: [String] = []
     
     /// Timestamp of last screenshot capture (not persisted, transient UI state)
     var lastScreenshotAt: Date?
@@ -19,7 +21,7 @@ struct Taskspace: Codable, Identifiable {
     var pendingDeletion: Bool = false
     
     private enum CodingKeys: String, CodingKey {
-        case id, name, description, state, logs, vscodeWindowID, createdAt, lastActivatedAt, collaborator
+        case id, name, description, state, logs, vscodeWindowID, createdAt, lastActivatedAt, collaborator, tags
     }
     
     // Custom decoder to handle migration from older versions without lastActivatedAt
@@ -39,9 +41,12 @@ struct Taskspace: Codable, Identifiable {
         
         // Migration: collaborator field is optional for backward compatibility
         collaborator = try container.decodeIfPresent(String.self, forKey: .collaborator)
+
+        // Migration: tags were added later
+        tags = try container.decodeIfPresent([String].self, forKey: .tags) ?? []
     }
     
-    init(name: String, description: String, initialPrompt: String? = nil, collaborator: String? = nil) {
+    init(name: String, description: String, initialPrompt: String? = nil, collaborator: String? = nil, tags: [String] = []) {
         self.id = UUID()
         self.name = name
         self.description = description
@@ -49,6 +54,7 @@ struct Taskspace: Codable, Identifiable {
         self.createdAt = Date()
         self.lastActivatedAt = self.createdAt  // Use creation time as initial activation time
         self.collaborator = collaborator
+        self.tags = tags
     }
     
     /// Directory path for this taskspace within project
@@ -2,9 +2,13 @@
 //!
 //! Handles Unix socket connection management, message serialization/deserialization,
 //! and forwards parsed IPCMessages via tokio channels.
+//!
+//! If the daemon goes away (e.g., it was restarted), the actor reconnects with
+//! exponential backoff. Messages sent in the meantime wait in the inbound channel,
+//! and the last Polo announcement is replayed so discovery re-establishes.
 
 use crate::actor::Actor;
-use crate::types::IPCMessage;
+use crate::types::{IPCMessage, IPCMessageType};
 use anyhow::Result;
 use std::process::Command;
 use std::time::Duration;
@@ -13,6 +17,20 @@ use tokio::net::UnixStream;
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 
+/// Delay before the first reconnection attempt
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(100);
+
+/// Upper bound for the reconnection delay
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Why a daemon connection ended
+enum ConnectionEnd {
+    /// Our own channels closed; the actor should stop
+    Shutdown,
+    /// The daemon connection was lost; the actor should reconnect
+    Disconnected,
+}
+
 /// Actor that manages daemon connection and message transport
 pub struct ClientActor {
     /// Channel to receive messages to send to daemon
@@ -26,10 +44,17 @@ pub struct ClientActor {
     identity_prefix: String,
     /// Options for daemon spawning
     options: crate::Options,
+    /// Last Polo sent to the daemon, replayed after reconnecting
+    announcement: Option<IPCMessage>,
+    /// Message whose write failed when the connection dropped, resent after reconnecting
+    unsent: Option<IPCMessage>,
 }
 
 impl Actor for ClientActor {
     async fn run(mut self) {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        let mut connected_before = false;
+
         loop {
             // Check if channels are closed
             if self.outbound_tx.is_closed() {
@@ -41,15 +66,28 @@ impl Actor for ClientActor {
                 break;
             }
 
-            match self.connect_and_run().await {
-                Ok(()) => {
-                    info!("Client actor completed normally");
-                    break;
+            match self.connect().await {
+                Ok(stream) => {
+                    if connected_before {
+                        info!("🔄 Reconnected to daemon");
+                    }
+                    connected_before = true;
+                    delay = INITIAL_RECONNECT_DELAY;
+
+                    match self.run_connection(stream).await {
+                        ConnectionEnd::Shutdown => {
+                            info!("Client actor completed normally");
+                            break;
+                        }
+                        ConnectionEnd::Disconnected => {
+                            warn!("Daemon unavailable, retrying connection");
+                        }
+                    }
                 }
                 Err(e) => {
-                    error!("Client actor error: {}", e);
-                    // TODO: Add reconnection logic with backoff
-                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    warn!("Daemon unavailable, retrying in {:?}: {}", delay, e);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
                 }
             }
         }
@@ -72,13 +110,15 @@ impl ClientActor {
             auto_start,
             identity_prefix,
             options,
+            announcement: None,
+            unsent: None,
         }
     }
 
     /// Generate identity string with prefix, PID, and truncated CWD
     fn generate_identity(&self) -> String {
         let pid = std::process::id();
-        let cwd = std::env::current_dir()
+        let cwd = crate::workspace_dir::current_dir()
             .map(|path| {
                 let components: Vec<_> = path.components().collect();
                 if components.len() <= 2 {
@@ -93,29 +133,46 @@ impl ClientActor {
         format!("{}(pid:{},cwd:{})", self.identity_prefix, pid, cwd)
     }
 
-    async fn connect_and_run(&mut self) -> Result<()> {
+    /// Connect to the daemon, starting one if `auto_start` is set
+    async fn connect(&self) -> Result<UnixStream> {
         let socket_path = crate::constants::daemon_socket_path(&self.socket_prefix);
 
         // Try to connect to existing daemon
-        let stream = match UnixStream::connect(&socket_path).await {
+        let result = UnixStream::connect(&socket_path).await;
+        if let Err(e) = &result {
+            // A daemon that died without cleaning up leaves its socket behind; a new
+            // daemon could not claim the path until it is gone
+            remove_stale_socket(&socket_path, e);
+        }
+
+        match result {
             Ok(stream) => {
                 info!("✅ Connected to existing daemon at {}", socket_path);
-                stream
+                Ok(stream)
             }
             Err(_) if self.auto_start => {
                 info!("No daemon found, attempting to start one...");
                 self.spawn_daemon().await?;
-                self.wait_for_daemon(&socket_path).await?
+                self.wait_for_daemon(&socket_path).await
             }
-            Err(e) => {
-                return Err(anyhow::anyhow!(
-                    "Failed to connect to daemon at {}: {}",
-                    socket_path,
-                    e
-                ));
-            }
-        };
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to connect to daemon at {}: {}",
+                socket_path,
+                e
+            )),
+        }
+    }
+
+    /// Write one message to the daemon as a JSON line
+    async fn write_message(
+        write_half: &mut tokio::net::unix::OwnedWriteHalf,
+        message: &IPCMessage,
+    ) -> std::io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        write_half.write_all(format!("{}\n", json).as_bytes()).await
+    }
 
+    async fn run_connection(&mut self, stream: UnixStream) -> ConnectionEnd {
         // Split stream for reading and writing
         let (read_half, mut write_half) = stream.into_split();
         let mut reader = BufReader::new(read_half);
@@ -130,6 +187,26 @@ impl ClientActor {
             error!("Failed to flush identify command: {}", e);
         }
 
+        // After a reconnect, re-announce ourselves and resend what was lost
+        let replay: Vec<IPCMessage> = self
+            .announcement
+            .iter()
+            .map(|polo| IPCMessage {
+                id: uuid::Uuid::new_v4().to_string(),
+                ..polo.clone()
+            })
+            .chain(self.unsent.take())
+            .collect();
+        for message in replay {
+            if let Err(e) = Self::write_message(&mut write_half, &message).await {
+                error!("Failed to replay message to daemon: {}", e);
+                if message.message_type != IPCMessageType::Polo {
+                    self.unsent = Some(message);
+                }
+                return ConnectionEnd::Disconnected;
+            }
+        }
+
         loop {
             tokio::select! {
                 // Read from daemon and forward to outbound channel
@@ -137,7 +214,7 @@ impl ClientActor {
                     match result {
                         Ok(0) => {
                             info!("Daemon connection closed");
-                            break;
+                            return ConnectionEnd::Disconnected;
                         }
                         Ok(_) => {
                             let message_str = line.trim();
@@ -146,7 +223,7 @@ impl ClientActor {
                                     Ok(message) => {
                                         if let Err(e) = self.outbound_tx.send(message).await {
                                             error!("Failed to forward message from daemon: {}", e);
-                                            break;
+                                            return ConnectionEnd::Shutdown;
                                         }
                                     }
                                     Err(e) => {
@@ -158,7 +235,7 @@ impl ClientActor {
                         }
                         Err(e) => {
                             error!("Error reading from daemon: {}", e);
-                            break;
+                            return ConnectionEnd::Disconnected;
                         }
                     }
                 }
@@ -167,29 +244,23 @@ impl ClientActor {
                 message = self.inbound_rx.recv() => {
                     match message {
                         Some(message) => {
-                            match serde_json::to_string(&message) {
-                                Ok(json) => {
-                                    let line = format!("{}\n", json);
-                                    if let Err(e) = write_half.write_all(line.as_bytes()).await {
-                                        error!("Failed to write to daemon: {}", e);
-                                        break;
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Failed to serialize message: {}", e);
-                                }
+                            if message.message_type == IPCMessageType::Polo {
+                                self.announcement = Some(message.clone());
+                            }
+                            if let Err(e) = Self::write_message(&mut write_half, &message).await {
+                                error!("Failed to write to daemon: {}", e);
+                                self.unsent = Some(message);
+                                return ConnectionEnd::Disconnected;
                             }
                         }
                         None => {
                             info!("Inbound channel closed");
-                            break;
+                            return ConnectionEnd::Shutdown;
                         }
                     }
                 }
             }
         }
-
-        Ok(())
     }
 
     async fn spawn_daemon(&self) -> Result<()> {
@@ -240,6 +311,27 @@ impl ClientActor {
     }
 }
 
+/// Remove the socket file at `socket_path` if connecting to it was refused, i.e. the
+/// file exists but no daemon is listening.
+fn remove_stale_socket(socket_path: &str, connect_error: &std::io::Error) {
+    if connect_error.kind() != std::io::ErrorKind::ConnectionRefused
+        || !std::path::Path::new(socket_path).exists()
+    {
+        return;
+    }
+
+    // A daemon may have bound the path since our attempt; only unlink if it still refuses
+    match std::os::unix::net::UnixStream::connect(socket_path) {
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {}
+        _ => return,
+    }
+
+    match std::fs::remove_file(socket_path) {
+        Ok(()) => warn!("Removed stale daemon socket at {}", socket_path),
+        Err(e) => warn!("Failed to remove stale daemon socket at {}: {}", socket_path, e),
+    }
+}
+
 /// Spawn a client connection. Returns a `Sender` that you can use to send
 /// messages to the client (which will rebroadcast them to everyone else)
 /// and a `Receiver` where you can receive messages from others.
@@ -258,3 +350,105 @@ pub fn spawn_client(
     // Return handle and the receiver for other actors to get messages from daemon
     (inbound_tx, outbound_rx)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tokio::net::UnixListener;
+    use tokio::time::timeout;
+
+    /// Accept one client on `listener` and read its first `count` lines
+    async fn accept_lines(listener: &UnixListener, count: usize) -> (UnixStream, Vec<String>) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read_half, write_half) = stream.into_split();
+        let mut reader = BufReader::new(read_half);
+        let mut lines = vec![];
+        for _ in 0..count {
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            lines.push(line.trim().to_string());
+        }
+        (reader.into_inner().reunite(write_half).unwrap(), lines)
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_and_replays_polo_after_daemon_restart() {
+        let prefix = format!("symposium-test-reconnect-{}", std::process::id());
+        let socket_path = crate::constants::daemon_socket_path(&prefix);
+        let _ = std::fs::remove_file(&socket_path);
+
+        // First "daemon"
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let options = crate::Options::parse_from(["symposium-mcp"]);
+        let (to_daemon_tx, _from_daemon_rx) = spawn_client(&prefix, false, "test-client", options);
+
+        let polo = IPCMessage {
+            message_type: IPCMessageType::Polo,
+            id: "polo-1".to_string(),
+            sender: crate::types::MessageSender {
+                working_directory: "/tmp".to_string(),
+                taskspace_uuid: None,
+                shell_pid: Some(42),
+            },
+            payload: serde_json::json!({}),
+        };
+        to_daemon_tx.send(polo).await.unwrap();
+
+        let (stream, lines) = timeout(Duration::from_secs(5), accept_lines(&listener, 2))
+            .await
+            .unwrap();
+        assert!(lines[0].starts_with("#identify:test-client"));
+        assert!(lines[1].contains(r#""type":"polo""#));
+
+        // Kill the daemon: drop the connection and the socket
+        drop(stream);
+        drop(listener);
+        std::fs::remove_file(&socket_path).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Restart it; the client reconnects, identifies, and re-announces itself
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let (_stream, lines) = timeout(Duration::from_secs(10), accept_lines(&listener, 2))
+            .await
+            .expect("client should reconnect to the restarted daemon");
+        assert!(lines[0].starts_with("#identify:test-client"));
+        assert!(lines[1].contains(r#""type":"polo""#));
+        assert!(lines[1].contains(r#""shellPid":42"#));
+        assert!(!lines[1].contains("polo-1"), "replayed polo should get a fresh id");
+
+        let _ = std::fs::remove_file(&socket_path);
+    }
+
+    #[tokio::test]
+    async fn test_recovers_from_stale_socket() {
+        let prefix = format!("symposium-test-stale-{}", std::process::id());
+        let socket_path = crate::constants::daemon_socket_path(&prefix);
+        let _ = std::fs::remove_file(&socket_path);
+
+        // A daemon that died without cleanup: the socket file exists, nobody listens
+        drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+        assert!(std::path::Path::new(&socket_path).exists());
+
+        let options = crate::Options::parse_from(["symposium-mcp"]);
+        let (_to_daemon_tx, _from_daemon_rx) = spawn_client(&prefix, false, "test-client", options);
+
+        // The client removes the stale socket...
+        timeout(Duration::from_secs(5), async {
+            while std::path::Path::new(&socket_path).exists() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("client should remove the stale socket");
+
+        // ...so a new daemon can claim the path, and the client connects to it
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let (_stream, lines) = timeout(Duration::from_secs(10), accept_lines(&listener, 1))
+            .await
+            .expect("client should connect to the new daemon");
+        assert!(lines[0].starts_with("#identify:test-client"));
+
+        let _ = std::fs::remove_file(&socket_path);
+    }
+}
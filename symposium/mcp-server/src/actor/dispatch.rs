@@ -4,7 +4,10 @@
 //! Extracted from the monolithic IPCCommunicator to provide focused responsibility.
 
 use crate::actor::Actor;
-use crate::types::{IPCMessage, IpcPayload, MessageSender, PoloPayload, ResponsePayload};
+use crate::types::{
+    IPCMessage, IpcPayload, MarcoMessage, MessageSender, Peer, PeerRole, PoloPayload,
+    ResponsePayload,
+};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
@@ -12,9 +15,18 @@ use std::collections::HashMap;
 use std::fmt::Display;
 use std::future::Future;
 use std::pin::Pin;
+use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
 use uuid;
 
+/// Reply timeout for handles that were not given one explicitly
+const DEFAULT_REQUEST_TIMEOUT: Duration =
+    Duration::from_secs(crate::constants::DEFAULT_IPC_TIMEOUT);
+
+/// Reply timeout for messages that wait for the user, so an unanswered prompt can't hang forever
+const DEFAULT_USER_WAIT_TIMEOUT: Duration =
+    Duration::from_secs(crate::constants::USER_WAIT_TIMEOUT);
+
 /// Mock actor function type - takes incoming and outgoing channels
 pub type MockActorFn = Box<
     dyn Fn(
@@ -25,15 +37,23 @@ pub type MockActorFn = Box<
         + Sync,
 >;
 
-/// Send a message on the IPC channel and optionally ask for a reply.
-struct DispatchRequest {
-    /// Message to send.
-    message: IPCMessage,
+/// Requests to the dispatch actor.
+enum DispatchRequest {
+    /// Send a message on the IPC channel and optionally ask for a reply.
+    Send {
+        /// Message to send.
+        message: IPCMessage,
+
+        /// If `Some`, then this is a channel on which the
+        /// sender expects a reply. We will wait for a reply
+        /// to `message.id` and then send the value.
+        reply_tx: Option<oneshot::Sender<ResponsePayload>>,
+    },
 
-    /// If `Some`, then this is a channel on which the
-    /// sender expects a reply. We will wait for a reply
-    /// to `message.id` and then send the value.
-    reply_tx: Option<oneshot::Sender<ResponsePayload>>,
+    /// Forward every Polo that arrives to `polo_tx`, until it is closed.
+    CollectPolos {
+        polo_tx: mpsc::UnboundedSender<IPCMessage>,
+    },
 }
 
 /// A [Tokio actor][] that shepherds the connection to the daemon.
@@ -67,6 +87,9 @@ struct DispatchActor {
     /// that the Sender in this map is closed when we send the data along.
     /// That's ok.
     pending_replies: HashMap<String, oneshot::Sender<ResponsePayload>>,
+
+    /// Channels of in-progress peer discoveries, which want to see every Polo
+    polo_collectors: Vec<mpsc::UnboundedSender<IPCMessage>>,
 }
 
 impl Actor for DispatchActor {
@@ -79,7 +102,7 @@ impl Actor for DispatchActor {
                 // Handle outgoing message requests
                 request = self.request_rx.recv() => {
                     match request {
-                        Some(DispatchRequest { message, reply_tx }) => {
+                        Some(DispatchRequest::Send { message, reply_tx }) => {
                             // Store reply channel if expecting a response
                             if let Some(reply_tx) = reply_tx {
                                 self.pending_replies.insert(message.id.clone(), reply_tx);
@@ -91,6 +114,9 @@ impl Actor for DispatchActor {
                                 break;
                             }
                         }
+                        Some(DispatchRequest::CollectPolos { polo_tx }) => {
+                            self.polo_collectors.push(polo_tx);
+                        }
                         None => {
                             tracing::info!("Request channel closed, shutting down dispatch actor");
                             break;
@@ -136,6 +162,7 @@ impl DispatchActor {
             sender,
             reference_handle,
             pending_replies: HashMap::new(),
+            polo_collectors: Vec::new(),
         }
     }
 
@@ -152,6 +179,11 @@ impl DispatchActor {
                     tracing::error!("Failed to route Marco message: {}", e);
                 }
             }
+            crate::types::IPCMessageType::Polo => {
+                // Only of interest while someone is discovering peers
+                self.polo_collectors
+                    .retain(|polo_tx| polo_tx.send(message.clone()).is_ok());
+            }
             crate::types::IPCMessageType::Response => {
                 if let Some(reply_tx) = self.pending_replies.remove(&message.id) {
                     tracing::debug!("Received `response` to message with id `{id}` from {sender:?}",
@@ -190,6 +222,16 @@ impl DispatchActor {
                     );
                 }
             }
+            crate::types::IPCMessageType::ApplyDiff => {
+                tracing::debug!("Received `apply_diff` with id `{id}` from {sender:?}",
+                    id = message.id,
+                    sender = message.sender,
+                );
+
+                if let Err(e) = self.handle_apply_diff(message).await {
+                    tracing::error!("Failed to handle ApplyDiff message: {}", e);
+                }
+            }
             _ => {
                 // Ignore other messages, not relevant to us
             }
@@ -216,6 +258,38 @@ impl DispatchActor {
         self.respond_to(&message.id, result).await
     }
 
+    /// Handle ApplyDiff messages by applying the patch to our working tree.
+    ///
+    /// The patch is checked and written in one `GitService::apply_patch`, so a patch
+    /// that no longer applies (the tree changed, or another server got there first) is
+    /// rejected without touching any files and reported back as a `conflict`.
+    async fn handle_apply_diff(&self, message: IPCMessage) -> anyhow::Result<()> {
+        let payload: crate::types::ApplyDiffPayload = serde_json::from_value(message.payload)
+            .with_context(|| format!("failed to deserialize ApplyDiff payload"))?;
+
+        // Only the server whose workspace rendered the button applies the patch
+        let workspace_root = crate::workspace_dir::workspace_root()?;
+        if workspace_root != std::path::Path::new(&payload.workspace_root) {
+            return Ok(());
+        }
+
+        let git = crate::git::GitService::new(&workspace_root.to_string_lossy())?;
+        let response = match git.apply_patch(&payload.patch) {
+            Ok(files) => crate::types::ApplyPatchResponse {
+                status: crate::types::ApplyPatchStatus::Applied,
+                conflicts: vec![],
+                message: Some(format!("Applied patch to {}", files.join(", "))),
+            },
+            Err(e) => crate::types::ApplyPatchResponse {
+                status: crate::types::ApplyPatchStatus::Conflict,
+                conflicts: vec![],
+                message: Some(format!("Patch no longer applies: {}", e.message())),
+            },
+        };
+
+        self.respond_to(&message.id, Ok::<_, String>(response)).await
+    }
+
     async fn respond_to<T: Serialize + std::fmt::Debug>(
         &self,
         incoming_message_id: &String,
@@ -252,7 +326,9 @@ impl DispatchActor {
             message_type: crate::types::IPCMessageType::Polo,
             id: fresh_message_id(),
             sender: self.sender.clone(),
-            payload: serde_json::to_value(PoloPayload {})?
+            payload: serde_json::to_value(PoloPayload {
+                role: Some(PeerRole::McpServer),
+            })?
         };
         Ok(self.client_tx.send(ipc_message).await?)
     }
@@ -266,6 +342,12 @@ pub struct DispatchHandle {
 
     /// Identity when sending messages
     sender: MessageSender,
+
+    /// How long to wait for a reply, unless the message waits for the user
+    request_timeout: Duration,
+
+    /// How long to wait for a reply to a message that waits for the user
+    user_wait_timeout: Duration,
 }
 
 impl DispatchHandle {
@@ -295,7 +377,24 @@ impl DispatchHandle {
         );
         actor.spawn();
 
-        Self { actor_tx, sender }
+        Self {
+            actor_tx,
+            sender,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            user_wait_timeout: DEFAULT_USER_WAIT_TIMEOUT,
+        }
+    }
+
+    /// Give up on replies that take longer than `request_timeout`
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Give up on replies that wait for the user after `user_wait_timeout`
+    pub fn with_user_wait_timeout(mut self, user_wait_timeout: Duration) -> Self {
+        self.user_wait_timeout = user_wait_timeout;
+        self
     }
 
     /// Spawn a dispatch actor with a mock actor for testing
@@ -322,7 +421,12 @@ impl DispatchHandle {
         );
         actor.spawn();
 
-        Self { actor_tx, sender }
+        Self {
+            actor_tx,
+            sender,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            user_wait_timeout: DEFAULT_USER_WAIT_TIMEOUT,
+        }
     }
 
     /// Send a message out into the ether and (optionally) await a response.
@@ -348,31 +452,68 @@ impl DispatchHandle {
         };
 
         self.actor_tx
-            .send(DispatchRequest { message, reply_tx })
+            .send(DispatchRequest::Send { message, reply_tx })
             .await?;
 
+        let start = std::time::Instant::now();
         match reply_rx {
-            Some(reply_rx) => tokio::select! {
-                response = reply_rx => {
-                    // Extract data from ResponsePayload wrapper
-                    let response = response?;
-                    if !response.success {
-                        return Err(anyhow::anyhow!("Request failed: {}", response.error.unwrap_or_default()));
-                    }
-                    
-                    // For unit type replies, data can be None
-                    let data = response.data.unwrap_or(serde_json::Value::Null);
-                    Ok(<M::Reply>::deserialize(data)?)
-                }
-                _ = tokio::time::sleep(std::time::Duration::from_secs(30)) => {
-                    return Err(anyhow::anyhow!("Request timed out after 30 seconds"));
+            Some(reply_rx) => {
+                let timeout = if M::WAITS_FOR_USER {
+                    self.user_wait_timeout
+                } else {
+                    self.request_timeout
+                };
+                let response = tokio::time::timeout(timeout, reply_rx)
+                    .await
+                    .map_err(|_| crate::ipc::IPCError::Timeout(timeout))?;
+                crate::metrics::record_ipc_request(start.elapsed());
+
+                // Extract data from ResponsePayload wrapper
+                let response = response?;
+                if !response.success {
+                    return Err(anyhow::anyhow!("Request failed: {}", response.error.unwrap_or_default()));
                 }
-            },
+
+                // For unit type replies, data can be None
+                let data = response.data.unwrap_or(serde_json::Value::Null);
+                Ok(<M::Reply>::deserialize(data)?)
+            }
 
             None => Ok(serde_json::from_value(serde_json::Value::Null)?),
         }
     }
 
+    /// Broadcast a Marco and collect the Polos that answer it within `window`.
+    ///
+    /// Each peer is listed once, however many Polos it sends. Polos without a
+    /// role come from MCP servers that predate roles.
+    pub async fn discover_peers(&self, window: Duration) -> anyhow::Result<Vec<Peer>> {
+        let (polo_tx, mut polo_rx) = mpsc::unbounded_channel();
+        self.actor_tx
+            .send(DispatchRequest::CollectPolos { polo_tx })
+            .await?;
+        self.send(MarcoMessage {}).await?;
+
+        let deadline = tokio::time::Instant::now() + window;
+        let mut peers: Vec<Peer> = vec![];
+        while let Ok(Some(polo)) = tokio::time::timeout_at(deadline, polo_rx.recv()).await {
+            let role = PoloPayload::deserialize(&polo.payload)
+                .ok()
+                .and_then(|payload| payload.role)
+                .unwrap_or(PeerRole::McpServer);
+            if peers.iter().any(|p| p.role == role && p.sender == polo.sender) {
+                continue;
+            }
+            peers.push(Peer {
+                role,
+                is_self: role == PeerRole::McpServer && polo.sender == self.sender,
+                sender: polo.sender,
+            });
+        }
+
+        // Dropping `polo_rx` unregisters us the next time a Polo arrives
+        Ok(peers)
+    }
 }
 
 fn create_sender(shell_pid: Option<u32>) -> crate::types::MessageSender {
@@ -388,7 +529,7 @@ fn create_sender(shell_pid: Option<u32>) -> crate::types::MessageSender {
 }
 
 fn working_directory() -> String {
-    std::env::current_dir()
+    crate::workspace_dir::current_dir()
         .unwrap_or_else(|_| std::path::PathBuf::from("/"))
         .to_string_lossy()
         .to_string()
@@ -2,24 +2,31 @@
 //!
 //! The repeater actor receives messages from clients and broadcasts them to all subscribers.
 //! It maintains a central log of all messages for debugging purposes.
+//!
+//! Each subscriber has a bounded queue
+//! ([`CLIENT_QUEUE_LIMIT`](crate::constants::CLIENT_QUEUE_LIMIT)). A subscriber whose
+//! queue is full is too slow to keep up, so it is dropped (with a warning) rather than
+//! letting its backlog grow without limit; its client handler then disconnects and the
+//! client reconnects with an empty queue.
 
 use std::collections::{HashMap, VecDeque};
 use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
 use tokio::sync::{mpsc, oneshot};
-use tracing::{error, info};
-
-/// Maximum number of messages to keep in history
-const MAX_MESSAGE_HISTORY: usize = 1024;
+use tracing::{error, info, warn};
 
 /// Messages sent to the repeater actor
 #[derive(Debug)]
 pub enum RepeaterMessage {
-    /// Subscribe to receive broadcast messages
-    Subscribe(mpsc::UnboundedSender<String>),
+    /// Subscribe to receive broadcast messages; the channel should hold
+    /// [`CLIENT_QUEUE_LIMIT`](crate::constants::CLIENT_QUEUE_LIMIT) messages
+    Subscribe { client_id: usize, sender: mpsc::Sender<String> },
     /// Incoming message from a client to be broadcast
     IncomingMessage { from_client_id: usize, content: String },
     /// Request debug dump of message history
     DebugDump(oneshot::Sender<Vec<LoggedMessage>>),
+    /// Request statistics about the repeater's state
+    DebugStats(oneshot::Sender<RepeaterStats>),
     /// Set identifier for a client for debugging
     DebugSetIdentifier { client_id: usize, identifier: String },
 }
@@ -33,22 +40,47 @@ pub struct LoggedMessage {
     pub content: String,
 }
 
+/// Snapshot of the repeater's state, reported by `#debug_stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct RepeaterStats {
+    /// Number of messages currently retained
+    pub history_len: usize,
+    /// Maximum number of messages retained before the oldest are evicted
+    pub history_limit: usize,
+    /// Number of messages evicted since the daemon started
+    pub evicted: u64,
+    /// Number of active subscribers
+    pub subscribers: usize,
+    /// Number of subscribers dropped because their queue was full
+    pub slow_subscribers_dropped: u64,
+}
+
 /// The repeater actor that handles message routing and logging
 struct RepeaterActor {
-    /// List of subscribers to broadcast messages to
-    subscribers: Vec<mpsc::UnboundedSender<String>>,
+    /// Subscribers to broadcast messages to, with the id of their client
+    subscribers: Vec<(usize, mpsc::Sender<String>)>,
+    /// Number of subscribers dropped because their queue was full
+    slow_subscribers_dropped: u64,
     /// History of broadcast messages for debugging
     message_history: VecDeque<LoggedMessage>,
+    /// Maximum number of messages to keep in history (oldest are evicted first)
+    history_limit: usize,
+    /// Number of messages evicted from history so far
+    evicted: u64,
     /// Client identifiers for debugging
     client_identifiers: HashMap<usize, String>,
 }
 
 impl RepeaterActor {
-    /// Create a new repeater actor
-    pub fn new() -> Self {
+    /// Create a new repeater actor keeping at most `history_limit` messages (at least one)
+    pub fn new(history_limit: usize) -> Self {
+        let history_limit = history_limit.max(1);
         Self {
             subscribers: Vec::new(),
-            message_history: VecDeque::with_capacity(MAX_MESSAGE_HISTORY),
+            slow_subscribers_dropped: 0,
+            message_history: VecDeque::with_capacity(history_limit),
+            history_limit,
+            evicted: 0,
             client_identifiers: HashMap::new(),
         }
     }
@@ -59,8 +91,8 @@ impl RepeaterActor {
 
         while let Some(message) = receiver.recv().await {
             match message {
-                RepeaterMessage::Subscribe(sender) => {
-                    self.subscribers.push(sender);
+                RepeaterMessage::Subscribe { client_id, sender } => {
+                    self.subscribers.push((client_id, sender));
                     info!("New subscriber added, total: {}", self.subscribers.len());
                 }
                 RepeaterMessage::IncomingMessage { from_client_id, content } => {
@@ -72,6 +104,18 @@ impl RepeaterActor {
                         error!("Failed to send debug dump response");
                     }
                 }
+                RepeaterMessage::DebugStats(response_sender) => {
+                    let stats = RepeaterStats {
+                        history_len: self.message_history.len(),
+                        history_limit: self.history_limit,
+                        evicted: self.evicted,
+                        subscribers: self.subscribers.len(),
+                        slow_subscribers_dropped: self.slow_subscribers_dropped,
+                    };
+                    if let Err(_) = response_sender.send(stats) {
+                        error!("Failed to send debug stats response");
+                    }
+                }
                 RepeaterMessage::DebugSetIdentifier { client_id, identifier } => {
                     self.client_identifiers.insert(client_id, identifier.clone());
                     info!("Set identifier for client {}: {}", client_id, identifier);
@@ -102,9 +146,10 @@ impl RepeaterActor {
             content: content.clone(),
         };
 
-        // Add to history
-        if self.message_history.len() >= MAX_MESSAGE_HISTORY {
+        // Add to history, evicting the oldest messages beyond the limit
+        while self.message_history.len() >= self.history_limit {
             self.message_history.pop_front();
+            self.evicted += 1;
         }
         self.message_history.push_back(logged_message);
 
@@ -119,12 +164,23 @@ impl RepeaterActor {
             }
         }
 
-        // For anything other than a log message, broadcast to all subscribers, removing closed channels
+        // For anything other than a log message, broadcast to all subscribers,
+        // removing closed channels and subscribers too slow to keep up
         if !is_log {
-            self.subscribers.retain(|sender| {
-                match sender.send(content.clone()) {
-                    Ok(_) => true,
-                    Err(_) => {
+            let slow_subscribers_dropped = &mut self.slow_subscribers_dropped;
+            self.subscribers.retain(|(client_id, sender)| {
+                match sender.try_send(content.clone()) {
+                    Ok(()) => true,
+                    Err(mpsc::error::TrySendError::Full(_)) => {
+                        warn!(
+                            "Client {} has {} unread messages, disconnecting it",
+                            client_id,
+                            sender.max_capacity()
+                        );
+                        *slow_subscribers_dropped += 1;
+                        false
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => {
                         // Channel is closed, remove this subscriber
                         false
                     }
@@ -137,9 +193,9 @@ impl RepeaterActor {
 }
 
 /// Spawn a repeater actor task and return the sender for communicating with it
-pub async fn spawn_repeater_task() -> mpsc::UnboundedSender<RepeaterMessage> {
+pub async fn spawn_repeater_task(history_limit: usize) -> mpsc::UnboundedSender<RepeaterMessage> {
     let (repeater_tx, repeater_rx) = mpsc::unbounded_channel::<RepeaterMessage>();
-    let repeater_actor = RepeaterActor::new();
+    let repeater_actor = RepeaterActor::new(history_limit);
     tokio::spawn(repeater_actor.run(repeater_rx));
     repeater_tx
 }
@@ -147,20 +203,21 @@ pub async fn spawn_repeater_task() -> mpsc::UnboundedSender<RepeaterMessage> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::{CLIENT_QUEUE_LIMIT, DEFAULT_MESSAGE_HISTORY_LIMIT};
     use tokio::sync::oneshot;
     use tokio::time::{timeout, Duration};
 
     #[tokio::test]
     async fn test_basic_message_routing() {
-        let tx = spawn_repeater_task().await;
+        let tx = spawn_repeater_task(DEFAULT_MESSAGE_HISTORY_LIMIT).await;
         
         // Create two subscribers
-        let (sub1_tx, mut sub1_rx) = mpsc::unbounded_channel();
-        let (sub2_tx, mut sub2_rx) = mpsc::unbounded_channel();
+        let (sub1_tx, mut sub1_rx) = mpsc::channel(CLIENT_QUEUE_LIMIT);
+        let (sub2_tx, mut sub2_rx) = mpsc::channel(CLIENT_QUEUE_LIMIT);
         
         // Subscribe both
-        tx.send(RepeaterMessage::Subscribe(sub1_tx)).unwrap();
-        tx.send(RepeaterMessage::Subscribe(sub2_tx)).unwrap();
+        tx.send(RepeaterMessage::Subscribe { client_id: 1, sender: sub1_tx }).unwrap();
+        tx.send(RepeaterMessage::Subscribe { client_id: 2, sender: sub2_tx }).unwrap();
         
         // Send a message
         tx.send(RepeaterMessage::IncomingMessage {
@@ -178,7 +235,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_client_identifiers() {
-        let tx = spawn_repeater_task().await;
+        let tx = spawn_repeater_task(DEFAULT_MESSAGE_HISTORY_LIMIT).await;
         
         // Set identifier for client 1
         tx.send(RepeaterMessage::DebugSetIdentifier {
@@ -204,11 +261,11 @@ mod tests {
 
     #[tokio::test]
     async fn test_closed_channel_cleanup() {
-        let tx = spawn_repeater_task().await;
+        let tx = spawn_repeater_task(DEFAULT_MESSAGE_HISTORY_LIMIT).await;
         
         // Create subscriber and then drop it
-        let (sub_tx, sub_rx) = mpsc::unbounded_channel();
-        tx.send(RepeaterMessage::Subscribe(sub_tx)).unwrap();
+        let (sub_tx, sub_rx) = mpsc::channel(CLIENT_QUEUE_LIMIT);
+        tx.send(RepeaterMessage::Subscribe { client_id: 2, sender: sub_tx }).unwrap();
         drop(sub_rx); // Close the receiver
         
         // Send a message - should not panic and should clean up the closed channel
@@ -225,10 +282,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_message_history_limit() {
-        let tx = spawn_repeater_task().await;
+        let tx = spawn_repeater_task(DEFAULT_MESSAGE_HISTORY_LIMIT).await;
         
-        // Send more than MAX_MESSAGE_HISTORY messages
-        for i in 0..MAX_MESSAGE_HISTORY + 10 {
+        // Send more than DEFAULT_MESSAGE_HISTORY_LIMIT messages
+        for i in 0..DEFAULT_MESSAGE_HISTORY_LIMIT + 10 {
             tx.send(RepeaterMessage::IncomingMessage {
                 from_client_id: 1,
                 content: format!("message {}", i),
@@ -241,10 +298,70 @@ mod tests {
         
         let history = timeout(Duration::from_millis(100), dump_rx).await.unwrap().unwrap();
         
-        // Should be limited to MAX_MESSAGE_HISTORY
-        assert_eq!(history.len(), MAX_MESSAGE_HISTORY);
+        // Should be limited to DEFAULT_MESSAGE_HISTORY_LIMIT
+        assert_eq!(history.len(), DEFAULT_MESSAGE_HISTORY_LIMIT);
         
         // Should contain the most recent messages
-        assert!(history.last().unwrap().content.contains(&format!("{}", MAX_MESSAGE_HISTORY + 9)));
+        assert!(history.last().unwrap().content.contains(&format!("{}", DEFAULT_MESSAGE_HISTORY_LIMIT + 9)));
+    }
+
+    #[tokio::test]
+    async fn test_configured_history_limit_evicts_oldest() {
+        let tx = spawn_repeater_task(3).await;
+
+        for i in 0..5 {
+            tx.send(RepeaterMessage::IncomingMessage {
+                from_client_id: 1,
+                content: format!("message {}", i),
+            }).unwrap();
+        }
+
+        let (dump_tx, dump_rx) = oneshot::channel();
+        tx.send(RepeaterMessage::DebugDump(dump_tx)).unwrap();
+        let history = timeout(Duration::from_millis(100), dump_rx).await.unwrap().unwrap();
+
+        // The two oldest messages are evicted, the newest are kept in order
+        let contents: Vec<&str> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["message 2", "message 3", "message 4"]);
+
+        let (stats_tx, stats_rx) = oneshot::channel();
+        tx.send(RepeaterMessage::DebugStats(stats_tx)).unwrap();
+        let stats = timeout(Duration::from_millis(100), stats_rx).await.unwrap().unwrap();
+        assert_eq!(stats.history_len, 3);
+        assert_eq!(stats.history_limit, 3);
+        assert_eq!(stats.evicted, 2);
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_is_dropped() {
+        let tx = spawn_repeater_task(DEFAULT_MESSAGE_HISTORY_LIMIT).await;
+
+        // One subscriber never reads, the other keeps up
+        let (slow_tx, mut slow_rx) = mpsc::channel(4);
+        let (fast_tx, mut fast_rx) = mpsc::channel(4);
+        tx.send(RepeaterMessage::Subscribe { client_id: 1, sender: slow_tx }).unwrap();
+        tx.send(RepeaterMessage::Subscribe { client_id: 2, sender: fast_tx }).unwrap();
+
+        for i in 0..100 {
+            tx.send(RepeaterMessage::IncomingMessage {
+                from_client_id: 3,
+                content: format!("message {}", i),
+            }).unwrap();
+            let received = timeout(Duration::from_millis(100), fast_rx.recv()).await.unwrap().unwrap();
+            assert_eq!(received, format!("message {}", i));
+        }
+
+        let (stats_tx, stats_rx) = oneshot::channel();
+        tx.send(RepeaterMessage::DebugStats(stats_tx)).unwrap();
+        let stats = timeout(Duration::from_millis(100), stats_rx).await.unwrap().unwrap();
+        assert_eq!(stats.subscribers, 1);
+        assert_eq!(stats.slow_subscribers_dropped, 1);
+
+        // The slow subscriber kept only what fit in its queue, then its channel was closed
+        let mut backlog = Vec::new();
+        while let Some(message) = slow_rx.recv().await {
+            backlog.push(message);
+        }
+        assert_eq!(backlog, vec!["message 0", "message 1", "message 2", "message 3"]);
     }
 }
@@ -0,0 +1,61 @@
+//! Detection of which agent CLI is driving this MCP server
+//!
+//! Agents differ in how they surface and invoke MCP tools, so the yiasou prompt
+//! includes a small agent-specific guidance snippet when we know which one is running.
+
+use crate::constants::AGENT_ENV_VAR;
+
+/// Agent CLIs that we have specific guidance for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentCli {
+    QCli,
+    ClaudeCode,
+}
+
+impl AgentCli {
+    /// Parse an agent name as given by the user or the launching agent
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "q" | "qcli" | "q-cli" | "q chat" => Some(AgentCli::QCli),
+            "claude" | "claude-code" | "claudecode" => Some(AgentCli::ClaudeCode),
+            _ => None,
+        }
+    }
+
+    /// Detect the agent from the environment inherited from the launching agent
+    ///
+    /// An explicit `SYMPOSIUM_AGENT` always wins; otherwise we fall back to
+    /// markers the agents set themselves.
+    pub fn detect_from_env() -> Option<Self> {
+        if let Ok(name) = std::env::var(AGENT_ENV_VAR) {
+            return Self::from_name(&name);
+        }
+
+        if std::env::var("CLAUDECODE").is_ok_and(|v| v == "1") {
+            return Some(AgentCli::ClaudeCode);
+        }
+
+        None
+    }
+
+    /// Guidance file (relative to the guidance folder) with agent-specific tips
+    pub fn guidance_file(self) -> &'static str {
+        match self {
+            AgentCli::QCli => "agents/q-cli.md",
+            AgentCli::ClaudeCode => "agents/claude-code.md",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(AgentCli::from_name("q"), Some(AgentCli::QCli));
+        assert_eq!(AgentCli::from_name("Q-CLI"), Some(AgentCli::QCli));
+        assert_eq!(AgentCli::from_name("claude-code"), Some(AgentCli::ClaudeCode));
+        assert_eq!(AgentCli::from_name("emacs"), None);
+    }
+}
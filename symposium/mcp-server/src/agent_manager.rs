@@ -17,6 +17,19 @@ use std::time::SystemTime;
 use tokio::fs;
 use tracing::{debug, info, warn};
 
+/// Version of the sessions file format written by this build.
+///
+/// - Version 0: a bare map from session UUID to [`AgentSession`]
+/// - Version 1: `{"schema_version": 1, "sessions": {...}}`
+const SESSIONS_SCHEMA_VERSION: u64 = 1;
+
+/// On-disk layout of the sessions file
+#[derive(Serialize)]
+struct SessionsFile<'a> {
+    schema_version: u64,
+    sessions: &'a HashMap<String, AgentSession>,
+}
+
 /// Agent session metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentSession {
@@ -27,6 +40,9 @@ pub struct AgentSession {
     pub status: AgentStatus,
     pub created_at: SystemTime,
     pub last_attached: Option<SystemTime>,
+    /// Taskspace the agent works in, if it was spawned for one
+    #[serde(default)]
+    pub taskspace_uuid: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +53,30 @@ pub enum AgentStatus {
     Stopped,
 }
 
+/// Script-friendly view of an agent session (used by `agent list --json`)
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentSessionSummary {
+    pub uuid: String,
+    pub status: AgentStatus,
+    pub tmux_session_name: String,
+    pub workdir: PathBuf,
+    pub agent_args: Vec<String>,
+    pub taskspace_uuid: Option<String>,
+}
+
+impl From<&AgentSession> for AgentSessionSummary {
+    fn from(session: &AgentSession) -> Self {
+        Self {
+            uuid: session.uuid.clone(),
+            status: session.status.clone(),
+            tmux_session_name: session.tmux_session_name.clone(),
+            workdir: session.working_directory.clone(),
+            agent_args: session.agent_command.clone(),
+            taskspace_uuid: session.taskspace_uuid.clone(),
+        }
+    }
+}
+
 /// Manages persistent agent sessions using tmux
 pub struct AgentManager {
     sessions: HashMap<String, AgentSession>,
@@ -60,12 +100,16 @@ impl AgentManager {
         Ok(manager)
     }
 
-    /// Spawn a new persistent agent session
+    /// Spawn a new persistent agent session, optionally for a taskspace
+    ///
+    /// A taskspace has at most one agent session, so spawning a second one for
+    /// the same taskspace fails.
     pub async fn spawn_agent(
         &mut self,
         uuid: String,
         agent_command: Vec<String>,
         working_directory: PathBuf,
+        taskspace_uuid: Option<String>,
     ) -> Result<()> {
         info!("Spawning agent session {} with command: {:?}", uuid, agent_command);
 
@@ -76,6 +120,15 @@ impl AgentManager {
         if self.sessions.contains_key(&uuid) {
             return Err(anyhow!("Agent session {} already exists", uuid));
         }
+        if let Some(taskspace) = &taskspace_uuid {
+            if let Some(existing) = self.find_by_taskspace(taskspace) {
+                return Err(anyhow!(
+                    "Taskspace {} already has agent session {}",
+                    taskspace,
+                    existing.uuid
+                ));
+            }
+        }
 
         // Create tmux session with agent command
         let mut tmux_cmd = Command::new("tmux");
@@ -118,6 +171,7 @@ impl AgentManager {
             status: AgentStatus::Starting,
             created_at: SystemTime::now(),
             last_attached: None,
+            taskspace_uuid,
         };
 
         // Store session
@@ -173,6 +227,13 @@ impl AgentManager {
         self.sessions.get(uuid)
     }
 
+    /// Find the agent session spawned for a taskspace
+    pub fn find_by_taskspace(&self, taskspace_uuid: &str) -> Option<&AgentSession> {
+        self.sessions
+            .values()
+            .find(|session| session.taskspace_uuid.as_deref() == Some(taskspace_uuid))
+    }
+
     /// Kill an agent session
     pub async fn kill_agent(&mut self, uuid: &str) -> Result<()> {
         let session = self.sessions.get(uuid)
@@ -254,6 +315,11 @@ impl AgentManager {
     }
 
     /// Load sessions from persistent storage
+    ///
+    /// Loading is best-effort: files from older versions are upgraded, and
+    /// sessions (or whole files) that can't be read are skipped with a warning.
+    /// Whenever something is skipped, the original file is first copied to
+    /// `<file>.bak`, since the next save would otherwise lose it.
     async fn load_sessions(&mut self) -> Result<()> {
         if !self.sessions_file.exists() {
             debug!("No existing sessions file found");
@@ -261,16 +327,71 @@ impl AgentManager {
         }
 
         let content = fs::read_to_string(&self.sessions_file).await?;
-        let sessions: HashMap<String, AgentSession> = serde_json::from_str(&content)?;
-        
+        let (sessions, complete) = Self::parse_sessions(&content);
+        if !complete {
+            let backup = self.sessions_file.with_extension("json.bak");
+            warn!(
+                "Some agent sessions could not be read; keeping a copy of {} at {}",
+                self.sessions_file.display(),
+                backup.display()
+            );
+            fs::write(&backup, &content).await?;
+        }
+
         self.sessions = sessions;
         info!("Loaded {} agent sessions from disk", self.sessions.len());
         Ok(())
     }
 
+    /// Parse the contents of a sessions file of any version, returning the
+    /// sessions that could be read and whether that was all of them
+    fn parse_sessions(content: &str) -> (HashMap<String, AgentSession>, bool) {
+        let value: serde_json::Value = match serde_json::from_str(content) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Ignoring unreadable sessions file: {}", e);
+                return (HashMap::new(), false);
+            }
+        };
+
+        // Version 0 files have no version field: the whole file is the session map
+        let version = value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0);
+        let entries = if version == 0 { Some(&value) } else { value.get("sessions") };
+        let Some(entries) = entries.and_then(|entries| entries.as_object()) else {
+            warn!("Ignoring sessions file (version {}) without a session map", version);
+            return (HashMap::new(), false);
+        };
+
+        let mut complete = true;
+        if version > SESSIONS_SCHEMA_VERSION {
+            warn!(
+                "Sessions file has version {}, newer than {}; reading what we can",
+                version, SESSIONS_SCHEMA_VERSION
+            );
+            complete = false;
+        }
+
+        let mut sessions = HashMap::new();
+        for (uuid, entry) in entries {
+            match serde_json::from_value::<AgentSession>(entry.clone()) {
+                Ok(session) => {
+                    sessions.insert(uuid.clone(), session);
+                }
+                Err(e) => {
+                    warn!("Skipping unreadable agent session {}: {}", uuid, e);
+                    complete = false;
+                }
+            }
+        }
+        (sessions, complete)
+    }
+
     /// Save sessions to persistent storage
     async fn save_sessions(&self) -> Result<()> {
-        let content = serde_json::to_string_pretty(&self.sessions)?;
+        let content = serde_json::to_string_pretty(&SessionsFile {
+            schema_version: SESSIONS_SCHEMA_VERSION,
+            sessions: &self.sessions,
+        })?;
         
         // Ensure parent directory exists
         if let Some(parent) = self.sessions_file.parent() {
@@ -321,6 +442,7 @@ mod tests {
                 "test-uuid".to_string(),
                 vec!["sleep".to_string(), "30".to_string()],
                 temp_dir.path().to_path_buf(),
+                None,
             ).await.unwrap();
             
             // Verify session was created
@@ -343,6 +465,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_session_summary_json() {
+        let session = AgentSession {
+            uuid: "test-uuid".to_string(),
+            tmux_session_name: "symposium-agent-test-uuid".to_string(),
+            agent_command: vec!["q".to_string(), "chat".to_string()],
+            working_directory: PathBuf::from("/tmp/work"),
+            status: AgentStatus::Running,
+            created_at: SystemTime::now(),
+            last_attached: None,
+            taskspace_uuid: Some("task-uuid".to_string()),
+        };
+
+        let json = serde_json::to_value(AgentSessionSummary::from(&session)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "uuid": "test-uuid",
+                "status": "Running",
+                "tmux_session_name": "symposium-agent-test-uuid",
+                "workdir": "/tmp/work",
+                "agent_args": ["q", "chat"],
+                "taskspace_uuid": "task-uuid",
+            })
+        );
+    }
+
     #[tokio::test]
     async fn test_session_file_persistence() {
         let temp_dir = tempdir().unwrap();
@@ -363,6 +512,7 @@ mod tests {
                 status: AgentStatus::Running,
                 created_at: SystemTime::now(),
                 last_attached: None,
+                taskspace_uuid: None,
             };
             
             manager.sessions.insert("test-uuid".to_string(), session);
@@ -381,4 +531,134 @@ mod tests {
             assert!(manager.sessions.contains_key("test-uuid"));
         }
     }
+
+    #[tokio::test]
+    async fn test_find_by_taskspace() {
+        let temp_dir = tempdir().unwrap();
+        let sessions_file = temp_dir.path().join("sessions.json");
+
+        // Sessions saved before taskspaces were recorded have no `taskspace_uuid`
+        let old_format = serde_json::json!({
+            "old-uuid": {
+                "uuid": "old-uuid",
+                "tmux_session_name": "symposium-agent-old-uuid",
+                "agent_command": ["q", "chat"],
+                "working_directory": "/tmp/old",
+                "status": "Running",
+                "created_at": { "secs_since_epoch": 0, "nanos_since_epoch": 0 },
+                "last_attached": null
+            }
+        });
+        std::fs::write(&sessions_file, old_format.to_string()).unwrap();
+
+        let mut manager = AgentManager {
+            sessions: HashMap::new(),
+            sessions_file,
+        };
+        manager.load_sessions().await.unwrap();
+        assert_eq!(manager.get_session("old-uuid").unwrap().taskspace_uuid, None);
+
+        let mut session = manager.get_session("old-uuid").unwrap().clone();
+        session.uuid = "new-uuid".to_string();
+        session.taskspace_uuid = Some("task-uuid".to_string());
+        manager.sessions.insert("new-uuid".to_string(), session);
+
+        assert_eq!(manager.find_by_taskspace("task-uuid").unwrap().uuid, "new-uuid");
+        assert!(manager.find_by_taskspace("other-task").is_none());
+
+        // A second agent for the same taskspace is refused before tmux is involved
+        let error = manager
+            .spawn_agent(
+                "dup-uuid".to_string(),
+                vec!["q".to_string()],
+                PathBuf::from("/tmp"),
+                Some("task-uuid".to_string()),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("already has agent session new-uuid"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_v0_sessions_file() {
+        let temp_dir = tempdir().unwrap();
+        let sessions_file = temp_dir.path().join("sessions.json");
+
+        // Version 0: a bare map of sessions, without `taskspace_uuid`
+        let v0 = serde_json::json!({
+            "test-uuid": {
+                "uuid": "test-uuid",
+                "tmux_session_name": "symposium-agent-test-uuid",
+                "agent_command": ["q", "chat"],
+                "working_directory": "/tmp/work",
+                "status": "Running",
+                "created_at": { "secs_since_epoch": 0, "nanos_since_epoch": 0 },
+                "last_attached": null
+            }
+        });
+        std::fs::write(&sessions_file, v0.to_string()).unwrap();
+
+        let mut manager = AgentManager {
+            sessions: HashMap::new(),
+            sessions_file: sessions_file.clone(),
+        };
+        manager.load_sessions().await.unwrap();
+        assert_eq!(manager.sessions.len(), 1);
+        assert_eq!(manager.get_session("test-uuid").unwrap().working_directory, PathBuf::from("/tmp/work"));
+
+        // Nothing was lost, so there is no backup
+        assert!(!temp_dir.path().join("sessions.json.bak").exists());
+
+        // Saving writes the current version, which loads back the same
+        manager.save_sessions().await.unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&sessions_file).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], SESSIONS_SCHEMA_VERSION);
+        assert_eq!(saved["sessions"]["test-uuid"]["uuid"], "test-uuid");
+
+        manager.sessions.clear();
+        manager.load_sessions().await.unwrap();
+        assert!(manager.get_session("test-uuid").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_sessions_are_skipped_and_backed_up() {
+        let temp_dir = tempdir().unwrap();
+        let sessions_file = temp_dir.path().join("sessions.json");
+        let backup_file = temp_dir.path().join("sessions.json.bak");
+
+        let newer = serde_json::json!({
+            "schema_version": SESSIONS_SCHEMA_VERSION + 1,
+            "sessions": {
+                "good": {
+                    "uuid": "good",
+                    "tmux_session_name": "symposium-agent-good",
+                    "agent_command": [],
+                    "working_directory": "/tmp",
+                    "status": "Running",
+                    "created_at": { "secs_since_epoch": 0, "nanos_since_epoch": 0 },
+                    "last_attached": null,
+                    "future_field": true
+                },
+                "bad": { "uuid": "bad", "status": "Hibernating" }
+            }
+        })
+        .to_string();
+        std::fs::write(&sessions_file, &newer).unwrap();
+
+        let mut manager = AgentManager {
+            sessions: HashMap::new(),
+            sessions_file: sessions_file.clone(),
+        };
+        manager.load_sessions().await.unwrap();
+        assert_eq!(manager.sessions.keys().collect::<Vec<_>>(), ["good"]);
+        assert_eq!(std::fs::read_to_string(&backup_file).unwrap(), newer);
+
+        // A file that isn't JSON at all leaves no sessions, but doesn't fail
+        std::fs::write(&sessions_file, "not json").unwrap();
+        manager.sessions.clear();
+        manager.load_sessions().await.unwrap();
+        assert!(manager.sessions.is_empty());
+        assert_eq!(std::fs::read_to_string(&backup_file).unwrap(), "not json");
+    }
 }
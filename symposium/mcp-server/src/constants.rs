@@ -12,12 +12,113 @@ pub const DEV_LOG_FILENAME: &str = "symposium-mcp.log";
 /// Default idle timeout for daemon in seconds
 pub const DEFAULT_DAEMON_IDLE_TIMEOUT: u64 = 30;
 
+/// Default time in seconds to wait for the IDE to answer an IPC request
+pub const DEFAULT_IPC_TIMEOUT: u64 = 10;
+
+/// Time in seconds to wait for a reply that needs the user (e.g., patch confirmation)
+pub const USER_WAIT_TIMEOUT: u64 = 30 * 60;
+
+/// Default number of messages the daemon keeps for `debug dump-messages`
+pub const DEFAULT_MESSAGE_HISTORY_LIMIT: usize = 1024;
+
+/// Broadcast messages the daemon queues for one client; a client that falls further behind is disconnected
+pub const CLIENT_QUEUE_LIMIT: usize = 1024;
+
+/// Project onboarding notes, relative to the workspace root, appended to the `/yiasou` prompt when present
+pub const PROJECT_ONBOARDING_FILE: &str = ".symposium/onboarding.md";
+
+/// Project guidance overriding or adding to the embedded guidance files, relative to the workspace root
+pub const PROJECT_GUIDANCE_DIR: &str = ".symposium/guidance";
+
+/// Project defaults (collaborator, log level), relative to the workspace root
+pub const PROJECT_CONFIG_FILE: &str = ".symposium/config.toml";
+
+/// Maximum number of crates `get_rust_crate_sources` extracts and searches at once
+pub const MAX_CONCURRENT_CRATE_SEARCHES: usize = 4;
+
+/// Walkthrough chunks that may wait to be sent while the next ones are parsed
+pub const WALKTHROUGH_CHUNK_QUEUE: usize = 4;
+
+/// Default number of paths `find_files` returns
+pub const DEFAULT_FIND_FILES_MAX_RESULTS: usize = 200;
+
+/// Default number of messages `debug_recent_messages` returns
+pub const DEFAULT_DEBUG_MESSAGE_COUNT: usize = 50;
+
+/// Default milliseconds `list_peers` waits for Polo replies
+pub const DEFAULT_PEER_DISCOVERY_WINDOW_MS: u64 = 500;
+
+/// Longest `list_peers` will wait for Polo replies, whatever it is asked for
+pub const MAX_PEER_DISCOVERY_WINDOW_MS: u64 = 5000;
+
+/// Default size limit in bytes for tool results before they are truncated
+pub const DEFAULT_MAX_RESULT_BYTES: usize = 64 * 1024;
+
+/// Environment variable a launching agent can set to identify itself (e.g., "q", "claude-code")
+pub const AGENT_ENV_VAR: &str = "SYMPOSIUM_AGENT";
+
+/// Environment variable that opts in to the local usage log (set to "1")
+pub const USAGE_LOG_ENV_VAR: &str = "SYMPOSIUM_USAGE_LOG";
+
+/// File name of the local usage log
+pub const USAGE_LOG_FILENAME: &str = "usage.json";
+
+/// Environment variable overriding the directory that holds the daemon socket
+pub const RUNTIME_DIR_ENV_VAR: &str = "SYMPOSIUM_RUNTIME_DIR";
+
+/// Directory for runtime files like the daemon socket:
+/// `$SYMPOSIUM_RUNTIME_DIR`, then `$XDG_RUNTIME_DIR`, then [`TEMP_DIR`]
+pub fn runtime_dir() -> String {
+    select_runtime_dir(
+        std::env::var(RUNTIME_DIR_ENV_VAR).ok(),
+        std::env::var("XDG_RUNTIME_DIR").ok(),
+    )
+}
+
+fn select_runtime_dir(symposium_dir: Option<String>, xdg_dir: Option<String>) -> String {
+    symposium_dir
+        .into_iter()
+        .chain(xdg_dir)
+        .find(|dir| !dir.is_empty())
+        .unwrap_or_else(|| TEMP_DIR.to_string())
+}
+
 /// Daemon socket path with custom prefix
 pub fn daemon_socket_path(prefix: &str) -> String {
-    format!("{}/{}.sock", TEMP_DIR, prefix)
+    socket_path_in(&runtime_dir(), prefix)
+}
+
+fn socket_path_in(runtime_dir: &str, prefix: &str) -> String {
+    format!("{}/{}.sock", runtime_dir, prefix)
 }
 
 /// Development log file path
 pub fn dev_log_path() -> String {
     format!("{}/{}", TEMP_DIR, DEV_LOG_FILENAME)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_dir_precedence() {
+        let dir = |symposium: Option<&str>, xdg: Option<&str>| {
+            select_runtime_dir(symposium.map(String::from), xdg.map(String::from))
+        };
+
+        assert_eq!(dir(Some("/run/symposium"), Some("/run/user/1000")), "/run/symposium");
+        assert_eq!(dir(None, Some("/run/user/1000")), "/run/user/1000");
+        assert_eq!(dir(Some(""), Some("/run/user/1000")), "/run/user/1000");
+        assert_eq!(dir(None, None), TEMP_DIR);
+    }
+
+    #[test]
+    fn test_daemon_socket_path_respects_runtime_dir() {
+        let runtime_dir = select_runtime_dir(Some("/run/symposium".to_string()), None);
+        assert_eq!(
+            socket_path_in(&runtime_dir, "symposium-test"),
+            "/run/symposium/symposium-test.sock"
+        );
+    }
+}
@@ -26,14 +26,18 @@ pub async fn handle_client(
     let mut line = String::new();
 
     // Create channel to receive messages from repeater
-    let (client_tx, mut client_rx) = mpsc::unbounded_channel::<String>();
+    let (client_tx, mut client_rx) =
+        mpsc::channel::<String>(crate::constants::CLIENT_QUEUE_LIMIT);
     
     // Subscribe to repeater
-    if let Err(e) = repeater_tx.send(RepeaterMessage::Subscribe(client_tx)) {
+    if let Err(e) = repeater_tx.send(RepeaterMessage::Subscribe { client_id, sender: client_tx }) {
         error!("Failed to subscribe client {} to repeater: {}", client_id, e);
         return;
     }
 
+    // Count this client as connected until the handler exits (or is aborted)
+    let _client_guard = crate::metrics::DaemonClientGuard::new();
+
     loop {
         tokio::select! {
             // Read messages from this client
@@ -87,6 +91,7 @@ pub async fn handle_client(
                         }
                     }
                     None => {
+                        // The repeater stopped, or dropped this client for falling behind
                         info!("Repeater channel closed, disconnecting client {}", client_id);
                         break;
                     }
@@ -131,6 +136,25 @@ async fn handle_debug_command(
             Err(_) => "[]".to_string(),
         };
         
+        let response_with_newline = format!("{}\n", response);
+        if let Err(e) = writer.write_all(response_with_newline.as_bytes()).await {
+            error!("Failed to send debug response: {}", e);
+        } else if let Err(e) = writer.flush().await {
+            error!("Failed to flush debug response: {}", e);
+        }
+    } else if command == "#debug_stats" {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        if let Err(e) = repeater_tx.send(RepeaterMessage::DebugStats(response_tx)) {
+            error!("Failed to request debug stats: {}", e);
+            return;
+        }
+
+        let response = match response_rx.await {
+            Ok(stats) => serde_json::to_string(&stats).unwrap_or_else(|_| "{}".to_string()),
+            Err(_) => "{}".to_string(),
+        };
+
         let response_with_newline = format!("{}\n", response);
         if let Err(e) = writer.write_all(response_with_newline.as_bytes()).await {
             error!("Failed to send debug response: {}", e);
@@ -148,15 +172,78 @@ async fn handle_debug_command(
     }
 }
 
+/// Ask a running daemon for its recent message history via `#debug_dump_messages`
+///
+/// Returns one JSON object per message (`timestamp`, `from_identifier`, `content`).
+pub async fn request_message_history(socket_path: &str) -> Result<Vec<serde_json::Value>> {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+
+    let stream = UnixStream::connect(socket_path).await?;
+    let (reader, mut writer) = stream.into_split();
+
+    // Send debug command
+    writer.write_all(b"#debug_dump_messages\n").await?;
+    writer.flush().await?;
+
+    read_debug_response(reader).await
+}
+
+/// A debug response that is not the expected JSON. Keeps the raw text, since it
+/// is the only clue to what the daemon actually sent.
+#[derive(Debug, thiserror::Error)]
+#[error("Malformed debug response ({} bytes)", raw.len())]
+pub struct MalformedDebugResponse {
+    pub raw: String,
+    #[source]
+    source: serde_json::Error,
+}
+
+/// Read a debug response: a single JSON line, however many chunks it arrives in.
+///
+/// Large histories make for very long lines that the daemon writes (and the socket
+/// delivers) piecemeal, so we buffer raw bytes up to the newline before decoding.
+/// A missing trailing newline (the daemon closed the connection) is tolerated.
+async fn read_debug_response<R>(reader: R) -> Result<Vec<serde_json::Value>>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use anyhow::Context;
+    use tokio::io::AsyncBufReadExt;
+
+    let mut buf_reader = tokio::io::BufReader::new(reader);
+    let mut response = Vec::new();
+    buf_reader.read_until(b'\n', &mut response).await?;
+
+    let response = std::str::from_utf8(&response)
+        .context("Debug response is not valid UTF-8")?
+        .trim();
+    if response.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    serde_json::from_str(response).map_err(|source| {
+        MalformedDebugResponse {
+            raw: response.to_string(),
+            source,
+        }
+        .into()
+    })
+}
+
 /// Run the message bus daemon with idle timeout instead of VSCode PID monitoring
-/// Daemon will automatically shut down after idle_timeout seconds of no connected clients
+/// Daemon will automatically shut down after idle_timeout seconds of no connected clients.
+/// With a `disconnect_grace`, it instead shuts down that long after the last client
+/// disconnects, unless a client reconnects in the meantime.
+/// At most `history_limit` messages are kept for `debug dump-messages`.
 pub async fn run_daemon_with_idle_timeout(
     socket_prefix: &str,
     idle_timeout_secs: u64,
+    disconnect_grace: Option<Duration>,
+    history_limit: usize,
     ready_barrier: Option<std::sync::Arc<tokio::sync::Barrier>>,
 ) -> Result<()> {
     use std::os::unix::net::UnixListener;
-    use std::path::Path;
 
     let socket_path = crate::constants::daemon_socket_path(socket_prefix);
     info!("daemon: attempting to claim socket: {}", socket_path);
@@ -194,8 +281,8 @@ pub async fn run_daemon_with_idle_timeout(
     };
 
     info!(
-        "🚀 daemon: message bus daemon started with {} second idle timeout",
-        idle_timeout_secs
+        "🚀 daemon: message bus daemon started with {} second idle timeout, keeping {} messages of history",
+        idle_timeout_secs, history_limit
     );
     info!("📡 daemon: listening on socket: {}", socket_path);
 
@@ -203,19 +290,20 @@ pub async fn run_daemon_with_idle_timeout(
     _listener.set_nonblocking(true)?;
     let listener = tokio::net::UnixListener::from_std(_listener)?;
 
-    // Signal that daemon is ready to accept connections
-    println!("DAEMON_READY");
-
-    // Set up graceful shutdown handling
+    // Set up graceful shutdown handling. The handlers are installed before we
+    // announce readiness so that a SIGTERM sent right after DAEMON_READY still
+    // goes through the clean shutdown path instead of the default disposition.
     let socket_path_for_cleanup = socket_path.clone();
 
-    // Create signal handlers
-    let ctrl_c = signal::ctrl_c();
-
-    let mut sigterm = {
+    // Create signal handlers. `signal::ctrl_c()` only registers on first poll,
+    // so SIGINT gets an explicit stream just like SIGTERM.
+    let (mut sigint, mut sigterm) = {
         #[cfg(unix)]
         {
-            signal::unix::signal(signal::unix::SignalKind::terminate())?
+            (
+                signal::unix::signal(signal::unix::SignalKind::interrupt())?,
+                signal::unix::signal(signal::unix::SignalKind::terminate())?,
+            )
         }
 
         #[cfg(not(unix))]
@@ -224,10 +312,13 @@ pub async fn run_daemon_with_idle_timeout(
         }
     };
 
+    // Signal that daemon is ready to accept connections
+    println!("DAEMON_READY");
+
     let shutdown = async move {
         tokio::select! {
             // Handle SIGTERM/SIGINT for graceful shutdown
-            _ = ctrl_c => {
+            _ = sigint.recv() => {
                 info!("🛑 Received SIGINT (Ctrl+C), shutting down gracefully...");
             }
             _ = sigterm.recv() => {
@@ -237,13 +328,21 @@ pub async fn run_daemon_with_idle_timeout(
     };
 
     let shutdown_result =
-        run_message_bus_with_shutdown_signal(listener, idle_timeout_secs, ready_barrier, shutdown)
-            .await;
-
-    // Clean up socket file on exit
-    if Path::new(&socket_path_for_cleanup).exists() {
-        std::fs::remove_file(&socket_path_for_cleanup)?;
-        info!("🧹 Cleaned up socket file: {}", socket_path_for_cleanup);
+        run_message_bus_with_shutdown_signal(
+            listener,
+            idle_timeout_secs,
+            disconnect_grace,
+            history_limit,
+            ready_barrier,
+            shutdown,
+        )
+        .await;
+
+    // Clean up socket file on exit so a restarted daemon can claim it immediately
+    match std::fs::remove_file(&socket_path_for_cleanup) {
+        Ok(()) => info!("🧹 Cleaned up socket file: {}", socket_path_for_cleanup),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => error!("⚠️  Could not remove socket file {}: {}", socket_path_for_cleanup, e),
     }
 
     info!("🛑 Daemon shutdown complete");
@@ -253,10 +352,13 @@ pub async fn run_daemon_with_idle_timeout(
 }
 
 /// Run the message bus loop with idle timeout and shutdown signal
-/// Shuts down when no clients connected for timeout period OR when shutdown signal received
+/// Shuts down when no clients connected for timeout period, when the disconnect grace
+/// runs out after the last client left, OR when shutdown signal received
 async fn run_message_bus_with_shutdown_signal(
     listener: tokio::net::UnixListener,
     idle_timeout_secs: u64,
+    disconnect_grace: Option<Duration>,
+    history_limit: usize,
     ready_barrier: Option<std::sync::Arc<tokio::sync::Barrier>>,
     shutdown: impl Future<Output = ()>,
 ) -> Result<()> {
@@ -270,12 +372,18 @@ async fn run_message_bus_with_shutdown_signal(
     }
 
     // Create repeater actor for message routing
-    let repeater_tx = spawn_repeater_task().await;
+    let repeater_tx = spawn_repeater_task(history_limit).await;
 
     // Track connected clients
     let mut clients: HashMap<usize, tokio::task::JoinHandle<()>> = HashMap::new();
     let mut next_client_id = 0;
 
+    // Client handlers report here when they finish, so the grace timer starts right away
+    let (disconnected_tx, mut disconnected_rx) = mpsc::unbounded_channel::<usize>();
+
+    // When the daemon shuts down unless a client reconnects; set while the last client is gone
+    let mut grace_deadline: Option<Instant> = None;
+
     // Track when we last had connected clients
     let mut last_activity = Instant::now();
     let idle_timeout = Duration::from_secs(idle_timeout_secs);
@@ -299,9 +407,17 @@ async fn run_message_bus_with_shutdown_signal(
                         // Update activity timestamp
                         last_activity = Instant::now();
 
+                        if grace_deadline.take().is_some() {
+                            info!("daemon: client {} connected within the disconnect grace, staying up", client_id);
+                        }
+
                         // Spawn task to handle this client
                         let repeater_tx_clone = repeater_tx.clone();
-                        let handle = tokio::spawn(handle_client(client_id, stream, repeater_tx_clone));
+                        let disconnected_tx = disconnected_tx.clone();
+                        let handle = tokio::spawn(async move {
+                            handle_client(client_id, stream, repeater_tx_clone).await;
+                            let _ = disconnected_tx.send(client_id);
+                        });
                         clients.insert(client_id, handle);
                     }
                     Err(e) => {
@@ -310,6 +426,24 @@ async fn run_message_bus_with_shutdown_signal(
                 }
             }
 
+            // Start the grace timer when the last client leaves
+            Some(client_id) = disconnected_rx.recv() => {
+                if clients.remove(&client_id).is_some() {
+                    info!("daemon: client {} disconnected", client_id);
+                }
+                last_activity = Instant::now();
+                if let (true, Some(grace)) = (clients.is_empty(), disconnect_grace) {
+                    info!("daemon: last client disconnected, shutting down in {:.1}s unless one reconnects", grace.as_secs_f64());
+                    grace_deadline = Some(Instant::now() + grace);
+                }
+            }
+
+            // Nobody reconnected in time
+            _ = tokio::time::sleep_until(grace_deadline.unwrap_or_else(Instant::now)), if grace_deadline.is_some() => {
+                info!("daemon: no client reconnected within the disconnect grace, shutting down");
+                break;
+            }
+
             // Check for idle timeout
             _ = idle_check_interval.tick() => {
                 // Clean up finished client tasks first
@@ -322,8 +456,9 @@ async fn run_message_bus_with_shutdown_signal(
                     }
                 });
 
-                // If no clients connected and idle timeout exceeded, shutdown
-                if clients.is_empty() {
+                // If no clients connected and idle timeout exceeded, shutdown.
+                // A running disconnect grace decides on its own.
+                if clients.is_empty() && grace_deadline.is_none() {
                     let idle_duration = last_activity.elapsed();
                     if idle_duration >= idle_timeout {
                         info!(
@@ -425,3 +560,115 @@ pub async fn run_client(socket_prefix: &str, auto_start: bool, identity_prefix:
     info!("Client bridge shutting down");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    /// Build a `#debug_dump_messages` response with `count` messages
+    fn synthetic_history(count: usize) -> String {
+        let entries: Vec<serde_json::Value> = (0..count)
+            .map(|i| {
+                serde_json::json!({
+                    "timestamp": i,
+                    "from_identifier": format!("MCP-Server-{}", i % 7),
+                    "content": format!("message {i} with multi-byte text ✓ {}", "x".repeat(100))
+                })
+            })
+            .collect();
+        serde_json::to_string(&entries).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_debug_response_reassembles_chunks() {
+        let response = format!("{}\n", synthetic_history(5000));
+        assert!(response.len() > 500_000);
+
+        // A small pipe forces the reader to see many partial reads
+        let (client, mut daemon) = tokio::io::duplex(1024);
+        let writer = tokio::spawn(async move {
+            // Odd-sized chunks split multi-byte characters across writes
+            for chunk in response.as_bytes().chunks(777) {
+                daemon.write_all(chunk).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let messages = read_debug_response(client).await.unwrap();
+        writer.await.unwrap();
+
+        assert_eq!(messages.len(), 5000);
+        assert_eq!(messages[4999]["timestamp"], 4999);
+        assert!(messages[4999]["content"].as_str().unwrap().starts_with("message 4999 with multi-byte text ✓"));
+    }
+
+    #[tokio::test]
+    async fn test_read_debug_response_without_trailing_newline() {
+        let (client, mut daemon) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            daemon.write_all(synthetic_history(3).as_bytes()).await.unwrap();
+            // Dropping the write half closes the stream without a newline
+        });
+
+        let messages = read_debug_response(client).await.unwrap();
+        writer.await.unwrap();
+        assert_eq!(messages.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_read_debug_response_empty() {
+        let (client, daemon) = tokio::io::duplex(64);
+        drop(daemon);
+
+        assert!(read_debug_response(client).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_debug_response_keeps_malformed_payload() {
+        let (client, mut daemon) = tokio::io::duplex(64);
+        daemon.write_all(b"not json\n").await.unwrap();
+
+        let error = read_debug_response(client).await.unwrap_err();
+        let malformed = error.downcast_ref::<MalformedDebugResponse>().unwrap();
+        assert_eq!(malformed.raw, "not json");
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_within_disconnect_grace() {
+        use tokio::time::{sleep, timeout};
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let listener = tokio::net::UnixListener::bind(&socket_path).unwrap();
+
+        // Long idle timeout, so only the grace can shut the daemon down
+        let grace = Duration::from_millis(500);
+        let daemon = tokio::spawn(run_message_bus_with_shutdown_signal(
+            listener,
+            3600,
+            Some(grace),
+            crate::constants::DEFAULT_MESSAGE_HISTORY_LIMIT,
+            None,
+            std::future::pending(),
+        ));
+
+        let client = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        drop(client);
+
+        // Reconnect halfway through the grace and stay past its original end
+        sleep(grace / 2).await;
+        let client = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+        sleep(grace).await;
+        assert!(!daemon.is_finished(), "daemon shut down although a client reconnected");
+
+        // Once the last client is gone for the whole grace, the daemon stops
+        drop(client);
+        timeout(grace * 4, daemon)
+            .await
+            .expect("daemon did not shut down after the disconnect grace")
+            .unwrap()
+            .unwrap();
+    }
+}
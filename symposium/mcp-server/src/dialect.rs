@@ -7,7 +7,82 @@ use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
 
 mod parser;
-pub use parser::{Ast, parse};
+pub use parser::{Ast, ParseError, parse};
+
+/// Why a Dialect program failed to evaluate.
+///
+/// Dialect functions report failures as `anyhow` errors. Those are classified when
+/// they leave the function: a `DialectError` passes through unchanged (so a function
+/// can report [`DialectError::NoResults`] itself), argument deserialization failures
+/// become [`DialectError::InvalidArguments`], failed IDE requests (marked with
+/// [`crate::ide::IdeRequestError`] where they are made) become [`DialectError::Ipc`],
+/// and anything else is [`DialectError::Failed`].
+#[derive(Debug, thiserror::Error)]
+pub enum DialectError {
+    /// The program is not valid Dialect syntax
+    #[error("parse error: {0}")]
+    Parse(#[from] ParseError),
+    #[error("unknown function: {name}")]
+    UnknownFunction { name: String },
+    #[error("too many arguments for function {function}: expected {expected}, got {got}")]
+    ArityMismatch {
+        function: String,
+        expected: usize,
+        got: usize,
+    },
+    /// Arguments are missing or have the wrong shape
+    #[error("{function}: {message}")]
+    InvalidArguments { function: String, message: String },
+    /// The IDE could not answer a request the function made
+    #[error("{function}: {message}")]
+    Ipc { function: String, message: String },
+    /// The function ran, but found nothing for its arguments
+    #[error("{function}: {message}")]
+    NoResults { function: String, message: String },
+    /// The function failed for another reason, e.g. a file it reads is missing
+    #[error("{function}: {message}")]
+    Failed { function: String, message: String },
+}
+
+impl DialectError {
+    /// Machine-readable name of the variant, reported to agents as `kind`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DialectError::Parse(_) => "parse_error",
+            DialectError::UnknownFunction { .. } => "unknown_function",
+            DialectError::ArityMismatch { .. } => "arity_mismatch",
+            DialectError::InvalidArguments { .. } => "invalid_arguments",
+            DialectError::Ipc { .. } => "ipc_error",
+            DialectError::NoResults { .. } => "no_results",
+            DialectError::Failed { .. } => "function_failed",
+        }
+    }
+
+    /// Classify an error returned by the Dialect function `function`
+    fn from_function_error(function: &str, error: anyhow::Error) -> Self {
+        let error = match error.downcast::<DialectError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        let message = format!("{:#}", error);
+        if error.is::<serde_json::Error>() {
+            DialectError::InvalidArguments {
+                function: function.to_string(),
+                message,
+            }
+        } else if error.chain().any(|cause| cause.is::<crate::ide::IdeRequestError>()) {
+            DialectError::Ipc {
+                function: function.to_string(),
+                message,
+            }
+        } else {
+            DialectError::Failed {
+                function: function.to_string(),
+                message,
+            }
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct DialectInterpreter<U: Send> {
@@ -69,8 +144,20 @@ impl<U: Send> DialectInterpreter<U> {
         self.add_function::<crate::ide::FindDefinitions>();
         self.add_function_with_name::<crate::ide::FindDefinitions>("finddefinition");
         self.add_function::<crate::ide::FindReferences>();
+        self.add_function::<crate::ide::RenamePreview>();
+        self.add_function::<crate::ide::DocumentSymbols>();
+        self.add_function::<crate::ide::Body>();
+        self.add_function::<crate::ide::DefinitionAt>();
         self.add_function::<crate::ide::Search>();
+        self.add_function::<crate::ide::Filter>();
+        self.add_function::<crate::ide::Count>();
+        self.add_function::<crate::ide::CountFiles>();
+        self.add_function::<crate::ide::PathContains>();
+        self.add_function::<crate::ide::PathMatches>();
+        self.add_function::<crate::ide::InFile>();
         self.add_function::<crate::ide::Lines>();
+        self.add_function::<crate::ide::WorkspaceRoot>();
+        self.add_function::<crate::ide::Concat>();
         self.add_function::<crate::ide::GitDiff>();
         self.add_function::<crate::ide::Comment>();
         self.add_function::<crate::ide::Action>();
@@ -79,7 +166,7 @@ impl<U: Send> DialectInterpreter<U> {
     pub fn evaluate(
         &mut self,
         program: &str,
-    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Value>> + '_>> {
+    ) -> Pin<Box<dyn Future<Output = Result<Value, DialectError>> + '_>> {
         let ast = parse(&program);
         Box::pin(async move { self.evaluate_ast(ast?).await })
     }
@@ -87,7 +174,7 @@ impl<U: Send> DialectInterpreter<U> {
     pub fn evaluate_ast(
         &mut self,
         ast: Ast,
-    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Value>> + '_>> {
+    ) -> Pin<Box<dyn Future<Output = Result<Value, DialectError>> + '_>> {
         Box::pin(async move {
             match ast {
                 Ast::Call(name, args) => {
@@ -123,29 +210,31 @@ impl<U: Send> DialectInterpreter<U> {
         &mut self,
         name: String,
         args: Vec<Value>,
-    ) -> anyhow::Result<Value> {
+    ) -> Result<Value, DialectError> {
         let name_lower = name.to_ascii_lowercase();
         let (func, parameter_order) = self
             .functions
             .get(&name_lower)
-            .ok_or_else(|| anyhow::anyhow!("unknown function: {}", name))?;
+            .ok_or_else(|| DialectError::UnknownFunction { name: name.clone() })?;
 
-        // Map positional args to named object
-        let mut arg_object = serde_json::Map::new();
-        for (i, value) in args.into_iter().enumerate() {
-            if let Some(&param_name) = parameter_order.get(i) {
-                arg_object.insert(param_name.to_string(), value);
-            } else {
-                anyhow::bail!(
-                    "too many arguments for function {}: expected {}, got {}",
-                    name,
-                    parameter_order.len(),
-                    i + 1
-                );
-            }
+        if args.len() > parameter_order.len() {
+            return Err(DialectError::ArityMismatch {
+                function: name,
+                expected: parameter_order.len(),
+                got: args.len(),
+            });
         }
 
-        func(self, Value::Object(arg_object)).await
+        // Map positional args to named object
+        let arg_object: serde_json::Map<String, Value> = parameter_order
+            .iter()
+            .map(|param_name| param_name.to_string())
+            .zip(args)
+            .collect();
+
+        func(self, Value::Object(arg_object))
+            .await
+            .map_err(|e| DialectError::from_function_error(&name, e))
     }
 
     async fn execute<F>(&mut self, value: Value) -> anyhow::Result<Value>
@@ -402,6 +402,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_count_of_call() {
+        check_parse(
+            "count(findReferences(`foo`))",
+            expect![[r#"
+                Call(
+                    "count",
+                    [
+                        Call(
+                            "findReferences",
+                            [
+                                String(
+                                    "foo",
+                                ),
+                            ],
+                        ),
+                    ],
+                )
+            "#]],
+        );
+    }
+
     #[test]
     fn test_parse_array() {
         check_parse(
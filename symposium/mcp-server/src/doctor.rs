@@ -0,0 +1,313 @@
+//! Health check for the IDE integration (`symposium-mcp doctor`)
+//!
+//! Walks the IPC stack one layer at a time -- PID discovery, daemon socket,
+//! daemon round trip, VSCode extension round trip -- so that "my IDE integration
+//! isn't working" can be narrowed down to the first layer that fails.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+
+use crate::types::{IPCMessage, IPCMessageType, MessageSender};
+
+/// How long to wait for the daemon or the extension to answer
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Outcome of a single check
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    /// What to try next when the check failed
+    pub hint: Option<&'static str>,
+}
+
+impl DoctorCheck {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            name,
+            passed: false,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+}
+
+const PID_DISCOVERY: &str = "PID discovery";
+const DAEMON_SOCKET: &str = "Daemon socket";
+const DAEMON_PING: &str = "Daemon round trip";
+const EXTENSION_PING: &str = "VSCode extension round trip";
+
+const DAEMON_HINT: &str =
+    "Start the daemon with `symposium-mcp daemon` (MCP servers also start it on first use)";
+
+/// Run every check against the daemon listening at `socket_path`.
+///
+/// Later checks build on earlier ones; once the daemon is unreachable the
+/// remaining checks are reported as failed rather than skipped silently.
+pub async fn run_checks(socket_path: &str) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    let (discovery, shell_pid) = check_pid_discovery().await;
+    checks.push(discovery);
+
+    let connection = match connect(socket_path).await {
+        Ok(connection) => {
+            checks.push(DoctorCheck::pass(
+                DAEMON_SOCKET,
+                format!("connected to {}", socket_path),
+            ));
+            connection
+        }
+        Err(check) => {
+            checks.push(check);
+            for name in [DAEMON_PING, EXTENSION_PING] {
+                checks.push(DoctorCheck::fail(
+                    name,
+                    "skipped: daemon socket is not reachable",
+                    DAEMON_HINT,
+                ));
+            }
+            return checks;
+        }
+    };
+    let (mut lines, mut writer) = connection;
+
+    checks.push(match ping_daemon(&mut lines, &mut writer).await {
+        Ok(detail) => DoctorCheck::pass(DAEMON_PING, detail),
+        Err(e) => DoctorCheck::fail(
+            DAEMON_PING,
+            e.to_string(),
+            "The daemon accepted the connection but did not answer; kill it and let it restart",
+        ),
+    });
+
+    checks.push(match ping_extension(&mut lines, &mut writer, shell_pid).await {
+        Ok(detail) => DoctorCheck::pass(EXTENSION_PING, detail),
+        Err(e) => DoctorCheck::fail(
+            EXTENSION_PING,
+            e.to_string(),
+            "Check that the Symposium extension is installed and enabled in the VSCode window that owns this terminal",
+        ),
+    });
+
+    checks
+}
+
+/// Print `checks` as a human-readable report
+pub fn print_report(checks: &[DoctorCheck]) {
+    println!("Symposium doctor");
+    println!("{}", "─".repeat(60));
+    for check in checks {
+        let mark = if check.passed { "✅" } else { "❌" };
+        println!("{} {}: {}", mark, check.name, check.detail);
+        if let Some(hint) = check.hint {
+            println!("   💡 {}", hint);
+        }
+    }
+    println!("{}", "─".repeat(60));
+
+    let failed = checks.iter().filter(|c| !c.passed).count();
+    if failed == 0 {
+        println!("All {} checks passed", checks.len());
+    } else {
+        println!("{} of {} checks failed", failed, checks.len());
+    }
+}
+
+/// Check 1: can we find the IDE by walking up the process tree?
+///
+/// Returns the terminal shell PID on success so the extension check can
+/// address the right window.
+async fn check_pid_discovery() -> (DoctorCheck, Option<u32>) {
+    match crate::pid_discovery::find_vscode_pid_from_mcp(std::process::id()).await {
+        Ok(Some((vscode_pid, shell_pid))) => (
+            DoctorCheck::pass(
+                PID_DISCOVERY,
+                format!("VSCode PID {}, terminal shell PID {}", vscode_pid, shell_pid),
+            ),
+            Some(shell_pid),
+        ),
+        Ok(None) => (
+            DoctorCheck::fail(
+                PID_DISCOVERY,
+                "no VSCode process found above this one",
+                "Run this command from a terminal inside VSCode",
+            ),
+            None,
+        ),
+        Err(e) => (
+            DoctorCheck::fail(
+                PID_DISCOVERY,
+                format!("process tree walk failed: {}", e),
+                "Run this command from a terminal inside VSCode",
+            ),
+            None,
+        ),
+    }
+}
+
+/// Check 2: does the daemon socket exist and accept connections?
+async fn connect(
+    socket_path: &str,
+) -> Result<(Lines<BufReader<OwnedReadHalf>>, OwnedWriteHalf), DoctorCheck> {
+    if !Path::new(socket_path).exists() {
+        return Err(DoctorCheck::fail(
+            DAEMON_SOCKET,
+            format!("no socket at {}", socket_path),
+            DAEMON_HINT,
+        ));
+    }
+
+    match UnixStream::connect(socket_path).await {
+        Ok(stream) => {
+            let (reader, writer) = stream.into_split();
+            Ok((BufReader::new(reader).lines(), writer))
+        }
+        Err(e) => Err(DoctorCheck::fail(
+            DAEMON_SOCKET,
+            format!("could not connect to {}: {}", socket_path, e),
+            "The socket is probably stale; remove it and restart the daemon",
+        )),
+    }
+}
+
+/// Check 3: does the daemon answer a `#debug_stats` request?
+async fn ping_daemon(
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+    writer: &mut OwnedWriteHalf,
+) -> Result<String> {
+    writer.write_all(b"#debug_stats\n").await?;
+    writer.flush().await?;
+
+    let line = tokio::time::timeout(CHECK_TIMEOUT, lines.next_line())
+        .await
+        .map_err(|_| anyhow!("no answer within {}s", CHECK_TIMEOUT.as_secs()))??
+        .ok_or_else(|| anyhow!("daemon closed the connection"))?;
+    let stats: serde_json::Value =
+        serde_json::from_str(&line).context("daemon sent a malformed reply")?;
+
+    Ok(format!("daemon answered debug_stats: {}", stats))
+}
+
+/// Check 4: does the VSCode extension for this window answer a request?
+///
+/// Sends a side-effect free `get_selection` request addressed like an MCP
+/// server in this terminal would address it, and waits for the matching
+/// `response`. Other traffic on the bus is ignored.
+async fn ping_extension(
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+    writer: &mut OwnedWriteHalf,
+    shell_pid: Option<u32>,
+) -> Result<String> {
+    let working_directory = crate::workspace_dir::current_dir()?.display().to_string();
+    let message = IPCMessage {
+        message_type: IPCMessageType::GetSelection,
+        id: uuid::Uuid::new_v4().to_string(),
+        sender: MessageSender {
+            working_directory,
+            taskspace_uuid: None,
+            shell_pid,
+        },
+        payload: serde_json::json!({}),
+    };
+    writer
+        .write_all(format!("{}\n", serde_json::to_string(&message)?).as_bytes())
+        .await?;
+    writer.flush().await?;
+
+    let reply = tokio::time::timeout(CHECK_TIMEOUT, wait_for_response(lines, &message.id))
+        .await
+        .map_err(|_| anyhow!("no extension answered within {}s", CHECK_TIMEOUT.as_secs()))??;
+
+    Ok(format!(
+        "extension in {} answered get_selection",
+        reply.sender.working_directory
+    ))
+}
+
+/// Read bus traffic until the `response` to message `id` arrives
+async fn wait_for_response(
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+    id: &str,
+) -> Result<IPCMessage> {
+    while let Some(line) = lines.next_line().await? {
+        let Ok(reply) = serde_json::from_str::<IPCMessage>(&line) else {
+            continue;
+        };
+        if reply.message_type == IPCMessageType::Response && reply.id == id {
+            return Ok(reply);
+        }
+    }
+    Err(anyhow!("daemon closed the connection"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_missing_daemon_fails_remaining_checks() {
+        let socket_path = crate::constants::daemon_socket_path(&format!(
+            "symposium-doctor-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+
+        let checks = run_checks(&socket_path).await;
+        let names: Vec<_> = checks.iter().map(|c| c.name).collect();
+        assert_eq!(names, [PID_DISCOVERY, DAEMON_SOCKET, DAEMON_PING, EXTENSION_PING]);
+
+        for check in &checks[1..] {
+            assert!(!check.passed, "{} should fail without a daemon", check.name);
+            assert!(check.hint.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daemon_checks_pass_against_running_daemon() {
+        use std::sync::Arc;
+        use tokio::sync::Barrier;
+
+        let socket_prefix = format!("symposium-doctor-test-{}", uuid::Uuid::new_v4());
+        let socket_path = crate::constants::daemon_socket_path(&socket_prefix);
+
+        let ready_barrier = Arc::new(Barrier::new(2));
+        let ready_barrier_clone = ready_barrier.clone();
+        let daemon_handle = tokio::spawn(async move {
+            crate::daemon::run_daemon_with_idle_timeout(
+                &socket_prefix,
+                30,
+                None,
+                crate::constants::DEFAULT_MESSAGE_HISTORY_LIMIT,
+                Some(ready_barrier_clone),
+            )
+            .await
+        });
+        ready_barrier.wait().await;
+
+        let checks = run_checks(&socket_path).await;
+        assert!(checks[1].passed, "{:?}", checks[1]);
+        assert!(checks[2].passed, "{:?}", checks[2]);
+
+        // No extension is listening, so the last check must fail with a hint
+        assert!(!checks[3].passed);
+        assert!(checks[3].hint.is_some());
+
+        daemon_handle.abort();
+        let _ = std::fs::remove_file(&socket_path);
+    }
+}
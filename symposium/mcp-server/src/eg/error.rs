@@ -26,12 +26,21 @@ pub enum EgError {
     /// I/O error
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
+    /// The project has no `Cargo.lock` to read resolved versions from
+    #[error("No Cargo.lock in {}; build the project or run `cargo generate-lockfile` first", .0.display())]
+    NoLockfile(std::path::PathBuf),
     /// Crate not found
     #[error("Crate '{0}' not found")]
     CrateNotFound(String),
     /// No matching versions found
     #[error("No versions of '{crate_name}' match constraint '{constraint}'")]
     NoMatchingVersions { crate_name: String, constraint: String },
+    /// The requested file does not exist in the crate's source
+    #[error("File '{}' not found in {crate_name} v{version}", .path.display())]
+    FileNotFound { crate_name: String, version: String, path: std::path::PathBuf },
+    /// A file path that is absolute or leaves the crate root
+    #[error("File path '{}' must be relative to the crate root", .0.display())]
+    InvalidFilePath(std::path::PathBuf),
     /// Other error
     #[error("Error: {0}")]
     Other(String),
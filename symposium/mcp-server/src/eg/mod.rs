@@ -7,7 +7,8 @@ pub mod rust;
 
 pub use error::{EgError, Result};
 
-use std::path::PathBuf;
+use regex::Regex;
+use std::path::{Path, PathBuf};
 
 /// Main entry point for example searches
 pub struct Eg;
@@ -30,6 +31,21 @@ pub struct SearchResult {
     pub example_matches: Vec<Match>,
     /// Matches found elsewhere in the crate
     pub other_matches: Vec<Match>,
+    /// Number of matches found before capping to the search's match limit
+    pub total_match_count: usize,
+}
+
+/// A single file read from a crate's source
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CrateFile {
+    /// The exact version the file was read from
+    pub version: String,
+    /// Path to the full crate extraction on disk
+    pub checkout_path: PathBuf,
+    /// Path of the file, relative to `checkout_path`
+    pub file_path: PathBuf,
+    /// Contents of the file
+    pub content: String,
 }
 
 /// A search match with context
@@ -46,3 +62,42 @@ pub struct Match {
     /// Lines after the match for context
     pub context_after: Vec<String>,
 }
+
+impl Match {
+    /// Every line of `content` matching `pattern`, with up to `context_lines`
+    /// lines of context on each side, reported under `file_path`
+    pub fn find_all(content: &str, file_path: &Path, pattern: &Regex, context_lines: usize) -> Vec<Match> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut matches = Vec::new();
+
+        for (line_idx, line) in lines.iter().enumerate() {
+            if pattern.is_match(line) {
+                let line_number = (line_idx + 1) as u32; // 1-based line numbers
+
+                // Get context lines
+                let context_start = line_idx.saturating_sub(context_lines);
+                let context_end = std::cmp::min(line_idx + context_lines + 1, lines.len());
+
+                let context_before = lines[context_start..line_idx]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect();
+
+                let context_after = lines[line_idx + 1..context_end]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect();
+
+                matches.push(Match {
+                    file_path: file_path.to_path_buf(),
+                    line_number,
+                    line_content: line.to_string(),
+                    context_before,
+                    context_after,
+                });
+            }
+        }
+
+        matches
+    }
+}
@@ -1,7 +1,15 @@
 //! Cache management for extracted crates
 
 use crate::eg::{Result, EgError};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// One lock per extraction directory (i.e. per crate name and version), so
+/// concurrent requests for the same crate wait for the first extraction and
+/// reuse it, while different crates still extract in parallel
+static EXTRACTION_LOCKS: Mutex<BTreeMap<PathBuf, Arc<tokio::sync::Mutex<()>>>> =
+    Mutex::new(BTreeMap::new());
 
 /// Manages access to cargo's cache and our extraction cache
 pub struct CacheManager {
@@ -23,10 +31,15 @@ impl CacheManager {
             .join("eg")
             .join("extractions");
         
-        Ok(Self { 
+        Ok(Self::with_dirs(cargo_cache_dir, extraction_cache_dir))
+    }
+
+    /// Create a cache manager over explicit cargo registry and extraction directories
+    fn with_dirs(cargo_cache_dir: PathBuf, extraction_cache_dir: PathBuf) -> Self {
+        Self {
             cargo_cache_dir,
             extraction_cache_dir,
-        })
+        }
     }
 
     /// Get or extract a crate, returning the path to the extracted source
@@ -36,8 +49,17 @@ impl CacheManager {
         version: &str,
         extractor: &super::CrateExtractor,
     ) -> Result<PathBuf> {
-        // 1. Check if already extracted in our cache
+        // 1. Check if already extracted in our cache. The check happens under the
+        // lock: a directory that exists while another request holds it is only
+        // partially extracted.
         let extraction_path = self.extraction_cache_dir.join(format!("{}-{}", crate_name, version));
+        let lock = EXTRACTION_LOCKS
+            .lock()
+            .unwrap()
+            .entry(extraction_path.clone())
+            .or_default()
+            .clone();
+        let _guard = lock.lock().await;
         if extraction_path.exists() {
             return Ok(extraction_path);
         }
@@ -107,5 +129,70 @@ impl CacheManager {
         
         Ok(None)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eg::rust::CrateExtractor;
+    use flate2::{Compression, write::GzEncoder};
 
+    /// Write a `.crate` tarball (a gzipped tar with a single `name-version/` directory)
+    fn write_crate_file(path: &std::path::Path, name: &str, version: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let encoder = GzEncoder::new(std::fs::File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let files = [
+            ("Cargo.toml", format!("[package]\nname = \"{name}\"\nversion = \"{version}\"\n")),
+            ("src/lib.rs", "pub fn hello() {}\n".to_string()),
+        ];
+        for (file, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("{name}-{version}/{file}"), content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_extraction_of_same_crate() {
+        let temp = tempfile::tempdir().unwrap();
+        let cargo_cache_dir = temp.path().join("registry");
+        write_crate_file(
+            &cargo_cache_dir.join("cache/index.test/foo-1.0.0.crate"),
+            "foo",
+            "1.0.0",
+        );
+        let cache_manager = Arc::new(CacheManager::with_dirs(
+            cargo_cache_dir,
+            temp.path().join("extractions"),
+        ));
+
+        let extract = |cache_manager: Arc<CacheManager>| {
+            tokio::spawn(async move {
+                cache_manager
+                    .get_or_extract_crate("foo", "1.0.0", &CrateExtractor::new())
+                    .await
+                    .unwrap()
+            })
+        };
+        let first = extract(cache_manager.clone());
+        let second = extract(cache_manager.clone());
+        let (first, second) = (first.await.unwrap(), second.await.unwrap());
+
+        assert_eq!(first, second);
+        assert!(first.join("Cargo.toml").is_file());
+        assert!(first.join("src/lib.rs").is_file());
+        // A second unpack over the flattened checkout would leave the archive's
+        // `foo-1.0.0/` directory behind instead of flattening it
+        let entries: Vec<_> = std::fs::read_dir(&first)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries.len(), 2, "extracted more than once: {entries:?}");
+    }
 }
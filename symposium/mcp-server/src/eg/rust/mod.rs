@@ -1,17 +1,29 @@
 //! Rust-specific example searching functionality
 
-use crate::eg::{Result, SearchResult};
+use crate::eg::{CrateFile, EgError, Result, SearchResult};
 use regex::Regex;
+use std::path::{Component, Path, PathBuf};
 
 mod version;
 mod cache;
 mod extraction;
 mod search;
+mod workspace;
 
 pub use version::VersionResolver;
 pub use cache::CacheManager;
 pub use extraction::CrateExtractor;
 pub use search::CrateSearcher;
+pub use workspace::workspace_graph;
+
+/// Number of lines captured around each match unless overridden
+pub const DEFAULT_CONTEXT_LINES: usize = 2;
+
+/// Largest accepted context, so a search can't return whole files
+pub const MAX_CONTEXT_LINES: usize = 50;
+
+/// Maximum number of matches returned unless overridden
+pub const DEFAULT_MAX_MATCHES: usize = 50;
 
 /// Builder for searching Rust crate examples
 pub struct RustCrateSearch {
@@ -19,6 +31,7 @@ pub struct RustCrateSearch {
     version_spec: Option<String>,
     pattern: Option<Regex>,
     context_lines: usize,
+    max_matches: usize,
 }
 
 impl RustCrateSearch {
@@ -28,7 +41,8 @@ impl RustCrateSearch {
             crate_name: name.to_string(),
             version_spec: None,
             pattern: None,
-            context_lines: 2, // Default context
+            context_lines: DEFAULT_CONTEXT_LINES,
+            max_matches: DEFAULT_MAX_MATCHES,
         }
     }
 
@@ -46,32 +60,86 @@ impl RustCrateSearch {
         Ok(self)
     }
 
-    /// Execute the search
-    pub async fn search(self) -> Result<SearchResult> {
-        // 1. Resolve version
+    /// Specify how many lines of context to capture before and after each match
+    /// (clamped to [`MAX_CONTEXT_LINES`])
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines.min(MAX_CONTEXT_LINES);
+        self
+    }
+
+    /// Specify the maximum number of matches to return. Matches in `examples/`
+    /// are never dropped; other matches fill whatever room they leave.
+    pub fn max_matches(mut self, max_matches: usize) -> Self {
+        self.max_matches = max_matches;
+        self
+    }
+
+    /// Resolve the version and get or extract the crate source, returning the
+    /// exact version and the path of its checkout
+    async fn checkout(&self) -> Result<(String, PathBuf)> {
         let resolver = VersionResolver::new();
         let version = resolver.resolve_version(&self.crate_name, self.version_spec.as_deref()).await?;
 
-        // 2. Get or extract crate source
         let cache_manager = CacheManager::new()?;
         let extractor = CrateExtractor::new();
-        
+
         let checkout_path = cache_manager.get_or_extract_crate(&self.crate_name, &version, &extractor).await?;
+        Ok((version, checkout_path))
+    }
+
+    /// Read a single file from the crate, without searching. `file_path` is
+    /// relative to the crate root and may not leave it.
+    pub async fn read_file(self, file_path: &Path) -> Result<CrateFile> {
+        let escapes = file_path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(EgError::InvalidFilePath(file_path.to_path_buf()));
+        }
+
+        let (version, checkout_path) = self.checkout().await?;
+        let full_path = checkout_path.join(file_path);
+        if !full_path.is_file() {
+            return Err(EgError::FileNotFound {
+                crate_name: self.crate_name,
+                version,
+                path: file_path.to_path_buf(),
+            });
+        }
+
+        let content = std::fs::read_to_string(&full_path)?;
+        Ok(CrateFile {
+            version,
+            checkout_path,
+            file_path: file_path.to_path_buf(),
+            content,
+        })
+    }
+
+    /// Execute the search
+    pub async fn search(self) -> Result<SearchResult> {
+        // 1-2. Resolve version, then get or extract crate source
+        let (version, checkout_path) = self.checkout().await?;
 
         // 3. Search the extracted crate
         let searcher = CrateSearcher::new();
-        let (example_matches, other_matches) = if let Some(pattern) = &self.pattern {
+        let (example_matches, mut other_matches) = if let Some(pattern) = &self.pattern {
             searcher.search_crate(&checkout_path, pattern, self.context_lines)?
         } else {
             // No pattern - just return empty matches but still provide checkout_path
             (Vec::new(), Vec::new())
         };
 
+        // 4. Cap the other matches; the curated examples are always kept
+        let total_match_count = example_matches.len() + other_matches.len();
+        other_matches.truncate(self.max_matches.saturating_sub(example_matches.len()));
+
         Ok(SearchResult {
             version,
             checkout_path,
             example_matches,
             other_matches,
+            total_match_count,
         })
     }
 }
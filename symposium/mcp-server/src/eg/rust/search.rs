@@ -77,43 +77,10 @@ impl CrateSearcher {
         let content = fs::read_to_string(file_path)
             .map_err(|e| EgError::Other(format!("Failed to read file {}: {}", file_path.display(), e)))?;
 
-        let lines: Vec<&str> = content.lines().collect();
-        let mut matches = Vec::new();
+        // Get relative path from base
+        let relative_path = file_path.strip_prefix(base_path).unwrap_or(file_path);
 
-        for (line_idx, line) in lines.iter().enumerate() {
-            if pattern.is_match(line) {
-                let line_number = (line_idx + 1) as u32; // 1-based line numbers
-                
-                // Get context lines
-                let context_start = line_idx.saturating_sub(context_lines);
-                let context_end = std::cmp::min(line_idx + context_lines + 1, lines.len());
-                
-                let context_before = lines[context_start..line_idx]
-                    .iter()
-                    .map(|s| s.to_string())
-                    .collect();
-                
-                let context_after = lines[line_idx + 1..context_end]
-                    .iter()
-                    .map(|s| s.to_string())
-                    .collect();
-
-                // Get relative path from base
-                let relative_path = file_path.strip_prefix(base_path)
-                    .unwrap_or(file_path)
-                    .to_path_buf();
-
-                matches.push(Match {
-                    file_path: relative_path,
-                    line_number,
-                    line_content: line.to_string(),
-                    context_before,
-                    context_after,
-                });
-            }
-        }
-
-        Ok(matches)
+        Ok(Match::find_all(&content, relative_path, pattern, context_lines))
     }
 
     /// Check if a file is in the examples directory
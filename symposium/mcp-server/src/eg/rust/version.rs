@@ -3,6 +3,18 @@
 use crate::eg::{Result, EgError};
 use cargo_metadata::{MetadataCommand, CargoOpt};
 use semver::{Version, VersionReq};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A package in the current project's resolved dependency graph
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProjectDependency {
+    pub name: String,
+    /// Version the lockfile resolves this package to
+    pub version: String,
+    /// Whether a workspace member depends on it directly (rather than transitively)
+    pub direct: bool,
+}
 
 /// Handles version resolution using the three-tier strategy
 pub struct VersionResolver;
@@ -44,6 +56,50 @@ impl VersionResolver {
         Err(EgError::CrateNotFound(crate_name.to_string()))
     }
 
+    /// List the dependencies of the project containing `dir`, at the versions
+    /// its `Cargo.lock` pins, sorted by name. Workspace members are left out.
+    ///
+    /// The lockfile is never created or updated; a project without one is an
+    /// [`EgError::NoLockfile`] error.
+    pub fn project_dependencies(&self, dir: &Path, direct_only: bool) -> Result<Vec<ProjectDependency>> {
+        // Locating the workspace needs no resolution, so it can't touch the lockfile
+        let workspace = MetadataCommand::new().current_dir(dir).no_deps().exec()?;
+        let workspace_root = workspace.workspace_root.into_std_path_buf();
+        if !workspace_root.join("Cargo.lock").exists() {
+            return Err(EgError::NoLockfile(workspace_root));
+        }
+
+        let metadata = MetadataCommand::new()
+            .current_dir(dir)
+            .features(CargoOpt::AllFeatures)
+            .other_options(vec!["--locked".to_string()])
+            .exec()?;
+
+        let members: HashSet<_> = metadata.workspace_members.iter().collect();
+        let direct: HashSet<_> = metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .filter(|node| members.contains(&node.id))
+            .flat_map(|node| node.deps.iter().map(|dep| &dep.pkg))
+            .collect();
+
+        let mut dependencies: Vec<_> = metadata
+            .packages
+            .iter()
+            .filter(|package| !members.contains(&package.id))
+            .map(|package| ProjectDependency {
+                name: package.name.to_string(),
+                version: package.version.to_string(),
+                direct: direct.contains(&package.id),
+            })
+            .filter(|dependency| dependency.direct || !direct_only)
+            .collect();
+        dependencies.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+        Ok(dependencies)
+    }
+
     /// Resolve version constraint to latest matching version
     async fn resolve_version_constraint(&self, crate_name: &str, constraint: &str) -> Result<String> {
         let req = VersionReq::parse(constraint)?;
@@ -100,3 +156,58 @@ impl VersionResolver {
         Ok(parsed_versions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_crate(dir: &Path, name: &str, dependencies: &str) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n{dependencies}"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+    }
+
+    #[test]
+    fn test_project_dependencies() {
+        // app -> util -> leaf, all path dependencies so no registry is needed
+        let temp = tempfile::tempdir().unwrap();
+        let app = temp.path().join("app");
+        write_crate(&app, "app", "util = { path = \"../util\" }\n");
+        write_crate(&temp.path().join("util"), "util", "leaf = { path = \"../leaf\" }\n");
+        write_crate(&temp.path().join("leaf"), "leaf", "");
+
+        let resolver = VersionResolver::new();
+        let err = resolver.project_dependencies(&app, false).unwrap_err();
+        assert!(matches!(err, EgError::NoLockfile(_)), "{err}");
+        assert!(!app.join("Cargo.lock").exists(), "the lockfile must not be created");
+
+        let status = std::process::Command::new(std::env::var("CARGO").unwrap_or("cargo".to_string()))
+            .args(["generate-lockfile", "--offline"])
+            .current_dir(&app)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let found = |direct_only| {
+            resolver
+                .project_dependencies(&app, direct_only)
+                .unwrap()
+                .into_iter()
+                .map(|d| (d.name, d.version, d.direct))
+                .collect::<Vec<_>>()
+        };
+        let version = "0.1.0".to_string();
+        assert_eq!(
+            found(false),
+            [
+                ("leaf".to_string(), version.clone(), false),
+                ("util".to_string(), version.clone(), true),
+            ]
+        );
+        assert_eq!(found(true), [("util".to_string(), version, true)]);
+    }
+}
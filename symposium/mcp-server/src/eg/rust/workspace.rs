@@ -0,0 +1,138 @@
+//! Dependency graph of a Cargo workspace, rendered as a mermaid flowchart
+
+use crate::eg::Result;
+use cargo_metadata::{DependencyKind, MetadataCommand};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Render the dependencies between the members of the workspace containing `dir`
+/// as a mermaid `flowchart`, ready to paste into a walkthrough's mermaid block.
+///
+/// Only edges between workspace members are drawn unless `include_external` is set,
+/// in which case every declared dependency appears as a rounded node. Dev-dependencies
+/// are dashed. Manifests are read without resolving dependencies, so no lockfile or
+/// registry access is needed.
+pub fn workspace_graph(dir: &Path, include_external: bool) -> Result<String> {
+    let metadata = MetadataCommand::new().current_dir(dir).no_deps().exec()?;
+
+    let members: BTreeSet<&str> = metadata
+        .workspace_packages()
+        .into_iter()
+        .map(|package| package.name.as_str())
+        .collect();
+
+    // (from, to) -> dev-only; a dependency that is also a normal or build one is drawn solid
+    let mut edges: BTreeMap<(&str, &str), bool> = BTreeMap::new();
+    let mut external: BTreeSet<&str> = BTreeSet::new();
+    for package in metadata.workspace_packages() {
+        for dependency in &package.dependencies {
+            let name = dependency.name.as_str();
+            let internal = dependency.path.is_some() && members.contains(name);
+            if !internal {
+                if !include_external {
+                    continue;
+                }
+                external.insert(name);
+            }
+
+            let dev = dependency.kind == DependencyKind::Development;
+            let edge = edges.entry((package.name.as_str(), name)).or_insert(dev);
+            *edge &= dev;
+        }
+    }
+
+    let mut graph = String::from("flowchart TD\n");
+    for member in &members {
+        graph.push_str(&format!("    {}[\"{}\"]\n", node_id(member), member));
+    }
+    for name in &external {
+        graph.push_str(&format!("    {}([\"{}\"])\n", node_id(name), name));
+    }
+    for ((from, to), dev) in &edges {
+        let arrow = if *dev { "-.->" } else { "-->" };
+        graph.push_str(&format!("    {} {} {}\n", node_id(from), arrow, node_id(to)));
+    }
+    if !external.is_empty() {
+        graph.push_str("    classDef external stroke-dasharray: 4 2\n");
+        let ids: Vec<String> = external.iter().map(|name| node_id(name)).collect();
+        graph.push_str(&format!("    class {} external\n", ids.join(",")));
+    }
+
+    Ok(graph)
+}
+
+/// Mermaid node id for a crate name; `-` and other punctuation are not allowed in ids
+fn node_id(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    fn write_crate(dir: &Path, name: &str, manifest_tail: &str) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n{manifest_tail}"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+    }
+
+    #[test]
+    fn test_workspace_graph() {
+        // Registry dependencies are only declared, never resolved or downloaded
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core-lib\", \"test-kit\"]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        write_crate(
+            &temp.path().join("app"),
+            "app",
+            "[dependencies]\ncore-lib = { path = \"../core-lib\" }\nserde = \"1\"\n\n\
+             [dev-dependencies]\ntest-kit = { path = \"../test-kit\" }\n",
+        );
+        write_crate(&temp.path().join("core-lib"), "core-lib", "[dependencies]\nregex = \"1\"\n");
+        write_crate(
+            &temp.path().join("test-kit"),
+            "test-kit",
+            "[dependencies]\ncore-lib = { path = \"../core-lib\" }\n\n\
+             [dev-dependencies]\ncore-lib = { path = \"../core-lib\" }\n",
+        );
+
+        let app = temp.path().join("app");
+        expect![[r#"
+            flowchart TD
+                app["app"]
+                core_lib["core-lib"]
+                test_kit["test-kit"]
+                app --> core_lib
+                app -.-> test_kit
+                test_kit --> core_lib
+        "#]]
+        .assert_eq(&workspace_graph(&app, false).unwrap());
+
+        expect![[r#"
+            flowchart TD
+                app["app"]
+                core_lib["core-lib"]
+                test_kit["test-kit"]
+                regex(["regex"])
+                serde(["serde"])
+                app --> core_lib
+                app --> serde
+                app -.-> test_kit
+                core_lib --> regex
+                test_kit --> core_lib
+                classDef external stroke-dasharray: 4 2
+                class regex,serde external
+        "#]]
+        .assert_eq(&workspace_graph(&app, true).unwrap());
+    }
+}
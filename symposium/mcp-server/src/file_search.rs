@@ -0,0 +1,208 @@
+//! Workspace file walking shared by Dialect's `search` and the `find_files`
+//! and `search_workspace` tools
+//!
+//! Walks skip files excluded by `.gitignore` (and the other ignore files the
+//! `ignore` crate understands), so build output and vendored code stay out of
+//! the results.
+
+use std::path::{Path, PathBuf};
+
+/// Which files a walk yields: all of them, those with an extension, or
+/// those matching a gitignore-style glob
+pub enum FileFilter {
+    All,
+    Extension(String),
+    Glob(ignore::gitignore::Gitignore),
+}
+
+impl FileFilter {
+    /// Parse a filter that is either a file extension (`rs` or `.rs`) or, if it
+    /// contains any of `*?[`, a glob relative to `root`
+    pub fn new(root: &Path, filter: Option<&str>) -> anyhow::Result<Self> {
+        let Some(filter) = filter else {
+            return Ok(FileFilter::All);
+        };
+
+        if filter.contains(['*', '?', '[']) {
+            Self::glob(root, filter)
+        } else if filter.starts_with('.') {
+            Ok(FileFilter::Extension(filter.to_string()))
+        } else {
+            // Normalize extension (add dot if missing)
+            Ok(FileFilter::Extension(format!(".{}", filter)))
+        }
+    }
+
+    /// A gitignore-style glob relative to `root`: without a slash it matches a
+    /// file name anywhere (`*.rs`, `Cargo.toml`), with one it is anchored to `root`
+    pub fn glob(root: &Path, glob: &str) -> anyhow::Result<Self> {
+        let mut builder = ignore::gitignore::GitignoreBuilder::new(root);
+        builder.add_line(None, glob)?;
+        Ok(FileFilter::Glob(builder.build()?))
+    }
+
+    pub fn matches(&self, file_path: &Path) -> bool {
+        match self {
+            FileFilter::All => true,
+            FileFilter::Extension(ext) => file_path.to_string_lossy().ends_with(ext.as_str()),
+            FileFilter::Glob(glob) => glob
+                .matched_path_or_any_parents(file_path, false)
+                .is_ignore(),
+        }
+    }
+}
+
+/// Files under `root` that pass `filter`, skipping ignored files. Entries
+/// within each directory are visited in file name order.
+pub fn walk_files(
+    root: &Path,
+    filter: &FileFilter,
+) -> impl Iterator<Item = Result<PathBuf, ignore::Error>> {
+    let walk = ignore::WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    walk.filter_map(move |result| match result {
+        Ok(entry) => {
+            let is_file = entry.file_type().is_some_and(|ft| ft.is_file());
+            (is_file && filter.matches(entry.path())).then(|| Ok(entry.into_path()))
+        }
+        Err(e) => Some(Err(e)),
+    })
+}
+
+/// Files found by [`find_files`]
+#[derive(Debug)]
+pub struct FoundFiles {
+    /// Matching paths relative to the search root, sorted
+    pub paths: Vec<String>,
+    /// Number of matches left out because of the result cap
+    pub omitted: usize,
+}
+
+/// Find files under `root` whose path matches the gitignore-style `glob`,
+/// returning at most `max_results` of them
+pub fn find_files(root: &Path, glob: &str, max_results: usize) -> anyhow::Result<FoundFiles> {
+    let filter = FileFilter::glob(root, glob)?;
+
+    let mut paths = Vec::new();
+    for path in walk_files(root, &filter) {
+        let path = path?;
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        paths.push(relative.to_string_lossy().to_string());
+    }
+    paths.sort();
+
+    let omitted = paths.len().saturating_sub(max_results);
+    paths.truncate(max_results);
+    Ok(FoundFiles { paths, omitted })
+}
+
+/// Matches found by [`search_workspace`]
+#[derive(Debug)]
+pub struct WorkspaceMatches {
+    /// Matches with paths relative to the search root, in walk order
+    pub matches: Vec<crate::eg::Match>,
+    /// Whether the search stopped at the result limit with more matches left
+    pub truncated: bool,
+}
+
+/// Search the files under `root` that pass `filter` for lines matching
+/// `pattern`, returning at most `max_matches` of them. The walk stops as soon
+/// as the limit is exceeded, so a broad pattern does not read the whole tree.
+/// Files that are not valid UTF-8 are skipped.
+pub fn search_workspace(
+    root: &Path,
+    pattern: &regex::Regex,
+    filter: &FileFilter,
+    context_lines: usize,
+    max_matches: usize,
+) -> anyhow::Result<WorkspaceMatches> {
+    let mut matches = Vec::new();
+    for path in walk_files(root, filter) {
+        let path = path?;
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        matches.extend(crate::eg::Match::find_all(&content, relative, pattern, context_lines));
+        if matches.len() > max_matches {
+            matches.truncate(max_matches);
+            return Ok(WorkspaceMatches { matches, truncated: true });
+        }
+    }
+
+    Ok(WorkspaceMatches { matches, truncated: false })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::TestRepo;
+
+    #[test]
+    fn test_find_files() {
+        let temp_repo = TestRepo::new()
+            .overwrite_and_add(".gitignore", "target/\n")
+            .overwrite_and_add("Cargo.toml", "[package]\n")
+            .overwrite_and_add("src/lib.rs", "\n")
+            .overwrite_and_add("src/bin/tool.rs", "\n")
+            .overwrite_and_add("tests/it.rs", "\n")
+            .overwrite_and_add("crates/inner/Cargo.toml", "[package]\n")
+            .commit("Initial commit")
+            .create();
+        std::fs::create_dir(temp_repo.path().join("target")).unwrap();
+        std::fs::write(temp_repo.path().join("target/generated.rs"), "\n").unwrap();
+        let root = temp_repo.path();
+
+        // Ignored files are skipped and results are sorted
+        let found = find_files(root, "*.rs", 10).unwrap();
+        assert_eq!(found.paths, ["src/bin/tool.rs", "src/lib.rs", "tests/it.rs"]);
+        assert_eq!(found.omitted, 0);
+
+        // A bare file name matches anywhere; a slash anchors the glob to the root
+        let found = find_files(root, "Cargo.toml", 10).unwrap();
+        assert_eq!(found.paths, ["Cargo.toml", "crates/inner/Cargo.toml"]);
+        let found = find_files(root, "src/*.rs", 10).unwrap();
+        assert_eq!(found.paths, ["src/lib.rs"]);
+
+        // Results beyond the cap are counted, not returned
+        let found = find_files(root, "*.rs", 2).unwrap();
+        assert_eq!(found.paths, ["src/bin/tool.rs", "src/lib.rs"]);
+        assert_eq!(found.omitted, 1);
+    }
+
+    #[test]
+    fn test_search_workspace() {
+        let temp_repo = TestRepo::new()
+            .overwrite_and_add(".gitignore", "target/\n")
+            .overwrite_and_add("src/lib.rs", "mod a;\nfn alpha() {}\nfn beta() {}\n")
+            .overwrite_and_add("src/a.rs", "fn alpha_helper() {}\n")
+            .overwrite_and_add("README.md", "alpha\n")
+            .commit("Initial commit")
+            .create();
+        std::fs::create_dir(temp_repo.path().join("target")).unwrap();
+        std::fs::write(temp_repo.path().join("target/alpha.rs"), "fn alpha() {}\n").unwrap();
+        let root = temp_repo.path();
+        let pattern = regex::Regex::new(r"fn alpha").unwrap();
+
+        // Ignored files are skipped; matches come in path order with context
+        let rs = FileFilter::new(root, Some("rs")).unwrap();
+        let found = search_workspace(root, &pattern, &rs, 1, 10).unwrap();
+        let lines: Vec<_> = found
+            .matches
+            .iter()
+            .map(|m| (m.file_path.to_string_lossy().to_string(), m.line_number))
+            .collect();
+        assert_eq!(lines, [("src/a.rs".to_string(), 1), ("src/lib.rs".to_string(), 2)]);
+        assert_eq!(found.matches[1].context_before, ["mod a;"]);
+        assert_eq!(found.matches[1].context_after, ["fn beta() {}"]);
+
+        // The search stops at the cap and reports that more matches exist
+        let found = search_workspace(root, &pattern, &FileFilter::All, 0, 1).unwrap();
+        assert_eq!(found.matches.len(), 1);
+        assert!(found.truncated);
+        let found = search_workspace(root, &pattern, &FileFilter::All, 0, 2).unwrap();
+        assert_eq!(found.matches.len(), 2);
+        assert!(!found.truncated);
+    }
+}
@@ -1,16 +1,56 @@
 use regex::Regex;
 use schemars::JsonSchema;
-use crate::git::{FileChange, DiffLineType};
+use crate::git::{FileChange, DiffLineType, language_for_path};
 
 /// Type of AI insight comment found in source code
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub enum CommentType {
+    #[serde(alias = "explanation")]
     Explanation,
+    #[serde(alias = "question")]
     Question,
+    #[serde(alias = "todo")]
     Todo,
+    #[serde(alias = "fixme")]
     Fixme,
 }
 
+/// A comment marker the parser recognizes, and the category it maps to
+///
+/// Configured in `.symposium/config.toml`:
+///
+/// ```toml
+/// [[comment_markers]]
+/// marker = "HACK:"
+/// category = "fixme"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct CommentMarker {
+    /// Text that follows the comment syntax, e.g. `💡` or `NOTE:`
+    pub marker: String,
+    /// Category of the comments introduced by this marker
+    pub category: CommentType,
+}
+
+impl CommentMarker {
+    pub fn new(marker: impl Into<String>, category: CommentType) -> Self {
+        Self {
+            marker: marker.into(),
+            category,
+        }
+    }
+
+    /// The markers recognized when none are configured: 💡, ❓, `TODO:`, and `FIXME:`
+    pub fn defaults() -> Vec<CommentMarker> {
+        vec![
+            CommentMarker::new("💡", CommentType::Explanation),
+            CommentMarker::new("❓", CommentType::Question),
+            CommentMarker::new("TODO:", CommentType::Todo),
+            CommentMarker::new("FIXME:", CommentType::Fixme),
+        ]
+    }
+}
+
 /// Parsed AI insight comment with type and content
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct ParsedComment {
@@ -27,6 +67,38 @@ pub struct CommentThread {
     pub comment_type: CommentType,
     pub content: String,
     pub responses: Vec<String>,
+    /// Whether the discussion has been resolved. Resolution is not tracked yet, so threads
+    /// from `get_review_comments` are always `false`; callers may set it before `write_review_todos`.
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+/// Comment threads belonging to a single file, in line order
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct FileCommentThreads {
+    pub file_path: String,
+    pub threads: Vec<CommentThread>,
+}
+
+/// Group comment threads by file, keeping files in order of first appearance
+/// and sorting each file's threads by line number.
+pub fn group_threads_by_file(threads: Vec<CommentThread>) -> Vec<FileCommentThreads> {
+    let mut files: Vec<FileCommentThreads> = Vec::new();
+
+    for thread in threads {
+        match files.iter_mut().find(|file| file.file_path == thread.file_path) {
+            Some(file) => file.threads.push(thread),
+            None => files.push(FileCommentThreads {
+                file_path: thread.file_path.clone(),
+                threads: vec![thread],
+            }),
+        }
+    }
+
+    for file in &mut files {
+        file.threads.sort_by_key(|thread| thread.line_number);
+    }
+    files
 }
 
 /// Parses AI insight comments from source code files.
@@ -38,25 +110,64 @@ pub struct CommentThread {
 /// - FIXME: Known issues that need addressing
 ///
 /// Supports multiple comment syntaxes (// # <!-- -->) for cross-language compatibility.
+/// Teams with their own conventions can swap in a different marker set with
+/// [`CommentParser::with_markers`].
 pub struct CommentParser {
-    lightbulb_regex: Regex,
-    question_regex: Regex,
-    todo_regex: Regex,
-    fixme_regex: Regex,
+    /// Pattern for each marker with its category, tried in order
+    patterns: Vec<(Regex, CommentType)>,
+    /// First marker in the `Todo` category, used when writing TODOs
+    todo_marker: Option<String>,
 }
 
 impl CommentParser {
-    /// Creates a new CommentParser with pre-compiled regex patterns.
+    /// Creates a new CommentParser recognizing the default markers.
     ///
     /// Initializes regex patterns for detecting AI insight comments across
     /// multiple programming languages and comment syntaxes.
     pub fn new() -> Self {
-        Self {
-            // Match various comment styles: //, #, <!-- -->, etc.
-            lightbulb_regex: Regex::new(r"(?://|#|<!--)\s*💡\s*(.+?)(?:-->)?$").unwrap(),
-            question_regex: Regex::new(r"(?://|#|<!--)\s*❓\s*(.+?)(?:-->)?$").unwrap(),
-            todo_regex: Regex::new(r"(?://|#|<!--)\s*TODO:\s*(.+?)(?:-->)?$").unwrap(),
-            fixme_regex: Regex::new(r"(?://|#|<!--)\s*FIXME:\s*(.+?)(?:-->)?$").unwrap(),
+        Self::with_markers(&CommentMarker::defaults())
+    }
+
+    /// Creates a CommentParser recognizing `markers` instead of the defaults.
+    ///
+    /// When a line matches several markers, the first one in `markers` wins.
+    pub fn with_markers(markers: &[CommentMarker]) -> Self {
+        let patterns = markers
+            .iter()
+            .map(|marker| {
+                // Match various comment styles: //, #, <!-- -->, etc.
+                let pattern = format!(
+                    r"(?://|#|<!--)\s*{}\s*(.+?)(?:-->)?$",
+                    regex::escape(&marker.marker)
+                );
+                (Regex::new(&pattern).unwrap(), marker.category.clone())
+            })
+            .collect();
+        let todo_marker = markers
+            .iter()
+            .find(|marker| marker.category == CommentType::Todo)
+            .map(|marker| marker.marker.clone());
+        Self { patterns, todo_marker }
+    }
+
+    /// Format a TODO comment for `file_path` using the file's comment syntax and
+    /// this parser's first `Todo` marker.
+    ///
+    /// Only the syntaxes and markers this parser recognizes are produced, so a
+    /// written comment is always picked up again as a `Todo` comment by this parser.
+    ///
+    /// # Returns
+    /// * `Some(String)` - Comment line without indentation, e.g. `// TODO: Handle errors`
+    /// * `None` - No `Todo` marker is configured, or the file's language has no
+    ///   comment syntax the parser understands
+    pub fn format_todo(&self, file_path: &str, content: &str) -> Option<String> {
+        let marker = self.todo_marker.as_deref()?;
+        let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        match language_for_path(file_path)? {
+            "python" | "ruby" | "shellscript" | "toml" | "yaml" => Some(format!("# {marker} {content}")),
+            "markdown" | "html" | "xml" => Some(format!("<!-- {marker} {content} -->")),
+            "json" | "css" | "sql" => None,
+            _ => Some(format!("// {marker} {content}")),
         }
     }
 
@@ -84,6 +195,7 @@ impl CommentParser {
                     comment_type: comment.comment_type,
                     content: comment.content,
                     responses: vec![],
+                    resolved: false,
                 });
             }
         }
@@ -123,7 +235,7 @@ impl CommentParser {
 
     /// Extract a single AI insight comment from a line of source code.
     ///
-    /// Matches against pre-compiled regex patterns for different comment types
+    /// Matches against the pre-compiled pattern for each marker in order
     /// and extracts the comment content while preserving type information.
     ///
     /// # Arguments
@@ -133,29 +245,12 @@ impl CommentParser {
     /// * `Some(ParsedComment)` - AI insight comment found with type and content
     /// * `None` - No AI insight comment detected on this line
     fn extract_comment(&self, line: &str) -> Option<ParsedComment> {
-        if let Some(caps) = self.lightbulb_regex.captures(line) {
-            Some(ParsedComment {
-                comment_type: CommentType::Explanation,
-                content: caps[1].trim().to_string(),
-            })
-        } else if let Some(caps) = self.question_regex.captures(line) {
-            Some(ParsedComment {
-                comment_type: CommentType::Question,
-                content: caps[1].trim().to_string(),
-            })
-        } else if let Some(caps) = self.todo_regex.captures(line) {
-            Some(ParsedComment {
-                comment_type: CommentType::Todo,
-                content: caps[1].trim().to_string(),
-            })
-        } else if let Some(caps) = self.fixme_regex.captures(line) {
-            Some(ParsedComment {
-                comment_type: CommentType::Fixme,
+        self.patterns.iter().find_map(|(regex, comment_type)| {
+            regex.captures(line).map(|caps| ParsedComment {
+                comment_type: comment_type.clone(),
                 content: caps[1].trim().to_string(),
             })
-        } else {
-            None
-        }
+        })
     }
 
     /// Parse AI insight comments from FileChange structures with diff hunks
@@ -185,6 +280,7 @@ impl CommentParser {
                                 comment_type: comment.comment_type,
                                 content: comment.content,
                                 responses: vec![],
+                                resolved: false,
                             });
                         }
                     }
@@ -246,4 +342,83 @@ mod tests {
         let comment = parser.extract_comment("let x = 42; // Regular comment");
         assert!(comment.is_none());
     }
+
+    #[test]
+    fn test_format_todo_round_trips() {
+        let parser = CommentParser::new();
+
+        for (path, expected) in [
+            ("src/lib.rs", "// TODO: Handle the error case"),
+            ("scripts/build.py", "# TODO: Handle the error case"),
+            ("README.md", "<!-- TODO: Handle the error case -->"),
+        ] {
+            let marker = parser.format_todo(path, "Handle the\nerror case").unwrap();
+            assert_eq!(marker, expected);
+
+            let comment = parser.extract_comment(&marker).unwrap();
+            assert_eq!(comment.comment_type, CommentType::Todo);
+            assert_eq!(comment.content, "Handle the error case");
+        }
+
+        assert!(parser.format_todo("package.json", "Nope").is_none());
+        assert!(parser.format_todo("Makefile", "Nope").is_none());
+
+        // Configured markers are written instead of `TODO:`
+        let parser = CommentParser::with_markers(&[
+            CommentMarker::new("HACK:", CommentType::Fixme),
+            CommentMarker::new("@todo", CommentType::Todo),
+        ]);
+        let marker = parser.format_todo("src/lib.rs", "Handle the error case").unwrap();
+        assert_eq!(marker, "// @todo Handle the error case");
+        assert_eq!(parser.extract_comment(&marker).unwrap().comment_type, CommentType::Todo);
+
+        // Without a `Todo` marker there is nothing the parser would read back
+        let parser = CommentParser::with_markers(&[CommentMarker::new("HACK:", CommentType::Fixme)]);
+        assert!(parser.format_todo("src/lib.rs", "Nope").is_none());
+    }
+
+    #[test]
+    fn test_default_markers_unchanged() {
+        let parser = CommentParser::with_markers(&CommentMarker::defaults());
+
+        for (line, comment_type, content) in [
+            ("// 💡 Stateless design", CommentType::Explanation, "Stateless design"),
+            ("# ❓ Rate limit?", CommentType::Question, "Rate limit?"),
+            ("// TODO: Handle errors", CommentType::Todo, "Handle errors"),
+            ("<!-- FIXME: Broken link -->", CommentType::Fixme, "Broken link"),
+        ] {
+            let comment = parser.extract_comment(line).unwrap();
+            assert_eq!(comment.comment_type, comment_type);
+            assert_eq!(comment.content, content);
+            assert_eq!(CommentParser::new().extract_comment(line), Some(comment));
+        }
+
+        assert!(parser.extract_comment("// NOTE: Not a default marker").is_none());
+    }
+
+    #[test]
+    fn test_custom_markers() {
+        let parser = CommentParser::with_markers(&[
+            CommentMarker::new("NOTE:", CommentType::Explanation),
+            CommentMarker::new("HACK:", CommentType::Fixme),
+            CommentMarker::new("XXX:", CommentType::Fixme),
+            CommentMarker::new("@review", CommentType::Question),
+        ]);
+
+        let comment = parser.extract_comment("// HACK: Works around a borrowck limitation").unwrap();
+        assert_eq!(comment.comment_type, CommentType::Fixme);
+        assert_eq!(comment.content, "Works around a borrowck limitation");
+
+        let comment = parser.extract_comment("# @review is this retry safe?").unwrap();
+        assert_eq!(comment.comment_type, CommentType::Question);
+        assert_eq!(comment.content, "is this retry safe?");
+
+        let comment = parser.extract_comment("<!-- NOTE: Kept for old links -->").unwrap();
+        assert_eq!(comment.comment_type, CommentType::Explanation);
+        assert_eq!(comment.content, "Kept for old links");
+
+        // The default markers are replaced, not extended
+        assert!(parser.extract_comment("// TODO: Add error handling").is_none());
+        assert!(parser.extract_comment("// 💡 Using JWT").is_none());
+    }
 }
@@ -1,6 +1,11 @@
-use git2::{Delta, DiffOptions, Oid, Repository};
+use git2::{
+    ApplyLocation, ApplyOptions, Delta, Diff, DiffFindOptions, DiffFormat, DiffOptions, Oid, Patch,
+    Repository, Status, StatusOptions,
+};
 use schemars::JsonSchema;
 
+use crate::git::{CommentParser, CommentThread, CommentType};
+
 /// Git service for repository operations.
 ///
 /// Provides Git repository analysis capabilities including commit parsing,
@@ -15,6 +20,10 @@ pub enum ChangeStatus {
     Added,
     Modified,
     Deleted,
+    /// Moved from `FileChange::old_path`, possibly with edits
+    Renamed,
+    /// Copied from `FileChange::old_path`, which still exists
+    Copied,
 }
 
 /// Represents a single line in a diff hunk
@@ -48,11 +57,165 @@ pub struct DiffHunk {
 /// Represents a single file change in a diff
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct FileChange {
+    /// Path of the file after the change (the new path for renames and copies)
     pub path: String,
     pub status: ChangeStatus,
+    /// Original path for renamed and copied files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Estimated similarity (0-100) between the old and new file for renames and copies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
     pub additions: usize,
     pub deletions: usize,
     pub hunks: Vec<DiffHunk>,
+    /// Language of the file for syntax highlighting (e.g., "rust"), detected from its extension
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Binary files are not diffed line by line; they have no hunks and zero line counts
+    #[serde(default)]
+    pub binary: bool,
+    /// Change in size in bytes (new minus old), reported for binary files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_delta: Option<i64>,
+}
+
+impl FileChange {
+    /// Placeholder text shown instead of a line diff for binary files
+    pub fn binary_summary(&self) -> Option<String> {
+        if !self.binary {
+            return None;
+        }
+        Some(match self.size_delta {
+            Some(delta) => format!("Binary file changed ({:+} bytes)", delta),
+            None => "Binary file changed".to_string(),
+        })
+    }
+}
+
+/// Range token for the working tree, accepted as the head of a range (e.g. `main..WORKING`)
+pub const WORKING_TREE: &str = "WORKING";
+
+/// A commit in a resolved range
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct CommitSummary {
+    pub oid: String,
+    /// First line of the commit message
+    pub summary: String,
+    pub author: String,
+}
+
+/// A commit in the history returned by [`GitService::log`]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct LogEntry {
+    pub oid: String,
+    /// First line of the commit message
+    pub summary: String,
+    pub author: String,
+    pub email: String,
+    /// Commit time in RFC 3339 format
+    pub date: String,
+}
+
+/// Filters applied while walking history in [`GitService::log`]
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Only commits made at or after this time (seconds since the Unix epoch)
+    pub since: Option<i64>,
+    /// Only commits whose author name or email contains this text (case-insensitive)
+    pub author: Option<String>,
+}
+
+/// Parse a `since` value into seconds since the Unix epoch.
+///
+/// Accepts relative times (`"2 days ago"`, `"3 hours ago"`, `"yesterday"`),
+/// dates (`"2025-01-31"`, midnight UTC) and RFC 3339 timestamps. Relative
+/// times are measured back from `now`.
+pub fn parse_since(value: &str, now: chrono::DateTime<chrono::Utc>) -> Result<i64, git2::Error> {
+    let value = value.trim();
+    let invalid = || {
+        git2::Error::from_str(&format!(
+            "Cannot parse since value '{}': expected a relative time like '2 days ago', \
+             'yesterday', a date like '2025-01-31', or an RFC 3339 timestamp",
+            value
+        ))
+    };
+
+    if value.eq_ignore_ascii_case("yesterday") {
+        return Ok((now - chrono::Duration::days(1)).timestamp());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?.and_utc().timestamp());
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp());
+    }
+
+    let words: Vec<&str> = value.split_whitespace().collect();
+    let [count, unit, "ago"] = words.as_slice() else {
+        return Err(invalid());
+    };
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    let seconds_per_unit = match unit.trim_end_matches('s') {
+        "second" => 1,
+        "minute" => 60,
+        "hour" => 60 * 60,
+        "day" => 24 * 60 * 60,
+        "week" => 7 * 24 * 60 * 60,
+        "month" => 30 * 24 * 60 * 60,
+        "year" => 365 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(now.timestamp() - count * seconds_per_unit)
+}
+
+/// Patch inserting TODO markers for unresolved review comments
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct TodoPatch {
+    /// Unified diff adding the markers, suitable for `git apply`
+    pub patch: String,
+    /// Files the patch modifies
+    pub files: Vec<String>,
+    /// Threads that were not written (file missing or no supported comment syntax)
+    pub skipped: Vec<String>,
+}
+
+/// Detect a file's language from its extension, using the identifiers
+/// understood by VSCode and common syntax highlighters.
+///
+/// # Returns
+/// * `Some(language)` - Known extension (e.g., `"rust"` for `src/lib.rs`)
+/// * `None` - No extension or an unrecognized one
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let extension = std::path::Path::new(path).extension()?.to_str()?;
+    let language = match extension.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "sh" | "bash" | "zsh" => "shellscript",
+        "md" | "markdown" => "markdown",
+        "json" => "json",
+        "toml" => "toml",
+        "yml" | "yaml" => "yaml",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "sql" => "sql",
+        "xml" => "xml",
+        _ => return None,
+    };
+    Some(language)
 }
 
 impl GitService {
@@ -69,12 +232,26 @@ impl GitService {
         Ok(GitService { repo })
     }
 
+    /// Open the repository at the workspace root, honoring the `--workspace`
+    /// override (see [`crate::workspace_dir::workspace_root`]).
+    pub fn for_workspace() -> Result<Self, git2::Error> {
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            git2::Error::from_str(&format!("Failed to determine workspace root: {e}"))
+        })?;
+        Self::new(&root.to_string_lossy())
+    }
+
     /// Parse a commit range string into base and head OIDs.
     ///
     /// Supports various Git commit range formats:
     /// - "HEAD" - Compare HEAD with working tree
     /// - "HEAD~2" - Compare HEAD~2 with working tree  
     /// - "abc123..def456" - Compare two specific commits
+    /// - "main...feature" - Compare feature with where it forked from main (their merge base)
+    /// - "main..WORKING" - Compare main with the working tree ([`WORKING_TREE`] as the head)
+    /// - "WORKING" - Same as "HEAD"
+    ///
+    /// As in git, an empty side of `..` or `...` means HEAD, so "main..." is "main...HEAD".
     ///
     /// # Arguments
     /// * `range` - Git commit range specification
@@ -83,21 +260,137 @@ impl GitService {
     /// * `Ok((base_oid, head_oid))` - Parsed commit OIDs (head_oid is None for working tree)
     /// * `Err(git2::Error)` - Invalid range or commit not found
     pub fn parse_commit_range(&self, range: &str) -> Result<(Oid, Option<Oid>), git2::Error> {
-        if range.contains("..") {
+        if let Some((base, head)) = range.split_once("...") {
+            // Symmetric range: base is where head forked from the given base
+            let head_oid = self.resolve_range_head(head)?;
+            let head_commit = match head_oid {
+                Some(oid) => oid,
+                None => self.resolve_range_base("HEAD")?,
+            };
+            let base_oid = self.repo.merge_base(self.resolve_range_base(base)?, head_commit)?;
+            Ok((base_oid, head_oid))
+        } else if let Some((base, head)) = range.split_once("..") {
             // Range format: base..head
-            let parts: Vec<&str> = range.split("..").collect();
-            if parts.len() != 2 {
+            if head.contains("..") {
                 return Err(git2::Error::from_str("Invalid range format"));
             }
-
-            let base_oid = self.repo.revparse_single(parts[0])?.id();
-            let head_oid = self.repo.revparse_single(parts[1])?.id();
-            Ok((base_oid, Some(head_oid)))
+            Ok((self.resolve_range_base(base)?, self.resolve_range_head(head)?))
+        } else if range == WORKING_TREE {
+            // Uncommitted changes alone, like "HEAD"
+            Ok((self.resolve_range_base("HEAD")?, None))
         } else {
             // Single commit: compare with working tree
-            let base_oid = self.repo.revparse_single(range)?.id();
-            Ok((base_oid, None))
+            Ok((self.resolve_range_base(range)?, None))
+        }
+    }
+
+    /// Commit for the base side of a range; empty means HEAD
+    fn resolve_range_base(&self, spec: &str) -> Result<Oid, git2::Error> {
+        let spec = match spec {
+            "" => "HEAD",
+            WORKING_TREE => {
+                return Err(git2::Error::from_str(&format!(
+                    "{WORKING_TREE} can only be the head of a range (e.g. \"main..{WORKING_TREE}\")"
+                )));
+            }
+            spec => spec,
+        };
+        Ok(self.repo.revparse_single(spec)?.peel_to_commit()?.id())
+    }
+
+    /// Commit for the head side of a range, or None for the working tree
+    fn resolve_range_head(&self, spec: &str) -> Result<Option<Oid>, git2::Error> {
+        if spec == WORKING_TREE {
+            return Ok(None);
         }
+        self.resolve_range_base(spec).map(Some)
+    }
+
+    /// Commits reachable from the head of a range but not from its base, oldest first.
+    ///
+    /// # Arguments
+    /// * `base_oid` - Base commit, excluded along with its ancestors
+    /// * `head_oid` - Head commit, or None for the working tree (whose commits end at HEAD)
+    ///
+    /// # Returns
+    /// * `Ok(Vec<CommitSummary>)` - Commits in the range; empty when head is base
+    /// * `Err(git2::Error)` - Git operation failed
+    pub fn commits_in_range(
+        &self,
+        base_oid: Oid,
+        head_oid: Option<Oid>,
+    ) -> Result<Vec<CommitSummary>, git2::Error> {
+        let mut revwalk = self.repo.revwalk()?;
+        match head_oid {
+            Some(oid) => revwalk.push(oid)?,
+            None => revwalk.push_head()?,
+        }
+        revwalk.hide(base_oid)?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+        revwalk
+            .map(|oid| {
+                let commit = self.repo.find_commit(oid?)?;
+                Ok(CommitSummary {
+                    oid: commit.id().to_string(),
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                    author: commit.author().name().unwrap_or_default().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Commits reachable from HEAD that match `filter`, newest first.
+    ///
+    /// # Arguments
+    /// * `filter` - Time and author filters, applied during the walk
+    /// * `max_count` - Maximum number of matching commits to return
+    ///
+    /// # Returns
+    /// * `Ok(Vec<LogEntry>)` - Matching commits; empty for a repository with no commits
+    /// * `Err(git2::Error)` - Git operation failed
+    pub fn log(&self, filter: &LogFilter, max_count: usize) -> Result<Vec<LogEntry>, git2::Error> {
+        if self.repo.head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        let author_filter = filter.author.as_ref().map(|author| author.to_lowercase());
+        let mut entries = Vec::new();
+        for oid in revwalk {
+            if entries.len() >= max_count {
+                break;
+            }
+            let commit = self.repo.find_commit(oid?)?;
+            let time = commit.time().seconds();
+            // Commits come newest first, so everything after this one is older still
+            if filter.since.is_some_and(|since| time < since) {
+                break;
+            }
+
+            let author = commit.author();
+            let name = author.name().unwrap_or_default().to_string();
+            let email = author.email().unwrap_or_default().to_string();
+            if let Some(needle) = &author_filter {
+                if !name.to_lowercase().contains(needle) && !email.to_lowercase().contains(needle) {
+                    continue;
+                }
+            }
+
+            entries.push(LogEntry {
+                oid: commit.id().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: name,
+                email,
+                date: chrono::DateTime::from_timestamp(time, 0)
+                    .unwrap_or_default()
+                    .to_rfc3339(),
+            });
+        }
+        Ok(entries)
     }
 
     /// Generate diff with file-level statistics between two commits or HEAD and working tree.
@@ -118,29 +411,62 @@ impl GitService {
         diff_opts.include_untracked(true);
         diff_opts.context_lines(3);
 
-        let diff = match head_oid {
-            Some(head_oid) => {
-                // Compare two commits
-                let base_tree = self.repo.find_commit(base_oid)?.tree()?;
-                let head_tree = self.repo.find_commit(head_oid)?.tree()?;
-                self.repo.diff_tree_to_tree(
-                    Some(&base_tree),
-                    Some(&head_tree),
-                    Some(&mut diff_opts),
-                )?
-            }
-            None => {
-                // Compare HEAD with working tree
-                let head_tree = self.repo.find_commit(base_oid)?.tree()?;
-                self.repo
-                    .diff_tree_to_workdir(Some(&head_tree), Some(&mut diff_opts))?
-            }
-        };
+        let mut diff = self.build_diff(base_oid, head_oid, false, false, &mut diff_opts)?;
+        Self::detect_renames(&mut diff)?;
+        Self::collect_file_changes(&diff)
+    }
 
+    /// Untracked files in the working tree, each as a fully-added file change.
+    ///
+    /// Files ignored by `.gitignore` are left out, and untracked directories are
+    /// listed file by file.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<FileChange>)` - One `Added` change per untracked file, with its whole content as a hunk
+    /// * `Err(git2::Error)` - Git operation failed
+    pub fn untracked_file_changes(&self) -> Result<Vec<FileChange>, git2::Error> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.include_untracked(true);
+        diff_opts.recurse_untracked_dirs(true);
+        diff_opts.show_untracked_content(true);
+        diff_opts.include_ignored(false);
+        diff_opts.context_lines(3);
+
+        let diff = self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?;
+        let untracked: Vec<String> = diff
+            .deltas()
+            .filter(|delta| delta.status() == Delta::Untracked)
+            .filter_map(|delta| delta.new_file().path())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        Ok(Self::collect_file_changes(&diff)?
+            .into_iter()
+            .filter(|change| untracked.contains(&change.path))
+            .collect())
+    }
+
+    /// Pair up deleted and added files that are really renames or copies, so they are
+    /// reported as one `FileChange` instead of a delete plus an add.
+    fn detect_renames(diff: &mut Diff<'_>) -> Result<(), git2::Error> {
+        let mut find_opts = DiffFindOptions::new();
+        find_opts.renames(true).copies(true).for_untracked(true);
+        diff.find_similar(Some(&mut find_opts))
+    }
+
+    /// Size change of a delta in bytes (a missing side counts as empty)
+    fn size_delta(delta: &git2::DiffDelta<'_>) -> i64 {
+        delta.new_file().size() as i64 - delta.old_file().size() as i64
+    }
+
+    /// Convert a git2 diff into file changes with hunks and line statistics.
+    fn collect_file_changes(diff: &Diff<'_>) -> Result<Vec<FileChange>, git2::Error> {
         use std::cell::RefCell;
 
         // Use RefCell for interior mutability since all closures are captured simultaneously
         let file_changes = RefCell::new(Vec::<FileChange>::new());
+        // Old and new sizes of renamed or copied files, for estimating their similarity
+        let rename_sizes = RefCell::new(Vec::<Option<(u64, u64)>>::new());
 
         diff.foreach(
             &mut |delta, _progress| {
@@ -148,11 +474,11 @@ impl GitService {
                     (Some(_old_path), Some(new_path)) => (
                         new_path.to_string_lossy().to_string(),
                         match delta.status() {
-                            Delta::Added => ChangeStatus::Added,
+                            Delta::Added | Delta::Untracked => ChangeStatus::Added,
                             Delta::Deleted => ChangeStatus::Deleted,
                             Delta::Modified => ChangeStatus::Modified,
-                            Delta::Renamed => ChangeStatus::Modified,
-                            Delta::Copied => ChangeStatus::Added,
+                            Delta::Renamed => ChangeStatus::Renamed,
+                            Delta::Copied => ChangeStatus::Copied,
                             _ => ChangeStatus::Modified,
                         },
                     ),
@@ -166,17 +492,44 @@ impl GitService {
                     (None, None) => return true,
                 };
 
+                let old_path = match status {
+                    ChangeStatus::Renamed | ChangeStatus::Copied => delta
+                        .old_file()
+                        .path()
+                        .map(|p| p.to_string_lossy().to_string()),
+                    _ => None,
+                };
+                rename_sizes.borrow_mut().push(
+                    old_path
+                        .is_some()
+                        .then(|| (delta.old_file().size(), delta.new_file().size())),
+                );
+
+                let language = language_for_path(&path).map(str::to_string);
+                let binary = delta.flags().is_binary();
                 file_changes.borrow_mut().push(FileChange {
                     path,
                     status,
+                    old_path,
+                    similarity: None,
                     additions: 0,
                     deletions: 0,
                     hunks: Vec::new(),
+                    language,
+                    binary,
+                    size_delta: binary.then(|| Self::size_delta(&delta)),
                 });
 
                 true
             },
-            None, // No binary callback
+            Some(&mut |delta, _binary| {
+                // Binary content is only summarized; no hunk or line callbacks follow
+                let mut file_changes = file_changes.borrow_mut();
+                let current_file = file_changes.last_mut().unwrap();
+                current_file.binary = true;
+                current_file.size_delta = Some(Self::size_delta(&delta));
+                true
+            }),
             Some(&mut |_delta, hunk| {
                 // Called once per hunk - finalize previous hunk, start new one
                 let mut file_changes = file_changes.borrow_mut();
@@ -238,6 +591,370 @@ impl GitService {
             }),
         )?;
 
-        Ok(file_changes.into_inner())
+        let mut file_changes = file_changes.into_inner();
+        for (change, sizes) in file_changes.iter_mut().zip(rename_sizes.into_inner()) {
+            if let Some((old_size, new_size)) = sizes {
+                change.similarity = Some(Self::estimate_similarity(change, old_size, new_size));
+            }
+        }
+
+        Ok(file_changes)
+    }
+
+    /// Estimate how much of the old file survives in the new one, as a percentage.
+    ///
+    /// Mirrors git's similarity index: bytes kept from the old file, relative to the
+    /// larger of the two files. Exact renames score 100.
+    fn estimate_similarity(change: &FileChange, old_size: u64, new_size: u64) -> u8 {
+        let larger = old_size.max(new_size);
+        if larger == 0 {
+            return 100;
+        }
+        let removed: u64 = change
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .filter(|line| line.line_type == DiffLineType::Removed)
+            .map(|line| line.content.len() as u64 + 1)
+            .sum();
+        (old_size.saturating_sub(removed) * 100 / larger) as u8
+    }
+
+    /// Generate a standard unified diff (patch) between two commits or a commit and the working tree.
+    ///
+    /// The exclude flags only apply when `head_oid` is None (i.e., comparing against the working tree):
+    /// - `exclude_unstaged` - Only include changes that are committed or staged in the index
+    /// - `exclude_staged` - Only include changes between the index and the working tree
+    ///
+    /// # Returns
+    /// * `Ok(String)` - Patch text with `---`/`+++`/`@@` headers, suitable for `git apply`
+    /// * `Err(git2::Error)` - Git operation failed
+    pub fn generate_patch(
+        &self,
+        base_oid: Oid,
+        head_oid: Option<Oid>,
+        exclude_unstaged: bool,
+        exclude_staged: bool,
+    ) -> Result<String, git2::Error> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.include_untracked(true);
+        diff_opts.show_untracked_content(true);
+        diff_opts.context_lines(3);
+
+        let diff = self.build_diff(
+            base_oid,
+            head_oid,
+            exclude_unstaged,
+            exclude_staged,
+            &mut diff_opts,
+        )?;
+
+        let mut patch = String::new();
+        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            // Content lines don't carry their origin marker, headers already do
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+
+        Ok(patch)
+    }
+
+    /// Build the underlying git2 diff for a base commit and optional head commit.
+    fn build_diff(
+        &self,
+        base_oid: Oid,
+        head_oid: Option<Oid>,
+        exclude_unstaged: bool,
+        exclude_staged: bool,
+        diff_opts: &mut DiffOptions,
+    ) -> Result<Diff<'_>, git2::Error> {
+        let base_tree = self.repo.find_commit(base_oid)?.tree()?;
+
+        match head_oid {
+            Some(head_oid) => {
+                // Compare two commits
+                let head_tree = self.repo.find_commit(head_oid)?.tree()?;
+                self.repo
+                    .diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Some(diff_opts))
+            }
+            None => match (exclude_unstaged, exclude_staged) {
+                // Compare base with working tree
+                (false, false) => self
+                    .repo
+                    .diff_tree_to_workdir(Some(&base_tree), Some(diff_opts)),
+                // Compare base with index (committed + staged changes)
+                (true, false) => self
+                    .repo
+                    .diff_tree_to_index(Some(&base_tree), None, Some(diff_opts)),
+                // Compare index with working tree (unstaged changes only)
+                (false, true) => self.repo.diff_index_to_workdir(None, Some(diff_opts)),
+                // Nothing left to show
+                (true, true) => self
+                    .repo
+                    .diff_tree_to_tree(Some(&base_tree), Some(&base_tree), Some(diff_opts)),
+            },
+        }
+    }
+
+    /// Count files with uncommitted changes in the working tree.
+    ///
+    /// Staged, unstaged, and untracked files are all counted (ignored files are not).
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of files that would be lost if the worktree were deleted
+    /// * `Err(git2::Error)` - Git operation failed
+    pub fn uncommitted_file_count(&self) -> Result<usize, git2::Error> {
+        let mut status_opts = StatusOptions::new();
+        status_opts.include_untracked(true);
+        status_opts.recurse_untracked_dirs(true);
+        status_opts.include_ignored(false);
+
+        let statuses = self.repo.statuses(Some(&mut status_opts))?;
+        Ok(statuses
+            .iter()
+            .filter(|entry| entry.status() != Status::CURRENT)
+            .count())
+    }
+
+    /// Build a patch that persists unresolved comment threads as TODO markers.
+    ///
+    /// Each marker is inserted directly above the thread's line, with that line's
+    /// indentation, the file's comment syntax, and `parser`'s TODO marker (see
+    /// [`CommentParser::format_todo`]). Threads `parser` can't write a marker for are skipped.
+    /// Resolved threads and threads that are already TODOs are left out. Threads whose
+    /// path is absolute or leaves the repository (`..`) are skipped. Nothing is
+    /// written to disk; the caller decides whether to apply the patch.
+    ///
+    /// # Returns
+    /// * `Ok(TodoPatch)` - Patch text (empty if there is nothing to write) and the files it touches
+    /// * `Err(git2::Error)` - Bare repository or diff generation failed
+    pub fn todo_patch(
+        &self,
+        threads: &[CommentThread],
+        parser: &CommentParser,
+    ) -> Result<TodoPatch, git2::Error> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("Repository has no working directory"))?;
+
+        // Group the markers to insert by file, keeping files in order of first appearance
+        let mut inserts: Vec<(String, Vec<(usize, String, String)>)> = Vec::new();
+        let mut skipped = Vec::new();
+        for thread in threads {
+            if thread.resolved || thread.comment_type == CommentType::Todo {
+                continue;
+            }
+            // Only plain relative paths, so a marker is never written outside the repository
+            let inside_repo = std::path::Path::new(&thread.file_path)
+                .components()
+                .all(|component| matches!(component, std::path::Component::Normal(_)));
+            if !inside_repo {
+                skipped.push(thread.thread_id.clone());
+                continue;
+            }
+            let Some(marker) = parser.format_todo(&thread.file_path, &thread.content) else {
+                skipped.push(thread.thread_id.clone());
+                continue;
+            };
+            let line = thread.line_number.max(1) as usize;
+            let insert = (line, marker, thread.thread_id.clone());
+            match inserts.iter_mut().find(|(path, _)| *path == thread.file_path) {
+                Some((_, markers)) => markers.push(insert),
+                None => inserts.push((thread.file_path.clone(), vec![insert])),
+            }
+        }
+
+        let mut patch = String::new();
+        let mut files = Vec::new();
+        for (path, mut markers) in inserts {
+            let Ok(old) = std::fs::read_to_string(workdir.join(&path)) else {
+                skipped.extend(markers.into_iter().map(|(_, _, thread_id)| thread_id));
+                continue;
+            };
+
+            let mut lines: Vec<String> = old.split_inclusive('\n').map(str::to_string).collect();
+
+            // Insert bottom-up so earlier line numbers stay valid
+            markers.sort_by(|a, b| b.0.cmp(&a.0));
+            for (line, marker, _) in markers {
+                let index = (line - 1).min(lines.len());
+                if index == lines.len() {
+                    // Appending after a final line that has no newline
+                    if let Some(last) = lines.last_mut().filter(|last| !last.ends_with('\n')) {
+                        last.push('\n');
+                    }
+                }
+                let indent: String = lines
+                    .get(index)
+                    .map(|text| text.chars().take_while(|c| *c == ' ' || *c == '\t').collect())
+                    .unwrap_or_default();
+                lines.insert(index, format!("{indent}{marker}\n"));
+            }
+            let new = lines.concat();
+
+            let file_path = std::path::Path::new(&path);
+            let mut file_patch = Patch::from_buffers(
+                old.as_bytes(),
+                Some(file_path),
+                new.as_bytes(),
+                Some(file_path),
+                Some(DiffOptions::new().context_lines(3)),
+            )?;
+            patch.push_str(&String::from_utf8_lossy(&file_patch.to_buf()?));
+            files.push(path);
+        }
+
+        Ok(TodoPatch {
+            patch,
+            files,
+            skipped,
+        })
+    }
+
+    /// Commit changes to the current branch.
+    ///
+    /// With `paths`, those files are staged first (a path missing from the working
+    /// tree is staged as a deletion) and the commit includes them along with
+    /// anything already staged. Without `paths`, only what is already staged is
+    /// committed. The author is taken from the repository's git configuration.
+    ///
+    /// # Returns
+    /// * `Ok(Oid)` - The new commit
+    /// * `Err(git2::Error)` - Nothing is staged, no git identity is configured, or a git operation failed
+    pub fn commit(&self, message: &str, paths: Option<&[String]>) -> Result<Oid, git2::Error> {
+        let mut index = self.repo.index()?;
+        if let Some(paths) = paths {
+            let workdir = self
+                .repo
+                .workdir()
+                .ok_or_else(|| git2::Error::from_str("Cannot commit in a bare repository"))?;
+            for path in paths {
+                let path = std::path::Path::new(path);
+                if workdir.join(path).exists() {
+                    index.add_path(path)?;
+                } else {
+                    index.remove_path(path)?;
+                }
+            }
+            index.write()?;
+        }
+
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let parent = match self.repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            // Unborn branch: this will be the first commit
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(e),
+        };
+
+        let unchanged = match &parent {
+            Some(parent) => parent.tree_id() == tree.id(),
+            None => tree.is_empty(),
+        };
+        if unchanged {
+            return Err(git2::Error::from_str("Nothing staged to commit"));
+        }
+
+        let signature = self.repo.signature()?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        self.repo
+            .commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+    }
+
+    /// Read a file's content as it was at a given revision.
+    ///
+    /// # Arguments
+    /// * `path` - Path relative to the repository root
+    /// * `revision` - Any revision git understands (e.g., "HEAD~2", "main", a commit SHA)
+    ///
+    /// # Returns
+    /// * `Ok(String)` - File content at that revision (invalid UTF-8 is replaced)
+    /// * `Err(git2::Error)` - Unknown revision, path missing at that revision, or not a file
+    pub fn file_at_revision(&self, path: &str, revision: &str) -> Result<String, git2::Error> {
+        let tree = self.repo.revparse_single(revision)?.peel_to_commit()?.tree()?;
+        let entry = tree.get_path(std::path::Path::new(path)).map_err(|_| {
+            git2::Error::from_str(&format!(
+                "Path '{}' does not exist at revision '{}'",
+                path, revision
+            ))
+        })?;
+
+        let blob = entry
+            .to_object(&self.repo)?
+            .into_blob()
+            .map_err(|_| git2::Error::from_str(&format!("'{}' is not a file at revision '{}'", path, revision)))?;
+
+        Ok(String::from_utf8_lossy(blob.content()).into_owned())
+    }
+
+    /// Check that a file exists in the working tree.
+    ///
+    /// # Arguments
+    /// * `path` - Path relative to the repository root
+    ///
+    /// # Returns
+    /// * `Ok(PathBuf)` - Absolute path of the file
+    /// * `Err(git2::Error)` - Bare repository, the path leaves the repository, or it is
+    ///   missing or not a file
+    pub fn working_tree_file(&self, path: &str) -> Result<std::path::PathBuf, git2::Error> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("Repository has no working tree"))?;
+        // Only plain relative paths, so nothing outside the repository is ever read
+        let inside_repo = std::path::Path::new(path)
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)));
+        if !inside_repo {
+            return Err(git2::Error::from_str(&format!(
+                "Path '{}' is not a relative path inside the repository",
+                path
+            )));
+        }
+        let file = workdir.join(path);
+        if !file.is_file() {
+            return Err(git2::Error::from_str(&format!(
+                "Path '{}' does not exist in the working tree",
+                path
+            )));
+        }
+        Ok(file)
+    }
+
+    /// Check whether a unified diff applies cleanly to the working tree, without modifying it.
+    ///
+    /// # Returns
+    /// * `Ok(())` - Patch applies cleanly
+    /// * `Err(git2::Error)` - Patch is malformed or conflicts with the working tree
+    pub fn check_patch(&self, patch: &str) -> Result<(), git2::Error> {
+        let diff = Diff::from_buffer(patch.as_bytes())?;
+        let mut apply_opts = ApplyOptions::new();
+        apply_opts.check(true);
+        self.repo
+            .apply(&diff, ApplyLocation::WorkDir, Some(&mut apply_opts))
+    }
+
+    /// Apply a unified diff to the working tree.
+    ///
+    /// libgit2 applies every hunk in memory before writing anything, so a patch that
+    /// no longer applies (e.g., the working tree changed, or another server already
+    /// applied it) is rejected without modifying any files.
+    ///
+    /// # Returns
+    /// * `Ok(files)` - Paths of the files the patch touched
+    /// * `Err(git2::Error)` - Patch is malformed or conflicts with the working tree
+    pub fn apply_patch(&self, patch: &str) -> Result<Vec<String>, git2::Error> {
+        let diff = Diff::from_buffer(patch.as_bytes())?;
+        self.repo.apply(&diff, ApplyLocation::WorkDir, None)?;
+
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect())
     }
 }
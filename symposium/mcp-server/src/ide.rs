@@ -1,9 +1,10 @@
 use std::{future::Future, pin::Pin};
 
+use anyhow::Context;
 use pulldown_cmark::Event;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::dialect::{DialectFunction, DialectInterpreter};
+use crate::dialect::{DialectError, DialectFunction, DialectInterpreter};
 
 pub mod ambiguity;
 pub mod test;
@@ -12,9 +13,27 @@ pub mod test;
 pub trait IpcClient: Send {
     async fn resolve_symbol_by_name(&mut self, name: &str) -> anyhow::Result<Vec<SymbolDef>>;
     async fn find_all_references(&mut self, symbol: &SymbolDef) -> anyhow::Result<Vec<FileRange>>;
+    async fn rename_preview(
+        &mut self,
+        symbol: &SymbolDef,
+        new_name: &str,
+    ) -> anyhow::Result<Vec<FileEdits>>;
+    async fn document_symbols(&mut self, path: &str) -> anyhow::Result<Vec<DocumentSymbol>>;
+    async fn definition_at(&mut self, location: &FileRange) -> anyhow::Result<Vec<SymbolDef>>;
     fn generate_uuid(&self) -> String;
 }
 
+/// A request to the IDE through [`IpcClient`] failed. Dialect reports these as
+/// [`DialectError::Ipc`], so they are not confused with other function failures.
+#[derive(Debug, thiserror::Error)]
+#[error("{0:#}")]
+pub struct IdeRequestError(anyhow::Error);
+
+/// Mark the error of an [`IpcClient`] request as an [`IdeRequestError`]
+fn ide_request<T>(result: anyhow::Result<T>) -> anyhow::Result<T> {
+    result.map_err(|e| IdeRequestError(e).into())
+}
+
 /// The "symbols" file is used as the expected argument
 /// for a number of other functions. It is intentionally
 /// flexible to enable LLM shorthands -- it can receive
@@ -43,7 +62,7 @@ impl Symbols {
             match self {
                 Symbols::Name(name) => {
                     // Call IPC: resolve-symbol-by-name (using Deref to access userdata directly)
-                    interpreter.resolve_symbol_by_name(name).await
+                    ide_request(interpreter.resolve_symbol_by_name(name).await)
                 }
 
                 Symbols::Array(symbols) => {
@@ -103,6 +122,46 @@ crate::dialect_value!(SymbolRef {
     referenced_at
 });
 
+/// The absolute workspace root, for building paths in expressions
+/// (e.g., `lines(concat(workspaceRoot(), "/src/main.rs"), 1, 10)`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceRoot {}
+
+impl<U: Send> DialectFunction<U> for WorkspaceRoot {
+    type Output = String;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &[];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        Ok(crate::workspace_dir::workspace_root()?
+            .to_string_lossy()
+            .to_string())
+    }
+}
+
+/// Concatenates two strings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Concat {
+    pub left: String,
+    pub right: String,
+}
+
+impl<U: Send> DialectFunction<U> for Concat {
+    type Output = String;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["left", "right"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        Ok(self.left + &self.right)
+    }
+}
+
 /// Represents a range of bytes in a file (or URI, etc).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Lines {
@@ -127,8 +186,11 @@ impl<U: Send> DialectFunction<U> for Lines {
     ) -> anyhow::Result<Self::Output> {
         let Lines { path, start, end } = self;
 
+        let root = crate::workspace_dir::workspace_root()?;
+
         // Find the length of the end line.
-        let content = std::fs::read_to_string(&path)?;
+        let content = std::fs::read_to_string(root.join(&path))
+            .with_context(|| format!("failed to read {path}"))?;
         let lines = content
             .lines()
             .skip(start - 1)
@@ -179,6 +241,31 @@ crate::dialect_value!(FileRange {
     content
 });
 
+impl FileRange {
+    /// This range with both ends moved inside `content`, the text of the file it
+    /// points into: a line past the end becomes the last line, and a column past
+    /// the end of its line becomes the position just after the line's last character.
+    pub fn clamped_to(&self, content: &str) -> FileRange {
+        let lines: Vec<&str> = content.lines().collect();
+        let clamp = |location: &FileLocation| {
+            let line = location.line.clamp(1, lines.len().max(1) as u32);
+            let line_length = lines
+                .get(line as usize - 1)
+                .map_or(0, |text| text.chars().count()) as u32;
+            FileLocation {
+                line,
+                column: location.column.clamp(1, line_length + 1),
+            }
+        };
+        FileRange {
+            path: self.path.clone(),
+            start: clamp(&self.start),
+            end: clamp(&self.end),
+            content: self.content.clone(),
+        }
+    }
+}
+
 /// A line/colum index.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileLocation {
@@ -210,28 +297,281 @@ impl<U: IpcClient> DialectFunction<U> for FindDefinitions {
     }
 }
 
+/// References to a symbol, without duplicates.
+///
+/// The IDE can report the same location more than once (e.g. once per matching
+/// definition); only the first is kept. Results are sorted by path, then position,
+/// unless `sorted` is false, in which case they stay in the order the IDE gave them.
+///
+/// Examples:
+/// - `findReferences("User")` - Every use of `User`, sorted
+/// - `findReferences("User", false)` - The same, in the IDE's order
 #[derive(Deserialize)]
 pub struct FindReferences {
     pub to: Symbols,
+    /// Sort by path, then position (default true)
+    pub sorted: Option<bool>,
 }
 
 impl<U: IpcClient> DialectFunction<U> for FindReferences {
     type Output = Vec<SymbolRef>;
 
-    const PARAMETER_ORDER: &'static [&'static str] = &["to"];
+    const PARAMETER_ORDER: &'static [&'static str] = &["to", "sorted"];
 
     async fn execute(
         self,
         interpreter: &mut DialectInterpreter<U>,
     ) -> anyhow::Result<Self::Output> {
         let definitions = self.to.resolve(interpreter).await?;
+        let mut output: Vec<SymbolRef> = vec![];
+        let mut seen = std::collections::HashSet::new();
+        for definition in definitions {
+            let locations = ide_request(interpreter.find_all_references(&definition).await)?;
+            for loc in locations {
+                let key = (
+                    loc.path.clone(),
+                    (loc.start.line, loc.start.column),
+                    (loc.end.line, loc.end.column),
+                );
+                if seen.insert(key) {
+                    output.push(SymbolRef {
+                        definition: definition.clone(),
+                        referenced_at: loc,
+                    });
+                }
+            }
+        }
+        if self.sorted.unwrap_or(true) {
+            output.sort_by(|a, b| {
+                let position = |r: &SymbolRef| {
+                    let at = &r.referenced_at;
+                    (at.path.clone(), at.start.line, at.start.column)
+                };
+                position(a).cmp(&position(b))
+            });
+        }
+        Ok(output)
+    }
+}
+
+/// Preview the edits an LSP rename would make, without applying them.
+///
+/// This is strictly a read operation: the IDE computes the rename in preview
+/// mode and no files are modified.
+///
+/// Examples:
+/// - `renamePreview(findDefinition("User"), "Account")` - Every edit needed to rename `User` to `Account`
+#[derive(Deserialize)]
+pub struct RenamePreview {
+    pub of: Symbols,
+    pub new_name: String,
+}
+
+impl<U: IpcClient> DialectFunction<U> for RenamePreview {
+    type Output = Vec<FileEdits>;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["of", "new_name"];
+
+    async fn execute(
+        self,
+        interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        let definitions = self.of.resolve(interpreter).await?;
         let mut output = vec![];
         for definition in definitions {
-            let locations = interpreter.find_all_references(&definition).await?;
-            output.extend(locations.into_iter().map(|loc| SymbolRef {
-                definition: definition.clone(),
-                referenced_at: loc,
-            }));
+            let edits = interpreter.rename_preview(&definition, &self.new_name).await;
+            output.extend(ide_request(edits)?);
+        }
+        Ok(output)
+    }
+}
+
+/// The edits a refactoring would make to a single file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEdits {
+    /// File path, relative to workspace root
+    pub path: String,
+
+    /// Edits to apply to this file
+    pub edits: Vec<TextEdit>,
+}
+
+/// A single text replacement within a file (corresponds to LSP TextEdit).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextEdit {
+    /// Range of text being replaced
+    pub range: TextRange,
+
+    /// Replacement text
+    pub new_text: String,
+}
+
+/// A start/end pair within a file whose path is known from context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextRange {
+    pub start: FileLocation,
+    pub end: FileLocation,
+}
+
+/// Outline a file as a hierarchical tree of symbols (LSP `textDocument/documentSymbol`).
+///
+/// Examples:
+/// - `documentSymbols("src/server.rs")` - Top-level items in `server.rs` with their nested members
+#[derive(Deserialize)]
+pub struct DocumentSymbols {
+    pub path: String,
+}
+
+impl<U: IpcClient> DialectFunction<U> for DocumentSymbols {
+    type Output = Vec<DocumentSymbol>;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["path"];
+
+    async fn execute(
+        self,
+        interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        ide_request(interpreter.document_symbols(&self.path).await)
+    }
+}
+
+/// A symbol in a file outline (corresponds loosely to LSP DocumentSymbol).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSymbol {
+    /// The symbol name (e.g., "User", "validateToken")
+    pub name: String,
+
+    /// The "kind" of symbol (e.g., "Struct", "Method")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
+    /// Full extent of the symbol, including its body
+    pub range: FileRange,
+
+    /// Symbols nested inside this one (e.g., the methods of a class)
+    #[serde(default)]
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// The full extent of a symbol's declaration, including its body.
+///
+/// Definitions usually point at just the symbol's name; this looks the symbol up
+/// in its file's outline (`documentSymbols`) to find the whole declaration.
+///
+/// Examples:
+/// - `body(findDefinition("validateToken"))` - Every line of the `validateToken` function
+#[derive(Deserialize)]
+pub struct Body {
+    pub of: Symbols,
+}
+
+impl<U: IpcClient> DialectFunction<U> for Body {
+    type Output = Vec<FileRange>;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["of"];
+
+    async fn execute(
+        self,
+        interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        let definitions = self.of.resolve(interpreter).await?;
+        let mut output = vec![];
+        for definition in definitions {
+            let outline =
+                ide_request(interpreter.document_symbols(&definition.defined_at.path).await)?;
+            let Some(range) = declaration_range(&outline, &definition) else {
+                return Err(DialectError::NoResults {
+                    function: "body".to_string(),
+                    message: format!(
+                        "`{}` not found in the outline of {}",
+                        definition.name, definition.defined_at.path
+                    ),
+                }
+                .into());
+            };
+            output.push(range);
+        }
+        Ok(output)
+    }
+}
+
+/// Range of the innermost outline entry named like `definition` that contains it.
+/// Entries with other names are never used, so a method missing from the outline
+/// is not mistaken for its `impl` block.
+fn declaration_range(outline: &[DocumentSymbol], definition: &SymbolDef) -> Option<FileRange> {
+    fn containing<'a>(symbols: &'a [DocumentSymbol], line: u32, found: &mut Vec<&'a DocumentSymbol>) {
+        for symbol in symbols {
+            if symbol.range.start.line <= line && line <= symbol.range.end.line {
+                found.push(symbol);
+                containing(&symbol.children, line, found);
+            }
+        }
+    }
+
+    let mut found = vec![];
+    containing(outline, definition.defined_at.start.line, &mut found);
+
+    // `found` runs from outermost to innermost
+    found
+        .iter()
+        .rev()
+        .find(|symbol| symbol.name == definition.name)
+        .map(|symbol| symbol.range.clone())
+}
+
+/// One file range or several, so position-based functions accept the result of
+/// `lines` as well as the list returned by `search`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FileRanges {
+    One(FileRange),
+    Many(Vec<FileRange>),
+}
+
+impl FileRanges {
+    pub fn into_vec(self) -> Vec<FileRange> {
+        match self {
+            FileRanges::One(range) => vec![range],
+            FileRanges::Many(ranges) => ranges,
+        }
+    }
+}
+
+/// The definition of whatever symbol is at a position (LSP `textDocument/definition`).
+///
+/// The position is the start of each given range. Definitions reached from several
+/// positions are only listed once.
+///
+/// Examples:
+/// - `definitionAt(lines("src/foo.rs", 42, 42))` - Definition of the symbol at the start of line 42
+/// - `definitionAt(search("src/", "User::new"))` - Definitions of what each match refers to
+#[derive(Deserialize)]
+pub struct DefinitionAt {
+    pub at: FileRanges,
+}
+
+impl<U: IpcClient> DialectFunction<U> for DefinitionAt {
+    type Output = Vec<SymbolDef>;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["at"];
+
+    async fn execute(
+        self,
+        interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        let mut output: Vec<SymbolDef> = vec![];
+        for location in self.at.into_vec() {
+            for definition in ide_request(interpreter.definition_at(&location).await)? {
+                let (path, start) = (&definition.defined_at.path, &definition.defined_at.start);
+                let seen = output.iter().any(|d| {
+                    d.defined_at.path == *path
+                        && d.defined_at.start.line == start.line
+                        && d.defined_at.start.column == start.column
+                });
+                if !seen {
+                    output.push(definition);
+                }
+            }
         }
         Ok(output)
     }
@@ -239,54 +579,59 @@ impl<U: IpcClient> DialectFunction<U> for FindReferences {
 
 /// Search for regex patterns in files, respecting gitignore rules.
 ///
+/// A relative `path` is taken relative to the workspace root, and the matches
+/// then have workspace-relative paths too.
+///
+/// The optional `filter` is either a file extension (`rs` or `.rs`) or, if it
+/// contains any of `*?[`, a gitignore-style glob relative to `path` (`*.rs`,
+/// `tests/**/*.rs`). Only files passing the filter are read.
+///
 /// Examples:
 /// - `{"search": {"path": "src/auth.rs", "regex": "fn\\s+\\w+"}}` - Find functions in specific file
-/// - `{"search": {"path": "src/", "regex": "TODO|FIXME", "extension": ".rs"}}` - Find todos in Rust files
-/// - `{"search": {"path": ".", "regex": "struct User\\b", "extension": "rs"}}` - Find User struct in Rust files
+/// - `{"search": {"path": "src/", "regex": "TODO|FIXME", "filter": ".rs"}}` - Find todos in Rust files
+/// - `{"search": {"path": ".", "regex": "struct User\\b", "filter": "rs"}}` - Find User struct in Rust files
+/// - `{"search": {"path": ".", "regex": "#\\[test\\]", "filter": "tests/**/*.rs"}}` - Find tests under `tests/`
 #[derive(Deserialize)]
 pub struct Search {
     pub path: String,
     pub regex: String,
-    pub extension: Option<String>,
+    #[serde(alias = "extension")]
+    pub filter: Option<String>,
 }
 
 impl<U: IpcClient> DialectFunction<U> for Search {
     type Output = Vec<FileRange>;
 
-    const PARAMETER_ORDER: &'static [&'static str] = &["path", "regex", "extension"];
+    const PARAMETER_ORDER: &'static [&'static str] = &["path", "regex", "filter"];
 
     async fn execute(
         self,
         _interpreter: &mut DialectInterpreter<U>,
     ) -> anyhow::Result<Self::Output> {
-        use ignore::Walk;
+        use crate::file_search::{FileFilter, walk_files};
         use regex::Regex;
         use std::path::Path;
 
         let regex = Regex::new(&self.regex)?;
         let mut results = Vec::new();
-        let search_path = Path::new(&self.path);
 
-        // Normalize extension (add dot if missing)
-        let extension_filter = self.extension.as_ref().map(|ext| {
-            if ext.starts_with('.') {
-                ext.clone()
-            } else {
-                format!(".{}", ext)
-            }
-        });
+        // Relative paths are relative to the workspace root, and so are the results
+        let workspace_root = crate::workspace_dir::workspace_root()?;
+        let search_path = workspace_root.join(&self.path);
+        let report_root = Path::new(&self.path)
+            .is_relative()
+            .then_some(workspace_root.as_path());
 
         // If it's a specific file, search just that file
         if search_path.is_file() {
-            results.extend(process_file(&self.path, &extension_filter, &regex));
+            let root = search_path.parent().unwrap_or(Path::new(""));
+            let filter = FileFilter::new(root, self.filter.as_deref())?;
+            results.extend(process_file(&search_path, report_root, &filter, &regex));
         } else if search_path.is_dir() {
             // Directory search with gitignore support
-            for result in Walk::new(&self.path) {
-                let entry = result?;
-                if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                    let path_str = entry.path().to_string_lossy().to_string();
-                    results.extend(process_file(&path_str, &extension_filter, &regex));
-                }
+            let filter = FileFilter::new(&search_path, self.filter.as_deref())?;
+            for path in walk_files(&search_path, &filter) {
+                results.extend(process_file(&path?, report_root, &filter, &regex));
             }
         }
         // If path doesn't exist, just return empty results
@@ -295,6 +640,226 @@ impl<U: IpcClient> DialectFunction<U> for Search {
     }
 }
 
+/// Keep the locations in a list that satisfy a predicate.
+///
+/// The predicate is itself a Dialect expression (`pathContains`, `pathMatches`
+/// or `inFile`) that evaluates to a [`LocationPredicate`], which is then applied
+/// to each item. Items are returned unchanged, so filtering `findReferences`
+/// still yields references. References are tested by where they are referenced
+/// from, definitions by where they are defined.
+///
+/// Examples:
+/// - `filter(findReferences("User"), pathContains("test"))` - References to `User` from test code
+/// - `filter(findDefinitions("User"), pathMatches("src/**"))` - Definitions of `User` under `src/`
+/// - `filter(search("src", "TODO"), inFile("src/main.rs"))` - Todos in one file
+#[derive(Deserialize)]
+pub struct Filter {
+    pub items: Vec<serde_json::Value>,
+    pub predicate: LocationPredicate,
+}
+
+impl<U: Send> DialectFunction<U> for Filter {
+    type Output = Vec<serde_json::Value>;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["items", "predicate"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        let mut output = vec![];
+        for item in self.items {
+            let Some(path) = location_path(&item) else {
+                return Err(DialectError::InvalidArguments {
+                    function: "filter".to_string(),
+                    message: format!("not a location: {item}"),
+                }
+                .into());
+            };
+            let matches = self.predicate.matches(path).map_err(|e| DialectError::InvalidArguments {
+                function: "filter".to_string(),
+                message: format!("{e:#}"),
+            })?;
+            if matches {
+                output.push(item);
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// The `count(items)` function: how many items a list holds.
+///
+/// Evaluates to a number rather than locations, so a walkthrough comment can
+/// state a total (interpolated as `{value}`) without listing every result.
+///
+/// Examples:
+/// - `count(findReferences("User"))` - Number of references to `User`
+/// - `count(filter(search("src", "TODO"), pathContains("auth")))` - Todos in auth code
+#[derive(Deserialize)]
+pub struct Count {
+    pub items: Vec<serde_json::Value>,
+}
+
+impl<U: Send> DialectFunction<U> for Count {
+    type Output = usize;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["items"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        Ok(self.items.len())
+    }
+}
+
+/// The `countFiles(items)` function: how many distinct files a list of locations spans.
+///
+/// Locations are grouped the same way as in [`Filter`].
+///
+/// Example:
+/// - `countFiles(findReferences("User"))` - Number of files referring to `User`
+#[derive(Deserialize)]
+pub struct CountFiles {
+    pub items: Vec<serde_json::Value>,
+}
+
+impl<U: Send> DialectFunction<U> for CountFiles {
+    type Output = usize;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["items"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        let mut paths = std::collections::BTreeSet::new();
+        for item in &self.items {
+            let Some(path) = location_path(item) else {
+                return Err(DialectError::InvalidArguments {
+                    function: "countFiles".to_string(),
+                    message: format!("not a location: {item}"),
+                }
+                .into());
+            };
+            paths.insert(path);
+        }
+        Ok(paths.len())
+    }
+}
+
+/// The path a location-like value points at: where a reference is made from,
+/// where a symbol is defined, or the path of a plain file range.
+fn location_path(item: &serde_json::Value) -> Option<&str> {
+    ["referencedAt", "definedAt", "range"]
+        .into_iter()
+        .find_map(|key| item.get(key))
+        .unwrap_or(item)
+        .get("path")?
+        .as_str()
+}
+
+/// A test on a location's path, produced by the predicate functions
+/// ([`PathContains`], [`PathMatches`], [`InFile`]) and consumed by [`Filter`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocationPredicate {
+    PathContains(String),
+    PathMatches(String),
+    InFile(String),
+}
+
+impl LocationPredicate {
+    pub fn matches(&self, path: &str) -> anyhow::Result<bool> {
+        use crate::file_search::FileFilter;
+        use std::path::Path;
+
+        Ok(match self {
+            LocationPredicate::PathContains(substr) => path.contains(substr.as_str()),
+            LocationPredicate::PathMatches(glob) => {
+                FileFilter::glob(Path::new(""), glob)?.matches(&workspace_relative(path))
+            }
+            LocationPredicate::InFile(file) => workspace_relative(path) == workspace_relative(file),
+        })
+    }
+}
+
+/// `path` relative to the workspace root, with any remaining root component
+/// dropped, so that IDE-reported absolute paths compare against relative ones.
+fn workspace_relative(path: &str) -> std::path::PathBuf {
+    use std::path::{Component, Path};
+
+    let path = Path::new(path);
+    let root = crate::workspace_dir::workspace_root().ok();
+    let relative = root
+        .as_deref()
+        .and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(path);
+    relative
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_) | Component::ParentDir))
+        .collect()
+}
+
+/// Predicate for [`Filter`]: the path contains `substr`.
+#[derive(Deserialize)]
+pub struct PathContains {
+    pub substr: String,
+}
+
+impl<U: Send> DialectFunction<U> for PathContains {
+    type Output = LocationPredicate;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["substr"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        Ok(LocationPredicate::PathContains(self.substr))
+    }
+}
+
+/// Predicate for [`Filter`]: the workspace-relative path matches a
+/// gitignore-style `glob` (`*.rs` matches anywhere, `src/**` is anchored).
+#[derive(Deserialize)]
+pub struct PathMatches {
+    pub glob: String,
+}
+
+impl<U: Send> DialectFunction<U> for PathMatches {
+    type Output = LocationPredicate;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["glob"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        Ok(LocationPredicate::PathMatches(self.glob))
+    }
+}
+
+/// Predicate for [`Filter`]: the location is in the file at `path`.
+#[derive(Deserialize)]
+pub struct InFile {
+    pub path: String,
+}
+
+impl<U: Send> DialectFunction<U> for InFile {
+    type Output = LocationPredicate;
+
+    const PARAMETER_ORDER: &'static [&'static str] = &["path"];
+
+    async fn execute(
+        self,
+        _interpreter: &mut DialectInterpreter<U>,
+    ) -> anyhow::Result<Self::Output> {
+        Ok(LocationPredicate::InFile(self.path))
+    }
+}
+
 /// Generate git diffs for commit ranges, respecting exclude options.
 ///
 /// Examples:
@@ -325,7 +890,7 @@ impl<U: IpcClient> DialectFunction<U> for GitDiff {
         use crate::git::GitService;
 
         // Use current directory as repo path (could be made configurable)
-        let git_service = GitService::new(".")?;
+        let git_service = GitService::for_workspace()?;
         let (base_oid, head_oid) = git_service.parse_commit_range(&self.commit_range)?;
         let file_changes = git_service.generate_diff(base_oid, head_oid)?;
 
@@ -365,6 +930,8 @@ pub enum ResolvedLocation {
     FileRange(FileRange),
     SearchResults(Vec<FileRange>),
     SymbolDefs(Vec<SymbolDef>),
+    RenameEdits(Vec<FileEdits>),
+    DocumentSymbols(Vec<DocumentSymbol>),
 }
 
 /// Resolved comment output from the [`Comment`] dialect function.
@@ -394,10 +961,29 @@ impl<U: IpcClient> DialectFunction<U> for Comment {
             ResolvedLocation::FileRange(range) => vec![range],
             ResolvedLocation::SymbolDefs(def) => def.iter().map(|d| d.defined_at.clone()).collect(),
             ResolvedLocation::SearchResults(results) => results,
+            ResolvedLocation::RenameEdits(files) => files
+                .into_iter()
+                .flat_map(|file| {
+                    let path = file.path;
+                    file.edits.into_iter().map(move |edit| FileRange {
+                        path: path.clone(),
+                        start: edit.range.start,
+                        end: edit.range.end,
+                        content: None,
+                    })
+                })
+                .collect(),
+            ResolvedLocation::DocumentSymbols(symbols) => {
+                symbols.into_iter().map(|symbol| symbol.range).collect()
+            }
         };
 
         if locations.is_empty() {
-            return Err(anyhow::anyhow!("Location resolved to empty search results"));
+            return Err(DialectError::NoResults {
+                function: "comment".to_string(),
+                message: "Location resolved to empty search results".to_string(),
+            }
+            .into());
         }
 
         // Process content elements - for now, convert strings to Markdown elements
@@ -452,21 +1038,19 @@ fn search_file_content(file_path: &str, content: &str, regex: &regex::Regex) ->
     results
 }
 
-fn matches_extension(file_path: &str, extension_filter: &Option<String>) -> bool {
-    match extension_filter {
-        Some(ext) => file_path.ends_with(ext),
-        None => true,
-    }
-}
-
+/// Search one file, reporting its path relative to `report_root` if one is given
 fn process_file(
-    file_path: &str,
-    extension_filter: &Option<String>,
+    file_path: &std::path::Path,
+    report_root: Option<&std::path::Path>,
+    filter: &crate::file_search::FileFilter,
     regex: &regex::Regex,
 ) -> Vec<FileRange> {
-    if matches_extension(file_path, extension_filter) {
+    if filter.matches(file_path) {
         if let Ok(content) = std::fs::read_to_string(file_path) {
-            return search_file_content(file_path, &content, regex);
+            let reported = report_root
+                .and_then(|root| file_path.strip_prefix(root).ok())
+                .unwrap_or(file_path);
+            return search_file_content(&reported.to_string_lossy(), &content, regex);
         }
     }
     Vec::new()
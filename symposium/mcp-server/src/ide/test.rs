@@ -1,9 +1,14 @@
 #![cfg(test)]
 use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::{
     dialect::{DialectFunction, DialectInterpreter},
-    ide::{FileLocation, FileRange, FindDefinitions, FindReferences, IpcClient, SymbolDef},
+    ide::{
+        DocumentSymbol, DocumentSymbols, FileEdits, FileLocation, FileRange, FindDefinitions,
+        FindReferences, IpcClient, RenamePreview, SymbolDef, TextEdit, TextRange,
+    },
 };
 use serde::Deserialize;
 
@@ -12,6 +17,8 @@ use serde::Deserialize;
 pub struct MockIpcClient {
     symbols: BTreeMap<String, Vec<SymbolDef>>,
     references: BTreeMap<String, Vec<FileRange>>,
+    /// Number of `resolve_symbol_by_name` calls, shared between clones
+    resolve_calls: Arc<AtomicUsize>,
 }
 
 impl MockIpcClient {
@@ -82,12 +89,25 @@ impl MockIpcClient {
         Self {
             symbols,
             references,
+            resolve_calls: Arc::new(AtomicUsize::new(0)),
         }
     }
+
+    /// Replace the references reported for `symbol`
+    pub fn with_references(mut self, symbol: &str, references: Vec<FileRange>) -> Self {
+        self.references.insert(symbol.to_string(), references);
+        self
+    }
+
+    /// How many symbols have been resolved through this client or its clones
+    pub fn resolve_call_count(&self) -> usize {
+        self.resolve_calls.load(Ordering::SeqCst)
+    }
 }
 
 impl IpcClient for MockIpcClient {
     async fn resolve_symbol_by_name(&mut self, name: &str) -> anyhow::Result<Vec<SymbolDef>> {
+        self.resolve_calls.fetch_add(1, Ordering::SeqCst);
         Ok(self.symbols.get(name).cloned().unwrap_or_default())
     }
 
@@ -102,6 +122,84 @@ impl IpcClient for MockIpcClient {
             .unwrap_or_default())
     }
 
+    async fn rename_preview(
+        &mut self,
+        symbol: &SymbolDef,
+        new_name: &str,
+    ) -> anyhow::Result<Vec<FileEdits>> {
+        // One edit at the definition plus one per known reference, grouped by file
+        let mut files: BTreeMap<String, Vec<TextEdit>> = BTreeMap::new();
+        let locations = std::iter::once(&symbol.defined_at)
+            .chain(self.references.get(&symbol.name).into_iter().flatten());
+        for location in locations {
+            files.entry(location.path.clone()).or_default().push(TextEdit {
+                range: TextRange {
+                    start: location.start.clone(),
+                    end: location.end.clone(),
+                },
+                new_text: new_name.to_string(),
+            });
+        }
+
+        Ok(files
+            .into_iter()
+            .map(|(path, edits)| FileEdits { path, edits })
+            .collect())
+    }
+
+    async fn definition_at(&mut self, location: &FileRange) -> anyhow::Result<Vec<SymbolDef>> {
+        // Resolve the identifier under the position, taken from the range's line content
+        let Some(line) = &location.content else {
+            return Ok(vec![]);
+        };
+        let offset = (location.start.column as usize).saturating_sub(1).min(line.len());
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let start = line[..offset].rfind(|c| !is_ident(c)).map_or(0, |i| i + 1);
+        let end = line[offset..].find(|c| !is_ident(c)).map_or(line.len(), |i| offset + i);
+        Ok(self.symbols.get(&line[start..end]).cloned().unwrap_or_default())
+    }
+
+    async fn document_symbols(&mut self, path: &str) -> anyhow::Result<Vec<DocumentSymbol>> {
+        if path == "src/unreachable.rs" {
+            anyhow::bail!("IDE did not respond");
+        }
+        if path != "src/models.rs" {
+            return Ok(vec![]);
+        }
+
+        let symbol = |name: &str, kind: &str, start: u32, end: u32, children: Vec<DocumentSymbol>| DocumentSymbol {
+            name: name.to_string(),
+            kind: Some(kind.to_string()),
+            range: FileRange {
+                path: path.to_string(),
+                start: FileLocation { line: start, column: 1 },
+                end: FileLocation { line: end, column: 2 },
+                content: None,
+            },
+            children,
+        };
+
+        Ok(vec![
+            symbol(
+                "User",
+                "Struct",
+                10,
+                13,
+                vec![
+                    symbol("id", "Field", 11, 11, vec![]),
+                    symbol("name", "Field", 12, 12, vec![]),
+                ],
+            ),
+            symbol(
+                "impl User",
+                "Object",
+                15,
+                19,
+                vec![symbol("new", "Method", 16, 18, vec![])],
+            ),
+        ])
+    }
+
     fn generate_uuid(&self) -> String {
         "DUMMY_UUID".to_string()
     }
@@ -282,6 +380,117 @@ async fn test_find_references() {
     .assert_debug_eq(&interpreter.evaluate("findReferences(\"User\")").await);
 }
 
+#[tokio::test]
+async fn test_rename_preview() {
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_function::<FindDefinitions>();
+    interpreter.add_function_with_name::<FindDefinitions>("finddefinition");
+    interpreter.add_function::<RenamePreview>();
+
+    expect_test::expect![[r#"
+        Ok(
+            Array [
+                Object {
+                    "edits": Array [
+                        Object {
+                            "new_text": String("Account"),
+                            "range": Object {
+                                "end": Object {
+                                    "column": Number(16),
+                                    "line": Number(5),
+                                },
+                                "start": Object {
+                                    "column": Number(12),
+                                    "line": Number(5),
+                                },
+                            },
+                        },
+                    ],
+                    "path": String("src/auth.rs"),
+                },
+                Object {
+                    "edits": Array [
+                        Object {
+                            "new_text": String("Account"),
+                            "range": Object {
+                                "end": Object {
+                                    "column": Number(12),
+                                    "line": Number(23),
+                                },
+                                "start": Object {
+                                    "column": Number(8),
+                                    "line": Number(23),
+                                },
+                            },
+                        },
+                    ],
+                    "path": String("src/handlers.rs"),
+                },
+                Object {
+                    "edits": Array [
+                        Object {
+                            "new_text": String("Account"),
+                            "range": Object {
+                                "end": Object {
+                                    "column": Number(4),
+                                    "line": Number(10),
+                                },
+                                "start": Object {
+                                    "column": Number(0),
+                                    "line": Number(10),
+                                },
+                            },
+                        },
+                    ],
+                    "path": String("src/models.rs"),
+                },
+            ],
+        )
+    "#]]
+    .assert_debug_eq(
+        &interpreter
+            .evaluate("renamePreview(findDefinition(\"User\"), \"Account\")")
+            .await,
+    );
+}
+
+#[tokio::test]
+async fn test_document_symbols() {
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_function::<DocumentSymbols>();
+
+    let result = interpreter
+        .evaluate("documentSymbols(\"src/models.rs\")")
+        .await
+        .unwrap();
+    let symbols: Vec<DocumentSymbol> = serde_json::from_value(result).unwrap();
+
+    let outline: Vec<(&str, Vec<&str>)> = symbols
+        .iter()
+        .map(|s| {
+            (
+                s.name.as_str(),
+                s.children.iter().map(|c| c.name.as_str()).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outline,
+        vec![("User", vec!["id", "name"]), ("impl User", vec!["new"])]
+    );
+    assert_eq!(symbols[0].kind.as_deref(), Some("Struct"));
+    assert_eq!(symbols[0].range.path, "src/models.rs");
+    assert_eq!(symbols[0].range.start.line, 10);
+    assert_eq!(symbols[1].children[0].kind.as_deref(), Some("Method"));
+
+    // Unknown files have an empty outline
+    let result = interpreter
+        .evaluate("documentSymbols(\"src/missing.rs\")")
+        .await
+        .unwrap();
+    assert_eq!(result, serde_json::json!([]));
+}
+
 #[tokio::test]
 async fn test_symbol_not_found() {
     let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
@@ -499,6 +708,61 @@ async fn test_search_function() {
     .assert_debug_eq(&result);
 }
 
+#[tokio::test]
+async fn test_search_filters_and_gitignore() {
+    use test_utils::TestRepo;
+
+    let temp_repo = TestRepo::new()
+        .overwrite_and_add(".gitignore", "target/\n")
+        .overwrite_and_add("src/lib.rs", "// needle\n")
+        .overwrite_and_add("src/notes.txt", "needle\n")
+        .overwrite_and_add("tests/it.rs", "// needle\n")
+        .commit("Initial commit")
+        .create();
+    std::fs::create_dir(temp_repo.path().join("target")).unwrap();
+    std::fs::write(temp_repo.path().join("target/generated.rs"), "// needle\n").unwrap();
+
+    let mock_client = MockIpcClient::new();
+    let mut interpreter = DialectInterpreter::new(mock_client);
+    interpreter.add_function::<crate::ide::Search>();
+
+    let root = temp_repo.path().to_str().unwrap();
+    async fn search(
+        interpreter: &mut DialectInterpreter<MockIpcClient>,
+        root: &str,
+        filter: &str,
+    ) -> Vec<String> {
+        let program = if filter.is_empty() {
+            format!(r#"search("{root}", "needle")"#)
+        } else {
+            format!(r#"search("{root}", "needle", "{filter}")"#)
+        };
+        let result = interpreter.evaluate(&program).await.unwrap();
+        let mut paths: Vec<String> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|range| {
+                let path = range["path"].as_str().unwrap();
+                path.strip_prefix(root).unwrap().trim_start_matches('/').to_string()
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    // Ignored files are skipped without a filter
+    assert_eq!(search(&mut interpreter, root, "").await, vec!["src/lib.rs", "src/notes.txt", "tests/it.rs"]);
+
+    // Extension filters, with or without the dot
+    assert_eq!(search(&mut interpreter, root, "rs").await, vec!["src/lib.rs", "tests/it.rs"]);
+    assert_eq!(search(&mut interpreter, root, ".txt").await, vec!["src/notes.txt"]);
+
+    // Globs match anywhere unless they contain a slash, which anchors them to the search path
+    assert_eq!(search(&mut interpreter, root, "*.rs").await, vec!["src/lib.rs", "tests/it.rs"]);
+    assert_eq!(search(&mut interpreter, root, "tests/**/*.rs").await, vec!["tests/it.rs"]);
+}
+
 #[tokio::test]
 async fn test_gitdiff_function() {
     use test_utils::TestRepo;
@@ -575,6 +839,7 @@ async fn test_gitdiff_function() {
                             "old_start": Number(1),
                         },
                     ],
+                    "language": String("rust"),
                     "path": String("src/main.rs"),
                     "status": String("Modified"),
                 },
@@ -762,3 +1027,285 @@ async fn test_lines_function() {
         v
     }));
 }
+
+#[tokio::test]
+async fn test_workspace_root_concat() {
+    let mock_client = MockIpcClient::new();
+    let mut interpreter = DialectInterpreter::new(mock_client);
+    interpreter.add_function::<crate::ide::WorkspaceRoot>();
+    interpreter.add_function::<crate::ide::Concat>();
+
+    let result = interpreter
+        .evaluate(r#"concat(workspaceRoot(), "/src/main.rs")"#)
+        .await
+        .unwrap();
+
+    let expected = crate::workspace_dir::workspace_root()
+        .unwrap()
+        .join("src/main.rs");
+    assert_eq!(result, serde_json::json!(expected.to_string_lossy()));
+}
+
+#[tokio::test]
+async fn test_filter_references() {
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_standard_ide_functions();
+
+    // Returns the path each filtered item is located at
+    async fn paths(interpreter: &mut DialectInterpreter<MockIpcClient>, program: &str) -> Vec<String> {
+        let result = interpreter.evaluate(program).await.unwrap();
+        let items: Vec<serde_json::Value> = serde_json::from_value(result).unwrap();
+        items
+            .iter()
+            .map(|item| {
+                let location = item.get("referencedAt").or(item.get("definedAt")).unwrap_or(item);
+                location["path"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    assert_eq!(
+        paths(&mut interpreter, r#"filter(findReferences("User"), pathContains("handlers"))"#).await,
+        ["src/handlers.rs"]
+    );
+    assert_eq!(
+        paths(&mut interpreter, r#"filter(findReferences("User"), pathMatches("auth.rs"))"#).await,
+        ["src/auth.rs"]
+    );
+    assert_eq!(
+        paths(&mut interpreter, r#"filter(findReferences("User"), pathMatches("src/**"))"#).await,
+        ["src/auth.rs", "src/handlers.rs"]
+    );
+    assert!(
+        paths(&mut interpreter, r#"filter(findReferences("User"), pathContains("test"))"#)
+            .await
+            .is_empty()
+    );
+
+    // Definitions are tested by where they are defined
+    assert_eq!(
+        paths(&mut interpreter, r#"filter(findDefinitions("validateToken"), inFile("src/utils.rs"))"#).await,
+        ["src/utils.rs"]
+    );
+
+    // Filtered references keep their shape
+    let result = interpreter
+        .evaluate(r#"filter(findReferences("User"), inFile("src/auth.rs"))"#)
+        .await
+        .unwrap();
+    assert_eq!(result[0]["name"], "User");
+    assert_eq!(result[0]["referencedAt"]["start"]["line"], 5);
+
+    let err = interpreter
+        .evaluate(r#"filter(["src/auth.rs"], pathContains("auth"))"#)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("filter: not a location"), "{err}");
+}
+
+#[tokio::test]
+async fn test_body() {
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_standard_ide_functions();
+
+    // The definition covers just the name; the body spans the whole struct
+    let result = interpreter
+        .evaluate(r#"body(findDefinition("User"))"#)
+        .await
+        .unwrap();
+    let ranges: Vec<FileRange> = serde_json::from_value(result).unwrap();
+    let spans: Vec<_> = ranges
+        .iter()
+        .map(|r| (r.path.as_str(), r.start.line, r.end.line))
+        .collect();
+    assert_eq!(spans, [("src/models.rs", 10, 13)]);
+
+    // A method inside an `impl` resolves to the method, not the whole block
+    let outline = MockIpcClient::new()
+        .document_symbols("src/models.rs")
+        .await
+        .unwrap();
+    let new = SymbolDef {
+        name: "new".to_string(),
+        kind: Some("method".to_string()),
+        defined_at: FileRange {
+            path: "src/models.rs".to_string(),
+            start: FileLocation { line: 16, column: 12 },
+            end: FileLocation { line: 16, column: 15 },
+            content: None,
+        },
+    };
+    let range = super::declaration_range(&outline, &new).unwrap();
+    assert_eq!((range.start.line, range.end.line), (16, 18));
+
+    // Only entries with the same name count, so the enclosing `impl` is not used
+    let helper = SymbolDef {
+        name: "helper".to_string(),
+        ..new
+    };
+    assert!(super::declaration_range(&outline, &helper).is_none());
+
+    // Symbols missing from their file's outline are an error
+    let err = interpreter
+        .evaluate(r#"body(findDefinition("validateToken"))"#)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not found in the outline"), "{err}");
+}
+
+#[tokio::test]
+async fn test_error_kinds() {
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_standard_ide_functions();
+
+    // A failed IDE request is an IPC error
+    let err = interpreter
+        .evaluate(r#"documentSymbols("src/unreachable.rs")"#)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), "ipc_error");
+    assert!(err.to_string().contains("IDE did not respond"), "{err}");
+
+    // Other failures inside a function are not
+    let err = interpreter
+        .evaluate(r#"lines("missing.rs", 1, 1)"#)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), "function_failed");
+    assert!(err.to_string().contains("failed to read missing.rs"), "{err}");
+}
+
+#[tokio::test]
+async fn test_definition_at_search_results() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path().to_str().unwrap();
+    std::fs::write(
+        temp.path().join("handlers.rs"),
+        "fn create_user() -> User {\n    User::new()\n}\n",
+    )
+    .unwrap();
+
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_standard_ide_functions();
+
+    // Both matches refer to `User`, so its definition is listed once
+    let result = interpreter
+        .evaluate(&format!(r#"definitionAt(search("{root}", "User"))"#))
+        .await
+        .unwrap();
+    let definitions: Vec<SymbolDef> = serde_json::from_value(result).unwrap();
+    let found: Vec<_> = definitions
+        .iter()
+        .map(|d| (d.name.as_str(), d.defined_at.path.as_str(), d.defined_at.start.line))
+        .collect();
+    assert_eq!(found, [("User", "src/models.rs", 10)]);
+
+    // A position that is not on a known symbol has no definition
+    let result = interpreter
+        .evaluate(&format!(r#"definitionAt(lines("{root}/handlers.rs", 3, 3))"#))
+        .await
+        .unwrap();
+    assert_eq!(result, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_count_results() {
+    let mut interpreter = DialectInterpreter::new(MockIpcClient::new());
+    interpreter.add_standard_ide_functions();
+
+    let result = interpreter.evaluate(r#"count(findReferences("User"))"#).await.unwrap();
+    assert_eq!(result, serde_json::json!(2));
+
+    let result = interpreter
+        .evaluate(r#"count(filter(findReferences("User"), pathContains("handlers")))"#)
+        .await
+        .unwrap();
+    assert_eq!(result, serde_json::json!(1));
+
+    let result = interpreter.evaluate(r#"count(findReferences("Unknown"))"#).await.unwrap();
+    assert_eq!(result, serde_json::json!(0));
+
+    let result = interpreter.evaluate(r#"countFiles(findDefinitions("validateToken"))"#).await.unwrap();
+    assert_eq!(result, serde_json::json!(2));
+
+    // A single location is not a list
+    assert!(interpreter.evaluate(r#"count(lines("Cargo.toml", 1, 1))"#).await.is_err());
+    assert!(interpreter.evaluate(r#"countFiles([1, 2])"#).await.is_err());
+}
+
+#[tokio::test]
+async fn test_find_references_dedup_and_sort() {
+    let range = |path: &str, line: u32, column: u32| FileRange {
+        path: path.to_string(),
+        start: FileLocation { line, column },
+        end: FileLocation { line, column: column + 4 },
+        content: None,
+    };
+    let client = MockIpcClient::new().with_references(
+        "User",
+        vec![
+            range("src/handlers.rs", 23, 8),
+            range("src/auth.rs", 9, 4),
+            range("src/handlers.rs", 23, 8),
+            range("src/auth.rs", 5, 12),
+            range("src/auth.rs", 5, 2),
+        ],
+    );
+    let mut interpreter = DialectInterpreter::new(client);
+    interpreter.add_function::<FindReferences>();
+
+    let locations = |result: serde_json::Value| -> Vec<String> {
+        result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let at = &r["referencedAt"];
+                format!("{}:{}:{}", at["path"].as_str().unwrap(), at["start"]["line"], at["start"]["column"])
+            })
+            .collect()
+    };
+
+    // Duplicates are dropped and the rest sorted by path, line, then column
+    let result = interpreter.evaluate(r#"findReferences("User")"#).await.unwrap();
+    assert_eq!(
+        locations(result),
+        ["src/auth.rs:5:2", "src/auth.rs:5:12", "src/auth.rs:9:4", "src/handlers.rs:23:8"]
+    );
+
+    // Unsorted keeps the IDE's order, still without duplicates
+    let result = interpreter.evaluate(r#"findReferences("User", false)"#).await.unwrap();
+    assert_eq!(
+        locations(result),
+        ["src/handlers.rs:23:8", "src/auth.rs:9:4", "src/auth.rs:5:12", "src/auth.rs:5:2"]
+    );
+}
+
+#[test]
+fn test_file_range_clamped_to() {
+    let content = "fn main() {\n    run();\n}\n";
+    let range = |start: (u32, u32), end: (u32, u32)| FileRange {
+        path: "src/main.rs".to_string(),
+        start: FileLocation { line: start.0, column: start.1 },
+        end: FileLocation { line: end.0, column: end.1 },
+        content: None,
+    };
+    let positions = |range: FileRange| {
+        (
+            (range.start.line, range.start.column),
+            (range.end.line, range.end.column),
+        )
+    };
+
+    // In bounds, including just past the end of a line: unchanged
+    assert_eq!(positions(range((1, 4), (2, 11)).clamped_to(content)), ((1, 4), (2, 11)));
+
+    // Columns past the end of their line, lines past the end of the file
+    assert_eq!(positions(range((2, 40), (9, 9)).clamped_to(content)), ((2, 11), (3, 2)));
+
+    // Zero is below the 1-based minimum
+    assert_eq!(positions(range((0, 0), (1, 0)).clamped_to(content)), ((1, 1), (1, 1)));
+
+    // An empty file has one empty line
+    assert_eq!(positions(range((3, 5), (4, 1)).clamped_to("")), ((1, 1), (1, 1)));
+}
@@ -4,11 +4,14 @@
 //! Ports the logic from server/src/ipc.ts to Rust with cross-platform support.
 
 use crate::{constants::DAEMON_SOCKET_PREFIX, types::{
-    FindAllReferencesPayload, GetSelectionMessage, GetSelectionResult, LogLevel, ResolveSymbolByNamePayload
+    DefinitionAtPayload, DocumentSymbolsPayload, FindAllReferencesPayload, GetSelectionMessage, GetSelectionResult, LogLevel,
+    RenamePreviewPayload, ResolveSymbolByNamePayload
 }};
 use anyhow::Context;
 
 use serde_json;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use tracing::{debug, error, info};
 use uuid::Uuid;
@@ -81,8 +84,11 @@ pub enum IPCError {
     #[error("Failed to write to IPC connection: {0}")]
     WriteError(#[from] std::io::Error),
 
-    #[error("Request timeout after 5 seconds")]
-    Timeout,
+    #[error("IDE did not respond within {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("No IDE connected: the MCP server is not running in a VSCode terminal and no extension answered")]
+    NoIdeConnected,
 
     #[error("Response channel closed")]
     ChannelClosed,
@@ -96,6 +102,73 @@ pub enum IPCError {
 
 pub type Result<T> = std::result::Result<T, IPCError>;
 
+/// How long to wait for the extension to answer a prepare_for_deletion request
+/// before falling back to immediate deletion.
+const PREPARE_FOR_DELETION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Scripted replies for a test-mode [`IPCCommunicator`], keyed by message type
+///
+/// Each request of a given type consumes the next queued reply. Requests with
+/// nothing queued fail right away, as if the app had rejected them, so tests
+/// never wait on the real request timeout.
+#[derive(Clone, Default)]
+pub struct MockResponses {
+    queued: Arc<Mutex<HashMap<crate::types::IPCMessageType, VecDeque<crate::types::ResponsePayload>>>>,
+}
+
+impl MockResponses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a successful reply carrying `data` for the next `message_type` request
+    pub fn push_ok(&self, message_type: crate::types::IPCMessageType, data: impl serde::Serialize) {
+        self.push(
+            message_type,
+            crate::types::ResponsePayload {
+                success: true,
+                error: None,
+                data: Some(serde_json::to_value(data).expect("mock response must serialize")),
+            },
+        );
+    }
+
+    /// Queue a failed reply for the next `message_type` request
+    pub fn push_err(&self, message_type: crate::types::IPCMessageType, error: impl Into<String>) {
+        self.push(
+            message_type,
+            crate::types::ResponsePayload {
+                success: false,
+                error: Some(error.into()),
+                data: None,
+            },
+        );
+    }
+
+    fn push(&self, message_type: crate::types::IPCMessageType, payload: crate::types::ResponsePayload) {
+        self.queued
+            .lock()
+            .unwrap()
+            .entry(message_type)
+            .or_default()
+            .push_back(payload);
+    }
+
+    /// The reply to `message_type`: the next scripted one, or a failure
+    fn reply_to(&self, message_type: &crate::types::IPCMessageType) -> crate::types::ResponsePayload {
+        self.queued
+            .lock()
+            .unwrap()
+            .get_mut(message_type)
+            .and_then(VecDeque::pop_front)
+            .unwrap_or_else(|| crate::types::ResponsePayload {
+                success: false,
+                error: Some(format!("no mock response scripted for {:?}", message_type)),
+                data: None,
+            })
+    }
+}
+
 /// Handles IPC communication between MCP server and VSCode extension
 ///
 /// IPC communication using actor-based dispatch system.
@@ -118,6 +191,57 @@ pub struct IPCCommunicator {
 
 
 
+/// A walkthrough being sent to VSCode in chunks, started by
+/// [`IPCCommunicator::begin_walkthrough`]
+///
+/// Every chunk is acknowledged before the next one is sent, so chunks are
+/// displayed in the order they are sent. Always end the stream with
+/// [`Self::finish`], passing an error if the walkthrough could not be completed.
+pub struct WalkthroughStream<'a> {
+    ipc: &'a IPCCommunicator,
+    walkthrough_id: String,
+    chunk_count: u32,
+}
+
+impl WalkthroughStream<'_> {
+    /// Send the next piece of the walkthrough's HTML
+    pub async fn send_chunk(&mut self, content: String) -> Result<()> {
+        let index = self.chunk_count;
+        self.chunk_count += 1;
+        if self.ipc.test_mode {
+            info!("Walkthrough chunk {index} called (test mode): {} bytes", content.len());
+            return Ok(());
+        }
+
+        let chunk = crate::types::PresentWalkthroughChunkMessage {
+            walkthrough_id: self.walkthrough_id.clone(),
+            index,
+            content,
+        };
+        self.ipc.send_to_ide(chunk).await.map_err(|e| {
+            IPCError::SendError(format!("Failed to send walkthrough chunk {index}: {}", e))
+        })
+    }
+
+    /// End the walkthrough; `error` tells VSCode the chunks sent so far are incomplete
+    pub async fn finish(self, error: Option<String>) -> Result<u32> {
+        if self.ipc.test_mode {
+            info!("End chunked walkthrough called (test mode): {} chunks", self.chunk_count);
+            return Ok(self.chunk_count);
+        }
+
+        let end = crate::types::PresentWalkthroughEndMessage {
+            walkthrough_id: self.walkthrough_id,
+            chunk_count: self.chunk_count,
+            error,
+        };
+        self.ipc.send_to_ide(end).await.map_err(|e| {
+            IPCError::SendError(format!("Failed to end chunked walkthrough: {}", e))
+        })?;
+        Ok(self.chunk_count)
+    }
+}
+
 impl IPCCommunicator {
     pub async fn new(
         shell_pid: Option<u32>,
@@ -125,6 +249,7 @@ impl IPCCommunicator {
         options: crate::Options,
     ) -> Result<Self> {
         info!("Creating IPC communicator for shell PID {shell_pid:?}");
+        let request_timeout = std::time::Duration::from_secs(options.ipc_timeout);
 
         // Create actor system alongside existing connection management
         let dispatch_handle = {
@@ -138,6 +263,7 @@ impl IPCCommunicator {
 
             // Create dispatch actor with client channels
             crate::actor::DispatchHandle::new(from_daemon_rx, to_daemon_tx, shell_pid, reference_handle)
+                .with_request_timeout(request_timeout)
         };
 
         Ok(Self {
@@ -149,12 +275,35 @@ impl IPCCommunicator {
 
     /// Creates a new IPCCommunicator in test mode
     /// In test mode, all IPC operations are mocked and only local logging occurs
-    pub fn new_test(_reference_handle: crate::actor::ReferenceHandle) -> Self {
+    pub fn new_test(reference_handle: crate::actor::ReferenceHandle) -> Self {
+        Self::new_test_with_responses(reference_handle, MockResponses::new())
+    }
+
+    /// Like [`Self::new_test`], but requests that reach the mocked daemon are
+    /// answered from `responses`
+    pub fn new_test_with_responses(
+        _reference_handle: crate::actor::ReferenceHandle,
+        responses: MockResponses,
+    ) -> Self {
         let mock_fn = Box::new(
-            |mut _rx: tokio::sync::mpsc::Receiver<crate::types::IPCMessage>,
-             _tx: tokio::sync::mpsc::Sender<crate::types::IPCMessage>| {
+            move |mut rx: tokio::sync::mpsc::Receiver<crate::types::IPCMessage>,
+                  tx: tokio::sync::mpsc::Sender<crate::types::IPCMessage>| {
+                let responses = responses.clone();
                 Box::pin(async move {
-                    // Minimal mock for test constructor
+                    // Answer every message; replies to fire-and-forget messages
+                    // have no pending request and are dropped by the dispatcher
+                    while let Some(message) = rx.recv().await {
+                        let payload = responses.reply_to(&message.message_type);
+                        let response = crate::types::IPCMessage {
+                            message_type: crate::types::IPCMessageType::Response,
+                            id: message.id,
+                            sender: message.sender,
+                            payload: serde_json::to_value(payload).unwrap(),
+                        };
+                        if tx.send(response).await.is_err() {
+                            break;
+                        }
+                    }
                 })
                     as std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             },
@@ -167,6 +316,46 @@ impl IPCCommunicator {
         }
     }
 
+    /// Terminal shell PID reported to the extension, if discovery succeeded
+    pub fn terminal_shell_pid(&self) -> Option<u32> {
+        self.terminal_shell_pid
+    }
+
+    /// Send a request that only the IDE can answer
+    ///
+    /// Without a shell PID the server was started outside a VSCode terminal,
+    /// so a request nobody answers means no IDE is connected, not a slow one.
+    async fn send_to_ide<M: crate::types::IpcPayload>(&self, message: M) -> anyhow::Result<M::Reply> {
+        match self.dispatch_handle.send(message).await {
+            Err(e)
+                if self.terminal_shell_pid.is_none()
+                    && matches!(e.downcast_ref::<IPCError>(), Some(IPCError::Timeout(_))) =>
+            {
+                Err(IPCError::NoIdeConnected.into())
+            }
+            result => result,
+        }
+    }
+
+    /// Project path and taskspace UUID for outgoing requests.
+    ///
+    /// In test mode we are usually not running inside a taskspace, so fall back
+    /// to placeholders and let the mocked daemon decide how to answer.
+    fn project_info(&self) -> Result<(String, String)> {
+        match extract_project_info() {
+            Err(_) if self.test_mode => Ok((
+                "/test/project.symposium".to_string(),
+                Uuid::nil().to_string(),
+            )),
+            result => result,
+        }
+    }
+
+    /// Whether IPC is mocked out (see [`Self::new_test`])
+    pub fn is_test_mode(&self) -> bool {
+        self.test_mode
+    }
+
     pub async fn initialize(&mut self) -> Result<()> {
         if self.test_mode {
             info!("IPC Communicator initialized (test mode) - creating mock actor");
@@ -196,6 +385,7 @@ impl IPCCommunicator {
                                                     "Mock initial prompt".to_string(),
                                                 ),
                                                 collaborator: Some("sparkle".to_string()),
+                                                tags: vec![],
                                             },
                                         )
                                         .unwrap(),
@@ -249,8 +439,7 @@ impl IPCCommunicator {
             base_uri: walkthrough.base_uri,
         };
         let _response: () = self
-            .dispatch_handle
-            .send(walkthrough_message)
+            .send_to_ide(walkthrough_message)
             .await
             .map_err(|e| {
                 IPCError::SendError(format!(
@@ -262,6 +451,81 @@ impl IPCCommunicator {
         Ok(())
     }
 
+    /// Start sending a walkthrough to VSCode in chunks; see [`WalkthroughStream`]
+    pub async fn begin_walkthrough(&self, base_uri: String) -> Result<WalkthroughStream<'_>> {
+        let walkthrough_id = Uuid::new_v4().to_string();
+        if self.test_mode {
+            info!("Begin chunked walkthrough called (test mode): {walkthrough_id}");
+        } else {
+            let begin = crate::types::PresentWalkthroughBeginMessage {
+                walkthrough_id: walkthrough_id.clone(),
+                base_uri,
+            };
+            self.send_to_ide(begin).await.map_err(|e| {
+                IPCError::SendError(format!("Failed to begin chunked walkthrough: {}", e))
+            })?;
+        }
+
+        Ok(WalkthroughStream {
+            ipc: self,
+            walkthrough_id,
+            chunk_count: 0,
+        })
+    }
+
+    /// Open `location` in the editor and select its range
+    pub async fn reveal_location(&self, location: crate::ide::FileRange) -> Result<()> {
+        if self.test_mode {
+            info!("Reveal location called (test mode): {:?}", location);
+            return Ok(());
+        }
+
+        let reveal_message = crate::types::RevealLocationMessage { location };
+        let _response: () = self
+            .send_to_ide(reveal_message)
+            .await
+            .map_err(|e| {
+                IPCError::SendError(format!("Failed to send reveal_location via actors: {}", e))
+            })?;
+        Ok(())
+    }
+
+    /// Open the editor's diff view for two versions of a file
+    pub async fn open_diff(&self, message: crate::types::OpenDiffMessage) -> Result<()> {
+        if self.test_mode {
+            info!(
+                "Open diff called (test mode): {} {}..{}",
+                message.path, message.base_revision, message.head_revision
+            );
+            return Ok(());
+        }
+
+        let _response: () = self.send_to_ide(message).await.map_err(|e| {
+            IPCError::SendError(format!("Failed to send open_diff via actors: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Editor tabs open in the IDE; empty when no IDE is connected
+    pub async fn list_open_editors(&self) -> Result<Vec<crate::types::OpenEditor>> {
+        if self.test_mode {
+            info!("List open editors called (test mode)");
+            return Ok(vec![]);
+        }
+
+        match self.send_to_ide(crate::types::ListOpenEditorsMessage {}).await {
+            Ok(editors) => Ok(editors),
+            Err(e) if matches!(e.downcast_ref::<IPCError>(), Some(IPCError::NoIdeConnected)) => {
+                info!("No IDE connected, so no open editors");
+                Ok(vec![])
+            }
+            Err(e) => Err(IPCError::SendError(format!(
+                "Failed to send list_open_editors via actors: {}",
+                e
+            ))),
+        }
+    }
+
     pub async fn get_selection(&self) -> Result<GetSelectionResult> {
         if self.test_mode {
             info!("Get selection called (test mode)");
@@ -282,8 +546,7 @@ impl IPCCommunicator {
         // Use actor dispatch system for get_selection request/reply
         let get_selection_message = GetSelectionMessage {};
         let selection: GetSelectionResult = self
-            .dispatch_handle
-            .send(get_selection_message)
+            .send_to_ide(get_selection_message)
             .await
             .map_err(|e| {
                 IPCError::SendError(format!("Failed to send get_selection via actors: {}", e))
@@ -332,6 +595,18 @@ impl IPCCommunicator {
         Ok(())
     }
 
+    /// Discover who else is on the message bus: broadcast a Marco and collect
+    /// the Polos that answer within `window`
+    pub async fn list_peers(&self, window: std::time::Duration) -> Result<Vec<crate::types::Peer>> {
+        let peers = self
+            .dispatch_handle
+            .discover_peers(window)
+            .await
+            .map_err(|e| IPCError::SendError(format!("Failed to discover peers via actors: {}", e)))?;
+        info!("Discovered {} peers on the message bus", peers.len());
+        Ok(peers)
+    }
+
     /// Send Goodbye discovery message (MCP server announces departure with shell PID)
     pub async fn send_goodbye(&self) -> Result<()> {
         if self.test_mode {
@@ -365,6 +640,7 @@ impl IPCCommunicator {
         task_description: String,
         initial_prompt: String,
         collaborator: Option<String>,
+        tags: Vec<String>,
     ) -> Result<()> {
         if self.test_mode {
             info!("Spawn taskspace called (test mode): {}", name);
@@ -380,6 +656,7 @@ impl IPCCommunicator {
             task_description,
             initial_prompt,
             collaborator,
+            tags,
         };
         self.dispatch_handle
             .send(spawn_payload)
@@ -395,11 +672,12 @@ impl IPCCommunicator {
         &self,
         message: String,
         category: crate::types::ProgressCategory,
+        fields: BTreeMap<String, String>,
     ) -> Result<()> {
         if self.test_mode {
             info!(
-                "Log progress called (test mode): {} - {:?}",
-                message, category
+                "Log progress called (test mode): {} - {:?} {:?}",
+                message, category, fields
             );
             return Ok(());
         }
@@ -411,6 +689,7 @@ impl IPCCommunicator {
             taskspace_uuid,
             message,
             category,
+            fields,
         };
         self.dispatch_handle
             .send(progress_payload)
@@ -422,9 +701,13 @@ impl IPCCommunicator {
     }
 
     /// Send signal_user message to request user attention
-    pub async fn signal_user(&self, message: String) -> Result<()> {
+    pub async fn signal_user(
+        &self,
+        message: String,
+        priority: crate::types::SignalPriority,
+    ) -> Result<()> {
         if self.test_mode {
-            info!("Signal user called (test mode): {}", message);
+            info!("Signal user called (test mode): {} ({})", message, priority.as_str());
             return Ok(());
         }
 
@@ -434,6 +717,7 @@ impl IPCCommunicator {
             project_path,
             taskspace_uuid,
             message,
+            priority,
         };
         self.dispatch_handle
             .send(signal_payload)
@@ -450,8 +734,9 @@ impl IPCCommunicator {
         name: String,
         description: String,
         collaborator: Option<String>,
+        tags: Option<Vec<String>>,
     ) -> Result<crate::types::TaskspaceStateResponse> {
-        let (project_path, taskspace_uuid) = extract_project_info()?;
+        let (project_path, taskspace_uuid) = self.project_info()?;
 
         // Use actor dispatch system for update_taskspace request/reply
         let request = crate::types::TaskspaceStateRequest {
@@ -460,6 +745,7 @@ impl IPCCommunicator {
             name: Some(name),
             description: Some(description),
             collaborator,
+            tags,
         };
         let response: crate::types::TaskspaceStateResponse =
             self.dispatch_handle.send(request).await.map_err(|e| {
@@ -502,7 +788,7 @@ impl IPCCommunicator {
     /// - Caller (get_taskspace_context) handles errors gracefully
     pub async fn get_taskspace_state(&self) -> Result<crate::types::TaskspaceStateResponse> {
         // Extract taskspace UUID from directory structure (task-UUID/.symposium pattern)
-        let (project_path, taskspace_uuid) = extract_project_info()?;
+        let (project_path, taskspace_uuid) = self.project_info()?;
 
         // Use new actor-based dispatch system
         let request = crate::types::TaskspaceStateRequest {
@@ -511,6 +797,7 @@ impl IPCCommunicator {
             name: None,
             description: None,
             collaborator: None,
+            tags: None,
         };
         let response: crate::types::TaskspaceStateResponse =
             self.dispatch_handle.send(request).await.map_err(|e| {
@@ -519,6 +806,62 @@ impl IPCCommunicator {
         return Ok(response);
     }
 
+    /// Ask the extension whether the current taskspace is safe to delete
+    ///
+    /// Returns `Ok(None)` if no reply arrives within `PREPARE_FOR_DELETION_TIMEOUT`
+    /// (e.g., an older extension that does not know this message), in which case
+    /// callers should fall back to deleting immediately.
+    pub async fn prepare_for_deletion(
+        &self,
+        uncommitted_file_count: usize,
+    ) -> Result<Option<crate::types::PrepareForDeletionResponse>> {
+        if self.test_mode {
+            info!(
+                "Prepare for deletion called (test mode): {} uncommitted files",
+                uncommitted_file_count
+            );
+            return Ok(None);
+        }
+
+        let (project_path, taskspace_uuid) = extract_project_info()?;
+        let payload = crate::types::PrepareForDeletionPayload {
+            project_path,
+            taskspace_uuid,
+            uncommitted_file_count,
+        };
+        match tokio::time::timeout(PREPARE_FOR_DELETION_TIMEOUT, self.dispatch_handle.send(payload))
+            .await
+        {
+            Ok(response) => response.map(Some).map_err(|e| {
+                IPCError::SendError(format!(
+                    "Failed to send prepare_for_deletion via actors: {}",
+                    e
+                ))
+            }),
+            Err(_) => {
+                info!("No prepare_for_deletion reply within {:?}", PREPARE_FOR_DELETION_TIMEOUT);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Send apply_patch message asking the user to review and apply a patch
+    ///
+    /// The extension always asks the user for confirmation before touching the working tree.
+    pub async fn apply_patch(
+        &self,
+        patch: String,
+        description: String,
+    ) -> Result<crate::types::ApplyPatchResponse> {
+        // In test mode the mocked daemon answers from its scripted responses
+        let (project_path, taskspace_uuid) = self.project_info()?;
+        let payload =
+            crate::types::ApplyPatchPayload::new(project_path, taskspace_uuid, patch, description);
+        self.send_to_ide(payload).await.map_err(|e| {
+            IPCError::SendError(format!("Failed to send apply_patch via actors: {}", e))
+        })
+    }
+
     /// Send delete_taskspace message to delete current taskspace
     pub async fn delete_taskspace(&self) -> Result<()> {
         if self.test_mode {
@@ -571,8 +914,7 @@ impl crate::ide::IpcClient for IPCCommunicator {
         };
 
         let symbols: Vec<crate::ide::SymbolDef> = self
-            .dispatch_handle
-            .send(payload)
+            .send_to_ide(payload)
             .await
             .with_context(|| format!("failed to resolve symbol '{name}'"))?;
 
@@ -592,7 +934,7 @@ impl crate::ide::IpcClient for IPCCommunicator {
         };
 
         let locations: Vec<crate::ide::FileRange> =
-            self.dispatch_handle.send(payload).await.with_context(|| {
+            self.send_to_ide(payload).await.with_context(|| {
                 format!(
                     "VSCode extension failed to find references for symbol '{}'",
                     symbol.name
@@ -602,6 +944,74 @@ impl crate::ide::IpcClient for IPCCommunicator {
         Ok(locations)
     }
 
+    async fn rename_preview(
+        &mut self,
+        symbol: &crate::ide::SymbolDef,
+        new_name: &str,
+    ) -> anyhow::Result<Vec<crate::ide::FileEdits>> {
+        if self.test_mode {
+            return Ok(vec![]);
+        }
+
+        let payload = RenamePreviewPayload {
+            symbol: symbol.clone(),
+            new_name: new_name.to_string(),
+        };
+
+        let edits: Vec<crate::ide::FileEdits> =
+            self.send_to_ide(payload).await.with_context(|| {
+                format!(
+                    "VSCode extension failed to preview rename of '{}' to '{new_name}'",
+                    symbol.name
+                )
+            })?;
+
+        Ok(edits)
+    }
+
+    async fn document_symbols(
+        &mut self,
+        path: &str,
+    ) -> anyhow::Result<Vec<crate::ide::DocumentSymbol>> {
+        if self.test_mode {
+            return Ok(vec![]);
+        }
+
+        let payload = DocumentSymbolsPayload {
+            path: path.to_string(),
+        };
+
+        let symbols: Vec<crate::ide::DocumentSymbol> = self
+            .send_to_ide(payload)
+            .await
+            .with_context(|| format!("VSCode extension failed to outline '{path}'"))?;
+
+        Ok(symbols)
+    }
+
+    async fn definition_at(
+        &mut self,
+        location: &crate::ide::FileRange,
+    ) -> anyhow::Result<Vec<crate::ide::SymbolDef>> {
+        if self.test_mode {
+            return Ok(vec![]);
+        }
+
+        let payload = DefinitionAtPayload {
+            location: location.clone(),
+        };
+
+        let definitions: Vec<crate::ide::SymbolDef> =
+            self.send_to_ide(payload).await.with_context(|| {
+                format!(
+                    "VSCode extension failed to find the definition at {}:{}:{}",
+                    location.path, location.start.line, location.start.column
+                )
+            })?;
+
+        Ok(definitions)
+    }
+
     fn generate_uuid(&self) -> String {
         uuid::Uuid::new_v4().to_string()
     }
@@ -615,7 +1025,9 @@ mod test {
 
     use crate::ipc::IPCCommunicator;
     use crate::types::{
-        IPCMessage, IPCMessageType, MessageSender, PresentReviewParams, ReviewMode,
+        ApplyPatchPayload, ApplyPatchResponse, ApplyPatchStatus, IPCMessage, IPCMessageType,
+        MessageSender, PresentReviewParams, ResponsePayload, ReviewMode, SignalPriority,
+        SignalUserPayload,
     };
     use serde_json;
 
@@ -678,4 +1090,345 @@ mod test {
         assert!(matches!(deserialized.mode, ReviewMode::Append));
         assert_eq!(deserialized.base_uri, "/project/root");
     }
+
+    #[test]
+    fn test_signal_user_priority_defaults_to_normal() {
+        // Payloads from older servers have no priority field
+        let payload: SignalUserPayload = serde_json::from_value(serde_json::json!({
+            "project_path": "/project",
+            "taskspace_uuid": "550e8400-e29b-41d4-a716-446655440000",
+            "message": "Need input",
+        }))
+        .unwrap();
+        assert_eq!(payload.priority, SignalPriority::Normal);
+
+        let payload = SignalUserPayload {
+            priority: SignalPriority::Urgent,
+            ..payload
+        };
+        assert_eq!(serde_json::to_value(&payload).unwrap()["priority"], "urgent");
+    }
+
+    #[tokio::test]
+    async fn test_discover_peers() {
+        use crate::types::{PeerRole, PoloPayload};
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        // Mock bus: a Marco is answered by an IDE and by another MCP server
+        // (twice), plus a Polo without a role from an older server
+        let mock_fn = Box::new(
+            |mut rx: tokio::sync::mpsc::Receiver<IPCMessage>,
+             tx: tokio::sync::mpsc::Sender<IPCMessage>| {
+                Box::pin(async move {
+                    let polo = |shell_pid: u32, role: Option<PeerRole>| IPCMessage {
+                        message_type: IPCMessageType::Polo,
+                        id: uuid::Uuid::new_v4().to_string(),
+                        sender: MessageSender {
+                            working_directory: "/project".to_string(),
+                            taskspace_uuid: None,
+                            shell_pid: Some(shell_pid),
+                        },
+                        payload: serde_json::to_value(PoloPayload { role }).unwrap(),
+                    };
+                    while let Some(message) = rx.recv().await {
+                        if message.message_type != IPCMessageType::Marco {
+                            continue;
+                        }
+                        for reply in [
+                            polo(1, Some(PeerRole::Ide)),
+                            polo(2, Some(PeerRole::McpServer)),
+                            polo(2, Some(PeerRole::McpServer)),
+                            polo(3, None),
+                        ] {
+                            let _ = tx.send(reply).await;
+                        }
+                    }
+                })
+                    as std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            },
+        ) as crate::actor::dispatch::MockActorFn;
+        let dispatch_handle = crate::actor::DispatchHandle::spawn_with_mock(mock_fn);
+
+        let peers = dispatch_handle
+            .discover_peers(std::time::Duration::from_millis(200))
+            .await
+            .unwrap();
+        let found: Vec<_> = peers
+            .iter()
+            .map(|p| (p.role, p.sender.shell_pid.unwrap(), p.is_self))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (PeerRole::Ide, 1, false),
+                (PeerRole::McpServer, 2, false),
+                (PeerRole::McpServer, 3, false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_requires_confirmation() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        // Mock extension that records what it receives and declines the patch
+        let (seen_tx, mut seen_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mock_fn = Box::new(
+            move |mut rx: tokio::sync::mpsc::Receiver<IPCMessage>,
+                  tx: tokio::sync::mpsc::Sender<IPCMessage>| {
+                let seen_tx = seen_tx.clone();
+                Box::pin(async move {
+                    while let Some(message) = rx.recv().await {
+                        let reply = IPCMessage {
+                            message_type: IPCMessageType::Response,
+                            id: message.id.clone(),
+                            sender: message.sender.clone(),
+                            payload: serde_json::to_value(ResponsePayload {
+                                success: true,
+                                error: None,
+                                data: Some(serde_json::json!({
+                                    "status": "declined",
+                                    "message": "User declined"
+                                })),
+                            })
+                            .unwrap(),
+                        };
+                        let _ = seen_tx.send(message);
+                        let _ = tx.send(reply).await;
+                    }
+                })
+                    as std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            },
+        ) as crate::actor::dispatch::MockActorFn;
+        let dispatch_handle = crate::actor::DispatchHandle::spawn_with_mock(mock_fn);
+
+        let patch = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+        let response: ApplyPatchResponse = dispatch_handle
+            .send(ApplyPatchPayload::new(
+                "/project".to_string(),
+                "550e8400-e29b-41d4-a716-446655440000".to_string(),
+                patch.to_string(),
+                "Rename old to new".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status, ApplyPatchStatus::Declined);
+
+        let message = seen_rx.recv().await.unwrap();
+        assert!(matches!(message.message_type, IPCMessageType::ApplyPatch));
+        let payload: ApplyPatchPayload = serde_json::from_value(message.payload).unwrap();
+        assert_eq!(payload.patch, patch);
+        assert!(payload.require_confirmation);
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_reply_reaches_caller() {
+        let responses = crate::ipc::MockResponses::new();
+        responses.push_ok(
+            IPCMessageType::ApplyPatch,
+            serde_json::json!({ "status": "applied", "conflicts": [], "message": "Patch applied" }),
+        );
+        responses.push_ok(
+            IPCMessageType::ApplyPatch,
+            serde_json::json!({ "status": "conflict", "conflicts": ["error: patch failed: src/lib.rs:1"] }),
+        );
+        responses.push_err(IPCMessageType::ApplyPatch, "git not found");
+        let ipc = IPCCommunicator::new_test_with_responses(
+            crate::actor::ReferenceHandle::new(),
+            responses,
+        );
+
+        let patch = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+        let applied = ipc
+            .apply_patch(patch.to_string(), "Rename old to new".to_string())
+            .await
+            .unwrap();
+        assert_eq!(applied.status, ApplyPatchStatus::Applied);
+
+        let conflict = ipc
+            .apply_patch(patch.to_string(), "Rename old to new".to_string())
+            .await
+            .unwrap();
+        assert_eq!(conflict.status, ApplyPatchStatus::Conflict);
+        assert_eq!(conflict.conflicts, ["error: patch failed: src/lib.rs:1"]);
+        assert_eq!(conflict.message, None);
+
+        let error = ipc
+            .apply_patch(patch.to_string(), "Rename old to new".to_string())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("git not found"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out_when_ide_does_not_respond() {
+        use crate::types::GetSelectionMessage;
+        use std::time::Duration;
+
+        // Mock IDE that reads requests and never answers, except that it accepts
+        // patches after a delay longer than the request timeout (a slow human)
+        let mock_fn = Box::new(
+            |mut rx: tokio::sync::mpsc::Receiver<IPCMessage>,
+             tx: tokio::sync::mpsc::Sender<IPCMessage>| {
+                Box::pin(async move {
+                    while let Some(message) = rx.recv().await {
+                        if !matches!(message.message_type, IPCMessageType::ApplyPatch) {
+                            continue;
+                        }
+                        tokio::time::sleep(Duration::from_millis(300)).await;
+                        let reply = IPCMessage {
+                            message_type: IPCMessageType::Response,
+                            id: message.id,
+                            sender: message.sender,
+                            payload: serde_json::to_value(ResponsePayload {
+                                success: true,
+                                error: None,
+                                data: Some(serde_json::json!({ "status": "applied" })),
+                            })
+                            .unwrap(),
+                        };
+                        let _ = tx.send(reply).await;
+                    }
+                })
+                    as std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            },
+        ) as crate::actor::dispatch::MockActorFn;
+        let dispatch_handle = crate::actor::DispatchHandle::spawn_with_mock(mock_fn)
+            .with_request_timeout(Duration::from_millis(100))
+            .with_user_wait_timeout(Duration::from_secs(5));
+
+        let error = dispatch_handle
+            .send(GetSelectionMessage {})
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("IDE did not respond within 100ms"),
+            "unexpected error: {error}"
+        );
+
+        // Requests that wait for the user get the longer user-wait timeout instead
+        let patch = ApplyPatchPayload::new(
+            "/project".to_string(),
+            "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            "--- a/x\n+++ b/x\n".to_string(),
+            "Slow review".to_string(),
+        );
+        let response: ApplyPatchResponse = dispatch_handle.send(patch.clone()).await.unwrap();
+        assert_eq!(response.status, ApplyPatchStatus::Applied);
+
+        // ...which still bounds them when nobody answers in time
+        let error = dispatch_handle
+            .with_user_wait_timeout(Duration::from_millis(150))
+            .send(patch)
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("IDE did not respond within 150ms"),
+            "unexpected error: {error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_ide_connected() {
+        use std::time::Duration;
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        // Mock bus where nobody answers
+        let silent = || {
+            Box::new(
+                |mut rx: tokio::sync::mpsc::Receiver<IPCMessage>,
+                 _tx: tokio::sync::mpsc::Sender<IPCMessage>| {
+                    Box::pin(async move { while rx.recv().await.is_some() {} })
+                        as std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+                },
+            ) as crate::actor::dispatch::MockActorFn
+        };
+        let communicator = |terminal_shell_pid| IPCCommunicator {
+            dispatch_handle: crate::actor::DispatchHandle::spawn_with_mock(silent())
+                .with_request_timeout(Duration::from_millis(100)),
+            terminal_shell_pid,
+            test_mode: false,
+        };
+
+        // Outside a VSCode terminal, silence means there is no IDE
+        let error = communicator(None).get_selection().await.unwrap_err();
+        assert!(error.to_string().contains("No IDE connected"), "unexpected error: {error}");
+
+        // Inside one, it is still reported as a timeout
+        let error = communicator(Some(42)).get_selection().await.unwrap_err();
+        assert!(
+            error.to_string().contains("IDE did not respond within 100ms"),
+            "unexpected error: {error}"
+        );
+
+        // Listing open editors without an IDE is not an error, there just are none
+        assert_eq!(communicator(None).list_open_editors().await.unwrap(), vec![]);
+        assert!(communicator(Some(42)).list_open_editors().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chunked_walkthrough_order() {
+        use crate::types::{PresentWalkthroughChunkMessage, PresentWalkthroughEndMessage};
+        use std::sync::{Arc, Mutex};
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        // Mock IDE that records and acknowledges every message
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let mock_fn = Box::new(
+            move |mut rx: tokio::sync::mpsc::Receiver<IPCMessage>,
+                  tx: tokio::sync::mpsc::Sender<IPCMessage>| {
+                Box::pin(async move {
+                    while let Some(message) = rx.recv().await {
+                        log.lock().unwrap().push(message.clone());
+                        let reply = IPCMessage {
+                            message_type: IPCMessageType::Response,
+                            id: message.id,
+                            sender: message.sender,
+                            payload: serde_json::to_value(ResponsePayload {
+                                success: true,
+                                error: None,
+                                data: None::<serde_json::Value>,
+                            })
+                            .unwrap(),
+                        };
+                        let _ = tx.send(reply).await;
+                    }
+                })
+                    as std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            },
+        ) as crate::actor::dispatch::MockActorFn;
+        let ipc = IPCCommunicator {
+            dispatch_handle: crate::actor::DispatchHandle::spawn_with_mock(mock_fn),
+            terminal_shell_pid: Some(42),
+            test_mode: false,
+        };
+
+        let mut stream = ipc.begin_walkthrough("/project".to_string()).await.unwrap();
+        stream.send_chunk("<h1>A</h1>".to_string()).await.unwrap();
+        stream.send_chunk("<p>B</p>".to_string()).await.unwrap();
+        assert_eq!(stream.finish(None).await.unwrap(), 2);
+
+        let received = received.lock().unwrap();
+        let types: Vec<_> = received.iter().map(|m| m.message_type.clone()).collect();
+        assert_eq!(
+            types,
+            [
+                IPCMessageType::PresentWalkthroughBegin,
+                IPCMessageType::PresentWalkthroughChunk,
+                IPCMessageType::PresentWalkthroughChunk,
+                IPCMessageType::PresentWalkthroughEnd,
+            ]
+        );
+        let chunk: PresentWalkthroughChunkMessage =
+            serde_json::from_value(received[2].payload.clone()).unwrap();
+        assert_eq!((chunk.index, chunk.content.as_str()), (1, "<p>B</p>"));
+        let end: PresentWalkthroughEndMessage =
+            serde_json::from_value(received[3].payload.clone()).unwrap();
+        assert_eq!((end.chunk_count, end.error), (2, None));
+        assert_eq!(end.walkthrough_id, chunk.walkthrough_id);
+    }
 }
@@ -3,21 +3,30 @@
 //! Rust implementation of the Dialectic MCP server for code review integration.
 
 pub mod actor;
+mod agent_cli;
 pub mod constants;
 mod daemon;
 mod dialect;
+pub mod doctor;
 mod eg;
+mod file_search;
 mod ide;
 mod ipc;
+pub mod metrics;
 mod pid_discovery;
+pub mod project_config;
 mod reference_store;
+mod state_bundle;
 pub mod structured_logging;
+mod walkthrough_export;
+mod walkthrough_history;
 mod walkthrough_parser;
 mod server;
 pub mod types;
+pub mod usage_log;
 
 pub mod git;
-mod workspace_dir;
+pub mod workspace_dir;
 mod agent_manager;
 
 // Re-export Options for use in main.rs
@@ -25,6 +34,7 @@ pub use crate::main_types::Options;
 
 mod main_types {
     use clap::Parser;
+    use std::path::PathBuf;
     use std::process::Command;
 
     #[derive(Parser, Debug, Clone)]
@@ -32,6 +42,31 @@ mod main_types {
         /// Enable development logging to the default log file
         #[arg(long, global = true)]
         pub dev_log: bool,
+
+        /// Serve Prometheus metrics at http://127.0.0.1:<PORT>/metrics (disabled by default)
+        #[arg(long, global = true)]
+        pub metrics_port: Option<u16>,
+
+        /// Truncate tool results larger than this many bytes, storing the full result
+        /// behind a `<symposium-ref>` (0 disables truncation)
+        #[arg(long, global = true, default_value_t = crate::constants::DEFAULT_MAX_RESULT_BYTES)]
+        pub max_result_bytes: usize,
+
+        /// Seconds to wait for the IDE to answer an IPC request before giving up.
+        /// Requests that wait for the user (e.g., patch confirmation) get 30 minutes instead.
+        #[arg(long, global = true, default_value_t = crate::constants::DEFAULT_IPC_TIMEOUT)]
+        pub ipc_timeout: u64,
+
+        /// Send walkthroughs larger than this many bytes of HTML to the IDE in chunks,
+        /// so it can start rendering before the whole walkthrough is resolved
+        /// (0, the default, always sends them in one message)
+        #[arg(long, global = true, default_value_t = 0)]
+        pub walkthrough_chunk_bytes: usize,
+
+        /// Resolve paths (walkthrough base URIs, files, git operations) against this
+        /// directory instead of the working directory the server was started in
+        #[arg(long, global = true)]
+        pub workspace: Option<PathBuf>,
     }
 
     impl Options {
@@ -42,6 +77,10 @@ mod main_types {
                 cmd.arg("--dev-log");
             }
 
+            // Note: --metrics-port is deliberately not reproduced, since a spawned
+            // daemon would compete with us for the same port.
+            // Neither is --workspace: the daemon is shared by all workspaces.
+
             // Pass RUST_LOG environment variable if set
             if let Ok(rust_log) = std::env::var("RUST_LOG") {
                 cmd.env("RUST_LOG", rust_log);
@@ -50,8 +89,11 @@ mod main_types {
     }
 }
 
-pub use daemon::{run_daemon_with_idle_timeout, run_client};
+pub use daemon::{
+    MalformedDebugResponse, request_message_history, run_client, run_daemon_with_idle_timeout,
+};
+pub use ipc::MockResponses;
 pub use pid_discovery::find_vscode_pid_from_mcp;
 pub use reference_store::ReferenceStore;
 pub use server::SymposiumServer;
-pub use agent_manager::AgentManager;
+pub use agent_manager::{AgentManager, AgentSessionSummary};
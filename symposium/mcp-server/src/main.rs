@@ -5,16 +5,19 @@
 //! Provides tools for AI assistants to display code reviews in VSCode.
 //! Acts as a communication bridge between AI and the VSCode extension via IPC.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use rmcp::{ServiceExt, transport::stdio};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use symposium_mcp::{
     AgentManager,
+    AgentSessionSummary,
     SymposiumServer,
     constants::DAEMON_SOCKET_PREFIX,
+    project_config::ProjectConfig,
     structured_logging,
+    workspace_dir,
 };
 
 #[derive(Parser)]
@@ -54,6 +57,15 @@ enum Command {
         /// Idle timeout in seconds before auto-shutdown (default: 30)
         #[arg(long, default_value = "30")]
         idle_timeout: u64,
+
+        /// Seconds to wait after the last client disconnects before shutting down; a client
+        /// reconnecting within it cancels the shutdown (default: off, only the idle timeout applies)
+        #[arg(long)]
+        disconnect_grace: Option<u64>,
+
+        /// Maximum number of messages kept for `debug dump-messages`; the oldest are evicted first
+        #[arg(long, default_value_t = symposium_mcp::constants::DEFAULT_MESSAGE_HISTORY_LIMIT)]
+        history_limit: usize,
     },
 
     /// Run as client - connects to daemon and bridges stdin/stdout
@@ -66,6 +78,12 @@ enum Command {
         auto_start: bool,
     },
 
+    /// Check each layer of the IDE integration and report what is broken
+    Doctor {
+        #[command(flatten)]
+        daemon_args: DaemonArgs,
+    },
+
     /// Debug daemon functionality
     #[command(subcommand)]
     Debug(DebugCommand),
@@ -73,6 +91,10 @@ enum Command {
     /// Manage persistent agent sessions
     #[command(subcommand)]
     Agent(AgentCommand),
+
+    /// Inspect the local usage log (enabled with SYMPOSIUM_USAGE_LOG=1)
+    #[command(subcommand)]
+    Usage(UsageCommand),
 }
 
 #[derive(Parser, Debug)]
@@ -92,6 +114,16 @@ enum DebugCommand {
     },
 }
 
+#[derive(Parser, Debug)]
+enum UsageCommand {
+    /// Show aggregated tool usage counters
+    Show {
+        /// Output the raw JSON file contents
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Parser, Debug)]
 enum AgentCommand {
     /// Spawn a new persistent agent session
@@ -104,12 +136,24 @@ enum AgentCommand {
         #[arg(long)]
         workdir: String,
 
+        /// Taskspace the agent works in (at most one agent per taskspace)
+        #[arg(long)]
+        taskspace: Option<String>,
+
         /// Agent command to run (e.g., "q chat --resume")
         agent_args: Vec<String>,
     },
 
     /// List all agent sessions
-    List,
+    List {
+        /// Output as JSON instead of human-readable format
+        #[arg(long)]
+        json: bool,
+
+        /// Only show the session spawned for this taskspace
+        #[arg(long)]
+        taskspace: Option<String>,
+    },
 
     /// Get attach command for an agent session
     Attach {
@@ -128,49 +172,79 @@ enum AgentCommand {
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Initialize structured logging with component-specific prefixes
-    let flush_guard = structured_logging::init_component_tracing(args.options.dev_log)
-        .expect("Failed to initialize logging");
+    // Everything below resolves paths against the workspace, so the override comes first
+    if let Some(workspace) = &args.options.workspace {
+        workspace_dir::set_override(workspace)
+            .with_context(|| format!("invalid --workspace {}", workspace.display()))?;
+    }
+
+    // Initialize structured logging with component-specific prefixes; RUST_LOG
+    // overrides any log level checked into the project config
+    let (project_config, project_config_warning) = ProjectConfig::for_current_workspace();
+    let flush_guard = structured_logging::init_component_tracing(
+        args.options.dev_log,
+        project_config.log_level.as_deref(),
+    )
+    .expect("Failed to initialize logging");
+    if let Some(warning) = project_config_warning {
+        warn!("{}", warning);
+    }
+
+    if let Some(port) = args.options.metrics_port {
+        symposium_mcp::metrics::spawn_metrics_server(port).await?;
+    }
 
-    info!("🔍 PROBE MODE DETECTED - Running PID discovery probe...");
+    info!("{}", startup_banner(args.command.as_ref()));
 
     match args.command {
         Some(Command::Probe {}) => {
-            info!("🔍 PROBE MODE DETECTED - Running PID discovery probe...");
             run_pid_probe().await?;
             info!("🔍 PROBE MODE COMPLETE - Exiting");
         }
         Some(Command::Daemon {
             daemon_args,
             idle_timeout,
+            disconnect_grace,
+            history_limit,
         }) => {
             let prefix = match &daemon_args.prefix {
                 Some(s) => s,
                 None => DAEMON_SOCKET_PREFIX,
             };
-            info!(
-                "🚀 DAEMON MODE - Starting message bus daemon with prefix {prefix}, idle timeout {idle_timeout}s",
-            );
-            symposium_mcp::run_daemon_with_idle_timeout(prefix, idle_timeout, None).await?;
+            info!("Starting message bus daemon with prefix {prefix}, idle timeout {idle_timeout}s, history limit {history_limit}");
+            let disconnect_grace = disconnect_grace.map(std::time::Duration::from_secs);
+            symposium_mcp::run_daemon_with_idle_timeout(prefix, idle_timeout, disconnect_grace, history_limit, None).await?;
         }
         Some(Command::Client { daemon_args, auto_start }) => {
             let prefix = match &daemon_args.prefix {
                 Some(s) => s,
                 None => DAEMON_SOCKET_PREFIX,
             };
-            info!("🔌 CLIENT MODE - Connecting to daemon with prefix {prefix}",);
+            info!("Connecting to daemon with prefix {prefix}");
             symposium_mcp::run_client(prefix, auto_start, &daemon_args.identity_prefix, args.options.clone()).await?;
         }
+        Some(Command::Doctor { daemon_args }) => {
+            let prefix = daemon_args.prefix.as_deref().unwrap_or(DAEMON_SOCKET_PREFIX);
+            let socket_path = symposium_mcp::constants::daemon_socket_path(prefix);
+            let checks = symposium_mcp::doctor::run_checks(&socket_path).await;
+            symposium_mcp::doctor::print_report(&checks);
+
+            // Scripts and CI rely on the exit status, not the report
+            if checks.iter().any(|check| !check.passed) {
+                std::process::exit(1);
+            }
+        }
         Some(Command::Debug(debug_cmd)) => {
             run_debug_command(debug_cmd).await?;
         }
         Some(Command::Agent(agent_cmd)) => {
-            info!("🤖 AGENT MANAGER MODE");
             run_agent_manager(agent_cmd).await?;
         }
+        Some(Command::Usage(usage_cmd)) => {
+            run_usage_command(usage_cmd)?;
+        }
         None => {
-            info!("Starting Symposium MCP Server (Rust)");
-            info!("MCP Server working directory: {:?}", std::env::current_dir());
+            info!("MCP Server working directory: {:?}", workspace_dir::current_dir());
 
             // Create our server instance
             let server = SymposiumServer::new(args.options.clone()).await?;
@@ -200,6 +274,23 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// The single line logged at startup announcing which mode we are running in.
+///
+/// Kept separate from `main` so that each mode logs exactly one accurate banner
+/// (in particular, the probe banner must not appear for the default server path).
+fn startup_banner(command: Option<&Command>) -> &'static str {
+    match command {
+        Some(Command::Probe {}) => "🔍 PROBE MODE DETECTED - Running PID discovery probe...",
+        Some(Command::Daemon { .. }) => "🚀 DAEMON MODE",
+        Some(Command::Client { .. }) => "🔌 CLIENT MODE",
+        Some(Command::Doctor { .. }) => "🩺 DOCTOR MODE",
+        Some(Command::Debug(_)) => "🐛 DEBUG MODE",
+        Some(Command::Agent(_)) => "🤖 AGENT MANAGER MODE",
+        Some(Command::Usage(_)) => "📊 USAGE MODE",
+        None => "Starting Symposium MCP Server (Rust)",
+    }
+}
+
 /// Run PID discovery probe for testing
 async fn run_pid_probe() -> Result<()> {
     use std::process;
@@ -244,14 +335,21 @@ async fn run_agent_manager(agent_cmd: AgentCommand) -> Result<()> {
     let mut manager = AgentManager::new(sessions_file).await?;
 
     match agent_cmd {
-        AgentCommand::Spawn { uuid, workdir, agent_args } => {
+        AgentCommand::Spawn { uuid, workdir, taskspace, agent_args } => {
             let workdir = PathBuf::from(workdir);
-            manager.spawn_agent(uuid, agent_args, workdir).await?;
+            manager.spawn_agent(uuid, agent_args, workdir, taskspace).await?;
             println!("Agent session spawned successfully");
         }
-        AgentCommand::List => {
-            let sessions = manager.list_sessions();
-            if sessions.is_empty() {
+        AgentCommand::List { json, taskspace } => {
+            let sessions = match &taskspace {
+                Some(taskspace) => manager.find_by_taskspace(taskspace).into_iter().collect(),
+                None => manager.list_sessions(),
+            };
+            if json {
+                let summaries: Vec<AgentSessionSummary> =
+                    sessions.into_iter().map(AgentSessionSummary::from).collect();
+                println!("{}", serde_json::to_string_pretty(&summaries)?);
+            } else if sessions.is_empty() {
                 println!("No active agent sessions");
             } else {
                 println!("Active agent sessions:");
@@ -261,6 +359,9 @@ async fn run_agent_manager(agent_cmd: AgentCommand) -> Result<()> {
                         session.status,
                         session.tmux_session_name
                     );
+                    if let Some(taskspace) = &session.taskspace_uuid {
+                        println!("    taskspace: {}", taskspace);
+                    }
                 }
             }
         }
@@ -276,52 +377,71 @@ async fn run_agent_manager(agent_cmd: AgentCommand) -> Result<()> {
     Ok(())
 }
 
+fn run_usage_command(usage_cmd: UsageCommand) -> Result<()> {
+    use symposium_mcp::{constants, usage_log};
+
+    match usage_cmd {
+        UsageCommand::Show { json } => {
+            let path = usage_log::default_path();
+            let counts = usage_log::read_counts(&path)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&counts)?);
+                return Ok(());
+            }
+
+            if counts.tools.is_empty() {
+                println!("No usage recorded in {}", path.display());
+                if std::env::var(constants::USAGE_LOG_ENV_VAR).as_deref() != Ok("1") {
+                    println!("Set {}=1 to enable the local usage log.", constants::USAGE_LOG_ENV_VAR);
+                }
+                return Ok(());
+            }
+
+            println!("Tool usage ({}):", path.display());
+            println!("{:<32} {:>8} {:>8} {:>8}", "TOOL", "CALLS", "ERRORS", "ERROR %");
+            for (tool, usage) in &counts.tools {
+                let error_rate = if usage.calls == 0 {
+                    0.0
+                } else {
+                    100.0 * usage.errors as f64 / usage.calls as f64
+                };
+                println!(
+                    "{:<32} {:>8} {:>8} {:>7.1}%",
+                    tool, usage.calls, usage.errors, error_rate
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn run_debug_command(debug_cmd: DebugCommand) -> Result<()> {
     use symposium_mcp::constants;
-    use tokio::io::{AsyncWriteExt, AsyncBufReadExt};
-    use tokio::net::UnixStream;
-    
+
     match debug_cmd {
         DebugCommand::DumpMessages { daemon_args, count, json } => {
             let socket_prefix = daemon_args.prefix.as_deref().unwrap_or(constants::DAEMON_SOCKET_PREFIX);
             let socket_path = constants::daemon_socket_path(socket_prefix);
             
-            // Connect to daemon
-            let stream = match UnixStream::connect(&socket_path).await {
-                Ok(stream) => stream,
+            let messages = match symposium_mcp::request_message_history(&socket_path).await {
+                Ok(messages) => messages,
                 Err(e) => {
-                    println!("Failed to connect to daemon at {}: {}", socket_path, e);
-                    println!("Make sure the daemon is running.");
+                    println!("Failed to read message history from daemon at {}: {}", socket_path, e);
+                    match e.downcast_ref::<symposium_mcp::MalformedDebugResponse>() {
+                        Some(malformed) => println!("Raw response: {}", malformed.raw),
+                        None => println!("Make sure the daemon is running."),
+                    }
                     return Ok(());
                 }
             };
             
-            let (reader, mut writer) = stream.into_split();
-            
-            // Send debug command
-            writer.write_all(b"#debug_dump_messages\n").await?;
-            writer.flush().await?;
-            
-            // Read response (single JSON line)
-            let mut response = String::new();
-            let mut buf_reader = tokio::io::BufReader::new(reader);
-            buf_reader.read_line(&mut response).await?;
-            
-            if response.trim().is_empty() {
+            if messages.is_empty() {
                 println!("No messages in daemon history.");
                 return Ok(());
             }
             
-            // Parse JSON response
-            let messages: Vec<serde_json::Value> = match serde_json::from_str(response.trim()) {
-                Ok(msgs) => msgs,
-                Err(e) => {
-                    println!("Failed to parse daemon response: {}", e);
-                    println!("Raw response: {}", response.trim());
-                    return Ok(());
-                }
-            };
-            
             let recent_messages = if messages.len() > count {
                 &messages[messages.len() - count..]
             } else {
@@ -357,3 +477,20 @@ async fn run_debug_command(debug_cmd: DebugCommand) -> Result<()> {
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_banner_only_in_probe_mode() {
+        let server = Args::parse_from(["symposium-mcp"]);
+        assert!(!startup_banner(server.command.as_ref()).contains("PROBE"));
+
+        let daemon = Args::parse_from(["symposium-mcp", "daemon"]);
+        assert!(!startup_banner(daemon.command.as_ref()).contains("PROBE"));
+
+        let probe = Args::parse_from(["symposium-mcp", "probe"]);
+        assert!(startup_banner(probe.command.as_ref()).contains("PROBE MODE DETECTED"));
+    }
+}
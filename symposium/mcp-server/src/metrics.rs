@@ -0,0 +1,221 @@
+//! Lightweight metrics with Prometheus text exposition
+//!
+//! Counters live in process-wide statics so any component (MCP server tools,
+//! IPC dispatch, daemon) can record without threading a handle around. When
+//! enabled with `--metrics-port`, a minimal HTTP endpoint serves them at `/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info};
+
+/// Invocations per MCP tool, keyed by tool name
+static TOOL_CALLS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Failed invocations per MCP tool, keyed by tool name
+static TOOL_ERRORS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Number of IPC requests that awaited a reply
+static IPC_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// Total time spent waiting for IPC replies, in microseconds
+static IPC_LATENCY_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Clients currently connected to the daemon
+static DAEMON_CLIENTS: AtomicI64 = AtomicI64::new(0);
+
+/// Record one invocation of an MCP tool
+pub fn record_tool_call(tool: &str, success: bool) {
+    if let Ok(mut calls) = TOOL_CALLS.lock() {
+        *calls.entry(tool.to_string()).or_default() += 1;
+    }
+    if !success {
+        if let Ok(mut errors) = TOOL_ERRORS.lock() {
+            *errors.entry(tool.to_string()).or_default() += 1;
+        }
+    }
+}
+
+/// Snapshot of tool invocation counts, keyed by tool name
+pub fn tool_call_counts() -> BTreeMap<String, u64> {
+    TOOL_CALLS.lock().map(|calls| calls.clone()).unwrap_or_default()
+}
+
+/// Snapshot of failed tool invocation counts, keyed by tool name
+pub fn tool_error_counts() -> BTreeMap<String, u64> {
+    TOOL_ERRORS.lock().map(|errors| errors.clone()).unwrap_or_default()
+}
+
+/// Record the round-trip latency of an IPC request
+pub fn record_ipc_request(elapsed: Duration) {
+    IPC_REQUESTS.fetch_add(1, Ordering::Relaxed);
+    IPC_LATENCY_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// Tracks a connected daemon client for as long as it is alive
+pub struct DaemonClientGuard(());
+
+impl DaemonClientGuard {
+    pub fn new() -> Self {
+        DAEMON_CLIENTS.fetch_add(1, Ordering::Relaxed);
+        DaemonClientGuard(())
+    }
+}
+
+impl Drop for DaemonClientGuard {
+    fn drop(&mut self) {
+        DAEMON_CLIENTS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+
+    write_labeled_counter(
+        &mut out,
+        "symposium_tool_calls_total",
+        "Number of MCP tool invocations.",
+        &TOOL_CALLS,
+    );
+    write_labeled_counter(
+        &mut out,
+        "symposium_tool_errors_total",
+        "Number of MCP tool invocations that returned an error.",
+        &TOOL_ERRORS,
+    );
+
+    let requests = IPC_REQUESTS.load(Ordering::Relaxed);
+    let latency = IPC_LATENCY_MICROS.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+    let _ = writeln!(out, "# HELP symposium_ipc_request_duration_seconds Round-trip latency of IPC requests awaiting a reply.");
+    let _ = writeln!(out, "# TYPE symposium_ipc_request_duration_seconds summary");
+    let _ = writeln!(out, "symposium_ipc_request_duration_seconds_sum {latency}");
+    let _ = writeln!(out, "symposium_ipc_request_duration_seconds_count {requests}");
+
+    let clients = DAEMON_CLIENTS.load(Ordering::Relaxed);
+    let _ = writeln!(out, "# HELP symposium_daemon_clients Number of clients connected to the daemon.");
+    let _ = writeln!(out, "# TYPE symposium_daemon_clients gauge");
+    let _ = writeln!(out, "symposium_daemon_clients {clients}");
+
+    out
+}
+
+fn write_labeled_counter(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    values: &Mutex<BTreeMap<String, u64>>,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} counter");
+    if let Ok(values) = values.lock() {
+        for (tool, count) in values.iter() {
+            let _ = writeln!(out, "{name}{{tool=\"{}\"}} {count}", escape_label(tool));
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Bind the metrics endpoint on localhost and serve it in the background
+pub async fn spawn_metrics_server(port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    info!("Serving Prometheus metrics on http://{}/metrics", listener.local_addr()?);
+    tokio::spawn(serve(listener));
+    Ok(())
+}
+
+/// Answer HTTP requests on `listener` until it fails
+pub async fn serve(listener: TcpListener) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _addr)) => {
+                tokio::spawn(handle_connection(stream));
+            }
+            Err(e) => {
+                debug!("Metrics listener failed to accept connection: {}", e);
+            }
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream) {
+    // We only need the request line; metrics requests have no body
+    let mut buf = [0u8; 1024];
+    let n = match stream.read(&mut buf).await {
+        Ok(n) => n,
+        Err(e) => {
+            debug!("Failed to read metrics request: {}", e);
+            return;
+        }
+    };
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+
+    let response = if path == "/metrics" {
+        let body = render();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        debug!("Failed to write metrics response: {}", e);
+    }
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_renders_counters() {
+        record_tool_call("metrics_test_tool", true);
+        record_tool_call("metrics_test_tool", false);
+        record_ipc_request(Duration::from_millis(5));
+        let _client = DaemonClientGuard::new();
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE symposium_tool_calls_total counter\n"));
+        assert!(response.contains("symposium_tool_calls_total{tool=\"metrics_test_tool\"} 2\n"));
+        assert!(response.contains("symposium_tool_errors_total{tool=\"metrics_test_tool\"} 1\n"));
+        assert!(response.contains("# TYPE symposium_ipc_request_duration_seconds summary\n"));
+        assert!(response.contains("symposium_ipc_request_duration_seconds_count "));
+        assert!(response.contains("# TYPE symposium_daemon_clients gauge\n"));
+        assert!(response.contains("symposium_daemon_clients "));
+
+        // Anything other than /metrics is a 404
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+}
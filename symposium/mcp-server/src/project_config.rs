@@ -0,0 +1,137 @@
+//! Checked-in project defaults from `.symposium/config.toml`
+//!
+//! Lets a team set defaults for everyone working in the repository:
+//!
+//! ```toml
+//! default_collaborator = "socrates"
+//! log_level = "info"
+//!
+//! # Replaces the default 💡/❓/TODO:/FIXME: markers for review comments
+//! [[comment_markers]]
+//! marker = "HACK:"
+//! category = "fixme"
+//! ```
+//!
+//! Every key is optional, and explicit flags, environment variables, and tool
+//! parameters always take precedence over these defaults.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::constants::PROJECT_CONFIG_FILE;
+use crate::git::{CommentMarker, CommentParser};
+
+/// Contents of `.symposium/config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    /// Collaborator used by `/yiasou` when neither the prompt nor the taskspace names one
+    pub default_collaborator: Option<String>,
+    /// Log filter (e.g. `info`, `symposium_mcp=debug`) used when `RUST_LOG` is unset
+    pub log_level: Option<String>,
+    /// Markers recognized when collecting review comments, replacing the defaults
+    pub comment_markers: Option<Vec<CommentMarker>>,
+}
+
+impl ProjectConfig {
+    /// Read the config under `workspace_root`; a missing file gives the defaults
+    pub fn load(workspace_root: &Path) -> anyhow::Result<Self> {
+        let path = workspace_root.join(PROJECT_CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        // An empty marker would turn every comment into a review comment
+        let mut markers = config.comment_markers.iter().flatten();
+        if let Some(index) = markers.position(|m| m.marker.trim().is_empty()) {
+            anyhow::bail!(
+                "invalid {}: comment_markers[{}] has an empty marker",
+                path.display(),
+                index
+            );
+        }
+        Ok(config)
+    }
+
+    /// Comment parser for the configured markers, or the default markers if none are set
+    pub fn comment_parser(&self) -> CommentParser {
+        match &self.comment_markers {
+            Some(markers) => CommentParser::with_markers(markers),
+            None => CommentParser::new(),
+        }
+    }
+
+    /// The config for the current workspace, falling back to the defaults when it
+    /// can't be read. The second element says why, for the caller to log once logging
+    /// is set up (the log level itself comes from this config).
+    pub fn for_current_workspace() -> (Self, Option<String>) {
+        let loaded = crate::workspace_dir::workspace_root()
+            .map_err(anyhow::Error::from)
+            .and_then(|root| Self::load(&root));
+        match loaded {
+            Ok(config) => (config, None),
+            Err(e) => (
+                Self::default(),
+                Some(format!("Ignoring project config: {e:#}")),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let workspace = tempfile::tempdir().unwrap();
+
+        // A missing file gives the defaults
+        assert_eq!(ProjectConfig::load(workspace.path()).unwrap(), ProjectConfig::default());
+
+        std::fs::create_dir(workspace.path().join(".symposium")).unwrap();
+        let path = workspace.path().join(PROJECT_CONFIG_FILE);
+        std::fs::write(&path, "default_collaborator = \"socrates\"\n").unwrap();
+        assert_eq!(
+            ProjectConfig::load(workspace.path()).unwrap(),
+            ProjectConfig {
+                default_collaborator: Some("socrates".to_string()),
+                log_level: None,
+                comment_markers: None,
+            }
+        );
+
+        std::fs::write(
+            &path,
+            "[[comment_markers]]\nmarker = \"HACK:\"\ncategory = \"fixme\"\n",
+        )
+        .unwrap();
+        let config = ProjectConfig::load(workspace.path()).unwrap();
+        assert_eq!(
+            config.comment_markers,
+            Some(vec![CommentMarker::new("HACK:", crate::git::CommentType::Fixme)])
+        );
+
+        std::fs::write(
+            &path,
+            "[[comment_markers]]\nmarker = \"HACK:\"\ncategory = \"fixme\"\n\
+             [[comment_markers]]\nmarker = \"  \"\ncategory = \"todo\"\n",
+        )
+        .unwrap();
+        let error = ProjectConfig::load(workspace.path()).unwrap_err();
+        assert!(
+            format!("{error:#}").contains("comment_markers[1] has an empty marker"),
+            "{error:#}"
+        );
+
+        std::fs::write(&path, "log_level = 3\n").unwrap();
+        let error = ProjectConfig::load(workspace.path()).unwrap_err();
+        assert!(format!("{error:#}").contains("failed to parse"), "{error:#}");
+    }
+}
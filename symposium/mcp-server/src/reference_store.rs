@@ -1,5 +1,6 @@
 use anyhow::Result;
 use serde_json::Value;
+use crate::ide::{FileLocation, FileRange};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -46,6 +47,64 @@ impl Default for ReferenceStore {
     }
 }
 
+/// Extract the source location a stored reference points at, if any.
+///
+/// References are free-form JSON chosen by the extension, so this recognizes
+/// the shapes it currently produces: code selections (`relativePath` +
+/// `selectionRange`), walkthrough comment replies (`in-reply-to-comment-at`),
+/// comment threads (`file` + `line`), and plain `FileRange` values.
+/// Line numbers are 1-based throughout.
+pub fn reference_location(value: &Value) -> Option<FileRange> {
+    if let Some(reply) = value.get("in-reply-to-comment-at") {
+        return reference_location(reply);
+    }
+
+    if let Some(path) = value.get("relativePath").and_then(Value::as_str) {
+        let range = value.get("selectionRange")?;
+        return Some(FileRange {
+            path: path.to_string(),
+            start: json_location(range.get("start")?)?,
+            end: json_location(range.get("end")?)?,
+            content: value
+                .get("selectedText")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        });
+    }
+
+    let path = value
+        .get("path")
+        .or_else(|| value.get("file"))
+        .and_then(Value::as_str)?;
+
+    let (start, end) = if let Some(line) = value.get("line").and_then(Value::as_u64) {
+        let location = FileLocation {
+            line: line as u32,
+            column: 1,
+        };
+        (location.clone(), location)
+    } else {
+        let start = json_location(value.get("start")?)?;
+        let end = value.get("end").and_then(json_location).unwrap_or_else(|| start.clone());
+        (start, end)
+    };
+
+    Some(FileRange {
+        path: path.to_string(),
+        start,
+        end,
+        content: None,
+    })
+}
+
+/// Parse a `{line, column?}` object; a missing column means the start of the line.
+fn json_location(value: &Value) -> Option<FileLocation> {
+    Some(FileLocation {
+        line: value.get("line")?.as_u64()? as u32,
+        column: value.get("column").and_then(Value::as_u64).unwrap_or(1) as u32,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +163,45 @@ mod tests {
         
         assert_eq!(store.count().await, 2);
     }
+
+    #[test]
+    fn test_reference_location() {
+        let selection = serde_json::json!({
+            "relativePath": "src/lib.rs",
+            "selectionRange": {
+                "start": { "line": 10, "column": 5 },
+                "end": { "line": 12, "column": 1 }
+            },
+            "selectedText": "fn foo() {}"
+        });
+        let location = reference_location(&selection).unwrap();
+        assert_eq!(location.path, "src/lib.rs");
+        assert_eq!((location.start.line, location.start.column), (10, 5));
+        assert_eq!((location.end.line, location.end.column), (12, 1));
+        assert_eq!(location.content.as_deref(), Some("fn foo() {}"));
+
+        let reply = serde_json::json!({
+            "in-reply-to-comment-at": {
+                "file": "src/main.rs",
+                "start": { "line": 3 },
+                "end": { "line": 7 },
+                "comment": "Why?"
+            }
+        });
+        let location = reference_location(&reply).unwrap();
+        assert_eq!(location.path, "src/main.rs");
+        assert_eq!((location.start.line, location.end.line), (3, 7));
+
+        let comment = serde_json::json!({
+            "file": "README.md",
+            "line": 42,
+            "user_comment": "Typo here"
+        });
+        let location = reference_location(&comment).unwrap();
+        assert_eq!(location.path, "README.md");
+        assert_eq!((location.start.line, location.end.line), (42, 42));
+
+        let other = serde_json::json!({ "type": "test1", "notes": "no location" });
+        assert!(reference_location(&other).is_none());
+    }
 }
@@ -4,19 +4,22 @@
 //! to interact with the VSCode extension via IPC.
 
 use anyhow::Result;
+use futures::StreamExt;
 use indoc::indoc;
 use rmcp::{
-    handler::server::{router::{prompt::PromptRouter, tool::ToolRouter}, wrapper::Parameters}, model::*, prompt, prompt_handler, prompt_router, service::RequestContext, tool, tool_handler, tool_router, ErrorData as McpError, RoleServer, ServerHandler
+    handler::server::{router::{prompt::PromptRouter, tool::ToolRouter}, tool::ToolCallContext, wrapper::Parameters}, model::*, prompt, prompt_handler, prompt_router, service::RequestContext, tool, tool_router, ErrorData as McpError, RoleServer, ServerHandler
 };
 use rust_embed::RustEmbed;
 use serde_json;
 use tracing::{debug, error, info, warn};
 use crate::{structured_logging, types::TaskspaceStateResponse};
 
+use crate::agent_cli::AgentCli;
 use crate::dialect::DialectInterpreter;
 use crate::eg::Eg;
 use crate::ipc::IPCCommunicator;
 use crate::types::PresentWalkthroughParams;
+use crate::walkthrough_history::WalkthroughHistory;
 use serde::{Deserialize, Serialize};
 
 /// Embedded guidance files for agent initialization
@@ -24,6 +27,115 @@ use serde::{Deserialize, Serialize};
 #[folder = "src/guidance/"]
 struct GuidanceFiles;
 
+/// Guidance files: the embedded set, overlaid per file by the project's `.symposium/guidance/`
+///
+/// A project file with the same relative name as an embedded one replaces it;
+/// any other project file is added alongside the embedded ones.
+struct GuidanceLayers {
+    overlay_dir: Option<std::path::PathBuf>,
+}
+
+impl GuidanceLayers {
+    /// Only the embedded guidance
+    fn embedded() -> Self {
+        Self { overlay_dir: None }
+    }
+
+    /// The embedded guidance overlaid by `workspace_root`'s guidance directory
+    fn for_workspace(workspace_root: &std::path::Path) -> Self {
+        Self {
+            overlay_dir: Some(workspace_root.join(crate::constants::PROJECT_GUIDANCE_DIR)),
+        }
+    }
+
+    /// The guidance for the current workspace, or only the embedded guidance if it can't be found
+    fn for_current_workspace() -> Self {
+        match crate::workspace_dir::workspace_root() {
+            Ok(root) => Self::for_workspace(&root),
+            Err(e) => {
+                warn!("Failed to determine workspace root: {}", e);
+                Self::embedded()
+            }
+        }
+    }
+
+    /// Contents of the guidance file `name` (e.g. `agents/q-cli.md`), preferring the project's copy
+    fn get(&self, name: &str) -> Option<std::borrow::Cow<'static, [u8]>> {
+        if let Some(path) = self.overlay_path(name) {
+            match std::fs::read(&path) {
+                Ok(content) => {
+                    debug!("Using project guidance {}", path.display());
+                    return Some(content.into());
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to read project guidance {}: {}", path.display(), e),
+            }
+        }
+        GuidanceFiles::get(name).map(|file| file.data)
+    }
+
+    /// Names of all guidance files, embedded first and then project-only ones, without duplicates
+    fn file_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = GuidanceFiles::iter().map(|name| name.to_string()).collect();
+        if let Some(dir) = &self.overlay_dir {
+            let mut overlay_names = Vec::new();
+            Self::collect_markdown_files(dir, "", &mut overlay_names);
+            overlay_names.sort();
+            for name in overlay_names {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Path of `name` in the overlay directory; names that could escape it get `None`
+    fn overlay_path(&self, name: &str) -> Option<std::path::PathBuf> {
+        let dir = self.overlay_dir.as_ref()?;
+        let relative = std::path::Path::new(name);
+        let is_plain = relative
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)));
+        is_plain.then(|| dir.join(relative))
+    }
+
+    /// Add the `.md` files under `dir` to `names`, as `/`-separated paths starting with `prefix`
+    fn collect_markdown_files(dir: &std::path::Path, prefix: &str, names: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let Some(file_name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let name = format!("{prefix}{file_name}");
+            let path = entry.path();
+            if path.is_dir() {
+                Self::collect_markdown_files(&path, &format!("{name}/"), names);
+            } else if file_name.ends_with(".md") {
+                names.push(name);
+            }
+        }
+    }
+}
+
+/// A guidance document as listed by `list_guidance`
+#[derive(Debug, Clone, Serialize)]
+struct GuidanceEntry {
+    /// Name to pass to `expand_reference`, e.g. `walkthrough-format.md`
+    id: String,
+    title: String,
+    description: Option<String>,
+}
+
+/// A collaborator persona that `spawn_taskspace`, `update_taskspace`, and `@yiasou` accept
+#[derive(Debug, Clone, Serialize)]
+struct Collaborator {
+    name: String,
+    description: String,
+}
+
 /// Parameters for the expand_reference tool
 // ANCHOR: expand_reference_params
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -33,15 +145,32 @@ pub struct ExpandReferenceParams {
 }
 // ANCHOR_END: expand_reference_params
 
+/// Parameters for the open_reference tool
+// ANCHOR: open_reference_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct OpenReferenceParams {
+    /// The reference ID to open
+    pub id: String,
+}
+// ANCHOR_END: open_reference_params
+
 /// Parameters for the ide_operation tool
 // ANCHOR: ide_operation_params
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
 struct IdeOperationParams {
-    /// Dialect program to execute
-    program: String,
+    /// Dialect program to execute, or an array of programs to execute in order
+    program: DialectPrograms,
 }
 // ANCHOR_END: ide_operation_params
 
+/// One Dialect program, or several evaluated together in a single call
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(untagged)]
+enum DialectPrograms {
+    Single(String),
+    Batch(Vec<String>),
+}
+
 /// Parameters for the spawn_taskspace tool
 // ANCHOR: spawn_taskspace_params
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
@@ -51,9 +180,15 @@ struct SpawnTaskspaceParams {
     /// Description of the task to be performed
     task_description: String,
     /// Initial prompt to provide to the agent when it starts
-    initial_prompt: String,
+    initial_prompt: Option<String>,
+    /// Workspace-relative file to read the initial prompt from, instead of `initial_prompt`
+    /// (exactly one of the two is required)
+    initial_prompt_path: Option<String>,
     /// Collaborator for the new taskspace (optional, defaults to current taskspace's collaborator)
     collaborator: Option<String>,
+    /// Tags grouping the new taskspace with related ones, e.g. a shared feature name (optional)
+    #[serde(default)]
+    tags: Vec<String>,
 }
 // ANCHOR_END: spawn_taskspace_params
 
@@ -65,6 +200,10 @@ struct LogProgressParams {
     message: String,
     /// Category for visual indicator (info, warn, error, milestone, question)
     category: String,
+    /// Optional structured details for the app to render alongside the message,
+    /// e.g. {"completed": "3", "total": "10"} for a progress bar
+    #[serde(default)]
+    fields: std::collections::BTreeMap<String, String>,
 }
 // ANCHOR_END: log_progress_params
 
@@ -74,9 +213,32 @@ struct LogProgressParams {
 struct SignalUserParams {
     /// Message describing why user attention is needed
     message: String,
+    /// How urgently attention is needed: low (FYI), normal, or urgent (blocked). Defaults to normal
+    #[serde(default)]
+    priority: crate::types::SignalPriority,
 }
 // ANCHOR_END: signal_user_params
 
+/// Parameters for the preview_initialization tool
+// ANCHOR: preview_initialization_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct PreviewInitializationParams {
+    /// Collaborator whose initialization to preview (see `list_collaborators`)
+    collaborator: String,
+}
+// ANCHOR_END: preview_initialization_params
+
+/// Parameters for the delete_taskspace tool
+// ANCHOR: delete_taskspace_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct DeleteTaskspaceParams {
+    /// Delete even if the taskspace has uncommitted work. Only set this after the
+    /// user has confirmed they want to lose those changes.
+    #[serde(default)]
+    force: bool,
+}
+// ANCHOR_END: delete_taskspace_params
+
 /// Parameters for the update_taskspace tool
 // ANCHOR: update_taskspace_params
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
@@ -87,9 +249,248 @@ struct UpdateTaskspaceParams {
     description: String,
     /// Collaborator for the taskspace (optional)
     collaborator: Option<String>,
+    /// Replace the taskspace's tags (optional; omit to keep the current tags, `[]` to clear them)
+    #[serde(default)]
+    tags: Option<Vec<String>>,
 }
 // ANCHOR_END: update_taskspace_params
 
+/// Parameters for the get_patch tool
+// ANCHOR: get_patch_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetPatchParams {
+    /// Git commit range (e.g., "HEAD", "HEAD~3..HEAD", "main..feature").
+    /// A single commit compares that commit with the working tree.
+    range: String,
+    /// Exclude unstaged working tree changes (only applies when comparing with the working tree)
+    #[serde(default)]
+    exclude_unstaged: bool,
+    /// Exclude staged changes (only applies when comparing with the working tree)
+    #[serde(default)]
+    exclude_staged: bool,
+}
+// ANCHOR_END: get_patch_params
+
+/// Parameters for the apply_patch tool
+// ANCHOR: apply_patch_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct ApplyPatchParams {
+    /// Unified diff to apply to the working tree (as produced by `get_patch` or `git diff`)
+    patch: String,
+    /// Short explanation of the change, shown to the user when asking for confirmation
+    description: String,
+}
+// ANCHOR_END: apply_patch_params
+
+/// Parameters for the git_commit tool
+// ANCHOR: git_commit_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GitCommitParams {
+    /// Commit message
+    message: String,
+    /// Files to stage before committing, relative to the repository root.
+    /// When omitted, only changes that are already staged are committed.
+    #[serde(default)]
+    paths: Option<Vec<String>>,
+}
+// ANCHOR_END: git_commit_params
+
+/// Parameters for the get_review_comments tool
+// ANCHOR: get_review_comments_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetReviewCommentsParams {
+    /// Git commit range under review (e.g., "HEAD", "HEAD~3..HEAD", "main..feature").
+    /// A single commit compares that commit with the working tree. Defaults to "HEAD".
+    #[serde(default = "default_review_range")]
+    range: String,
+}
+// ANCHOR_END: get_review_comments_params
+
+/// Parameters for the resolve_commit_range tool
+// ANCHOR: resolve_commit_range_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct ResolveCommitRangeParams {
+    /// Git commit range to resolve (e.g., "HEAD~3..HEAD", "main...", "main..WORKING").
+    /// `...` starts from the merge base; `WORKING` as the head means the working tree.
+    range: String,
+}
+// ANCHOR_END: resolve_commit_range_params
+
+/// Parameters for the get_git_log tool
+// ANCHOR: get_git_log_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetGitLogParams {
+    /// Maximum number of commits to return. Defaults to 20.
+    #[serde(default = "default_log_max_count")]
+    max_count: usize,
+    /// Only commits made since this time: relative ("2 days ago", "yesterday"),
+    /// a date ("2025-01-31"), or an RFC 3339 timestamp
+    #[serde(default)]
+    since: Option<String>,
+    /// Only commits whose author name or email contains this text (case-insensitive)
+    #[serde(default)]
+    author: Option<String>,
+}
+// ANCHOR_END: get_git_log_params
+
+/// Parameters for the find_files tool
+// ANCHOR: find_files_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct FindFilesParams {
+    /// Gitignore-style glob relative to the workspace root. Without a slash it matches
+    /// file names anywhere (`*.rs`, `Cargo.toml`); with one it is anchored (`src/**/*.ts`).
+    glob: String,
+    /// Maximum number of paths to return (default 200)
+    #[serde(default)]
+    max_results: Option<usize>,
+}
+// ANCHOR_END: find_files_params
+
+/// Parameters for the search_workspace tool
+// ANCHOR: search_workspace_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct SearchWorkspaceParams {
+    /// Regex to search for, matched line by line
+    pattern: String,
+    /// Only search files matching this gitignore-style glob (e.g. `*.rs`, `src/**/*.ts`)
+    #[serde(default)]
+    glob: Option<String>,
+    /// Lines of context to include before and after each match (default 2, max 50)
+    #[serde(default)]
+    context_lines: Option<usize>,
+    /// Maximum number of matches to return (default 50); the search stops once it is reached
+    #[serde(default)]
+    max_matches: Option<usize>,
+}
+// ANCHOR_END: search_workspace_params
+
+/// Parameters for the debug_recent_messages tool
+// ANCHOR: debug_recent_messages_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct DebugRecentMessagesParams {
+    /// Number of most recent messages to return (default 50)
+    #[serde(default)]
+    count: Option<usize>,
+}
+// ANCHOR_END: debug_recent_messages_params
+
+/// Parameters for the list_peers tool
+// ANCHOR: list_peers_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct ListPeersParams {
+    /// How long to wait for replies, in milliseconds (default 500, at most 5000)
+    #[serde(default)]
+    window_ms: Option<u64>,
+}
+// ANCHOR_END: list_peers_params
+
+/// Parameters for the export_walkthrough tool
+// ANCHOR: export_walkthrough_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct ExportWalkthroughParams {
+    /// Walkthrough markdown, in the same format as for present_walkthrough
+    content: String,
+    /// Base directory path for resolving relative file references
+    #[serde(rename = "baseUri")]
+    base_uri: String,
+    /// Document title (defaults to "Walkthrough")
+    #[serde(default)]
+    title: Option<String>,
+}
+// ANCHOR_END: export_walkthrough_params
+
+/// Parameters for the validate_walkthrough tool
+// ANCHOR: validate_walkthrough_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct ValidateWalkthroughParams {
+    /// Walkthrough markdown, in the same format as for present_walkthrough
+    content: String,
+    /// Base directory path for resolving relative file references
+    #[serde(rename = "baseUri")]
+    base_uri: String,
+}
+// ANCHOR_END: validate_walkthrough_params
+
+/// Parameters for the get_file_at_revision tool
+// ANCHOR: get_file_at_revision_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetFileAtRevisionParams {
+    /// Path of the file, relative to the repository root
+    path: String,
+    /// Git revision to read the file from (e.g., "HEAD~1", "main", a commit SHA)
+    revision: String,
+}
+// ANCHOR_END: get_file_at_revision_params
+
+/// Parameters for the set_selection tool
+// ANCHOR: set_selection_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct SetSelectionParams {
+    /// Path of the file, relative to the workspace root
+    path: String,
+    /// First line of the selection (1-based)
+    start_line: u32,
+    /// Column on the first line where the selection starts (1-based)
+    start_col: u32,
+    /// Last line of the selection (1-based)
+    end_line: u32,
+    /// Column on the last line just after the selection ends (1-based)
+    end_col: u32,
+}
+// ANCHOR_END: set_selection_params
+
+/// Parameters for the open_diff tool
+// ANCHOR: open_diff_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct OpenDiffParams {
+    /// Path of the file, relative to the repository root
+    path: String,
+    /// Git revision for the left-hand side (e.g., "HEAD~1", "main", a commit SHA)
+    base_revision: String,
+    /// Git revision for the right-hand side, or "WORKING" for the file on disk
+    head_revision: String,
+}
+// ANCHOR_END: open_diff_params
+
+/// Parameters for the write_review_todos tool
+// ANCHOR: write_review_todos_params
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct WriteReviewTodosParams {
+    /// Comment threads to persist, as returned by `get_review_comments`.
+    /// Resolved threads are skipped.
+    threads: Vec<crate::git::CommentThread>,
+}
+// ANCHOR_END: write_review_todos_params
+
+/// Trim tags and drop empty and repeated ones, keeping the first occurrence's position
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Prefix each line with its 1-based line number, `cat -n` style
+fn number_lines(content: &str) -> String {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| format!("{:>6}\t{}\n", index + 1, line))
+        .collect()
+}
+
+fn default_review_range() -> String {
+    "HEAD".to_string()
+}
+
+fn default_log_max_count() -> usize {
+    20
+}
+
 /// Parameters for the get_rust_crate_source tool
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
 struct GetRustCrateSourceParams {
@@ -99,6 +500,62 @@ struct GetRustCrateSourceParams {
     version: Option<String>,
     /// Optional search pattern (regex)
     pattern: Option<String>,
+    /// Lines of context to include before and after each match (default 2, max 50)
+    context_lines: Option<usize>,
+    /// Maximum number of matches to return (default 50); matches in examples are always returned
+    max_matches: Option<usize>,
+    /// Report match file paths relative to `checkout_path` (default true); false gives absolute paths
+    #[serde(default = "default_relative_paths")]
+    relative_paths: bool,
+}
+
+fn default_relative_paths() -> bool {
+    true
+}
+
+/// A crate to search in a get_rust_crate_sources batch
+#[derive(Debug, Clone, Deserialize, Serialize, schemars::JsonSchema)]
+struct CrateSpec {
+    /// Name of the crate to search
+    crate_name: String,
+    /// Optional semver range (e.g., "1.0", "^1.2", "~1.2.3")
+    version: Option<String>,
+}
+
+/// Parameters for the get_rust_crate_sources tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetRustCrateSourcesParams {
+    /// Crates to extract and search
+    crates: Vec<CrateSpec>,
+    /// Optional search pattern (regex), applied to every crate
+    pattern: Option<String>,
+}
+
+/// Parameters for the get_crate_file tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetCrateFileParams {
+    /// Name of the crate to read from
+    crate_name: String,
+    /// Optional semver range (e.g., "1.0", "^1.2", "~1.2.3")
+    version: Option<String>,
+    /// Path of the file, relative to the crate root (e.g., "src/lib.rs")
+    file_path: String,
+}
+
+/// Parameters for the get_crate_dependencies tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetCrateDependenciesParams {
+    /// Only list crates the project depends on directly (default false)
+    #[serde(default)]
+    direct_only: bool,
+}
+
+/// Parameters for the workspace_graph tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct WorkspaceGraphParams {
+    /// Also show dependencies from outside the workspace (default false)
+    #[serde(default)]
+    include_external: bool,
 }
 
 /// Dialectic MCP Server
@@ -112,6 +569,18 @@ pub struct SymposiumServer {
     tool_router: ToolRouter<SymposiumServer>,
     prompt_router: PromptRouter<SymposiumServer>,
     reference_handle: crate::actor::ReferenceHandle,
+    /// Tool results larger than this are truncated (0 disables truncation)
+    max_result_bytes: usize,
+    /// Local usage counters, only when the user opted in
+    usage_log: Option<crate::usage_log::UsageLog>,
+    /// Previously presented walkthroughs, for `diff_walkthroughs`
+    walkthrough_history: WalkthroughHistory,
+    /// Socket of the message bus daemon, queried by `debug_recent_messages`
+    daemon_socket_path: String,
+    /// Defaults checked into the project's `.symposium/config.toml`
+    project_config: crate::project_config::ProjectConfig,
+    /// Walkthroughs are sent in chunks of about this many bytes (0 sends them whole)
+    walkthrough_chunk_bytes: usize,
 }
 
 #[tool_router]
@@ -125,7 +594,7 @@ impl SymposiumServer {
                 Some(shell_pid)
             }
             None => {
-                info!("Could not discover VSCode PID from process tree - continuing without shell PID");
+                warn!("Could not discover VSCode PID from process tree - continuing without shell PID; IDE tools may report no IDE connected");
                 None
             }
         };
@@ -135,6 +604,9 @@ impl SymposiumServer {
         // Create shared reference handle for both IPC and MCP tools
         let reference_handle = crate::actor::ReferenceHandle::new();
 
+        let max_result_bytes = options.max_result_bytes;
+        let walkthrough_chunk_bytes = options.walkthrough_chunk_bytes;
+        let dev_log = options.dev_log;
         let mut ipc = IPCCommunicator::new(shell_pid, reference_handle.clone(), options).await?;
 
         // Initialize IPC connection to message bus daemon (not directly to VSCode)
@@ -154,12 +626,38 @@ impl SymposiumServer {
         Ok(Self {
             ipc: ipc.clone(),
             interpreter,
-            tool_router: Self::tool_router(),
+            tool_router: Self::tool_router_for(dev_log),
             prompt_router: Self::prompt_router(),
             reference_handle,
+            max_result_bytes,
+            usage_log: crate::usage_log::UsageLog::from_env(),
+            walkthrough_history: WalkthroughHistory::for_current_taskspace(),
+            daemon_socket_path: crate::constants::daemon_socket_path(
+                crate::constants::DAEMON_SOCKET_PREFIX,
+            ),
+            project_config: Self::load_project_config(),
+            walkthrough_chunk_bytes,
         })
     }
 
+    /// The current workspace's project config, or the defaults if it can't be read
+    fn load_project_config() -> crate::project_config::ProjectConfig {
+        let (config, warning) = crate::project_config::ProjectConfig::for_current_workspace();
+        if let Some(warning) = warning {
+            warn!("{}", warning);
+        }
+        config
+    }
+
+    /// The tool router, leaving out debugging tools unless `--dev-log` was given
+    fn tool_router_for(dev_log: bool) -> ToolRouter<Self> {
+        let mut router = Self::tool_router();
+        if !dev_log {
+            router.remove_route("debug_recent_messages");
+        }
+        router
+    }
+
     /// Get a reference to the IPC communicator
     pub fn ipc(&self) -> &IPCCommunicator {
         &self.ipc
@@ -176,11 +674,71 @@ impl SymposiumServer {
         });
     }
 
+    /// Truncate a tool result whose text exceeds `max_result_bytes`.
+    ///
+    /// The full text is stored in the reference actor and the result is replaced
+    /// by a preview plus a `<symposium-ref>` that `expand_reference` resolves.
+    async fn limit_result_size(&self, mut result: CallToolResult) -> CallToolResult {
+        let limit = self.max_result_bytes;
+        let full_text = result
+            .content
+            .iter()
+            .filter_map(|content| content.as_text().map(|text| text.text.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if limit == 0 || full_text.len() <= limit {
+            return result;
+        }
+
+        let reference_id = format!("result-{}", uuid::Uuid::new_v4());
+        if let Err(e) = self
+            .reference_handle
+            .store_reference(reference_id.clone(), serde_json::Value::String(full_text.clone()))
+            .await
+        {
+            warn!("Failed to store oversized tool result, returning it untruncated: {}", e);
+            return result;
+        }
+
+        // Cut at a char boundary so the preview stays valid UTF-8
+        let mut preview_len = limit;
+        while !full_text.is_char_boundary(preview_len) {
+            preview_len -= 1;
+        }
+
+        info!(
+            "Truncated tool result from {} to {} bytes (reference {})",
+            full_text.len(),
+            preview_len,
+            reference_id
+        );
+
+        let preview = format!(
+            "{}\n\n[Result truncated: showing {} of {} bytes. \
+             Use the `expand_reference` tool on <symposium-ref id='{}'/> to get the full result.]",
+            &full_text[..preview_len],
+            preview_len,
+            full_text.len(),
+            reference_id
+        );
+
+        // Keep any non-text content (e.g., images) after the preview
+        result.content.retain(|content| content.as_text().is_none());
+        result.content.insert(0, Content::text(preview));
+        result
+    }
+
     /// Creates a new DialecticServer in test mode
     /// In test mode, IPC operations are mocked and don't require a VSCode connection
     pub fn new_test() -> Self {
+        Self::new_test_with_responses(crate::ipc::MockResponses::new())
+    }
+
+    /// Creates a test-mode server whose IPC requests are answered from `responses`
+    pub fn new_test_with_responses(responses: crate::ipc::MockResponses) -> Self {
         let reference_handle = crate::actor::ReferenceHandle::new();
-        let ipc = IPCCommunicator::new_test(reference_handle.clone());
+        let ipc = IPCCommunicator::new_test_with_responses(reference_handle.clone(), responses);
         info!("DialecticServer initialized in test mode");
 
         // Initialize Dialect interpreter with IDE functions for test mode
@@ -193,6 +751,18 @@ impl SymposiumServer {
             tool_router: Self::tool_router(),
             prompt_router: Self::prompt_router(),
             reference_handle,
+            max_result_bytes: crate::constants::DEFAULT_MAX_RESULT_BYTES,
+            usage_log: None,
+            walkthrough_history: WalkthroughHistory::new(
+                std::path::Path::new(crate::constants::TEMP_DIR)
+                    .join("symposium-walkthroughs")
+                    .join(format!("test-{}", uuid::Uuid::new_v4())),
+            ),
+            daemon_socket_path: crate::constants::daemon_socket_path(
+                crate::constants::DAEMON_SOCKET_PREFIX,
+            ),
+            project_config: Default::default(),
+            walkthrough_chunk_bytes: 0,
         }
     }
 
@@ -241,130 +811,530 @@ impl SymposiumServer {
         // ANCHOR_END: present_walkthrough_tool
         debug!("Received present_walkthrough tool call with markdown content ({} chars)", params.content.len());
 
+        // Convert baseURI to absolute path, fallback to current working directory
+        let absolute_base_uri = crate::workspace_dir::absolute_base_uri(&params.base_uri);
+
         // Parse markdown with XML elements and resolve Dialect expressions
         let mut parser =
             crate::walkthrough_parser::WalkthroughParser::new(self.interpreter.clone())
                 .with_base_uri(params.base_uri.clone());
-        let resolved_html = parser
-            .parse_and_normalize(&params.content)
-            .await
-            .map_err(|e| {
+
+        if self.walkthrough_chunk_bytes > 0 {
+            self.present_walkthrough_chunked(&mut parser, &params.content, absolute_base_uri)
+                .await?;
+        } else {
+            let resolved_html = parser
+                .parse_and_normalize(&params.content)
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(
+                        "Failed to parse walkthrough markdown",
+                        Some(serde_json::json!({"error": e.to_string()})),
+                    )
+                })?;
+
+            // Create resolved walkthrough with HTML content
+            let resolved = crate::ide::ResolvedWalkthrough {
+                content: resolved_html,
+                base_uri: absolute_base_uri,
+            };
+
+            // Send resolved walkthrough to VSCode extension
+            self.ipc.present_walkthrough(resolved).await.map_err(|e| {
                 McpError::internal_error(
-                    "Failed to parse walkthrough markdown",
+                    "Failed to present walkthrough",
                     Some(serde_json::json!({"error": e.to_string()})),
                 )
             })?;
-
-        // Convert baseURI to absolute path, fallback to current working directory
-        let absolute_base_uri = std::path::Path::new(&params.base_uri)
-            .canonicalize()
-            .or_else(|_| crate::workspace_dir::current_dir())
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| params.base_uri.clone());
-
-        // Create resolved walkthrough with HTML content
-        let resolved = crate::ide::ResolvedWalkthrough {
-            content: resolved_html,
-            base_uri: absolute_base_uri,
-        };
-
-        // Send resolved walkthrough to VSCode extension
-        self.ipc.present_walkthrough(resolved).await.map_err(|e| {
-            McpError::internal_error(
-                "Failed to present walkthrough",
-                Some(serde_json::json!({"error": e.to_string()})),
-            )
-        })?;
+        }
 
         // Log success
         info!("Walkthrough successfully sent to VSCode");
 
+        // Remember it so the next revision can be diffed against it
+        match self.walkthrough_history.record(&params.content) {
+            Ok(revision) => debug!("Recorded walkthrough revision {}", revision),
+            Err(e) => warn!("Failed to record walkthrough revision: {}", e),
+        }
+
         Ok(CallToolResult::success(vec![Content::text(
             "Walkthrough successfully processed and presented in VSCode",
         )]))
     }
 
-    /// Get the currently selected text from any active editor in VSCode
-    ///
-    /// Works with source files, review panels, and any other text editor.
-    /// Returns null if no text is selected or no active editor is found.
-    // ANCHOR: get_selection_tool
+    /// Render a walkthrough as a standalone HTML document
+    // ANCHOR: export_walkthrough_tool
     #[tool(
-        description = "\
-            Get the currently selected text from any active editor in VSCode.\n\
-            Works with source files, review panels, and any other text editor.\n\
-            Returns null if no text is selected or no active editor is found.\
-        "
+        description = "Render a walkthrough (same markdown as present_walkthrough) as a standalone HTML document \
+                       for sharing outside the IDE, e.g. in a PR description, docs, or email. \
+                       Comment locations are resolved as usual and theme colors are inlined. \
+                       Returns the full HTML document; nothing is shown in the IDE."
     )]
-    async fn get_selection(&self) -> Result<CallToolResult, McpError> {
-        // ANCHOR_END: get_selection_tool
-        // Request current selection from VSCode extension via IPC
-        info!("Requesting current selection from VSCode extension...");
+    async fn export_walkthrough(
+        &self,
+        Parameters(params): Parameters<ExportWalkthroughParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: export_walkthrough_tool
+        debug!("Exporting walkthrough ({} chars)", params.content.len());
 
-        let result = self.ipc.get_selection().await.map_err(|e| {
+        let mut parser =
+            crate::walkthrough_parser::WalkthroughParser::new(self.interpreter.clone())
+                .with_base_uri(params.base_uri.clone());
+        let body = parser.parse_and_normalize(&params.content).await.map_err(|e| {
             McpError::internal_error(
-                "IPC communication failed",
-                Some(serde_json::json!({
-                    "error": e.to_string()
-                })),
+                "Failed to parse walkthrough markdown",
+                Some(serde_json::json!({"error": e.to_string()})),
             )
         })?;
 
-        let status_msg = if result.selected_text.is_some() {
-            "text selected"
-        } else {
-            "no selection"
-        };
+        let title = params.title.as_deref().unwrap_or("Walkthrough");
+        Ok(CallToolResult::success(vec![Content::text(
+            crate::walkthrough_export::standalone_document(title, &body),
+        )]))
+    }
 
-        info!("selection retrieved: {}", status_msg);
+    /// Check a walkthrough's references without presenting it
+    // ANCHOR: validate_walkthrough_tool
+    #[tool(
+        description = "Check a walkthrough (same markdown as present_walkthrough) without showing it in the IDE. \
+                       Resolves every comment location and gitdiff range and returns a JSON report: \
+                       `valid` (no failures), element counts by type, the references that resolved \
+                       (with how many locations each found), and the ones that failed with their error. \
+                       Use this to catch broken references before presenting."
+    )]
+    async fn validate_walkthrough(
+        &self,
+        Parameters(params): Parameters<ValidateWalkthroughParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: validate_walkthrough_tool
+        debug!("Validating walkthrough ({} chars)", params.content.len());
 
-        // Convert result to JSON and return
-        let json_content = Content::json(result).map_err(|e| {
+        let mut parser =
+            crate::walkthrough_parser::WalkthroughParser::new(self.interpreter.clone())
+                .with_base_uri(params.base_uri.clone());
+        let report = parser.validate(&params.content).await.map_err(|e| {
             McpError::internal_error(
-                "Serialization failed",
-                Some(serde_json::json!({
-                    "error": format!("Failed to serialize selection result: {}", e)
-                })),
+                "Failed to parse walkthrough markdown",
+                Some(serde_json::json!({"error": e.to_string()})),
             )
         })?;
 
-        Ok(CallToolResult::success(vec![json_content]))
+        Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+            "valid": report.errors.is_empty(),
+            "element_counts": report.element_counts,
+            "resolved": report.resolved,
+            "errors": report.errors,
+        }))?]))
     }
 
-    /// Execute IDE operations using Dialect mini-language
-    ///
-    /// Provides access to VSCode's Language Server Protocol (LSP) capabilities
-    /// through a composable function system for symbol resolution and reference finding.
-    // ANCHOR: ide_operation_tool
+    /// Show what changed between the last two presented walkthroughs
     #[tool(
-        description = "\
-            Execute IDE operations using a structured JSON mini-language.\n\
-            This tool provides access to VSCode's Language Server Protocol (LSP) capabilities\n\
-            through a composable function system.\n\
-            \n\
-            Common operations:\n\
-            - findDefinitions(\"MyFunction\") or findDefinition(\"MyFunction\") - list of locations where a symbol named `MyFunction` is defined\n\
-            - findReferences(\"MyFunction\") - list of locations where a symbol named `MyFunction` is referenced\n\
-            \n\
-            To find full guidelines for usage, use the `expand_reference` with `walkthrough-format.md`.\n\
-            "
+        description = "Show what changed between the last two walkthroughs presented in this taskspace. \
+                       Reports added, removed, and changed sections (by heading), with line-level changes \
+                       for changed sections. Use this after revising a walkthrough so the user can see what you changed."
     )]
-    async fn ide_operation(
-        &self,
-        Parameters(params): Parameters<IdeOperationParams>,
+    async fn diff_walkthroughs(&self) -> Result<CallToolResult, McpError> {
+        let revisions = self.walkthrough_history.last_two().map_err(|e| {
+            McpError::internal_error(
+                "Failed to read walkthrough history",
+                Some(serde_json::json!({"error": e.to_string()})),
+            )
+        })?;
+
+        let Some(((previous, old), (latest, new))) = revisions else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "Fewer than two walkthroughs have been presented; nothing to compare",
+            )]));
+        };
+
+        let diff = crate::walkthrough_history::diff_walkthroughs(&old, &new);
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Changes from walkthrough revision {previous} to {latest}:\n\n{diff}"
+        ))]))
+    }
+
+    /// Find workspace files by glob
+    // ANCHOR: find_files_tool
+    #[tool(
+        description = "Find files in the workspace whose path matches a glob, skipping files ignored by .gitignore. \
+                       Returns sorted paths relative to the workspace root, one per line. \
+                       Useful for getting oriented in an unfamiliar repository before deeper `ide_operation` lookups."
+    )]
+    async fn find_files(
+        &self,
+        Parameters(params): Parameters<FindFilesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: find_files_tool
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            McpError::internal_error(
+                "Failed to determine workspace root",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+        let max_results = params
+            .max_results
+            .unwrap_or(crate::constants::DEFAULT_FIND_FILES_MAX_RESULTS);
+
+        // Walking the workspace is blocking filesystem work
+        let glob = params.glob.clone();
+        let found = tokio::task::spawn_blocking(move || {
+            crate::file_search::find_files(&root, &glob, max_results)
+        })
+        .await
+        .map_err(|e| {
+            McpError::internal_error(
+                "Task execution failed",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?
+        .map_err(|e| {
+            McpError::invalid_params(
+                "Failed to find files",
+                Some(serde_json::json!({
+                    "error": e.to_string(),
+                    "glob": params.glob
+                })),
+            )
+        })?;
+
+        if found.paths.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No files match `{}`",
+                params.glob
+            ))]));
+        }
+
+        let mut text = found.paths.join("\n");
+        if found.omitted > 0 {
+            text.push_str(&format!(
+                "\n... {} more (raise max_results to see them)",
+                found.omitted
+            ));
+        }
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    /// Search workspace files for a regex
+    // ANCHOR: search_workspace_tool
+    #[tool(
+        description = "Search the files in the workspace for lines matching a regex, skipping files ignored by .gitignore. \
+                       Optionally restrict the search to files matching a glob. Each match reports its file path \
+                       (relative to the workspace root), line number, and surrounding context lines, \
+                       in the same shape as `get_rust_crate_source` matches."
+    )]
+    async fn search_workspace(
+        &self,
+        Parameters(params): Parameters<SearchWorkspaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: search_workspace_tool
+        let context_lines = params
+            .context_lines
+            .unwrap_or(crate::eg::rust::DEFAULT_CONTEXT_LINES);
+        if context_lines > crate::eg::rust::MAX_CONTEXT_LINES {
+            return Err(McpError::invalid_params(
+                format!(
+                    "context_lines must be at most {} (got {})",
+                    crate::eg::rust::MAX_CONTEXT_LINES,
+                    context_lines
+                ),
+                None,
+            ));
+        }
+        let max_matches = params
+            .max_matches
+            .unwrap_or(crate::eg::rust::DEFAULT_MAX_MATCHES);
+
+        let pattern = regex::Regex::new(&params.pattern).map_err(|e| {
+            McpError::invalid_params(format!("Invalid regex pattern: {}", e), None)
+        })?;
+
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            McpError::internal_error(
+                "Failed to determine workspace root",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        let filter = match &params.glob {
+            Some(glob) => crate::file_search::FileFilter::glob(&root, glob),
+            None => Ok(crate::file_search::FileFilter::All),
+        }
+        .map_err(|e| {
+            McpError::invalid_params(
+                "Invalid glob",
+                Some(serde_json::json!({
+                    "error": e.to_string(),
+                    "glob": params.glob
+                })),
+            )
+        })?;
+
+        let found = crate::file_search::search_workspace(&root, &pattern, &filter, context_lines, max_matches)
+            .map_err(|e| {
+                McpError::internal_error(
+                    "Failed to search workspace",
+                    Some(serde_json::json!({
+                        "error": e.to_string()
+                    })),
+                )
+            })?;
+
+        let matches: Vec<_> = found
+            .matches
+            .into_iter()
+            .map(|m| {
+                let path = m.file_path.clone();
+                Self::match_response(m, path)
+            })
+            .collect();
+        let response = serde_json::json!({
+            "workspace_root": root,
+            "matches": matches,
+            "truncated": found.truncated,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap(),
+        )]))
+    }
+
+    /// Show recent message bus traffic, for debugging IPC behavior
+    ///
+    /// Only registered when the server runs with `--dev-log`.
+    // ANCHOR: debug_recent_messages_tool
+    #[tool(
+        description = "Debugging aid: return the most recent messages seen by the Symposium message bus daemon \
+                       as JSON (timestamp in milliseconds, sender identifier, raw message content). \
+                       Use this to check whether your IPC requests and the IDE's replies actually went over the bus."
+    )]
+    async fn debug_recent_messages(
+        &self,
+        Parameters(params): Parameters<DebugRecentMessagesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: debug_recent_messages_tool
+        let mut messages = crate::daemon::request_message_history(&self.daemon_socket_path)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    "Failed to read message history from daemon",
+                    Some(serde_json::json!({
+                        "error": e.to_string(),
+                        "socket_path": self.daemon_socket_path
+                    })),
+                )
+            })?;
+
+        let count = params
+            .count
+            .unwrap_or(crate::constants::DEFAULT_DEBUG_MESSAGE_COUNT);
+        let total = messages.len();
+        messages.drain(..total.saturating_sub(count));
+
+        let result = serde_json::json!({
+            "total": total,
+            "messages": messages,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&result).unwrap_or_default(),
+        )]))
+    }
+
+    /// List the components connected to the message bus
+    // ANCHOR: list_peers_tool
+    #[tool(
+        description = "List the components connected to the Symposium message bus: VSCode windows (`ide`) \
+                       and MCP servers in other taskspaces (`mcp_server`). \
+                       Broadcasts a discovery request and collects the replies for a short window. \
+                       Each peer comes with its working directory, taskspace UUID and shell PID; `is_self` marks this server."
+    )]
+    async fn list_peers(
+        &self,
+        Parameters(params): Parameters<ListPeersParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: list_peers_tool
+        let window_ms = params
+            .window_ms
+            .unwrap_or(crate::constants::DEFAULT_PEER_DISCOVERY_WINDOW_MS)
+            .min(crate::constants::MAX_PEER_DISCOVERY_WINDOW_MS);
+
+        let peers = self
+            .ipc
+            .list_peers(std::time::Duration::from_millis(window_ms))
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    "Failed to discover peers",
+                    Some(serde_json::json!({
+                        "error": e.to_string()
+                    })),
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&peers).unwrap_or_default(),
+        )]))
+    }
+
+    /// Get the absolute path of the workspace root
+    // ANCHOR: get_workspace_root_tool
+    #[tool(
+        description = "Get the canonical absolute path of the workspace root \
+                       (the enclosing git repository, or the current directory if there is none)."
+    )]
+    async fn get_workspace_root(&self) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: get_workspace_root_tool
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            McpError::internal_error(
+                "Failed to determine workspace root",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            root.to_string_lossy().to_string(),
+        )]))
+    }
+
+    /// List the editor tabs open in VSCode
+    // ANCHOR: list_open_editors_tool
+    #[tool(
+        description = "List the files the user has open in VSCode, one entry per file: \
+                       `path` (relative to the workspace root when inside it), `is_active` (the focused editor), \
+                       `is_dirty` (unsaved changes), and `language`. Returns an empty list when no IDE is connected. \
+                       Use it to refer to \"the file you have open\" without asking."
+    )]
+    async fn list_open_editors(&self) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: list_open_editors_tool
+        debug!("Listing open editors");
+
+        let editors = self.ipc.list_open_editors().await.map_err(|e| {
+            McpError::internal_error(
+                "IPC communication failed",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::json(editors)?]))
+    }
+
+    /// Get the currently selected text from any active editor in VSCode
+    ///
+    /// Works with source files, review panels, and any other text editor.
+    /// Returns null if no text is selected or no active editor is found.
+    // ANCHOR: get_selection_tool
+    #[tool(
+        description = "\
+            Get the currently selected text from any active editor in VSCode.\n\
+            Works with source files, review panels, and any other text editor.\n\
+            Returns null if no text is selected or no active editor is found.\
+        "
+    )]
+    async fn get_selection(&self) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: get_selection_tool
+        // Request current selection from VSCode extension via IPC
+        info!("Requesting current selection from VSCode extension...");
+
+        let result = self.ipc.get_selection().await.map_err(|e| {
+            McpError::internal_error(
+                "IPC communication failed",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        let status_msg = if result.selected_text.is_some() {
+            "text selected"
+        } else {
+            "no selection"
+        };
+
+        info!("selection retrieved: {}", status_msg);
+
+        // Convert result to JSON and return
+        let json_content = Content::json(result).map_err(|e| {
+            McpError::internal_error(
+                "Serialization failed",
+                Some(serde_json::json!({
+                    "error": format!("Failed to serialize selection result: {}", e)
+                })),
+            )
+        })?;
+
+        Ok(CallToolResult::success(vec![json_content]))
+    }
+
+    /// Execute IDE operations using Dialect mini-language
+    ///
+    /// Provides access to VSCode's Language Server Protocol (LSP) capabilities
+    /// through a composable function system for symbol resolution and reference finding.
+    // ANCHOR: ide_operation_tool
+    #[tool(
+        description = "\
+            Execute IDE operations using a structured JSON mini-language.\n\
+            This tool provides access to VSCode's Language Server Protocol (LSP) capabilities\n\
+            through a composable function system.\n\
+            \n\
+            Common operations:\n\
+            - findDefinitions(\"MyFunction\") or findDefinition(\"MyFunction\") - list of locations where a symbol named `MyFunction` is defined\n\
+            - findReferences(\"MyFunction\") - list of locations where a symbol named `MyFunction` is referenced\n\
+            - renamePreview(findDefinition(\"MyFunction\"), \"newName\") - edits a rename would make; read-only, never modifies files\n\
+            - documentSymbols(\"src/lib.rs\") - outline of a file as a tree of symbols with nested children\n\
+            - body(findDefinition(\"foo\")) - full declaration range of a symbol, including its body\n\
+            - definitionAt(search(\"src/\", \"User::new\")) - definition of the symbol at the start of each range (from search or lines)\n\
+            - filter(findReferences(\"MyFunction\"), pathContains(\"test\")) - keep only locations matching pathContains(\"substr\"), pathMatches(\"glob\") or inFile(\"path\")\n\
+            \n\
+            Pass an array of programs to run several lookups in one call; the result is an array in the same order.\n\
+            \n\
+            To find full guidelines for usage, use the `expand_reference` with `walkthrough-format.md`.\n\
+            "
+    )]
+    async fn ide_operation(
+        &self,
+        Parameters(params): Parameters<IdeOperationParams>,
     ) -> Result<CallToolResult, McpError> {
         // ANCHOR_END: ide_operation_tool
         debug!("Received ide_operation tool call with program: {:?}", params.program);
 
         info!("Executing Dialect program...");
 
-        let program = params.program;
         let mut interpreter = self.interpreter.clone();
 
         let result = tokio::task::spawn_blocking(move || {
             tokio::runtime::Handle::current().block_on(async move {
-                // Parse and evaluate the Dialect program string
-                interpreter.evaluate(&program).await
+                // Parse and evaluate the Dialect program string(s)
+                // Failures carry the error's `kind` so agents can tell a typo from an IDE problem
+                match params.program {
+                    DialectPrograms::Single(program) => interpreter.evaluate(&program).await.map_err(|e| {
+                        serde_json::json!({
+                            "kind": e.kind(),
+                            "error": e.to_string(),
+                        })
+                    }),
+                    DialectPrograms::Batch(programs) => {
+                        let mut results = Vec::with_capacity(programs.len());
+                        for (index, program) in programs.iter().enumerate() {
+                            let result = interpreter.evaluate(program).await.map_err(|e| {
+                                serde_json::json!({
+                                    "kind": e.kind(),
+                                    "error": format!("program {} (`{}`) failed: {}", index, program, e),
+                                    "program_index": index,
+                                })
+                            })?;
+                            results.push(result);
+                        }
+                        Ok(serde_json::Value::Array(results))
+                    }
+                }
             })
         })
         .await
@@ -376,14 +1346,7 @@ impl SymposiumServer {
                 })),
             )
         })?
-        .map_err(|e| {
-            McpError::internal_error(
-                "Dialect execution failed",
-                Some(serde_json::json!({
-                    "error": e.to_string()
-                })),
-            )
-        })?;
+        .map_err(|data| McpError::internal_error("Dialect execution failed", Some(data)))?;
 
         info!("Dialect execution completed successfully");
 
@@ -425,6 +1388,11 @@ impl SymposiumServer {
         if let Some(context) = self.reference_handle.get_reference(&params.id).await {
             info!("Reference {} expanded successfully", params.id);
 
+            // Stored text (e.g., a truncated tool result) is returned verbatim
+            if let serde_json::Value::String(text) = context {
+                return Ok(CallToolResult::success(vec![Content::text(text)]));
+            }
+
             return Ok(CallToolResult::success(vec![Content::text(
                 serde_json::to_string_pretty(&context).map_err(|e| {
                     McpError::internal_error(
@@ -438,8 +1406,8 @@ impl SymposiumServer {
         }
 
         // Not found in reference actor, try guidance files
-        if let Some(file) = GuidanceFiles::get(&params.id) {
-            let content = String::from_utf8_lossy(&file.data);
+        if let Some(data) = GuidanceLayers::for_current_workspace().get(&params.id) {
+            let content = String::from_utf8_lossy(&data);
 
             info!("Guidance file {} loaded successfully", params.id);
 
@@ -450,7 +1418,7 @@ impl SymposiumServer {
 
         // Special case: "yiasou" or "hi" returns the same content as @yiasou stored prompt
         if params.id == "yiasou" || params.id == "hi" {
-            match self.assemble_yiasou_prompt(None).await {
+            match self.assemble_yiasou_prompt(None, None).await {
                 Ok(prompt_content) => {
                     info!("Yiasou prompt assembled successfully via expand_reference");
 
@@ -478,50 +1446,159 @@ impl SymposiumServer {
         ))
     }
 
-    /// Create a new taskspace with initial prompt
+    /// Resolve a compact reference back to its source location
     ///
-    /// This tool allows agents to spawn new taskspaces for collaborative work.
-    /// The taskspace will be created with the specified name, description, and initial prompt.
-    // ANCHOR: spawn_taskspace_tool
-    #[tool(
-        description = "Create a new taskspace with name, description, and initial prompt. \
-                       The new taskspace will be launched with VSCode and the configured agent tool."
-    )]
-    async fn spawn_taskspace(
+    /// If the reference points at a file location (a code selection or a comment),
+    /// asks VSCode to open the file there. Otherwise returns the reference context,
+    /// just like `expand_reference`.
+    // ANCHOR: open_reference_tool
+    #[tool(description = "
+        Open the source location behind a compact reference (`<symposium-ref id='..'/>`) in the user's editor. \
+        Invoke with the contents of `id` attribute. If the reference has no file location, \
+        returns its full context as JSON instead, like expand_reference. \
+    ")]
+    async fn open_reference(
         &self,
-        Parameters(params): Parameters<SpawnTaskspaceParams>,
+        Parameters(params): Parameters<OpenReferenceParams>,
     ) -> Result<CallToolResult, McpError> {
-        // ANCHOR_END: spawn_taskspace_tool
-        info!("Creating new taskspace: {}", params.name);
+        // ANCHOR_END: open_reference_tool
+        debug!("Opening reference: {}", params.id);
 
-        // Default collaborator to current taskspace's collaborator if none specified
-        let collaborator = if params.collaborator.is_some() {
-            params.collaborator
-        } else {
-            // Get current taskspace collaborator as fallback
-            self.get_taskspace_context()
-                .await
-                .and_then(|ts| ts.collaborator)
+        let Some(context) = self.reference_handle.get_reference(&params.id).await else {
+            info!("Reference {} not found", params.id);
+            return Err(McpError::invalid_params(
+                "Reference not found",
+                Some(serde_json::json!({
+                    "reference_id": params.id
+                })),
+            ));
         };
 
-        // Send spawn_taskspace message to Symposium app via daemon
-        match self
-            .ipc
-            .spawn_taskspace(
-                params.name.clone(),
-                params.task_description,
-                params.initial_prompt,
-                collaborator,
+        let Some(location) = crate::reference_store::reference_location(&context) else {
+            info!("Reference {} has no location, returning context", params.id);
+            return Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&context).map_err(|e| {
+                    McpError::internal_error(
+                        "Failed to serialize reference context",
+                        Some(serde_json::json!({
+                            "error": e.to_string()
+                        })),
+                    )
+                })?,
+            )]));
+        };
+
+        let summary = format!("{}:{}", location.path, location.start.line);
+        self.ipc.reveal_location(location).await.map_err(|e| {
+            McpError::internal_error(
+                "Failed to reveal location",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        info!("Reference {} opened at {}", params.id, summary);
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Opened {} in the editor",
+            summary
+        ))]))
+    }
+
+    /// List the collaborator personas that taskspaces can be configured with
+    // ANCHOR: list_collaborators_tool
+    #[tool(description = "List the collaborator personas accepted by `spawn_taskspace`, `update_taskspace`, \
+                       and the yiasou prompt, with a short description of each.")]
+    async fn list_collaborators(&self) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: list_collaborators_tool
+        Ok(CallToolResult::success(vec![Content::json(Self::known_collaborators())?]))
+    }
+
+    /// List the guidance documents the agent can load
+    // ANCHOR: list_guidance_tool
+    #[tool(description = "List the available guidance documents with their titles and descriptions, \
+                       including the project's own from `.symposium/guidance/`. \
+                       Load one by passing its `id` to `expand_reference`.")]
+    async fn list_guidance(&self) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: list_guidance_tool
+        let guidance = GuidanceLayers::for_current_workspace();
+        Ok(CallToolResult::success(vec![Content::json(Self::guidance_entries(&guidance))?]))
+    }
+
+    /// Show the initialization prompt a collaborator would receive
+    // ANCHOR: preview_initialization_tool
+    #[tool(description = "Preview the initialization (yiasou) prompt that an agent would receive with the given \
+                       collaborator, without applying it. Useful for choosing a collaborator for `spawn_taskspace`.")]
+    async fn preview_initialization(
+        &self,
+        Parameters(params): Parameters<PreviewInitializationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: preview_initialization_tool
+        let mut prompt = self
+            .assemble_yiasou_prompt(Some(params.collaborator.clone()), None)
+            .await?;
+        if let Some(warning) = Self::collaborator_warning(Some(&params.collaborator)) {
+            prompt = format!("{warning}\n\n{prompt}");
+        }
+        Ok(CallToolResult::success(vec![Content::text(prompt)]))
+    }
+
+    /// Create a new taskspace with initial prompt
+    ///
+    /// This tool allows agents to spawn new taskspaces for collaborative work.
+    /// The taskspace will be created with the specified name, description, and initial prompt.
+    // ANCHOR: spawn_taskspace_tool
+    #[tool(
+        description = "Create a new taskspace with name, description, and initial prompt. \
+                       Give the prompt inline as `initial_prompt`, or write a long prompt to a file \
+                       in the workspace and pass its path as `initial_prompt_path`. \
+                       Give related taskspaces the same `tags` so the app can group them. \
+                       The new taskspace will be launched with VSCode and the configured agent tool."
+    )]
+    async fn spawn_taskspace(
+        &self,
+        Parameters(params): Parameters<SpawnTaskspaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: spawn_taskspace_tool
+        info!("Creating new taskspace: {}", params.name);
+
+        let initial_prompt = Self::resolve_initial_prompt(
+            params.initial_prompt,
+            params.initial_prompt_path.as_deref(),
+        )?;
+        let collaborator_warning = Self::collaborator_warning(params.collaborator.as_deref());
+
+        // Default collaborator to current taskspace's collaborator if none specified
+        let collaborator = if params.collaborator.is_some() {
+            params.collaborator
+        } else {
+            // Get current taskspace collaborator as fallback
+            self.get_taskspace_context()
+                .await
+                .and_then(|ts| ts.collaborator)
+        };
+
+        // Send spawn_taskspace message to Symposium app via daemon
+        match self
+            .ipc
+            .spawn_taskspace(
+                params.name.clone(),
+                params.task_description,
+                initial_prompt,
+                collaborator,
+                normalize_tags(params.tags),
             )
             .await
         {
             Ok(()) => {
                 info!("Taskspace '{}' created successfully", params.name);
 
-                Ok(CallToolResult::success(vec![Content::text(format!(
-                    "Taskspace '{}' created successfully",
-                    params.name
-                ))]))
+                let mut message = format!("Taskspace '{}' created successfully", params.name);
+                if let Some(warning) = collaborator_warning {
+                    message.push_str("\n\n");
+                    message.push_str(&warning);
+                }
+                Ok(CallToolResult::success(vec![Content::text(message)]))
             }
             Err(e) => {
                 error!("Failed to create taskspace '{}': {}", params.name, e);
@@ -564,7 +1641,7 @@ impl SymposiumServer {
         // Send log_progress message to Symposium app via daemon
         match self
             .ipc
-            .log_progress(params.message.clone(), category)
+            .log_progress(params.message.clone(), category, params.fields)
             .await
         {
             Ok(()) => {
@@ -595,209 +1672,1076 @@ impl SymposiumServer {
     /// causing the taskspace to move toward the front of the Symposium panel.
     // ANCHOR: signal_user_tool
     #[tool(description = "Request user attention for assistance. \
-                       The taskspace will be highlighted and moved toward the front of the panel.")]
+                       The taskspace will be highlighted and moved toward the front of the panel. \
+                       Set priority to 'urgent' when blocked or 'low' for FYI updates (default 'normal').")]
     async fn signal_user(
         &self,
         Parameters(params): Parameters<SignalUserParams>,
     ) -> Result<CallToolResult, McpError> {
         // ANCHOR_END: signal_user_tool
-        info!("Requesting user attention: {}", params.message);
+        let priority = params.priority.as_str();
+        info!("Requesting user attention ({}): {}", priority, params.message);
 
         // Send signal_user message to Symposium app via daemon
-        match self.ipc.signal_user(params.message.clone()).await {
+        match self.ipc.signal_user(params.message.clone(), params.priority).await {
             Ok(()) => {
                 info!("User attention requested successfully");
 
                 Ok(CallToolResult::success(vec![Content::text(format!(
-                    "User attention requested: {}",
-                    params.message
+                    "User attention requested ({} priority): {}",
+                    priority, params.message
                 ))]))
             }
             Err(e) => {
-                error!("Failed to request user attention: {}", e);
-
+                error!("Failed to request user attention: {}", e);
+
+                Err(McpError::internal_error(
+                    "Failed to request user attention",
+                    Some(serde_json::json!({
+                        "error": e.to_string(),
+                        "message": params.message,
+                        "priority": priority
+                    })),
+                ))
+            }
+        }
+    }
+
+    // ANCHOR: update_taskspace_tool
+    #[tool(
+        description = "Update the name and description of the current taskspace. \
+                       Use this to set meaningful names and descriptions based on user interaction. \
+                       Pass `tags` to replace the tags that group this taskspace with related ones."
+    )]
+    async fn update_taskspace(
+        &self,
+        Parameters(params): Parameters<UpdateTaskspaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: update_taskspace_tool
+        info!("Updating taskspace: {} - {}", params.name, params.description);
+        let tags = params.tags.map(normalize_tags);
+
+        // Send update_taskspace message to Symposium app via daemon
+        match self
+            .ipc
+            .update_taskspace(
+                params.name.clone(),
+                params.description.clone(),
+                params.collaborator.clone(),
+                tags.clone(),
+            )
+            .await
+        {
+            Ok(state) => {
+                info!("Taskspace updated successfully");
+
+                // Note: GUI app automatically clears initial_prompt on update
+                let mut status_msg = if state.initial_prompt.is_none() {
+                    format!(
+                        "Taskspace updated: {} - {} (initial prompt cleared)",
+                        params.name, params.description
+                    )
+                } else {
+                    format!(
+                        "Taskspace updated: {} - {}",
+                        params.name, params.description
+                    )
+                };
+                if let Some(tags) = &tags {
+                    status_msg.push_str(&format!("\nTags: {}", tags.join(", ")));
+                }
+                if let Some(warning) = Self::collaborator_warning(params.collaborator.as_deref()) {
+                    status_msg.push_str("\n\n");
+                    status_msg.push_str(&warning);
+                }
+
+                Ok(CallToolResult::success(vec![Content::text(status_msg)]))
+            }
+            Err(e) => {
+                error!("Failed to update taskspace: {}", e);
+
+                Err(McpError::internal_error(
+                    "Failed to update taskspace",
+                    Some(serde_json::json!({
+                        "error": e.to_string(),
+                        "name": params.name,
+                        "description": params.description
+                    })),
+                ))
+            }
+        }
+    }
+
+    /// Describe the taskspace this agent runs in
+    // ANCHOR: get_taskspace_info_tool
+    #[tool(
+        description = "Get the current taskspace's UUID, worktree path, base repository path, name, description, and tags. \
+                       Returns `in_taskspace: false` (not an error) when not running in a taskspace."
+    )]
+    async fn get_taskspace_info(&self) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: get_taskspace_info_tool
+        let info = match crate::ipc::extract_project_info() {
+            Ok((project_path, taskspace_uuid)) => {
+                // Name and description live in the app; paths are known locally
+                let state = self.get_taskspace_context().await;
+                serde_json::json!({
+                    "in_taskspace": true,
+                    "uuid": taskspace_uuid,
+                    "worktree_path": crate::workspace_dir::workspace_root()
+                        .ok()
+                        .map(|root| root.to_string_lossy().to_string()),
+                    "base_repo_path": project_path,
+                    "name": state.as_ref().and_then(|s| s.name.clone()),
+                    "description": state.as_ref().and_then(|s| s.description.clone()),
+                    "tags": state.as_ref().map(|s| s.tags.clone()),
+                })
+            }
+            Err(e) => serde_json::json!({
+                "in_taskspace": false,
+                "message": format!("Not in a taskspace: {}", e),
+            }),
+        };
+
+        Ok(CallToolResult::success(vec![Content::json(info)?]))
+    }
+
+    // ANCHOR: delete_taskspace_tool
+    #[tool(
+        description = "Delete the current taskspace. This will remove the taskspace directory, \
+                       close associated VSCode windows, and clean up git worktrees. \
+                       If the taskspace has uncommitted work, deletion is not performed and \
+                       warnings are returned instead; confirm with the user, then call again with \
+                       `force: true` to delete anyway."
+    )]
+    async fn delete_taskspace(
+        &self,
+        Parameters(params): Parameters<DeleteTaskspaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: delete_taskspace_tool
+        info!("Deleting current taskspace (force: {})", params.force);
+
+        if !params.force {
+            // Count uncommitted files locally so the extension can make an informed decision
+            let uncommitted_file_count = match crate::git::GitService::for_workspace()
+                .and_then(|git| git.uncommitted_file_count())
+            {
+                Ok(count) => count,
+                Err(e) => {
+                    warn!("Failed to count uncommitted files: {}", e);
+                    0
+                }
+            };
+
+            // Give the extension a chance to veto the deletion before it happens
+            // A failed request is treated like no reply rather than aborting the deletion
+            let response = match self.ipc.prepare_for_deletion(uncommitted_file_count).await {
+                Ok(response) => response,
+                Err(e) => {
+                    warn!("Failed to prepare taskspace for deletion: {}", e);
+                    None
+                }
+            };
+            if response.is_none() {
+                info!("No prepare_for_deletion reply, relying on the local uncommitted file count");
+            }
+
+            if let Some(message) = Self::deletion_blocker(uncommitted_file_count, response) {
+                info!("Taskspace deletion deferred until the user confirms");
+                return Ok(CallToolResult::success(vec![Content::text(message)]));
+            }
+        }
+
+        // Send delete_taskspace message to Symposium app via daemon
+        match self.ipc.delete_taskspace().await {
+            Ok(()) => {
+                info!("Taskspace deletion initiated");
+
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Taskspace deletion initiated successfully".to_string(),
+                )]))
+            }
+            Err(e) => {
+                error!("Failed to delete taskspace: {}", e);
+
+                Err(McpError::internal_error(
+                    "Failed to delete taskspace",
+                    Some(serde_json::json!({
+                        "error": e.to_string()
+                    })),
+                ))
+            }
+        }
+    }
+
+    /// Get a unified diff (patch) for a commit range
+    #[tool(
+        description = "Get a standard unified diff (patch) for a git commit range. \
+                       Returns raw patch text that can be reasoned about or applied with `git apply`. \
+                       Use `exclude_unstaged`/`exclude_staged` to filter working tree changes."
+    )]
+    async fn get_patch(
+        &self,
+        Parameters(params): Parameters<GetPatchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Generating patch for range {:?}", params.range);
+
+        let patch = crate::git::GitService::for_workspace()
+            .and_then(|git| {
+                let (base_oid, head_oid) = git.parse_commit_range(&params.range)?;
+                git.generate_patch(
+                    base_oid,
+                    head_oid,
+                    params.exclude_unstaged,
+                    params.exclude_staged,
+                )
+            })
+            .map_err(|e| {
+                error!("Failed to generate patch: {}", e);
+                McpError::internal_error(
+                    "Failed to generate patch",
+                    Some(serde_json::json!({
+                        "error": e.to_string(),
+                        "range": params.range
+                    })),
+                )
+            })?;
+
+        if patch.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No changes in range {}",
+                params.range
+            ))]));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(patch)]))
+    }
+
+    /// Show a file as it was at an earlier revision
+    // ANCHOR: get_file_at_revision_tool
+    #[tool(
+        description = "Get the content of a file as it was at a given git revision (commit, branch, tag, or `HEAD~N`). \
+                       Returns the content with line numbers. Useful for explaining how code used to look."
+    )]
+    async fn get_file_at_revision(
+        &self,
+        Parameters(params): Parameters<GetFileAtRevisionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: get_file_at_revision_tool
+        debug!("Reading {} at revision {}", params.path, params.revision);
+
+        let content = crate::git::GitService::for_workspace()
+            .and_then(|git| git.file_at_revision(&params.path, &params.revision))
+            .map_err(|e| {
+                McpError::invalid_params(
+                    "Failed to read file at revision",
+                    Some(serde_json::json!({
+                        "error": e.message(),
+                        "path": params.path,
+                        "revision": params.revision
+                    })),
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(number_lines(&content))]))
+    }
+
+    /// Open the IDE's diff view for two revisions of a file
+    // ANCHOR: open_diff_tool
+    #[tool(
+        description = "Open the user's editor on a side-by-side diff of one file between two git revisions. \
+                       Use `WORKING` as head_revision to compare with the file on disk, which stays editable. \
+                       Both versions are read from git first, so an unknown revision or missing file is reported as an error."
+    )]
+    async fn open_diff(
+        &self,
+        Parameters(params): Parameters<OpenDiffParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: open_diff_tool
+        debug!(
+            "Opening diff of {} between {} and {}",
+            params.path, params.base_revision, params.head_revision
+        );
+
+        let message = crate::git::GitService::for_workspace()
+            .and_then(|git| {
+                let base_content = git.file_at_revision(&params.path, &params.base_revision)?;
+                let head_content = if params.head_revision == crate::git::WORKING_TREE {
+                    git.working_tree_file(&params.path)?;
+                    None
+                } else {
+                    Some(git.file_at_revision(&params.path, &params.head_revision)?)
+                };
+                Ok(crate::types::OpenDiffMessage {
+                    path: params.path.clone(),
+                    base_revision: params.base_revision.clone(),
+                    base_content,
+                    head_revision: params.head_revision.clone(),
+                    head_content,
+                })
+            })
+            .map_err(|e| {
+                McpError::invalid_params(
+                    "Failed to read file for diff",
+                    Some(serde_json::json!({
+                        "error": e.message(),
+                        "path": params.path,
+                        "base_revision": params.base_revision,
+                        "head_revision": params.head_revision
+                    })),
+                )
+            })?;
+
+        self.ipc.open_diff(message).await.map_err(|e| {
+            McpError::internal_error(
+                "Failed to open diff",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Opened diff of {} ({}..{}) in the editor",
+            params.path, params.base_revision, params.head_revision
+        ))]))
+    }
+
+    /// Select a range in the user's editor
+    // ANCHOR: set_selection_tool
+    #[tool(
+        description = "Select a range of a file in the user's editor, to point their attention at it. \
+                       The file is opened if it isn't already. Lines and columns are 1-based; a range \
+                       that runs past the end of the file or of a line is clamped to it, and the reply \
+                       says where the selection ended up."
+    )]
+    async fn set_selection(
+        &self,
+        Parameters(params): Parameters<SetSelectionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: set_selection_tool
+        debug!("Setting selection in {}: {:?}", params.path, params);
+
+        let requested = crate::ide::FileRange {
+            path: params.path.clone(),
+            start: crate::ide::FileLocation { line: params.start_line, column: params.start_col },
+            end: crate::ide::FileLocation { line: params.end_line, column: params.end_col },
+            content: None,
+        };
+        let position = |location: &crate::ide::FileLocation| (location.line, location.column);
+        if position(&requested.start) > position(&requested.end) {
+            return Err(McpError::invalid_params(
+                "Selection starts after it ends",
+                Some(serde_json::json!({ "path": params.path })),
+            ));
+        }
+
+        // Only plain relative paths, so the selection stays inside the workspace
+        let relative = std::path::Path::new(&params.path);
+        let file = crate::workspace_dir::workspace_root()
+            .ok()
+            .filter(|_| relative.components().all(|c| matches!(c, std::path::Component::Normal(_))))
+            .map(|root| root.join(relative));
+        let Some(content) = file.and_then(|file| std::fs::read_to_string(file).ok()) else {
+            return Err(McpError::invalid_params(
+                format!("No such file in the workspace: {}", params.path),
+                Some(serde_json::json!({ "path": params.path })),
+            ));
+        };
+
+        let selection = requested.clamped_to(&content);
+        let clamped = position(&selection.start) != position(&requested.start)
+            || position(&selection.end) != position(&requested.end);
+        let summary = format!(
+            "{}:{}:{}-{}:{}",
+            selection.path,
+            selection.start.line,
+            selection.start.column,
+            selection.end.line,
+            selection.end.column
+        );
+
+        self.ipc.reveal_location(selection).await.map_err(|e| {
+            McpError::internal_error(
+                "Failed to set selection",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        let mut text = format!("Selected {} in the editor", summary);
+        if clamped {
+            text.push_str(&format!(
+                " (clamped from {}:{}-{}:{}, which is outside the file)",
+                params.start_line, params.start_col, params.end_line, params.end_col
+            ));
+        }
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    /// Propose a patch for the user to apply
+    #[tool(
+        description = "Propose a patch (unified diff) to apply to the working tree. \
+                       The patch is first checked locally; if it applies cleanly it is shown to the user, \
+                       who must explicitly confirm before any file is modified. \
+                       Returns whether the patch was applied, declined, or conflicted."
+    )]
+    async fn apply_patch(
+        &self,
+        Parameters(params): Parameters<ApplyPatchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        info!("Proposing patch: {}", params.description);
+
+        // Safe mode: never ask the user about a patch that cannot apply cleanly
+        let check = crate::git::GitService::for_workspace().and_then(|git| git.check_patch(&params.patch));
+        if let Err(e) = check {
+            info!("Patch does not apply cleanly: {}", e);
+            let response = crate::types::ApplyPatchResponse {
+                status: crate::types::ApplyPatchStatus::Conflict,
+                conflicts: vec![e.message().to_string()],
+                message: Some("Patch does not apply cleanly to the working tree; nothing was changed".to_string()),
+            };
+            return Ok(CallToolResult::success(vec![Content::json(response)?]));
+        }
+
+        match self.ipc.apply_patch(params.patch, params.description).await {
+            Ok(response) => {
+                info!("Patch proposal finished with status {:?}", response.status);
+                Ok(CallToolResult::success(vec![Content::json(response)?]))
+            }
+            Err(e) => {
+                error!("Failed to apply patch: {}", e);
+
+                Err(McpError::internal_error(
+                    "Failed to apply patch",
+                    Some(serde_json::json!({
+                        "error": e.to_string()
+                    })),
+                ))
+            }
+        }
+    }
+
+    /// Commit staged changes (or the given paths) with an agent-authored message
+    // ANCHOR: git_commit_tool
+    #[tool(
+        description = "Commit changes to the current git branch. With `paths`, those files are staged first \
+                       (deleted files are staged as deletions); without it, only already-staged changes are committed. \
+                       Refuses to create an empty commit. Returns the new commit id."
+    )]
+    async fn git_commit(
+        &self,
+        Parameters(params): Parameters<GitCommitParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: git_commit_tool
+        info!("Committing: {}", params.message);
+
+        let oid = crate::git::GitService::for_workspace()
+            .and_then(|git| git.commit(&params.message, params.paths.as_deref()))
+            .map_err(|e| {
+                McpError::invalid_params(
+                    "Failed to commit",
+                    Some(serde_json::json!({
+                        "error": e.message(),
+                        "paths": params.paths
+                    })),
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+            "commit": oid.to_string(),
+        }))?]))
+    }
+
+    /// Get the comment threads of the review for a commit range
+    // ANCHOR: get_review_comments_tool
+    #[tool(
+        description = "Get the full comment threads for the changes in a git commit range. \
+                       Comments are found by the project's configured markers (💡/❓/TODO:/FIXME: by default). \
+                       Returns JSON grouped by file: each thread has its line number, comment type \
+                       (Explanation/Question/Todo/Fixme), content, and the responses so far. Resolution is not tracked yet, \
+                       so `resolved` is always false."
+    )]
+    async fn get_review_comments(
+        &self,
+        Parameters(params): Parameters<GetReviewCommentsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: get_review_comments_tool
+        debug!("Collecting review comments for range {:?}", params.range);
+
+        let file_changes = crate::git::GitService::for_workspace()
+            .and_then(|git| {
+                let (base_oid, head_oid) = git.parse_commit_range(&params.range)?;
+                git.generate_diff(base_oid, head_oid)
+            })
+            .map_err(|e| {
+                error!("Failed to generate diff for review comments: {}", e);
+                McpError::internal_error(
+                    "Failed to generate diff for review comments",
+                    Some(serde_json::json!({
+                        "error": e.to_string(),
+                        "range": params.range
+                    })),
+                )
+            })?;
+
+        let threads = self
+            .project_config
+            .comment_parser()
+            .parse_file_changes(&file_changes)
+            .map_err(|e| {
+                McpError::internal_error(
+                    "Failed to parse review comments",
+                    Some(serde_json::json!({
+                        "error": e.to_string()
+                    })),
+                )
+            })?;
+
+        let files = crate::git::group_threads_by_file(threads);
+        Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+            "range": params.range,
+            "files": files,
+        }))?]))
+    }
+
+    /// Resolve a commit range to concrete commits without creating a review
+    // ANCHOR: resolve_commit_range_tool
+    #[tool(
+        description = "Resolve a git commit range to concrete commits without creating a review, \
+                       to check a range before starting a review. Accepts `base..head`, `base...head` \
+                       (from the merge base), a single commit (compared with the working tree), and \
+                       `WORKING` as the head for uncommitted changes. Returns JSON with base_oid, \
+                       head_oid (null for the working tree), commit_count, and the commits oldest first."
+    )]
+    async fn resolve_commit_range(
+        &self,
+        Parameters(params): Parameters<ResolveCommitRangeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: resolve_commit_range_tool
+        debug!("Resolving commit range {:?}", params.range);
+
+        let (base_oid, head_oid, commits) = crate::git::GitService::for_workspace()
+            .and_then(|git| {
+                let (base_oid, head_oid) = git.parse_commit_range(&params.range)?;
+                let commits = git.commits_in_range(base_oid, head_oid)?;
+                Ok((base_oid, head_oid, commits))
+            })
+            .map_err(|e| {
+                McpError::invalid_params(
+                    format!("Failed to resolve commit range {:?}", params.range),
+                    Some(serde_json::json!({
+                        "error": e.message(),
+                        "code": format!("{:?}", e.code()),
+                        "range": params.range
+                    })),
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+            "range": params.range,
+            "base_oid": base_oid.to_string(),
+            "head_oid": head_oid.map(|oid| oid.to_string()),
+            "commit_count": commits.len(),
+            "commits": commits,
+        }))?]))
+    }
+
+    /// List recent commits, optionally filtered by time and author
+    // ANCHOR: get_git_log_tool
+    #[tool(
+        description = "List recent commits reachable from HEAD, newest first. Optionally filter by \
+                       `since` (\"2 days ago\", \"yesterday\", \"2025-01-31\", or an RFC 3339 timestamp) \
+                       and `author` (case-insensitive substring of the author name or email). \
+                       Returns JSON with the commits' oid, summary, author, email, and date."
+    )]
+    async fn get_git_log(
+        &self,
+        Parameters(params): Parameters<GetGitLogParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: get_git_log_tool
+        debug!(
+            "Getting git log (max {}, since {:?}, author {:?})",
+            params.max_count, params.since, params.author
+        );
+
+        let since = params
+            .since
+            .as_deref()
+            .map(|since| crate::git::parse_since(since, chrono::Utc::now()))
+            .transpose()
+            .map_err(|e| {
+                McpError::invalid_params(
+                    e.message().to_string(),
+                    Some(serde_json::json!({ "since": params.since })),
+                )
+            })?;
+        let filter = crate::git::LogFilter {
+            since,
+            author: params.author.clone(),
+        };
+
+        let commits = crate::git::GitService::for_workspace()
+            .and_then(|git| git.log(&filter, params.max_count))
+            .map_err(|e| {
+                error!("Failed to read git log: {}", e);
+                McpError::internal_error(
+                    "Failed to read git log",
+                    Some(serde_json::json!({
+                        "error": e.message(),
+                        "code": format!("{:?}", e.code()),
+                    })),
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+            "commit_count": commits.len(),
+            "commits": commits,
+        }))?]))
+    }
+
+    /// Persist unresolved review comments as TODO markers in the source
+    // ANCHOR: write_review_todos_tool
+    #[tool(
+        description = "Write unresolved review comment threads into the source as TODO comments, \
+                       using each file's comment syntax and the project's TODO marker, one line above the commented line. \
+                       The edit is proposed as a patch that the user must confirm before any file is modified. \
+                       Returns whether it was applied and which files were modified."
+    )]
+    async fn write_review_todos(
+        &self,
+        Parameters(params): Parameters<WriteReviewTodosParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // ANCHOR_END: write_review_todos_tool
+        info!("Writing {} review threads as TODOs", params.threads.len());
+
+        let git_error = |e: git2::Error| {
+            error!("Failed to prepare review TODOs: {}", e);
+            McpError::internal_error(
+                "Failed to prepare review TODOs",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        };
+
+        let git = crate::git::GitService::for_workspace().map_err(git_error)?;
+        let todo_patch = git
+            .todo_patch(&params.threads, &self.project_config.comment_parser())
+            .map_err(git_error)?;
+
+        if todo_patch.patch.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+                "status": "nothing_to_write",
+                "files_modified": [],
+                "skipped": todo_patch.skipped,
+            }))?]));
+        }
+
+        // Safe mode: never ask the user about a patch that cannot apply cleanly
+        git.check_patch(&todo_patch.patch).map_err(git_error)?;
+
+        let description = format!(
+            "Add TODO markers for unresolved review comments in {}",
+            todo_patch.files.join(", ")
+        );
+        let response = self
+            .ipc
+            .apply_patch(todo_patch.patch, description)
+            .await
+            .map_err(|e| {
+                error!("Failed to apply review TODOs: {}", e);
+                McpError::internal_error(
+                    "Failed to apply review TODOs",
+                    Some(serde_json::json!({
+                        "error": e.to_string()
+                    })),
+                )
+            })?;
+
+        let files_modified = match response.status {
+            crate::types::ApplyPatchStatus::Applied => todo_patch.files,
+            _ => vec![],
+        };
+        Ok(CallToolResult::success(vec![Content::json(serde_json::json!({
+            "status": response.status,
+            "files_modified": files_modified,
+            "skipped": todo_patch.skipped,
+            "message": response.message,
+        }))?]))
+    }
+
+    /// Capture a snapshot of server state for bug reports
+    #[tool(
+        description = "Capture a snapshot of the Symposium server state for attaching to a bug report. \
+                       Returns a single JSON bundle with configuration, tool invocation counters, recent daemon messages, \
+                       presence, the guidance file list, and version info. Secrets are redacted and home directory paths shortened."
+    )]
+    async fn capture_state_bundle(&self) -> Result<CallToolResult, McpError> {
+        info!("Capturing state bundle");
+
+        let guidance_files = GuidanceLayers::for_current_workspace().file_names();
+        let bundle = crate::state_bundle::StateBundle::capture(&self.ipc, guidance_files).await;
+
+        Ok(CallToolResult::success(vec![Content::json(bundle)?]))
+    }
+
+    /// Get Rust crate source with optional pattern search
+    #[tool(description = "Get Rust crate source with optional pattern search. Always returns the source path, and optionally performs pattern matching if a search pattern is provided.")]
+    async fn get_rust_crate_source(
+        &self,
+        Parameters(GetRustCrateSourceParams { crate_name, version, pattern, context_lines, max_matches, relative_paths }): Parameters<GetRustCrateSourceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Getting Rust crate source for '{}' version: {:?} pattern: {:?}", crate_name, version, pattern);
+
+        let mut search = Self::rust_crate_search(&crate_name, version.as_deref(), pattern.as_deref(), context_lines)?;
+        if let Some(max_matches) = max_matches {
+            search = search.max_matches(max_matches);
+        }
+
+        match search.search().await {
+            Ok(result) => {
+                let response = Self::crate_source_response(&crate_name, result, pattern.is_some(), relative_paths);
+                Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&response).unwrap())]))
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to get Rust crate source: {}", e);
                 Err(McpError::internal_error(
-                    "Failed to request user attention",
+                    error_msg,
                     Some(serde_json::json!({
-                        "error": e.to_string(),
-                        "message": params.message
+                        "crate_name": crate_name,
+                        "error": e.to_string()
                     })),
                 ))
             }
         }
     }
 
-    // ANCHOR: update_taskspace_tool
-    #[tool(
-        description = "Update the name and description of the current taskspace. \
-                       Use this to set meaningful names and descriptions based on user interaction."
-    )]
-    async fn update_taskspace(
+    /// Get Rust crate sources for several crates, searching each for the same pattern
+    #[tool(description = "Get the sources of several Rust crates at once, optionally searching all of them for the same pattern. \
+                          Returns one entry per crate, in request order, shaped like the get_rust_crate_source response; \
+                          a crate that fails to resolve gets an entry with an `error` field instead of failing the whole batch.")]
+    async fn get_rust_crate_sources(
         &self,
-        Parameters(params): Parameters<UpdateTaskspaceParams>,
+        Parameters(GetRustCrateSourcesParams { crates, pattern }): Parameters<GetRustCrateSourcesParams>,
     ) -> Result<CallToolResult, McpError> {
-        // ANCHOR_END: update_taskspace_tool
-        info!("Updating taskspace: {} - {}", params.name, params.description);
+        debug!("Getting Rust crate sources for {} crates pattern: {:?}", crates.len(), pattern);
 
-        // Send update_taskspace message to Symposium app via daemon
-        match self
-            .ipc
-            .update_taskspace(params.name.clone(), params.description.clone(), params.collaborator.clone())
-            .await
-        {
-            Ok(state) => {
-                info!("Taskspace updated successfully");
+        // Validate the shared pattern once so a bad regex fails the call rather than every entry
+        if let Some(pattern) = &pattern {
+            regex::Regex::new(pattern).map_err(|e| {
+                McpError::invalid_params(format!("Invalid regex pattern: {}", e), None)
+            })?;
+        }
 
-                // Note: GUI app automatically clears initial_prompt on update
-                let status_msg = if state.initial_prompt.is_none() {
-                    format!(
-                        "Taskspace updated: {} - {} (initial prompt cleared)",
-                        params.name, params.description
-                    )
-                } else {
-                    format!(
-                        "Taskspace updated: {} - {}",
-                        params.name, params.description
-                    )
-                };
+        let results: Vec<serde_json::Value> = futures::stream::iter(crates)
+            .map(|spec| {
+                let pattern = pattern.clone();
+                async move {
+                    let search = Self::rust_crate_search(&spec.crate_name, spec.version.as_deref(), pattern.as_deref(), None);
+                    let result = match search {
+                        Ok(search) => search.search().await.map_err(|e| e.to_string()),
+                        Err(e) => Err(e.message.to_string()),
+                    };
+                    match result {
+                        Ok(result) => Self::crate_source_response(&spec.crate_name, result, pattern.is_some(), true),
+                        Err(error) => serde_json::json!({
+                            "crate_name": spec.crate_name,
+                            "error": format!("Failed to get Rust crate source: {}", error)
+                        }),
+                    }
+                }
+            })
+            .buffered(crate::constants::MAX_CONCURRENT_CRATE_SEARCHES)
+            .collect()
+            .await;
 
-                Ok(CallToolResult::success(vec![Content::text(status_msg)]))
-            }
-            Err(e) => {
-                error!("Failed to update taskspace: {}", e);
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&results).unwrap())]))
+    }
 
-                Err(McpError::internal_error(
-                    "Failed to update taskspace",
-                    Some(serde_json::json!({
-                        "error": e.to_string(),
-                        "name": params.name,
-                        "description": params.description
-                    })),
-                ))
-            }
-        }
+    /// Read a single file from a Rust crate's source
+    #[tool(description = "Get the content of one file from a Rust crate's source, with line numbers, without searching. \
+                          `file_path` is relative to the crate root (e.g. `src/lib.rs`); the version is resolved \
+                          like get_rust_crate_source and the extraction cache is reused.")]
+    async fn get_crate_file(
+        &self,
+        Parameters(GetCrateFileParams { crate_name, version, file_path }): Parameters<GetCrateFileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Reading '{}' from Rust crate '{}' version: {:?}", file_path, crate_name, version);
+
+        let search = Self::rust_crate_search(&crate_name, version.as_deref(), None, None)?;
+        let file = search
+            .read_file(std::path::Path::new(&file_path))
+            .await
+            .map_err(|e| {
+                let data = Some(serde_json::json!({
+                    "crate_name": crate_name,
+                    "file_path": file_path,
+                    "error": e.to_string()
+                }));
+                match e {
+                    crate::eg::EgError::FileNotFound { .. } | crate::eg::EgError::InvalidFilePath(_) => {
+                        McpError::invalid_params(e.to_string(), data)
+                    }
+                    _ => McpError::internal_error(format!("Failed to get Rust crate source: {}", e), data),
+                }
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{} v{}: {}\n\n{}",
+            crate_name,
+            file.version,
+            file.file_path.display(),
+            number_lines(&file.content)
+        ))]))
     }
 
-    #[tool(
-        description = "Delete the current taskspace. This will remove the taskspace directory, \
-                       close associated VSCode windows, and clean up git worktrees."
-    )]
-    async fn delete_taskspace(&self) -> Result<CallToolResult, McpError> {
-        info!("Deleting current taskspace");
+    /// List the current project's crate dependencies from its lockfile
+    #[tool(description = "List the Rust crates the current project depends on, with the versions its Cargo.lock resolves them to. \
+                          Each entry says whether the dependency is direct or transitive; set `direct_only` to skip transitive ones. \
+                          Use this to decide which crate to look at with get_rust_crate_source.")]
+    async fn get_crate_dependencies(
+        &self,
+        Parameters(GetCrateDependenciesParams { direct_only }): Parameters<GetCrateDependenciesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Listing crate dependencies (direct only: {})", direct_only);
 
-        // Send delete_taskspace message to Symposium app via daemon
-        match self.ipc.delete_taskspace().await {
-            Ok(()) => {
-                info!("Taskspace deletion initiated");
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            McpError::internal_error(
+                "Failed to determine workspace root",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
 
-                Ok(CallToolResult::success(vec![Content::text(
-                    "Taskspace deletion initiated successfully".to_string(),
-                )]))
-            }
-            Err(e) => {
-                error!("Failed to delete taskspace: {}", e);
+        // `cargo metadata` runs as a blocking subprocess
+        let dependencies = tokio::task::spawn_blocking(move || {
+            crate::eg::rust::VersionResolver::new().project_dependencies(&root, direct_only)
+        })
+        .await
+        .map_err(|e| {
+            McpError::internal_error(
+                "Task execution failed",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
 
-                Err(McpError::internal_error(
-                    "Failed to delete taskspace",
-                    Some(serde_json::json!({
-                        "error": e.to_string()
-                    })),
-                ))
+        match dependencies {
+            Ok(dependencies) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&dependencies).unwrap(),
+            )])),
+            // Not a failure of the tool: tell the agent what to do instead
+            Err(e @ crate::eg::EgError::NoLockfile(_)) => {
+                Ok(CallToolResult::success(vec![Content::text(e.to_string())]))
             }
+            Err(e) => Err(McpError::internal_error(
+                format!("Failed to list crate dependencies: {}", e),
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )),
         }
     }
 
-    /// Get Rust crate source with optional pattern search
-    #[tool(description = "Get Rust crate source with optional pattern search. Always returns the source path, and optionally performs pattern matching if a search pattern is provided.")]
-    async fn get_rust_crate_source(
+    /// Draw the dependencies between the current workspace's crates
+    #[tool(description = "Render the dependencies between the crates of the current Cargo workspace as a mermaid flowchart. \
+                          Only edges between workspace members are shown unless `include_external` is set. \
+                          Dev-dependencies are dashed. The result can be pasted as-is into a walkthrough's mermaid block.")]
+    async fn workspace_graph(
         &self,
-        Parameters(GetRustCrateSourceParams { crate_name, version, pattern }): Parameters<GetRustCrateSourceParams>,
+        Parameters(WorkspaceGraphParams { include_external }): Parameters<WorkspaceGraphParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Getting Rust crate source for '{}' version: {:?} pattern: {:?}", crate_name, version, pattern);
+        debug!("Rendering workspace graph (include external: {})", include_external);
+
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            McpError::internal_error(
+                "Failed to determine workspace root",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        // `cargo metadata` runs as a blocking subprocess
+        let graph = tokio::task::spawn_blocking(move || {
+            crate::eg::rust::workspace_graph(&root, include_external)
+        })
+        .await
+        .map_err(|e| {
+            McpError::internal_error(
+                "Task execution failed",
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?
+        .map_err(|e| {
+            McpError::internal_error(
+                format!("Failed to read the workspace manifests: {}", e),
+                Some(serde_json::json!({
+                    "error": e.to_string()
+                })),
+            )
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(graph)]))
+    }
+}
+
+impl SymposiumServer {
+    /// Build a crate search, reporting an invalid pattern or context size as invalid params
+    fn rust_crate_search(
+        crate_name: &str,
+        version: Option<&str>,
+        pattern: Option<&str>,
+        context_lines: Option<usize>,
+    ) -> Result<crate::eg::rust::RustCrateSearch, McpError> {
+        let mut search = Eg::rust_crate(crate_name);
+
+        if let Some(context_lines) = context_lines {
+            if context_lines > crate::eg::rust::MAX_CONTEXT_LINES {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "context_lines must be at most {} (got {})",
+                        crate::eg::rust::MAX_CONTEXT_LINES,
+                        context_lines
+                    ),
+                    None,
+                ));
+            }
+            search = search.context_lines(context_lines);
+        }
 
-        let has_pattern = pattern.is_some();
-        let mut search = Eg::rust_crate(&crate_name);
-        
         // Use version resolver for semver range support and project detection
         if let Some(version_spec) = version {
-            search = search.version(&version_spec);
+            search = search.version(version_spec);
         }
-        
+
         if let Some(pattern) = pattern {
-            search = search.pattern(&pattern).map_err(|e| {
+            search = search.pattern(pattern).map_err(|e| {
                 let error_msg = format!("Invalid regex pattern: {}", e);
                 McpError::invalid_params(error_msg, None)
             })?;
         }
 
-        match search.search().await {
-            Ok(result) => {
-                let mut response = serde_json::json!({
-                    "crate_name": crate_name,
-                    "version": result.version,
-                    "checkout_path": result.checkout_path.to_string_lossy(),
-                    "message": format!("Crate {} v{} extracted to {}", 
-                                     crate_name, result.version, result.checkout_path.display())
-                });
-                
-                // Only include match results if a pattern was provided
-                if has_pattern {
-                    // Convert to new response format with context strings
-                    let example_matches: Vec<_> = result.example_matches.into_iter().map(|m| {
-                        let mut context_lines = m.context_before.clone();
-                        context_lines.push(m.line_content.clone());
-                        context_lines.extend(m.context_after.clone());
-                        
-                        let context_start_line = m.line_number.saturating_sub(m.context_before.len() as u32);
-                        let context_end_line = m.line_number + m.context_after.len() as u32;
-                        
-                        serde_json::json!({
-                            "file_path": m.file_path,
-                            "line_number": m.line_number,
-                            "context_start_line": context_start_line,
-                            "context_end_line": context_end_line,
-                            "context": context_lines.join("\n")
-                        })
-                    }).collect();
-                    
-                    let other_matches: Vec<_> = result.other_matches.into_iter().map(|m| {
-                        let mut context_lines = m.context_before.clone();
-                        context_lines.push(m.line_content.clone());
-                        context_lines.extend(m.context_after.clone());
-                        
-                        let context_start_line = m.line_number.saturating_sub(m.context_before.len() as u32);
-                        let context_end_line = m.line_number + m.context_after.len() as u32;
-                        
-                        serde_json::json!({
-                            "file_path": m.file_path,
-                            "line_number": m.line_number,
-                            "context_start_line": context_start_line,
-                            "context_end_line": context_end_line,
-                            "context": context_lines.join("\n")
-                        })
-                    }).collect();
-                    
-                    response["example_matches"] = serde_json::to_value(example_matches).unwrap();
-                    response["other_matches"] = serde_json::to_value(other_matches).unwrap();
-                }
-                
-                Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&response).unwrap())]))
+        Ok(search)
+    }
+
+    /// Convert a crate search result into the get_rust_crate_source response
+    fn crate_source_response(
+        crate_name: &str,
+        result: crate::eg::SearchResult,
+        has_pattern: bool,
+        relative_paths: bool,
+    ) -> serde_json::Value {
+        // Match paths are relative to the checkout; join them back on request
+        let checkout_path = result.checkout_path.clone();
+        let file_path = |path: std::path::PathBuf| {
+            if relative_paths { path } else { checkout_path.join(path) }
+        };
+
+        let mut response = serde_json::json!({
+            "crate_name": crate_name,
+            "version": result.version,
+            "checkout_path": result.checkout_path.to_string_lossy(),
+            "message": format!("Crate {} v{} extracted to {}", 
+                             crate_name, result.version, result.checkout_path.display())
+        });
+        
+        // Only include match results if a pattern was provided
+        if has_pattern {
+            // Convert to new response format with context strings
+            let example_matches: Vec<_> = result.example_matches.into_iter().map(|m| {
+                let path = file_path(m.file_path.clone());
+                Self::match_response(m, path)
+            }).collect();
+
+            let other_matches: Vec<_> = result.other_matches.into_iter().map(|m| {
+                let path = file_path(m.file_path.clone());
+                Self::match_response(m, path)
+            }).collect();
+            
+            response["example_matches"] = serde_json::to_value(example_matches).unwrap();
+            response["other_matches"] = serde_json::to_value(other_matches).unwrap();
+            response["total_match_count"] = serde_json::json!(result.total_match_count);
+        }
+
+        response
+    }
+
+    /// Send a walkthrough to VSCode in chunks while it is still being parsed, so the
+    /// IDE can show the first blocks before slow Dialect lookups further down finish
+    async fn present_walkthrough_chunked(
+        &self,
+        parser: &mut crate::walkthrough_parser::WalkthroughParser<IPCCommunicator>,
+        content: &str,
+        base_uri: String,
+    ) -> Result<(), McpError> {
+        let ipc_error = |e: crate::ipc::IPCError| {
+            McpError::internal_error(
+                "Failed to present walkthrough",
+                Some(serde_json::json!({"error": e.to_string()})),
+            )
+        };
+
+        let mut stream = self.ipc.begin_walkthrough(base_uri).await.map_err(ipc_error)?;
+
+        // Parse and send concurrently; chunks wait in the channel while one is in flight
+        let (chunk_tx, mut chunk_rx) =
+            tokio::sync::mpsc::channel(crate::constants::WALKTHROUGH_CHUNK_QUEUE);
+        let send_chunks = async {
+            while let Some(chunk) = chunk_rx.recv().await {
+                stream.send_chunk(chunk).await?;
+            }
+            Ok::<(), crate::ipc::IPCError>(())
+        };
+        let (parsed, sent) = tokio::join!(
+            parser.parse_and_normalize_chunked(content, self.walkthrough_chunk_bytes, chunk_tx),
+            send_chunks,
+        );
+
+        // A failed send also fails the parse (its receiver is gone), so report the send
+        sent.map_err(ipc_error)?;
+        match parsed {
+            Ok(()) => {
+                let chunk_count = stream.finish(None).await.map_err(ipc_error)?;
+                debug!("Walkthrough sent in {} chunks", chunk_count);
+                Ok(())
             }
             Err(e) => {
-                let error_msg = format!("Failed to get Rust crate source: {}", e);
+                if let Err(end_error) = stream.finish(Some(e.to_string())).await {
+                    warn!("Failed to end incomplete walkthrough: {}", end_error);
+                }
                 Err(McpError::internal_error(
-                    error_msg,
-                    Some(serde_json::json!({
-                        "crate_name": crate_name,
-                        "error": e.to_string()
-                    })),
+                    "Failed to parse walkthrough markdown",
+                    Some(serde_json::json!({"error": e.to_string()})),
                 ))
             }
         }
     }
-}
 
-impl SymposiumServer {
+    /// A search match as reported by `get_rust_crate_source` and `search_workspace`:
+    /// the matching line with its context joined into one string
+    fn match_response(m: crate::eg::Match, file_path: std::path::PathBuf) -> serde_json::Value {
+        let context_start_line = m.line_number.saturating_sub(m.context_before.len() as u32);
+        let context_end_line = m.line_number + m.context_after.len() as u32;
+
+        let mut context_lines = m.context_before;
+        context_lines.push(m.line_content);
+        context_lines.extend(m.context_after);
+
+        serde_json::json!({
+            "file_path": file_path,
+            "line_number": m.line_number,
+            "context_start_line": context_start_line,
+            "context_end_line": context_end_line,
+            "context": context_lines.join("\n")
+        })
+    }
+
     fn parse_yaml_metadata(content: &str) -> (Option<String>, Option<String>) {
         if !content.starts_with("---\n") {
             return (None, None);
@@ -829,12 +2773,178 @@ impl SymposiumServer {
         }
     }
 
+    /// Built-in `sparkle` plus every guidance file whose front matter says `persona: true`
+    fn known_collaborators() -> Vec<Collaborator> {
+        let mut collaborators = vec![Collaborator {
+            name: "sparkle".to_string(),
+            description: "Sparkle identity, loaded with the `embody_sparkle` tool (default)".to_string(),
+        }];
+
+        let guidance = GuidanceLayers::for_current_workspace();
+        for file_path in guidance.file_names() {
+            let Some(name) = file_path.strip_suffix(".md").filter(|name| !name.contains('/')) else {
+                continue;
+            };
+            let Some(data) = guidance.get(&file_path) else {
+                continue;
+            };
+            let content = String::from_utf8_lossy(&data);
+            if !Self::is_persona(&content) {
+                continue;
+            }
+            let (_, description) = Self::parse_yaml_metadata(&content);
+            collaborators.push(Collaborator {
+                name: name.to_string(),
+                description: description.unwrap_or_default(),
+            });
+        }
+
+        collaborators
+    }
+
+    /// Whether a guidance file's front matter marks it as a collaborator persona
+    fn is_persona(content: &str) -> bool {
+        let Some(front_matter) = content
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("\n---\n"))
+            .map(|(front_matter, _)| front_matter)
+        else {
+            return false;
+        };
+        front_matter
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .any(|(key, value)| key.trim() == "persona" && value.trim() == "true")
+    }
+
+    /// Warning to include in a tool result when `collaborator` is not a known persona
+    fn collaborator_warning(collaborator: Option<&str>) -> Option<String> {
+        let collaborator = collaborator?;
+        let known = Self::known_collaborators();
+        if known.iter().any(|c| c.name == collaborator) {
+            return None;
+        }
+
+        warn!("Unknown collaborator {collaborator:?}");
+        let names: Vec<_> = known.iter().map(|c| format!("`{}`", c.name)).collect();
+        Some(format!(
+            "Warning: unknown collaborator `{}`; the default collaboration style will be used. \
+             Known collaborators: {} (see `list_collaborators`).",
+            collaborator,
+            names.join(", ")
+        ))
+    }
+
+    /// The initial prompt for `spawn_taskspace`: given inline, or read from a file
+    /// that must lie inside the workspace
+    fn resolve_initial_prompt(
+        inline: Option<String>,
+        path: Option<&str>,
+    ) -> Result<String, McpError> {
+        let path = match (inline, path) {
+            (Some(prompt), None) => return Ok(prompt),
+            (None, Some(path)) => path,
+            (inline, path) => {
+                return Err(McpError::invalid_params(
+                    "Provide exactly one of `initial_prompt` or `initial_prompt_path`",
+                    Some(serde_json::json!({
+                        "initial_prompt": inline.is_some(),
+                        "initial_prompt_path": path,
+                    })),
+                ));
+            }
+        };
+
+        let root = crate::workspace_dir::workspace_root().map_err(|e| {
+            McpError::internal_error(
+                "Failed to determine workspace root",
+                Some(serde_json::json!({"error": e.to_string()})),
+            )
+        })?;
+
+        // Canonicalizing resolves `..` and symlinks, so anything that ends up
+        // outside the root was an attempt to escape it
+        let full_path = root.join(path).canonicalize().map_err(|e| {
+            McpError::invalid_params(
+                "Failed to read initial prompt file",
+                Some(serde_json::json!({"path": path, "error": e.to_string()})),
+            )
+        })?;
+        if !full_path.starts_with(&root) {
+            return Err(McpError::invalid_params(
+                "initial_prompt_path must be inside the workspace",
+                Some(serde_json::json!({"path": path})),
+            ));
+        }
+
+        std::fs::read_to_string(&full_path).map_err(|e| {
+            McpError::invalid_params(
+                "Failed to read initial prompt file",
+                Some(serde_json::json!({"path": path, "error": e.to_string()})),
+            )
+        })
+    }
+
+    /// Why a non-forced `delete_taskspace` must not proceed, if it must not.
+    ///
+    /// The extension's answer to `prepare_for_deletion` decides; without one
+    /// (e.g., an older extension) any locally detected uncommitted file blocks.
+    fn deletion_blocker(
+        uncommitted_file_count: usize,
+        response: Option<crate::types::PrepareForDeletionResponse>,
+    ) -> Option<String> {
+        let response = match response {
+            Some(response) if response.safe_to_delete => return None,
+            Some(response) => response,
+            None if uncommitted_file_count == 0 => return None,
+            None => crate::types::PrepareForDeletionResponse {
+                safe_to_delete: false,
+                warnings: vec![],
+                uncommitted_file_count,
+            },
+        };
+
+        let mut message = format!(
+            "Taskspace was NOT deleted. It has {} file(s) with uncommitted changes.\n",
+            response.uncommitted_file_count
+        );
+        for warning in &response.warnings {
+            message.push_str(&format!("- {}\n", warning));
+        }
+        message.push_str(
+            "Confirm with the user before deleting this taskspace; \
+             call `delete_taskspace` with `force: true` once they agree.",
+        );
+        Some(message)
+    }
+
+    /// Every guidance document with the title and description from its front matter
+    fn guidance_entries(guidance: &GuidanceLayers) -> Vec<GuidanceEntry> {
+        guidance
+            .file_names()
+            .into_iter()
+            .filter_map(|id| {
+                let data = guidance.get(&id)?;
+                let (title, description) = Self::parse_yaml_metadata(&String::from_utf8_lossy(&data));
+                Some(GuidanceEntry {
+                    title: title.unwrap_or_else(|| id.clone()),
+                    id,
+                    description,
+                })
+            })
+            .collect()
+    }
+
     fn generate_resources() -> Vec<Resource> {
+        Self::generate_resources_from(&GuidanceLayers::for_current_workspace())
+    }
+
+    fn generate_resources_from(guidance: &GuidanceLayers) -> Vec<Resource> {
         let mut resources = Vec::new();
 
-        for file_path in GuidanceFiles::iter() {
-            if let Some(file) = GuidanceFiles::get(&file_path) {
-                let content = String::from_utf8_lossy(&file.data);
+        for file_path in guidance.file_names() {
+            if let Some(data) = guidance.get(&file_path) {
+                let content = String::from_utf8_lossy(&data);
                 let (name, description) = Self::parse_yaml_metadata(&content);
 
                 resources.push(Resource {
@@ -843,7 +2953,7 @@ impl SymposiumServer {
                         name: name.unwrap_or_else(|| file_path.to_string()),
                         description,
                         mime_type: Some("text/markdown".into()),
-                        size: Some(file.data.len() as u32),
+                        size: Some(data.len() as u32),
                         icons: None,
                         title: None,
                     },
@@ -855,7 +2965,11 @@ impl SymposiumServer {
         resources
     }
 
-    async fn assemble_yiasou_prompt(&self, collaborator: Option<String>) -> Result<String, McpError> {
+    async fn assemble_yiasou_prompt(
+        &self,
+        collaborator: Option<String>,
+        agent: Option<String>,
+    ) -> Result<String, McpError> {
         let mut prompt = String::default();
 
         prompt.push_str(indoc! {
@@ -869,6 +2983,11 @@ impl SymposiumServer {
         self.push_context(&mut prompt, "walkthrough-format.md");
         self.push_context(&mut prompt, "coding-guidelines.md");
         self.push_context(&mut prompt, "mcp-tool-usage-suggestions.md");
+        self.push_agent_guidance(&mut prompt, agent);
+        match crate::workspace_dir::workspace_root() {
+            Ok(root) => self.push_project_onboarding(&mut prompt, &root),
+            Err(e) => warn!("Failed to determine workspace root: {}", e),
+        }
         if let Some(taskspace) = &taskspace {
             self.push_taskspace_context(&mut prompt, taskspace);
             self.push_collaboration_patterns(&mut prompt, collaborator, taskspace.collaborator.as_deref());
@@ -894,16 +3013,48 @@ impl SymposiumServer {
     }
 
     fn push_context(&self, prompt: &mut String, file_name: &str) {
-        let Some(file) = GuidanceFiles::get(&file_name) else {
+        let Some(data) = GuidanceLayers::for_current_workspace().get(file_name) else {
             panic!("no context file named {file_name}");
         };
 
-        let content = String::from_utf8_lossy(&file.data);
+        let content = String::from_utf8_lossy(&data);
+        prompt.push_str("<context>");
+        prompt.push_str(&content);
+        prompt.push_str("</context>");
+    }
+
+    /// Include the project's own onboarding notes (`.symposium/onboarding.md`), if it has any.
+    fn push_project_onboarding(&self, prompt: &mut String, workspace_root: &std::path::Path) {
+        let path = workspace_root.join(crate::constants::PROJECT_ONBOARDING_FILE);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return;
+        };
+
+        debug!("Including project onboarding from {}", path.display());
         prompt.push_str("<context>");
+        prompt.push_str("# Project Onboarding\n\n");
         prompt.push_str(&content);
         prompt.push_str("</context>");
     }
 
+    /// Include guidance specific to the agent CLI, if we know which one is running.
+    ///
+    /// An explicit `agent` (from the prompt arguments) takes precedence over the environment.
+    fn push_agent_guidance(&self, prompt: &mut String, agent: Option<String>) {
+        let agent = match agent {
+            Some(name) => AgentCli::from_name(&name),
+            None => AgentCli::detect_from_env(),
+        };
+
+        match agent {
+            Some(agent) => {
+                debug!("Including guidance for agent {agent:?}");
+                self.push_context(prompt, agent.guidance_file());
+            }
+            None => debug!("Unknown agent CLI, using generic guidance only"),
+        }
+    }
+
     fn push_taskspace_context(&self, prompt: &mut String, taskspace: &TaskspaceStateResponse) {
         prompt.push_str(&indoc::formatdoc! {
             "
@@ -949,7 +3100,11 @@ impl SymposiumServer {
         collaborator: Option<String>,
         taskspace_collaborator: Option<&str>,
     ) {
-        let collaborator = collaborator.as_deref().or(taskspace_collaborator).unwrap_or("sparkle");
+        let collaborator = collaborator
+            .as_deref()
+            .or(taskspace_collaborator)
+            .or(self.project_config.default_collaborator.as_deref())
+            .unwrap_or("sparkle");
 
         match collaborator {
             "sparkle" => {
@@ -963,16 +3118,32 @@ impl SymposiumServer {
                 });
             }
 
-            "socrates" => {
-                self.push_context(prompt, "socrates.md");
+            // Any other collaborator is a persona file in the guidance folder
+            // (e.g., `socrates` loads `socrates.md`)
+            _ => {
+                let file_name = format!("{collaborator}.md");
+                let is_persona = GuidanceLayers::for_current_workspace()
+                    .get(&file_name)
+                    .is_some_and(|data| Self::is_persona(&String::from_utf8_lossy(&data)));
+                if is_persona {
+                    self.push_context(prompt, &file_name);
+                } else {
+                    warn!("No persona file found for collaborator {collaborator:?}");
+                    prompt.push_str(&indoc::formatdoc! {
+                        "
+                        <note>
+                            No persona definition was found for collaborator `{collaborator}`
+                            (expected a guidance file named `{file_name}`).
+                            Proceed with your default collaboration style.
+                        </note>
+                        "
+                    });
+                }
             }
-
-            _ => {}
         }
     }
 }
 
-#[tool_handler]
 #[prompt_handler]
 impl ServerHandler for SymposiumServer {
     fn get_info(&self) -> ServerInfo {
@@ -989,14 +3160,19 @@ impl ServerHandler for SymposiumServer {
             instructions: Some(
                 "This server provides tools for AI assistants to perform IDE operations and display walkthroughs in VSCode. \
                 Use 'get_selection' to retrieve currently selected text from the active editor, \
+                'list_open_editors' to see which files the user has open, \
                 'ide_operation' to execute IDE operations like finding symbol definitions and references using Dialect function calls, \
                 'present_walkthrough' to display structured code walkthroughs with interactive elements, \
+                'validate_walkthrough' to check a walkthrough's references before presenting it, \
                 'request_review' to create synthetic pull requests from Git commit ranges with AI insight comments, \
                 'update_review' to manage review workflows and wait for user feedback, \
                 'get_review_status' to check the current synthetic PR status, \
+                'get_review_comments' to read the comment threads for a commit range, \
                 'spawn_taskspace' to create new taskspaces for collaborative work, \
                 'log_progress' to report agent progress with visual indicators, \
                 'signal_user' to request user attention when assistance is needed, \
+                'list_guidance' to see which guidance documents you can load with 'expand_reference', \
+                'get_taskspace_info' to find out which taskspace you are in and where it lives on disk, \
                 and 'update_taskspace' to update taskspace names and descriptions."
                     .to_string(),
             ),
@@ -1012,6 +3188,52 @@ impl ServerHandler for SymposiumServer {
         Ok(self.get_info())
     }
 
+    /// Dispatch to the tool router, recording invocation metrics along the way.
+    /// (This is what `#[tool_handler]` would generate, plus the metrics.)
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool_name = request.name.to_string();
+        let tcc = ToolCallContext::new(self, request, context);
+        let result = self.tool_router.call(tcc).await;
+
+        let success = matches!(&result, Ok(r) if r.is_error != Some(true));
+        crate::metrics::record_tool_call(&tool_name, success);
+
+        // Only our own tool names are logged, never names sent by the client for unknown tools
+        if let Some(usage_log) = &self.usage_log
+            && self.tool_router.list_all().iter().any(|tool| tool.name == tool_name)
+        {
+            // File IO and waiting on other servers' locks stay off the async runtime
+            let usage_log = usage_log.clone();
+            let tool_name = tool_name.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = usage_log.record_tool_call(&tool_name, success) {
+                    warn!("Failed to update usage log: {}", e);
+                }
+            });
+        }
+
+        // Expanding a reference must return the full content, or large results could never be read
+        match result {
+            Ok(result) if tool_name != "expand_reference" => Ok(self.limit_result_size(result).await),
+            result => result,
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            tools: self.tool_router.list_all(),
+            next_cursor: None,
+        })
+    }
+
     async fn list_resources(
         &self,
         _request: Option<PaginatedRequestParam>,
@@ -1030,11 +3252,11 @@ impl ServerHandler for SymposiumServer {
         request: ReadResourceRequestParam,
         _context: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, McpError> {
-        let content = GuidanceFiles::get(&request.uri)
+        let content = GuidanceLayers::for_current_workspace()
+            .get(&request.uri)
             .ok_or_else(|| {
                 McpError::resource_not_found(format!("Resource not found: {}", request.uri), None)
             })?
-            .data
             .into_owned();
 
         let content_str = String::from_utf8(content).map_err(|_| {
@@ -1053,6 +3275,8 @@ impl ServerHandler for SymposiumServer {
 #[serde(rename_all = "camelCase")]
 struct CollaboratorPromptParams {
     collaborator: Option<String>,
+    /// Agent CLI in use (e.g., "q", "claude-code"); detected from the environment if omitted
+    agent: Option<String>,
 }
 
 #[prompt_router]
@@ -1063,9 +3287,9 @@ impl SymposiumServer {
     )]
     async fn yiasou_prompt(
         &self,
-        Parameters(CollaboratorPromptParams { collaborator }): Parameters<CollaboratorPromptParams>,
+        Parameters(CollaboratorPromptParams { collaborator, agent }): Parameters<CollaboratorPromptParams>,
     ) -> Result<GetPromptResult, McpError> {
-        let content = self.assemble_yiasou_prompt(collaborator).await?;
+        let content = self.assemble_yiasou_prompt(collaborator, agent).await?;
         Ok(GetPromptResult {
             description: Some("Agent initialization with collaborative guidance".to_string()),
             messages: vec![PromptMessage::new_text(PromptMessageRole::User, content)],
@@ -1117,6 +3341,87 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_present_walkthrough_chunked() {
+        let mut server = SymposiumServer::new_test();
+        server.walkthrough_chunk_bytes = 1;
+
+        let params = PresentWalkthroughParams {
+            content: "# Test\n\nFirst\n\n```mermaid\ngraph TD\n  A --> B\n```\n\nLast\n".to_string(),
+            base_uri: ".".to_string(),
+        };
+        let result = server.present_walkthrough(Parameters(params)).await;
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn test_list_open_editors_without_ide() {
+        let server = SymposiumServer::new_test();
+        let result = server.list_open_editors().await.unwrap();
+        assert_eq!(result.content[0].as_text().unwrap().text, "[]");
+    }
+
+    #[tokio::test]
+    async fn test_set_selection_rejects_bad_input() {
+        let server = SymposiumServer::new_test();
+        let params = |path: &str, start_line, end_line| SetSelectionParams {
+            path: path.to_string(),
+            start_line,
+            start_col: 1,
+            end_line,
+            end_col: 1,
+        };
+
+        let error = server.set_selection(Parameters(params("no/such/file.rs", 1, 2))).await.unwrap_err();
+        assert!(error.message.contains("No such file"), "{}", error.message);
+
+        let error = server.set_selection(Parameters(params("../outside.rs", 1, 2))).await.unwrap_err();
+        assert!(error.message.contains("No such file"), "{}", error.message);
+
+        let error = server.set_selection(Parameters(params("no/such/file.rs", 3, 2))).await.unwrap_err();
+        assert!(error.message.contains("starts after it ends"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_export_walkthrough() {
+        let server = SymposiumServer::new_test();
+
+        let params = ExportWalkthroughParams {
+            content: "# Login flow\n\nTokens are checked first.\n\n```mermaid\ngraph TD\n  A --> B\n```\n"
+                .to_string(),
+            base_uri: ".".to_string(),
+            title: Some("Login flow".to_string()),
+        };
+        let result = server.export_walkthrough(Parameters(params)).await.unwrap();
+        let html = result.content[0].as_text().unwrap().text.clone();
+
+        assert!(html.starts_with("<!DOCTYPE html>\n<html"), "{html}");
+        assert!(html.contains("<title>Login flow</title>"));
+        assert!(html.contains("<h1>Login flow</h1>"));
+        assert!(html.contains("<mermaid>"));
+        assert!(html.contains("mermaid.min.js"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_walkthrough() {
+        let server = SymposiumServer::new_test();
+
+        let params = ValidateWalkthroughParams {
+            content: "# Login flow\n\n```comment\nlocation: unknownFunction(`User`)\n\nBroken\n```\n"
+                .to_string(),
+            base_uri: ".".to_string(),
+        };
+        let result = server.validate_walkthrough(Parameters(params)).await.unwrap();
+        let report: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        assert_eq!(report["valid"], false);
+        assert_eq!(report["element_counts"]["comment"], 1);
+        assert_eq!(report["errors"][0]["expression"], "unknownFunction(`User`)");
+        assert_eq!(report["resolved"].as_array().unwrap().len(), 0);
+    }
+
     #[test]
     fn test_resource_definitions() {
         // Test that we can create the resource definitions correctly
@@ -1177,133 +3482,713 @@ mod tests {
         let socrates_str = String::from_utf8(socrates_content.data.into_owned()).unwrap();
         assert!(socrates_str.contains("Mindful Collaboration Patterns"));
 
-        let walkthrough_content = GuidanceFiles::get("walkthrough-format.md").unwrap();
-        let walkthrough_str = String::from_utf8(walkthrough_content.data.into_owned()).unwrap();
-        assert!(walkthrough_str.contains("Walkthrough Format"));
+        let walkthrough_content = GuidanceFiles::get("walkthrough-format.md").unwrap();
+        let walkthrough_str = String::from_utf8(walkthrough_content.data.into_owned()).unwrap();
+        assert!(walkthrough_str.contains("Walkthrough Format"));
+
+        let coding_content = GuidanceFiles::get("coding-guidelines.md").unwrap();
+        let coding_str = String::from_utf8(coding_content.data.into_owned()).unwrap();
+        assert!(coding_str.contains("Coding Guidelines"));
+    }
+
+    #[test]
+    fn test_resource_contents_creation() {
+        // Test that we can create ResourceContents correctly
+        let content = ResourceContents::text("Hello world", "test.md");
+
+        match content {
+            ResourceContents::TextResourceContents {
+                uri,
+                text,
+                mime_type,
+                ..
+            } => {
+                assert_eq!(uri, "test.md");
+                assert_eq!(text, "Hello world");
+                assert_eq!(mime_type, Some("text".to_string()));
+            }
+            _ => panic!("Expected TextResourceContents"),
+        }
+    }
+
+    #[test]
+    fn test_yaml_metadata_parsing() {
+        let content_with_yaml = r#"---
+name: "Test Resource"
+description: "A test resource for testing"
+---
+
+# Test Content
+
+This is test content."#;
+
+        let (name, description) = SymposiumServer::parse_yaml_metadata(content_with_yaml);
+        assert_eq!(name, Some("Test Resource".to_string()));
+        assert_eq!(description, Some("A test resource for testing".to_string()));
+
+        // Test content without YAML
+        let content_without_yaml = "# Just a heading\n\nSome content.";
+        let (name, description) = SymposiumServer::parse_yaml_metadata(content_without_yaml);
+        assert_eq!(name, None);
+        assert_eq!(description, None);
+    }
+
+    #[test]
+    fn test_list_resources_output() {
+        // Test the actual resource generation logic used by list_resources
+        let resources = SymposiumServer::generate_resources();
+
+        // Verify we have resources for all guidance files
+        let expected_count = GuidanceFiles::iter().count();
+        assert_eq!(resources.len(), expected_count);
+
+        // Check that all files have proper metadata
+        let socrates_resource = resources.iter().find(|r| r.raw.uri == "socrates.md").unwrap();
+        assert_eq!(socrates_resource.raw.name, "Collaboration Patterns");
+        assert_eq!(
+            socrates_resource.raw.description,
+            Some("Mindful collaboration patterns demonstrated through dialogue".to_string())
+        );
+        assert!(socrates_resource.raw.size.unwrap() > 0);
+
+        let walkthrough_resource = resources
+            .iter()
+            .find(|r| r.raw.uri == "walkthrough-format.md")
+            .unwrap();
+        assert_eq!(walkthrough_resource.raw.name, "Walkthrough Format");
+        assert_eq!(
+            walkthrough_resource.raw.description,
+            Some(
+                "Specification for creating interactive code walkthroughs with code block elements"
+                    .to_string()
+            )
+        );
+
+        let coding_resource = resources
+            .iter()
+            .find(|r| r.raw.uri == "coding-guidelines.md")
+            .unwrap();
+        assert_eq!(coding_resource.raw.name, "Coding Guidelines");
+        assert_eq!(
+            coding_resource.raw.description,
+            Some(
+                "Development best practices and standards for the Symposium project"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_guidance_entries() {
+        let entries = SymposiumServer::guidance_entries(&GuidanceLayers::embedded());
+        assert_eq!(entries.len(), GuidanceFiles::iter().count());
+
+        let walkthrough = entries.iter().find(|e| e.id == "walkthrough-format.md").unwrap();
+        assert_eq!(walkthrough.title, "Walkthrough Format");
+        assert!(walkthrough.description.is_some());
+
+        // Project documents are listed too, titled by their id without front matter
+        let workspace = tempfile::tempdir().unwrap();
+        let guidance_dir = workspace.path().join(crate::constants::PROJECT_GUIDANCE_DIR);
+        std::fs::create_dir_all(&guidance_dir).unwrap();
+        std::fs::write(guidance_dir.join("team.md"), "# Team conventions\n").unwrap();
+
+        let entries = SymposiumServer::guidance_entries(&GuidanceLayers::for_workspace(workspace.path()));
+        let team = entries.iter().find(|e| e.id == "team.md").unwrap();
+        assert_eq!(team.title, "team.md");
+        assert_eq!(team.description, None);
+    }
+
+    #[test]
+    fn test_layered_guidance() {
+        let workspace = tempfile::tempdir().unwrap();
+        let guidance_dir = workspace.path().join(crate::constants::PROJECT_GUIDANCE_DIR);
+        std::fs::create_dir_all(guidance_dir.join("agents")).unwrap();
+        let socrates = "---\nname: \"Team Socrates\"\ndescription: \"Our own take\"\n---\nAsk first.\n";
+        std::fs::write(guidance_dir.join("socrates.md"), socrates).unwrap();
+        std::fs::write(guidance_dir.join("team-conventions.md"), "# Conventions\n").unwrap();
+        std::fs::write(guidance_dir.join("agents/custom.md"), "# Custom agent\n").unwrap();
+        std::fs::write(guidance_dir.join("notes.txt"), "not guidance").unwrap();
+
+        let guidance = GuidanceLayers::for_workspace(workspace.path());
+
+        // Project files win over embedded ones, which remain the fallback
+        assert_eq!(&*guidance.get("socrates.md").unwrap(), socrates.as_bytes());
+        assert_eq!(
+            guidance.get("coding-guidelines.md").unwrap(),
+            GuidanceFiles::get("coding-guidelines.md").unwrap().data
+        );
+        assert!(guidance.get("../config.toml").is_none());
+
+        // Listing merges both layers without duplicates
+        let names = guidance.file_names();
+        let embedded: Vec<String> = GuidanceFiles::iter().map(|name| name.to_string()).collect();
+        assert_eq!(&names[..embedded.len()], &embedded[..]);
+        assert_eq!(&names[embedded.len()..], ["agents/custom.md", "team-conventions.md"]);
+
+        let resources = SymposiumServer::generate_resources_from(&guidance);
+        assert_eq!(resources.len(), embedded.len() + 2);
+        let socrates = resources.iter().find(|r| r.raw.uri == "socrates.md").unwrap();
+        assert_eq!(socrates.raw.name, "Team Socrates");
+        assert_eq!(socrates.raw.description, Some("Our own take".to_string()));
+        let conventions = resources.iter().find(|r| r.raw.uri == "team-conventions.md").unwrap();
+        assert_eq!(conventions.raw.name, "team-conventions.md");
+
+        // Without a project directory, only the embedded guidance is listed
+        let resources = SymposiumServer::generate_resources_from(&GuidanceLayers::embedded());
+        assert_eq!(resources.len(), embedded.len());
+    }
+
+    #[tokio::test]
+    async fn test_yiasou_prompt_generation() {
+        let server = SymposiumServer::new_test();
+
+        let prompt = server.assemble_yiasou_prompt(None, None).await.unwrap();
+
+        // Verify the prompt contains some basic text.
+        assert!(prompt.contains("Hi, welcome!"));
+    }
+
+    #[tokio::test]
+    async fn test_yiasou_prompt_agent_guidance() {
+        let server = SymposiumServer::new_test();
+
+        let prompt = server
+            .assemble_yiasou_prompt(None, Some("q".to_string()))
+            .await
+            .unwrap();
+        assert!(prompt.contains("# Q CLI Tips"));
+        assert!(!prompt.contains("# Claude Code Tips"));
+
+        let prompt = server
+            .assemble_yiasou_prompt(None, Some("claude-code".to_string()))
+            .await
+            .unwrap();
+        assert!(prompt.contains("# Claude Code Tips"));
+        assert!(!prompt.contains("# Q CLI Tips"));
+
+        // Unknown agents get only the generic guidance
+        let prompt = server
+            .assemble_yiasou_prompt(None, Some("emacs".to_string()))
+            .await
+            .unwrap();
+        assert!(!prompt.contains("# Q CLI Tips"));
+        assert!(!prompt.contains("# Claude Code Tips"));
+        assert!(prompt.contains("# MCP Tool Usage Suggestions"));
+    }
+
+    #[tokio::test]
+    async fn test_yiasou_prompt_project_default_collaborator() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::create_dir(workspace.path().join(".symposium")).unwrap();
+        std::fs::write(
+            workspace.path().join(crate::constants::PROJECT_CONFIG_FILE),
+            "default_collaborator = \"socrates\"\n",
+        )
+        .unwrap();
+
+        let mut server = SymposiumServer::new_test();
+        server.project_config = crate::project_config::ProjectConfig::load(workspace.path()).unwrap();
+
+        // The project default replaces sparkle...
+        let prompt = server.assemble_yiasou_prompt(None, None).await.unwrap();
+        assert!(prompt.contains("# Mindful Collaboration Patterns"));
+        assert!(!prompt.contains("embody_sparkle"));
+
+        // ...but an explicit collaborator still wins
+        let prompt = server
+            .assemble_yiasou_prompt(Some("sparkle".to_string()), None)
+            .await
+            .unwrap();
+        assert!(prompt.contains("embody_sparkle"));
+        assert!(!prompt.contains("# Mindful Collaboration Patterns"));
+    }
+
+    #[test]
+    fn test_yiasou_prompt_project_onboarding() {
+        let server = SymposiumServer::new_test();
+        let workspace = tempfile::tempdir().unwrap();
+
+        // Missing file is a no-op
+        let mut prompt = String::new();
+        server.push_project_onboarding(&mut prompt, workspace.path());
+        assert!(prompt.is_empty());
+
+        std::fs::create_dir(workspace.path().join(".symposium")).unwrap();
+        std::fs::write(
+            workspace.path().join(".symposium/onboarding.md"),
+            "Run `just check` before committing.\n",
+        )
+        .unwrap();
+
+        let mut prompt = String::new();
+        server.push_project_onboarding(&mut prompt, workspace.path());
+        assert_eq!(
+            prompt,
+            "<context># Project Onboarding\n\nRun `just check` before committing.\n</context>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collaboration_patterns_persona_files() {
+        let server = SymposiumServer::new_test();
+
+        // Known persona files are spliced in as context
+        let mut prompt = String::new();
+        server.push_collaboration_patterns(&mut prompt, Some("socrates".to_string()), None);
+        assert!(prompt.contains("<context>"));
+        assert!(prompt.contains("# Mindful Collaboration Patterns"));
+
+        // Unknown personas produce a clear note rather than silently doing nothing
+        let mut prompt = String::new();
+        server.push_collaboration_patterns(&mut prompt, Some("hypatia".to_string()), None);
+        assert!(!prompt.contains("<context>"));
+        assert!(prompt.contains("No persona definition was found for collaborator `hypatia`"));
+        assert!(prompt.contains("hypatia.md"));
+    }
+
+    #[tokio::test]
+    async fn test_expand_reference_yiasou() {
+        let server = SymposiumServer::new_test();
+
+        // Test that expand_reference with "yiasou" returns the same content as the stored prompt
+        let params = ExpandReferenceParams {
+            id: "yiasou".to_string(),
+        };
+        let result = server.expand_reference(Parameters(params)).await.unwrap();
+
+        // Should be successful
+        assert!(matches!(
+            result,
+            CallToolResult {
+                is_error: Some(false),
+                ..
+            }
+        ));
+
+        // Should have content
+        assert!(!result.content.is_empty());
+    }
+
+    fn mock_taskspace_state(initial_prompt: Option<&str>) -> crate::types::TaskspaceStateResponse {
+        crate::types::TaskspaceStateResponse {
+            name: Some("Fix login".to_string()),
+            description: Some("Repair the OAuth flow".to_string()),
+            initial_prompt: initial_prompt.map(str::to_string),
+            collaborator: None,
+            tags: vec![],
+        }
+    }
+
+    fn update_taskspace_params() -> UpdateTaskspaceParams {
+        UpdateTaskspaceParams {
+            name: "Fix login".to_string(),
+            description: "Repair the OAuth flow".to_string(),
+            collaborator: None,
+            tags: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_taskspace_initial_prompt_cleared() {
+        let responses = crate::ipc::MockResponses::new();
+        responses.push_ok(crate::types::IPCMessageType::TaskspaceState, mock_taskspace_state(None));
+        let server = SymposiumServer::new_test_with_responses(responses);
+
+        let result = server
+            .update_taskspace(Parameters(update_taskspace_params()))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(text, "Taskspace updated: Fix login - Repair the OAuth flow (initial prompt cleared)");
+    }
+
+    #[tokio::test]
+    async fn test_update_taskspace_initial_prompt_kept() {
+        let responses = crate::ipc::MockResponses::new();
+        responses.push_ok(
+            crate::types::IPCMessageType::TaskspaceState,
+            mock_taskspace_state(Some("Start by reading auth.rs")),
+        );
+        let server = SymposiumServer::new_test_with_responses(responses);
+
+        let result = server
+            .update_taskspace(Parameters(update_taskspace_params()))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(text, "Taskspace updated: Fix login - Repair the OAuth flow");
+    }
+
+    #[tokio::test]
+    async fn test_get_taskspace_info_outside_taskspace() {
+        // The test process does not run inside a `project.symposium/task-$UUID` directory
+        assert!(crate::ipc::extract_project_info().is_err());
+
+        let server = SymposiumServer::new_test();
+        let result = server.get_taskspace_info().await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let info: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(info["in_taskspace"], false);
+        assert!(info["message"].as_str().unwrap().starts_with("Not in a taskspace"));
+        assert!(info.get("uuid").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_collaborators() {
+        let server = SymposiumServer::new_test();
+        let result = server.list_collaborators().await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let collaborators: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let names: Vec<&str> = collaborators
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(names, ["sparkle", "socrates"]);
+        assert!(collaborators[1]["description"].as_str().unwrap().contains("collaboration"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_initialization() {
+        async fn preview(server: &SymposiumServer, collaborator: &str) -> String {
+            let params = PreviewInitializationParams {
+                collaborator: collaborator.to_string(),
+            };
+            let result = server.preview_initialization(Parameters(params)).await.unwrap();
+            result.content[0].as_text().unwrap().text.clone()
+        }
+        let server = SymposiumServer::new_test();
+
+        let sparkle = preview(&server, "sparkle").await;
+        assert!(sparkle.contains("Hi, welcome!"));
+        assert!(sparkle.contains("embody_sparkle"));
 
-        let coding_content = GuidanceFiles::get("coding-guidelines.md").unwrap();
-        let coding_str = String::from_utf8(coding_content.data.into_owned()).unwrap();
-        assert!(coding_str.contains("Coding Guidelines"));
+        let socrates = preview(&server, "socrates").await;
+        assert!(socrates.contains("# Mindful Collaboration Patterns"));
+        assert!(!socrates.contains("embody_sparkle"));
+
+        let unknown = preview(&server, "hypatia").await;
+        assert!(unknown.starts_with("Warning: unknown collaborator `hypatia`"));
+        assert!(unknown.contains("No persona definition was found for collaborator `hypatia`"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_collaborator_warning() {
+        let server = SymposiumServer::new_test();
+        let result = server
+            .spawn_taskspace(Parameters(SpawnTaskspaceParams {
+                name: "Fix login".to_string(),
+                task_description: "Repair the OAuth flow".to_string(),
+                initial_prompt: Some("Start by reading auth.rs".to_string()),
+                initial_prompt_path: None,
+                collaborator: Some("hypatia".to_string()),
+                tags: vec![],
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Taskspace 'Fix login' created successfully"));
+        assert!(text.contains("Warning: unknown collaborator `hypatia`"));
+
+        let responses = crate::ipc::MockResponses::new();
+        responses.push_ok(
+            crate::types::IPCMessageType::TaskspaceState,
+            mock_taskspace_state(None),
+        );
+        let server = SymposiumServer::new_test_with_responses(responses);
+        let result = server
+            .update_taskspace(Parameters(UpdateTaskspaceParams {
+                collaborator: Some("socrates".to_string()),
+                ..update_taskspace_params()
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(!text.contains("Warning"));
     }
 
     #[test]
-    fn test_resource_contents_creation() {
-        // Test that we can create ResourceContents correctly
-        let content = ResourceContents::text("Hello world", "test.md");
+    fn test_normalize_tags() {
+        let tags = ["auth", " auth ", "", "  ", "oauth-rewrite", "auth"];
+        assert_eq!(
+            normalize_tags(tags.iter().map(|t| t.to_string()).collect()),
+            ["auth", "oauth-rewrite"]
+        );
+    }
 
-        match content {
-            ResourceContents::TextResourceContents {
-                uri,
-                text,
-                mime_type,
-                ..
-            } => {
-                assert_eq!(uri, "test.md");
-                assert_eq!(text, "Hello world");
-                assert_eq!(mime_type, Some("text".to_string()));
-            }
-            _ => panic!("Expected TextResourceContents"),
+    #[tokio::test]
+    async fn test_spawn_taskspace_initial_prompt_sources() {
+        async fn spawn(
+            initial_prompt: Option<&str>,
+            initial_prompt_path: Option<&str>,
+        ) -> Result<CallToolResult, McpError> {
+            SymposiumServer::new_test()
+                .spawn_taskspace(Parameters(SpawnTaskspaceParams {
+                    name: "Fix login".to_string(),
+                    task_description: "Repair the OAuth flow".to_string(),
+                    initial_prompt: initial_prompt.map(str::to_string),
+                    initial_prompt_path: initial_prompt_path.map(str::to_string),
+                    collaborator: None,
+                    tags: vec![],
+                }))
+                .await
         }
-    }
 
-    #[test]
-    fn test_yaml_metadata_parsing() {
-        let content_with_yaml = r#"---
-name: "Test Resource"
-description: "A test resource for testing"
----
+        // Exactly one source is required
+        let err = spawn(None, None).await.unwrap_err();
+        assert!(err.message.contains("exactly one of"));
+        let err = spawn(Some("inline"), Some("prompt.md")).await.unwrap_err();
+        assert!(err.message.contains("exactly one of"));
+        assert!(spawn(Some("inline"), None).await.is_ok());
 
-# Test Content
+        // A prompt file is read relative to the workspace root
+        let root = crate::workspace_dir::workspace_root().unwrap();
+        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap()
+            .join("Cargo.toml");
+        let relative = manifest.strip_prefix(&root).unwrap().to_string_lossy().to_string();
+        let prompt = SymposiumServer::resolve_initial_prompt(None, Some(&relative)).unwrap();
+        assert!(prompt.contains("[package]"));
+
+        // ...and may not escape it, whether through `..` or an absolute path
+        for outside in ["..", "/"] {
+            let err = SymposiumServer::resolve_initial_prompt(None, Some(outside)).unwrap_err();
+            assert!(err.message.contains("must be inside the workspace"), "{outside}: {err:?}");
+        }
+    }
 
-This is test content."#;
+    #[tokio::test]
+    async fn test_debug_recent_messages() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
-        let (name, description) = SymposiumServer::parse_yaml_metadata(content_with_yaml);
-        assert_eq!(name, Some("Test Resource".to_string()));
-        assert_eq!(description, Some("A test resource for testing".to_string()));
+        // Only offered with --dev-log
+        let has_tool = |router: ToolRouter<SymposiumServer>| {
+            router.list_all().iter().any(|tool| tool.name == "debug_recent_messages")
+        };
+        assert!(!has_tool(SymposiumServer::tool_router_for(false)));
+        assert!(has_tool(SymposiumServer::tool_router_for(true)));
+
+        // A fake daemon that answers one `#debug_dump_messages` request
+        let prefix = format!("symposium-test-debug-messages-{}", std::process::id());
+        let socket_path = crate::constants::daemon_socket_path(&prefix);
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = tokio::net::UnixListener::bind(&socket_path).unwrap();
+        let daemon = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read_half, mut write_half) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read_half).read_line(&mut line).await.unwrap();
+            assert_eq!(line, "#debug_dump_messages\n");
+
+            let history: Vec<_> = (0..5)
+                .map(|i| {
+                    serde_json::json!({
+                        "timestamp": i,
+                        "from_identifier": "mcp-server",
+                        "content": format!("message {i}")
+                    })
+                })
+                .collect();
+            let response = format!("{}\n", serde_json::to_string(&history).unwrap());
+            write_half.write_all(response.as_bytes()).await.unwrap();
+        });
 
-        // Test content without YAML
-        let content_without_yaml = "# Just a heading\n\nSome content.";
-        let (name, description) = SymposiumServer::parse_yaml_metadata(content_without_yaml);
-        assert_eq!(name, None);
-        assert_eq!(description, None);
+        let mut server = SymposiumServer::new_test();
+        server.daemon_socket_path = socket_path.clone();
+        let result = server
+            .debug_recent_messages(Parameters(DebugRecentMessagesParams { count: Some(2) }))
+            .await
+            .unwrap();
+        daemon.await.unwrap();
+        let _ = std::fs::remove_file(&socket_path);
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let result: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(result["total"], 5);
+        let contents: Vec<_> = result["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(contents, ["message 3", "message 4"]);
     }
 
     #[test]
-    fn test_list_resources_output() {
-        // Test the actual resource generation logic used by list_resources
-        let resources = SymposiumServer::generate_resources();
-
-        // Verify we have resources for all guidance files
-        let expected_count = GuidanceFiles::iter().count();
-        assert_eq!(resources.len(), expected_count);
+    fn test_deletion_blocker() {
+        use crate::types::PrepareForDeletionResponse;
+
+        // The extension's verdict wins over the local count
+        let safe = PrepareForDeletionResponse {
+            safe_to_delete: true,
+            warnings: vec![],
+            uncommitted_file_count: 3,
+        };
+        assert!(SymposiumServer::deletion_blocker(3, Some(safe)).is_none());
 
-        // Check that all files have proper metadata
-        let socrates_resource = resources.iter().find(|r| r.raw.uri == "socrates.md").unwrap();
-        assert_eq!(socrates_resource.raw.name, "Collaboration Patterns");
-        assert_eq!(
-            socrates_resource.raw.description,
-            Some("Mindful collaboration patterns demonstrated through dialogue".to_string())
-        );
-        assert!(socrates_resource.raw.size.unwrap() > 0);
+        let unsafe_response = PrepareForDeletionResponse {
+            safe_to_delete: false,
+            warnings: vec!["2 commits not merged".to_string()],
+            uncommitted_file_count: 1,
+        };
+        let message = SymposiumServer::deletion_blocker(0, Some(unsafe_response)).unwrap();
+        assert!(message.starts_with("Taskspace was NOT deleted. It has 1 file(s)"));
+        assert!(message.contains("- 2 commits not merged"));
+        assert!(message.contains("`force: true`"));
+
+        // Without an answer, uncommitted local files block deletion
+        assert!(SymposiumServer::deletion_blocker(0, None).is_none());
+        let message = SymposiumServer::deletion_blocker(4, None).unwrap();
+        assert!(message.contains("It has 4 file(s)"));
+    }
 
-        let walkthrough_resource = resources
-            .iter()
-            .find(|r| r.raw.uri == "walkthrough-format.md")
+    #[tokio::test]
+    async fn test_delete_taskspace_force_skips_check() {
+        let server = SymposiumServer::new_test();
+        let result = server
+            .delete_taskspace(Parameters(DeleteTaskspaceParams { force: true }))
+            .await
             .unwrap();
-        assert_eq!(walkthrough_resource.raw.name, "Walkthrough Format");
-        assert_eq!(
-            walkthrough_resource.raw.description,
-            Some(
-                "Specification for creating interactive code walkthroughs with code block elements"
-                    .to_string()
-            )
-        );
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(text, "Taskspace deletion initiated successfully");
+    }
 
-        let coding_resource = resources
-            .iter()
-            .find(|r| r.raw.uri == "coding-guidelines.md")
-            .unwrap();
-        assert_eq!(coding_resource.raw.name, "Coding Guidelines");
-        assert_eq!(
-            coding_resource.raw.description,
-            Some(
-                "Development best practices and standards for the Symposium project"
-                    .to_string()
-            )
-        );
+    #[tokio::test]
+    async fn test_update_taskspace_app_error() {
+        let responses = crate::ipc::MockResponses::new();
+        responses.push_err(crate::types::IPCMessageType::TaskspaceState, "taskspace not found");
+        let server = SymposiumServer::new_test_with_responses(responses);
+
+        let error = server
+            .update_taskspace(Parameters(update_taskspace_params()))
+            .await
+            .unwrap_err();
+        assert!(error.data.unwrap().to_string().contains("taskspace not found"));
     }
 
     #[tokio::test]
-    async fn test_yiasou_prompt_generation() {
+    async fn test_open_reference() {
         let server = SymposiumServer::new_test();
 
-        let prompt = server.assemble_yiasou_prompt(None).await.unwrap();
+        server
+            .reference_handle
+            .store_reference(
+                "sel".to_string(),
+                serde_json::json!({
+                    "relativePath": "src/lib.rs",
+                    "selectionRange": {
+                        "start": { "line": 10, "column": 1 },
+                        "end": { "line": 12, "column": 4 }
+                    },
+                    "selectedText": "fn foo() {}"
+                }),
+            )
+            .await
+            .unwrap();
+        server
+            .reference_handle
+            .store_reference("note".to_string(), serde_json::json!({ "notes": "no location" }))
+            .await
+            .unwrap();
+
+        let result = server
+            .open_reference(Parameters(OpenReferenceParams {
+                id: "sel".to_string(),
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(text, "Opened src/lib.rs:10 in the editor");
 
-        // Verify the prompt contains some basic text.
-        assert!(prompt.contains("Hi, welcome!"));
+        let result = server
+            .open_reference(Parameters(OpenReferenceParams {
+                id: "note".to_string(),
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("no location"));
+
+        assert!(server
+            .open_reference(Parameters(OpenReferenceParams {
+                id: "missing".to_string(),
+            }))
+            .await
+            .is_err());
     }
 
     #[tokio::test]
-    async fn test_expand_reference_yiasou() {
+    async fn test_diff_walkthroughs_reports_changed_sections() {
         let server = SymposiumServer::new_test();
 
-        // Test that expand_reference with "yiasou" returns the same content as the stored prompt
-        let params = ExpandReferenceParams {
-            id: "yiasou".to_string(),
+        let present = |content: &str| PresentWalkthroughParams {
+            content: content.to_string(),
+            base_uri: ".".to_string(),
         };
-        let result = server.expand_reference(Parameters(params)).await.unwrap();
+        server
+            .present_walkthrough(Parameters(present(
+                "# Overview\nThe parser reads input.\n\n# Details\nOld details.\n\n# Obsolete\nGone soon.\n",
+            )))
+            .await
+            .unwrap();
+        server
+            .present_walkthrough(Parameters(present(
+                "# Overview\nThe parser reads input.\n\n# Details\nNew details.\n\n# Testing\nRun the tests.\n",
+            )))
+            .await
+            .unwrap();
 
-        // Should be successful
-        assert!(matches!(
-            result,
-            CallToolResult {
-                is_error: Some(false),
-                ..
-            }
-        ));
+        let result = server.diff_walkthroughs().await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
 
-        // Should have content
-        assert!(!result.content.is_empty());
+        assert!(text.contains("revision 1 to 2"), "{text}");
+        assert!(text.contains("Added sections:\n  # Testing"), "{text}");
+        assert!(text.contains("Removed sections:\n  # Obsolete"), "{text}");
+        assert!(text.contains("Changed sections:\n  # Details"), "{text}");
+        assert!(text.contains("- Old details."), "{text}");
+        assert!(text.contains("+ New details."), "{text}");
+        assert!(!text.contains("# Overview"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_result_truncated_to_reference() {
+        let mut server = SymposiumServer::new_test();
+        server.max_result_bytes = 100;
+
+        let full_text = "0123456789".repeat(50);
+        let result = server
+            .limit_result_size(CallToolResult::success(vec![Content::text(full_text.clone())]))
+            .await;
+
+        assert_eq!(result.content.len(), 1);
+        let preview = result.content[0].as_text().unwrap().text.clone();
+        assert!(preview.starts_with(&full_text[..100]));
+        assert!(!preview.contains(&full_text[..101]));
+        assert!(preview.contains("showing 100 of 500 bytes"));
+
+        // The reference id in the preview expands to the full content
+        let id = preview
+            .split("<symposium-ref id='")
+            .nth(1)
+            .and_then(|rest| rest.split('\'').next())
+            .expect("preview should contain a symposium-ref")
+            .to_string();
+        let expanded = server
+            .expand_reference(Parameters(ExpandReferenceParams { id }))
+            .await
+            .unwrap();
+        assert_eq!(expanded.content[0].as_text().unwrap().text, full_text);
+
+        // Small results pass through untouched
+        let small = server
+            .limit_result_size(CallToolResult::success(vec![Content::text("short")]))
+            .await;
+        assert_eq!(small.content[0].as_text().unwrap().text, "short");
     }
 
     #[test]
@@ -1340,6 +4225,62 @@ This is test content."#;
         assert!(socrates_str.contains("beginner's mind"));
     }
 
+    #[tokio::test]
+    async fn test_ide_operation_batch() {
+        let server = SymposiumServer::new_test();
+
+        let text = |result: CallToolResult| -> serde_json::Value {
+            let content = result.content.first().and_then(|c| c.as_text()).expect("Expected text content");
+            serde_json::from_str(&content.text).unwrap()
+        };
+
+        // The single-string form is unchanged
+        let params: IdeOperationParams =
+            serde_json::from_value(serde_json::json!({ "program": r#"concat("a", "b")"# })).unwrap();
+        let result = server.ide_operation(Parameters(params)).await.unwrap();
+        assert_eq!(text(result), serde_json::json!("ab"));
+
+        // An array of programs yields an array of results, in order
+        let params: IdeOperationParams = serde_json::from_value(serde_json::json!({
+            "program": [r#"concat("a", "b")"#, r#"concat("c", "d")"#]
+        }))
+        .unwrap();
+        let result = server.ide_operation(Parameters(params)).await.unwrap();
+        assert_eq!(text(result), serde_json::json!(["ab", "cd"]));
+
+        // A failing program fails the batch and is identified by index
+        let params: IdeOperationParams = serde_json::from_value(serde_json::json!({
+            "program": [r#"concat("a", "b")"#, "noSuchFunction()"]
+        }))
+        .unwrap();
+        let error = server.ide_operation(Parameters(params)).await.unwrap_err();
+        let data = error.data.unwrap();
+        assert!(data["error"].as_str().unwrap().contains("program 1"));
+        assert_eq!(data["kind"], "unknown_function");
+        assert_eq!(data["program_index"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_ide_operation_error_kinds() {
+        let server = SymposiumServer::new_test();
+
+        let kind = |program: &str| {
+            let params: IdeOperationParams =
+                serde_json::from_value(serde_json::json!({ "program": program })).unwrap();
+            let server = &server;
+            async move {
+                let error = server.ide_operation(Parameters(params)).await.unwrap_err();
+                error.data.unwrap()["kind"].as_str().unwrap().to_string()
+            }
+        };
+
+        assert_eq!(kind(r#"concat("a""#).await, "parse_error");
+        assert_eq!(kind("noSuchFunction()").await, "unknown_function");
+        assert_eq!(kind(r#"count([], "extra")"#).await, "arity_mismatch");
+        assert_eq!(kind(r#"filter(["not a location"], pathContains("src"))"#).await, "invalid_arguments");
+        assert_eq!(kind(r#"lines("missing.rs", 1, 1)"#).await, "function_failed");
+    }
+
     // {RFD:rust-crate-sources-tool} Tests for Rust crate source functionality
     #[tokio::test]
     async fn test_get_rust_crate_source_extraction_only() {
@@ -1350,6 +4291,9 @@ This is test content."#;
             crate_name: "serde".to_string(),
             version: None,
             pattern: None,
+            context_lines: None,
+            max_matches: None,
+            relative_paths: true,
         };
         
         let result = server.get_rust_crate_source(Parameters(params)).await;
@@ -1389,6 +4333,9 @@ This is test content."#;
             crate_name: "serde".to_string(),
             version: None,
             pattern: Some("derive".to_string()),
+            context_lines: None,
+            max_matches: None,
+            relative_paths: true,
         };
         
         let result = server.get_rust_crate_source(Parameters(params)).await;
@@ -1440,6 +4387,9 @@ This is test content."#;
             crate_name: "serde".to_string(),
             version: Some("1.0".to_string()),
             pattern: None,
+            context_lines: None,
+            max_matches: None,
+            relative_paths: true,
         };
         
         let result = server.get_rust_crate_source(Parameters(params)).await;
@@ -1475,6 +4425,9 @@ This is test content."#;
             crate_name: "serde".to_string(),
             version: None,
             pattern: Some("[invalid regex".to_string()),
+            context_lines: None,
+            max_matches: None,
+            relative_paths: true,
         };
         
         let result = server.get_rust_crate_source(Parameters(params)).await;
@@ -1484,4 +4437,195 @@ This is test content."#;
         let error = result.unwrap_err();
         assert!(error.to_string().contains("Invalid regex pattern"));
     }
+
+    // {RFD:rust-crate-sources-tool} Test batch search with a partial failure
+    #[tokio::test]
+    async fn test_get_rust_crate_sources_partial_failure() {
+        let server = SymposiumServer::new_test();
+
+        let params = GetRustCrateSourcesParams {
+            crates: vec![
+                CrateSpec { crate_name: "serde".to_string(), version: None },
+                CrateSpec { crate_name: "this-crate-does-not-exist-symposium".to_string(), version: None },
+            ],
+            pattern: Some("derive".to_string()),
+        };
+
+        let result = server.get_rust_crate_sources(Parameters(params)).await.unwrap();
+        let content = result.content.first().and_then(|c| c.as_text()).expect("Expected text content");
+        let response: serde_json::Value = serde_json::from_str(&content.text).unwrap();
+        let entries = response.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        // Results come back in request order, shaped like get_rust_crate_source
+        assert_eq!(entries[0]["crate_name"], "serde");
+        assert!(entries[0]["checkout_path"].is_string());
+        assert!(entries[0]["example_matches"].is_array());
+        assert!(entries[0]["error"].is_null());
+
+        assert_eq!(entries[1]["crate_name"], "this-crate-does-not-exist-symposium");
+        assert!(entries[1]["error"].is_string());
+    }
+
+    // {RFD:rust-crate-sources-tool} Test invalid regex pattern handling in batch mode
+    #[tokio::test]
+    async fn test_get_rust_crate_sources_invalid_pattern() {
+        let server = SymposiumServer::new_test();
+
+        let params = GetRustCrateSourcesParams {
+            crates: vec![CrateSpec { crate_name: "serde".to_string(), version: None }],
+            pattern: Some("[invalid regex".to_string()),
+        };
+
+        let error = server.get_rust_crate_sources(Parameters(params)).await.unwrap_err();
+        assert!(error.to_string().contains("Invalid regex pattern"));
+    }
+
+    // {RFD:rust-crate-sources-tool} Test configurable context size
+    #[tokio::test]
+    async fn test_get_rust_crate_source_context_lines() {
+        let server = SymposiumServer::new_test();
+
+        let params = GetRustCrateSourceParams {
+            crate_name: "serde".to_string(),
+            version: None,
+            pattern: Some("derive".to_string()),
+            context_lines: Some(0),
+            max_matches: None,
+            relative_paths: true,
+        };
+
+        let result = server.get_rust_crate_source(Parameters(params)).await.unwrap();
+        let content = result.content.first().and_then(|c| c.as_text()).expect("Expected text content");
+        let response: serde_json::Value = serde_json::from_str(&content.text).unwrap();
+
+        // With no context, each match is just its own line
+        for m in response["other_matches"].as_array().unwrap() {
+            assert_eq!(m["context_start_line"], m["line_number"]);
+            assert_eq!(m["context_end_line"], m["line_number"]);
+            assert!(!m["context"].as_str().unwrap().contains('\n'));
+        }
+    }
+
+    // {RFD:rust-crate-sources-tool} Test context size validation
+    #[tokio::test]
+    async fn test_get_rust_crate_source_context_lines_too_large() {
+        let server = SymposiumServer::new_test();
+
+        let params = GetRustCrateSourceParams {
+            crate_name: "serde".to_string(),
+            version: None,
+            pattern: Some("derive".to_string()),
+            context_lines: Some(crate::eg::rust::MAX_CONTEXT_LINES + 1),
+            max_matches: None,
+            relative_paths: true,
+        };
+
+        let error = server.get_rust_crate_source(Parameters(params)).await.unwrap_err();
+        assert!(error.to_string().contains("context_lines must be at most"));
+    }
+
+    // {RFD:rust-crate-sources-tool} Test match limit
+    #[tokio::test]
+    async fn test_get_rust_crate_source_max_matches() {
+        let server = SymposiumServer::new_test();
+
+        let params = GetRustCrateSourceParams {
+            crate_name: "serde".to_string(),
+            version: None,
+            pattern: Some("fn ".to_string()),
+            context_lines: None,
+            max_matches: Some(3),
+            relative_paths: true,
+        };
+
+        let result = server.get_rust_crate_source(Parameters(params)).await.unwrap();
+        let content = result.content.first().and_then(|c| c.as_text()).expect("Expected text content");
+        let response: serde_json::Value = serde_json::from_str(&content.text).unwrap();
+
+        let returned = response["example_matches"].as_array().unwrap().len()
+            + response["other_matches"].as_array().unwrap().len();
+        assert_eq!(returned, 3);
+        // The total still reports how many matches exist
+        assert!(response["total_match_count"].as_u64().unwrap() > 3);
+    }
+
+    // {RFD:rust-crate-sources-tool} Test relative vs absolute match paths
+    #[tokio::test]
+    async fn test_get_rust_crate_source_relative_paths() {
+        let server = SymposiumServer::new_test();
+
+        let search = |relative_paths| GetRustCrateSourceParams {
+            crate_name: "serde".to_string(),
+            version: None,
+            pattern: Some("derive".to_string()),
+            context_lines: None,
+            max_matches: Some(1),
+            relative_paths,
+        };
+        let first_match = |result: CallToolResult| {
+            let content = result.content.first().and_then(|c| c.as_text()).expect("Expected text content");
+            let response: serde_json::Value = serde_json::from_str(&content.text).unwrap();
+            let m = response["example_matches"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .chain(response["other_matches"].as_array().unwrap())
+                .next()
+                .unwrap()
+                .clone();
+            (response["checkout_path"].as_str().unwrap().to_string(), m["file_path"].as_str().unwrap().to_string())
+        };
+
+        // Defaults to paths relative to the checkout
+        let params: GetRustCrateSourceParams =
+            serde_json::from_value(serde_json::json!({ "crate_name": "serde", "pattern": "derive", "max_matches": 1 })).unwrap();
+        assert!(params.relative_paths);
+
+        let (checkout_path, relative) = first_match(server.get_rust_crate_source(Parameters(search(true))).await.unwrap());
+        assert!(!std::path::Path::new(&relative).is_absolute());
+
+        let (_, absolute) = first_match(server.get_rust_crate_source(Parameters(search(false))).await.unwrap());
+        assert_eq!(std::path::Path::new(&absolute), std::path::Path::new(&checkout_path).join(&relative));
+    }
+
+    // {RFD:rust-crate-sources-tool} Test reading a single file from a crate
+    #[tokio::test]
+    async fn test_get_crate_file() {
+        let server = SymposiumServer::new_test();
+        let read = |file_path: &str| GetCrateFileParams {
+            crate_name: "serde".to_string(),
+            version: None,
+            file_path: file_path.to_string(),
+        };
+
+        let result = server.get_crate_file(Parameters(read("src/lib.rs"))).await.unwrap();
+        let text = &result.content.first().and_then(|c| c.as_text()).expect("Expected text content").text;
+        assert!(text.starts_with("serde v"), "{text}");
+        assert!(text.contains("src/lib.rs\n\n     1\t"), "{text}");
+
+        let error = server.get_crate_file(Parameters(read("src/no_such_file.rs"))).await.unwrap_err();
+        assert!(error.message.contains("not found in serde v"), "{}", error.message);
+
+        let error = server.get_crate_file(Parameters(read("../Cargo.toml"))).await.unwrap_err();
+        assert!(error.message.contains("must be relative to the crate root"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_search_workspace_invalid_params() {
+        let server = SymposiumServer::new_test();
+        let search = |pattern: &str, context_lines| SearchWorkspaceParams {
+            pattern: pattern.to_string(),
+            glob: None,
+            context_lines,
+            max_matches: None,
+        };
+
+        let error = server.search_workspace(Parameters(search("(", None))).await.unwrap_err();
+        assert!(error.message.contains("Invalid regex pattern"));
+
+        let too_large = Some(crate::eg::rust::MAX_CONTEXT_LINES + 1);
+        let error = server.search_workspace(Parameters(search("fn", too_large))).await.unwrap_err();
+        assert!(error.message.contains("context_lines must be at most"));
+    }
 }
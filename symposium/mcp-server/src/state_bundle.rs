@@ -0,0 +1,247 @@
+//! Diagnostic state bundle for bug reports
+//!
+//! Gathers configuration, tool counters, recent daemon messages, presence and
+//! version information into one JSON document a user can attach to an issue.
+//! Values that look like secrets are replaced with a placeholder and paths under
+//! the home directory are shortened to `~`.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::constants::{DAEMON_SOCKET_PREFIX, daemon_socket_path};
+use crate::ipc::IPCCommunicator;
+
+/// Number of daemon messages included in a bundle
+const MAX_BUNDLE_MESSAGES: usize = 50;
+
+/// Placeholder for values that were removed from the bundle
+const REDACTED: &str = "[redacted]";
+
+/// Variable or field names containing any of these are treated as secrets
+const SECRET_MARKERS: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "PASSWD", "KEY", "CREDENTIAL", "AUTH"];
+
+/// Environment variables (by prefix) relevant to how the server behaves
+const ENV_PREFIXES: &[&str] = &["SYMPOSIUM_", "RUST_LOG", "CLAUDECODE", "XDG_RUNTIME_DIR"];
+
+#[derive(Debug, Serialize)]
+pub struct StateBundle {
+    pub version: VersionInfo,
+    pub config: ConfigInfo,
+    pub tool_calls: ToolCounters,
+    pub daemon_messages: DaemonMessages,
+    pub presence: PresenceInfo,
+    pub guidance_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub symposium_mcp: String,
+    pub os: String,
+    pub arch: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigInfo {
+    pub daemon_socket: String,
+    pub working_directory: Option<String>,
+    pub environment: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ToolCounters {
+    pub calls: BTreeMap<String, u64>,
+    pub errors: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DaemonMessages {
+    pub messages: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PresenceInfo {
+    pub shell_pid: Option<u32>,
+    pub test_mode: bool,
+    pub project_path: Option<String>,
+    pub taskspace_uuid: Option<String>,
+}
+
+impl StateBundle {
+    /// Capture the current server state
+    pub async fn capture(ipc: &IPCCommunicator, guidance_files: Vec<String>) -> Self {
+        let socket_path = daemon_socket_path(DAEMON_SOCKET_PREFIX);
+
+        let daemon_messages = if ipc.is_test_mode() {
+            DaemonMessages {
+                messages: vec![],
+                error: Some("daemon is not connected in test mode".to_string()),
+            }
+        } else {
+            match crate::daemon::request_message_history(&socket_path).await {
+                Ok(messages) => {
+                    let skip = messages.len().saturating_sub(MAX_BUNDLE_MESSAGES);
+                    DaemonMessages {
+                        messages: messages.into_iter().skip(skip).map(redact_message).collect(),
+                        error: None,
+                    }
+                }
+                Err(e) => DaemonMessages {
+                    messages: vec![],
+                    error: Some(redact_paths(&e.to_string())),
+                },
+            }
+        };
+
+        let project_info = crate::ipc::extract_project_info().ok();
+
+        Self {
+            version: VersionInfo {
+                symposium_mcp: env!("CARGO_PKG_VERSION").to_string(),
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+            },
+            config: ConfigInfo {
+                daemon_socket: redact_paths(&socket_path),
+                working_directory: crate::workspace_dir::current_dir()
+                    .ok()
+                    .map(|dir| redact_paths(&dir.to_string_lossy())),
+                environment: collect_environment(std::env::vars()),
+            },
+            tool_calls: ToolCounters {
+                calls: crate::metrics::tool_call_counts(),
+                errors: crate::metrics::tool_error_counts(),
+            },
+            daemon_messages,
+            presence: PresenceInfo {
+                shell_pid: ipc.terminal_shell_pid(),
+                test_mode: ipc.is_test_mode(),
+                project_path: project_info.as_ref().map(|(path, _)| redact_paths(path)),
+                taskspace_uuid: project_info.map(|(_, uuid)| uuid),
+            },
+            guidance_files,
+        }
+    }
+}
+
+/// Keep the relevant environment variables, replacing secret values
+fn collect_environment(vars: impl IntoIterator<Item = (String, String)>) -> BTreeMap<String, String> {
+    vars.into_iter()
+        .filter(|(name, _)| ENV_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+        .map(|(name, value)| {
+            let value = if is_secret_name(&name) {
+                REDACTED.to_string()
+            } else {
+                redact_paths(&value)
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// Shorten paths under the user's home directory to `~`
+fn redact_paths(text: &str) -> String {
+    match dirs::home_dir() {
+        Some(home) if home.as_os_str().len() > 1 => text.replace(&*home.to_string_lossy(), "~"),
+        _ => text.to_string(),
+    }
+}
+
+/// Redact a daemon message; its `content` is usually a serialized IPC message
+fn redact_message(mut message: Value) -> Value {
+    if let Some(content) = message.get_mut("content") {
+        if let Some(text) = content.as_str().map(str::to_owned) {
+            *content = match serde_json::from_str::<Value>(&text) {
+                Ok(mut parsed) => {
+                    redact_value(&mut parsed);
+                    Value::String(parsed.to_string())
+                }
+                Err(_) => Value::String(redact_paths(&text)),
+            };
+        }
+    }
+    message
+}
+
+/// Recursively replace secret fields and shorten paths in a JSON value
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_secret_name(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::String(text) => *text = redact_paths(text),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_omits_secrets() {
+        let env = collect_environment(vec![
+            ("SYMPOSIUM_AGENT".to_string(), "claude-code".to_string()),
+            ("SYMPOSIUM_API_TOKEN".to_string(), "hunter2".to_string()),
+            ("RUST_LOG".to_string(), "debug".to_string()),
+            ("GITHUB_TOKEN".to_string(), "ghp_secret".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]);
+
+        assert_eq!(env.get("SYMPOSIUM_AGENT").map(String::as_str), Some("claude-code"));
+        assert_eq!(env.get("SYMPOSIUM_API_TOKEN").map(String::as_str), Some(REDACTED));
+        assert_eq!(env.get("RUST_LOG").map(String::as_str), Some("debug"));
+        assert!(!env.contains_key("GITHUB_TOKEN"));
+        assert!(!env.contains_key("PATH"));
+    }
+
+    #[test]
+    fn test_message_content_redacted() {
+        let message = serde_json::json!({
+            "timestamp": 1,
+            "from_identifier": "MCP-Server-1",
+            "content": r#"{"type":"log","payload":{"auth_token":"hunter2","message":"hi"}}"#
+        });
+
+        let redacted = redact_message(message).to_string();
+        assert!(!redacted.contains("hunter2"));
+        assert!(redacted.contains(REDACTED));
+        assert!(redacted.contains("hi"));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_sections() {
+        let ipc = IPCCommunicator::new_test(crate::actor::ReferenceHandle::new());
+        let bundle = StateBundle::capture(&ipc, vec!["main.md".to_string()]).await;
+        let json = serde_json::to_value(&bundle).unwrap();
+
+        for section in [
+            "version",
+            "config",
+            "tool_calls",
+            "daemon_messages",
+            "presence",
+            "guidance_files",
+        ] {
+            assert!(json.get(section).is_some(), "missing section {section}");
+        }
+        assert_eq!(json["version"]["symposium_mcp"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["presence"]["test_mode"], true);
+        assert_eq!(json["guidance_files"][0], "main.md");
+    }
+}
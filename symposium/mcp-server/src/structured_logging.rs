@@ -84,8 +84,12 @@ impl tracing::field::Visit for MessageVisitor {
 }
 
 /// Initialize tracing with component-prefixed logging that sends to both stderr and daemon
+///
+/// `default_log_level` (from the project config) filters stderr output when
+/// `RUST_LOG` is not set.
 pub fn init_component_tracing(
     enable_dev_log: bool,
+    default_log_level: Option<&str>,
 ) -> Result<Option<tracing_appender::non_blocking::WorkerGuard>, Box<dyn std::error::Error>> {
     if enable_dev_log {
         use std::fs::OpenOptions;
@@ -116,11 +120,21 @@ pub fn init_component_tracing(
 
         Ok(Some(guard))
     } else {
+        let env_filter = match default_log_level {
+            Some(level) if std::env::var_os("RUST_LOG").is_none() => {
+                tracing_subscriber::EnvFilter::try_new(level).unwrap_or_else(|e| {
+                    eprintln!("Ignoring invalid log_level {level:?} in project config: {e}");
+                    tracing_subscriber::EnvFilter::from_default_env()
+                })
+            }
+            _ => tracing_subscriber::EnvFilter::from_default_env(),
+        };
+
         tracing_subscriber::registry()
             .with(
                 tracing_subscriber::fmt::layer()
                     .with_writer(std::io::stderr)
-                    .with_filter(tracing_subscriber::EnvFilter::from_default_env())
+                    .with_filter(env_filter)
             )
             .with(ForwardToSubscriberLayer)
             .init();
@@ -3,6 +3,8 @@
 //! Mirrors the TypeScript types from server/src/types.ts to ensure
 //! protocol compatibility across the IPC boundary.
 
+use std::collections::BTreeMap;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +15,10 @@ pub trait IpcPayload:
     /// Whether this message type expects a reply
     const EXPECTS_REPLY: bool;
 
+    /// Whether the reply waits on a human, so it gets the long user-wait timeout
+    /// instead of the IPC request timeout
+    const WAITS_FOR_USER: bool = false;
+
     /// The type of the reply (use () for no meaningful reply)
     type Reply: serde::de::DeserializeOwned + Send + 'static;
 
@@ -107,6 +113,140 @@ impl IpcPayload for PresentWalkthroughMessage {
     }
 }
 
+/// Start of a walkthrough sent in chunks
+///
+/// Followed by [`PresentWalkthroughChunkMessage`]s with consecutive `index`es
+/// and then one [`PresentWalkthroughEndMessage`], all with the same
+/// `walkthrough_id`. Each message is acknowledged before the next is sent, so
+/// they arrive in order; the receiver appends chunk contents in `index` order.
+// ANCHOR: present_walkthrough_chunked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresentWalkthroughBeginMessage {
+    pub walkthrough_id: String,
+    #[serde(rename = "baseUri")]
+    pub base_uri: String,
+}
+
+/// One piece of a chunked walkthrough's HTML, ending between top-level blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresentWalkthroughChunkMessage {
+    pub walkthrough_id: String,
+    /// Position of this chunk, starting at 0
+    pub index: u32,
+    pub content: String,
+}
+
+/// End of a chunked walkthrough
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresentWalkthroughEndMessage {
+    pub walkthrough_id: String,
+    /// Number of chunks sent, so the receiver can detect a missing one
+    pub chunk_count: u32,
+    /// Set when the walkthrough failed part way; the chunks so far are incomplete
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+// ANCHOR_END: present_walkthrough_chunked
+
+impl IpcPayload for PresentWalkthroughBeginMessage {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = ();
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::PresentWalkthroughBegin
+    }
+}
+
+impl IpcPayload for PresentWalkthroughChunkMessage {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = ();
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::PresentWalkthroughChunk
+    }
+}
+
+impl IpcPayload for PresentWalkthroughEndMessage {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = ();
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::PresentWalkthroughEnd
+    }
+}
+
+/// Reveal location message - opens a file and selects a range in the editor
+// ANCHOR: reveal_location_message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevealLocationMessage {
+    pub location: crate::ide::FileRange,
+}
+// ANCHOR_END: reveal_location_message
+
+impl IpcPayload for RevealLocationMessage {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = ();
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::RevealLocation
+    }
+}
+
+/// Open diff message - shows two versions of a file in the editor's diff view
+// ANCHOR: open_diff_message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenDiffMessage {
+    /// Path of the file, relative to the repository root
+    pub path: String,
+    /// Revision of the left-hand side, used in the diff title
+    pub base_revision: String,
+    /// File content at `base_revision`
+    pub base_content: String,
+    /// Revision of the right-hand side, or `WORKING` for the working tree
+    pub head_revision: String,
+    /// File content at `head_revision`; `None` for the working tree, which the
+    /// editor opens from disk so it stays editable
+    pub head_content: Option<String>,
+}
+// ANCHOR_END: open_diff_message
+
+impl IpcPayload for OpenDiffMessage {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = ();
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::OpenDiff
+    }
+}
+
+/// List open editors message - asks the IDE which editor tabs are open
+// ANCHOR: list_open_editors_message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListOpenEditorsMessage {}
+
+/// An editor tab open in the IDE, as listed in reply to [`ListOpenEditorsMessage`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenEditor {
+    /// Path relative to the workspace root, or absolute for files outside it
+    pub path: String,
+    /// Whether this is the editor with focus
+    pub is_active: bool,
+    /// Whether the editor has unsaved changes
+    pub is_dirty: bool,
+    /// Language id of the document (e.g., "rust"), if the IDE has loaded it
+    pub language: Option<String>,
+}
+// ANCHOR_END: list_open_editors_message
+
+impl IpcPayload for ListOpenEditorsMessage {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = Vec<OpenEditor>;
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::ListOpenEditors
+    }
+}
+
 /// Polo discovery message - announces presence with shell PID
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoloMessage {
@@ -184,9 +324,34 @@ pub struct GetSelectionResult {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PoloPayload {
     // Shell PID is now at top level in IPCMessage
+
+    /// What kind of component is answering; absent from older MCP servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<PeerRole>,
 }
 // ANCHOR_END: polo_payload
 
+/// The kind of component on the other end of a Polo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerRole {
+    /// The VSCode extension
+    Ide,
+    /// An MCP server, usually in another taskspace
+    McpServer,
+}
+
+/// A component that answered a Marco, as reported by `list_peers`
+#[derive(Debug, Clone, Serialize)]
+pub struct Peer {
+    pub role: PeerRole,
+    /// The peer's identity, as it appears in its messages
+    #[serde(flatten)]
+    pub sender: MessageSender,
+    /// Whether this is the MCP server that asked
+    pub is_self: bool,
+}
+
 impl IpcPayload for PoloPayload {
     const EXPECTS_REPLY: bool = false;
     type Reply = ();
@@ -243,6 +408,56 @@ impl IpcPayload for FindAllReferencesPayload {
     }
 }
 
+/// Payload for RenamePreview messages
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RenamePreviewPayload {
+    /// The resolved symbol to rename
+    pub symbol: crate::ide::SymbolDef,
+    /// The proposed new name
+    pub new_name: String,
+}
+
+impl IpcPayload for RenamePreviewPayload {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = Vec<crate::ide::FileEdits>;
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::RenamePreview
+    }
+}
+
+/// Payload for DocumentSymbols messages
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DocumentSymbolsPayload {
+    /// File to outline, relative to workspace root
+    pub path: String,
+}
+
+impl IpcPayload for DocumentSymbolsPayload {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = Vec<crate::ide::DocumentSymbol>;
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::DocumentSymbols
+    }
+}
+
+/// Payload for DefinitionAt messages
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DefinitionAtPayload {
+    /// Position to go to the definition from (the start of the range)
+    pub location: crate::ide::FileRange,
+}
+
+impl IpcPayload for DefinitionAtPayload {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = Vec<crate::ide::SymbolDef>;
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::DefinitionAt
+    }
+}
+
 /// Payload for Response messages (replaces IPCResponse struct)
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ResponsePayload<T = serde_json::Value> {
@@ -260,7 +475,7 @@ pub struct ResponsePayload<T = serde_json::Value> {
 
 /// Sender information for message routing
 // ANCHOR: message_sender
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct MessageSender {
     /// Working directory - always present for reliable matching
     #[serde(rename = "workingDirectory")]
@@ -296,11 +511,15 @@ pub struct IPCMessage {
 // ANCHOR_END: ipc_message
 
 /// IPC message types
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum IPCMessageType {
     PresentReview,
     PresentWalkthrough,
+    /// Start, piece, and end of a walkthrough sent in chunks
+    PresentWalkthroughBegin,
+    PresentWalkthroughChunk,
+    PresentWalkthroughEnd,
     Log,
     GetSelection,
     /// Extension broadcasts "who's out there?" to discover active MCP servers
@@ -315,6 +534,12 @@ pub enum IPCMessageType {
     ResolveSymbolByName,
     /// Find all references to a symbol - returns Vec<FileLocation>
     FindAllReferences,
+    /// Compute (but never apply) the edits of an LSP rename - returns Vec<FileEdits>
+    RenamePreview,
+    /// Outline a file via LSP documentSymbol - returns Vec<DocumentSymbol>
+    DocumentSymbols,
+    /// Go to the definition of the symbol at a position - returns Vec<SymbolDef>
+    DefinitionAt,
 
     /// User feedback from VSCode extension (comments, review completion)
     UserFeedback,
@@ -338,6 +563,18 @@ pub enum IPCMessageType {
     RegisterTaskspaceWindow,
     /// Delete current taskspace
     DeleteTaskspace,
+    /// Ask whether the current taskspace can be deleted without losing work
+    PrepareForDeletion,
+    /// Apply a patch to the working tree after the user confirms it
+    ApplyPatch,
+    /// User clicked an `applydiff` button in a walkthrough (extension → MCP server)
+    ApplyDiff,
+    /// Open a file in the editor and reveal a range within it
+    RevealLocation,
+    /// Open the editor's diff view for two versions of a file
+    OpenDiff,
+    /// List the editor tabs open in the IDE - returns Vec<OpenEditor>
+    ListOpenEditors,
 }
 
 // ANCHOR: store_reference_payload
@@ -393,6 +630,9 @@ pub struct SpawnTaskspacePayload {
     pub task_description: String,
     pub initial_prompt: String,
     pub collaborator: Option<String>,
+    /// Tags grouping the new taskspace with related ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 // ANCHOR_END: spawn_taskspace_payload
 
@@ -413,6 +653,10 @@ pub struct LogProgressPayload {
     pub taskspace_uuid: String,
     pub message: String,
     pub category: ProgressCategory,
+    /// Structured details (e.g. `completed`/`total`) the app may render
+    /// alongside the message; the message remains the primary display
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 // ANCHOR_END: log_progress_payload
 
@@ -439,6 +683,30 @@ pub enum ProgressCategory {
 }
 // ANCHOR_END: progress_category
 
+/// How urgently a signal_user request needs the user's attention
+// ANCHOR: signal_priority
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SignalPriority {
+    /// FYI, whenever the user gets a chance
+    Low,
+    #[default]
+    Normal,
+    /// The agent is blocked and needs the user now
+    Urgent,
+}
+// ANCHOR_END: signal_priority
+
+impl SignalPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalPriority::Low => "low",
+            SignalPriority::Normal => "normal",
+            SignalPriority::Urgent => "urgent",
+        }
+    }
+}
+
 /// Payload for signal_user messages
 // ANCHOR: signal_user_payload
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -446,6 +714,8 @@ pub struct SignalUserPayload {
     pub project_path: String,
     pub taskspace_uuid: String,
     pub message: String,
+    #[serde(default)]
+    pub priority: SignalPriority,
 }
 // ANCHOR_END: signal_user_payload
 
@@ -490,6 +760,9 @@ pub struct TaskspaceStateRequest {
     pub description: Option<String>,
     /// New collaborator to set (None = don't update)
     pub collaborator: Option<String>,
+    /// New tags, replacing the current ones (None = don't update)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
 }
 
 impl IpcPayload for TaskspaceStateRequest {
@@ -544,6 +817,9 @@ pub struct TaskspaceStateResponse {
     pub initial_prompt: Option<String>,
     /// Collaborator for this taskspace
     pub collaborator: Option<String>,
+    /// Tags grouping this taskspace with related ones
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Payload for delete_taskspace messages
@@ -563,3 +839,120 @@ impl IpcPayload for DeleteTaskspacePayload {
         IPCMessageType::DeleteTaskspace
     }
 }
+
+/// Payload for prepare_for_deletion messages
+///
+/// Sent before `delete_taskspace` so the extension can veto (or warn about)
+/// deleting a taskspace that still contains uncommitted work.
+// ANCHOR: prepare_for_deletion_payload
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PrepareForDeletionPayload {
+    pub project_path: String,
+    pub taskspace_uuid: String,
+    /// Number of uncommitted files detected locally by the MCP server
+    pub uncommitted_file_count: usize,
+}
+// ANCHOR_END: prepare_for_deletion_payload
+
+impl IpcPayload for PrepareForDeletionPayload {
+    const EXPECTS_REPLY: bool = true;
+    type Reply = PrepareForDeletionResponse;
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::PrepareForDeletion
+    }
+}
+
+/// Response for prepare_for_deletion messages
+// ANCHOR: prepare_for_deletion_response
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PrepareForDeletionResponse {
+    /// Whether the taskspace can be deleted without further confirmation
+    pub safe_to_delete: bool,
+    /// Human-readable warnings to relay to the user
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Number of files with uncommitted changes
+    pub uncommitted_file_count: usize,
+}
+// ANCHOR_END: prepare_for_deletion_response
+
+/// Payload for apply_patch messages
+///
+/// The extension must show the patch to the user and only apply it to the
+/// working tree once they explicitly confirm.
+// ANCHOR: apply_patch_payload
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApplyPatchPayload {
+    pub project_path: String,
+    pub taskspace_uuid: String,
+    /// Unified diff to apply
+    pub patch: String,
+    /// Short explanation shown to the user alongside the patch
+    pub description: String,
+    /// Always true: the extension must ask the user before touching any files
+    pub require_confirmation: bool,
+}
+// ANCHOR_END: apply_patch_payload
+
+impl ApplyPatchPayload {
+    /// Create a payload that requires user confirmation before applying.
+    pub fn new(project_path: String, taskspace_uuid: String, patch: String, description: String) -> Self {
+        Self {
+            project_path,
+            taskspace_uuid,
+            patch,
+            description,
+            require_confirmation: true,
+        }
+    }
+}
+
+impl IpcPayload for ApplyPatchPayload {
+    const EXPECTS_REPLY: bool = true;
+    // The user reviews the patch before answering
+    const WAITS_FOR_USER: bool = true;
+    type Reply = ApplyPatchResponse;
+
+    fn message_type(&self) -> IPCMessageType {
+        IPCMessageType::ApplyPatch
+    }
+}
+
+/// Outcome of an apply_patch request
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyPatchStatus {
+    /// User confirmed and the patch applied cleanly
+    Applied,
+    /// The patch did not apply to the current working tree
+    Conflict,
+    /// User declined to apply the patch
+    Declined,
+}
+
+/// Payload for apply_diff messages, sent by the extension when the user
+/// clicks an `applydiff` walkthrough button
+// ANCHOR: apply_diff_payload
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApplyDiffPayload {
+    /// Workspace root of the MCP server that rendered the walkthrough;
+    /// other servers ignore the message
+    pub workspace_root: String,
+    /// Unified diff to apply
+    pub patch: String,
+}
+// ANCHOR_END: apply_diff_payload
+
+/// Response for apply_patch messages
+// ANCHOR: apply_patch_response
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApplyPatchResponse {
+    pub status: ApplyPatchStatus,
+    /// Files (or hunks) that failed to apply, when status is `conflict`
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// Optional human-readable detail
+    pub message: Option<String>,
+}
+// ANCHOR_END: apply_patch_response
@@ -0,0 +1,191 @@
+//! Opt-in, strictly local usage log
+//!
+//! When the user sets `SYMPOSIUM_USAGE_LOG=1`, the MCP server keeps aggregated
+//! per-tool counters in a JSON file on disk. Nothing is ever sent over the
+//! network; the user decides whether to share the file. View it with
+//! `symposium-mcp usage show`.
+//!
+//! The file contains only counters, never arguments, paths, or results:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "tools": {
+//!     "get_selection": { "calls": 12, "errors": 1 },
+//!     "ide_operation": { "calls": 40, "errors": 3 }
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{TEMP_DIR, USAGE_LOG_ENV_VAR, USAGE_LOG_FILENAME};
+
+/// Version of the on-disk format described in the module docs
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Aggregated usage counters, as stored on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageCounts {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolUsage>,
+}
+
+/// Counters for a single tool
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolUsage {
+    pub calls: u64,
+    pub errors: u64,
+}
+
+impl Default for UsageCounts {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// Handle to the usage log file; only exists when the user opted in
+#[derive(Debug, Clone)]
+pub struct UsageLog {
+    path: PathBuf,
+}
+
+impl UsageLog {
+    /// The usage log, if enabled via `SYMPOSIUM_USAGE_LOG=1`
+    pub fn from_env() -> Option<Self> {
+        let setting = std::env::var(USAGE_LOG_ENV_VAR).ok();
+        Self::from_setting(setting.as_deref(), default_path())
+    }
+
+    /// The usage log at `path`, if `setting` (the env var value) opts in
+    pub fn from_setting(setting: Option<&str>, path: PathBuf) -> Option<Self> {
+        (setting == Some("1")).then_some(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add one invocation of `tool` to the counters on disk
+    ///
+    /// Blocks on file IO and on other servers updating the log; call it off the async runtime.
+    pub fn record_tool_call(&self, tool: &str, success: bool) -> anyhow::Result<()> {
+        // Every server process updates the same file, so hold the lock across read-modify-write
+        let _lock = lock_log(&self.path)?;
+        let mut counts = read_counts(&self.path)?;
+        let usage = counts.tools.entry(tool.to_string()).or_default();
+        usage.calls += 1;
+        if !success {
+            usage.errors += 1;
+        }
+        write_counts(&self.path, &counts)
+    }
+}
+
+/// Where the usage log lives: the platform's local data directory, or the temp dir
+pub fn default_path() -> PathBuf {
+    match dirs::data_local_dir() {
+        Some(dir) => dir.join("symposium").join(USAGE_LOG_FILENAME),
+        None => Path::new(TEMP_DIR).join(USAGE_LOG_FILENAME),
+    }
+}
+
+/// Read the counters at `path`; a missing file means nothing has been recorded yet
+pub fn read_counts(path: &Path) -> anyhow::Result<UsageCounts> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .with_context(|| format!("Malformed usage log at {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(UsageCounts::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read usage log at {}", path.display())),
+    }
+}
+
+/// Take an exclusive lock guarding the usage log at `path`, released when the returned file is dropped
+fn lock_log(path: &Path) -> anyhow::Result<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let lock_path = path.with_extension("json.lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open usage log lock at {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock usage log at {}", lock_path.display()))?;
+    Ok(file)
+}
+
+fn write_counts(path: &Path, counts: &UsageCounts) -> anyhow::Result<()> {
+    // Write then rename so a concurrent reader never sees a partial file; the temp
+    // name is per process so servers never write into each other's temp file
+    let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp_path, serde_json::to_string_pretty(counts)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(USAGE_LOG_FILENAME);
+
+        assert!(UsageLog::from_setting(None, path.clone()).is_none());
+        assert!(UsageLog::from_setting(Some("0"), path.clone()).is_none());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_enabled_records_tool_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(USAGE_LOG_FILENAME);
+
+        let log = UsageLog::from_setting(Some("1"), path.clone()).unwrap();
+        log.record_tool_call("get_selection", true).unwrap();
+        log.record_tool_call("get_selection", false).unwrap();
+        log.record_tool_call("ide_operation", true).unwrap();
+
+        let counts = read_counts(&path).unwrap();
+        assert_eq!(counts.schema_version, SCHEMA_VERSION);
+        assert_eq!(counts.tools["get_selection"], ToolUsage { calls: 2, errors: 1 });
+        assert_eq!(counts.tools["ide_operation"], ToolUsage { calls: 1, errors: 0 });
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(USAGE_LOG_FILENAME);
+
+        // Separate handles, like separate server processes sharing the file
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let log = UsageLog::from_setting(Some("1"), path.clone()).unwrap();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        log.record_tool_call("get_selection", true).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let counts = read_counts(&path).unwrap();
+        assert_eq!(counts.tools["get_selection"], ToolUsage { calls: 100, errors: 0 });
+    }
+}
@@ -0,0 +1,131 @@
+//! Standalone HTML export of walkthroughs
+//!
+//! The walkthrough parser produces HTML fragments styled with VSCode theme
+//! variables (`var(--vscode-editor-background)` and friends), which only resolve
+//! inside the webview. This module wraps such a fragment in a complete HTML
+//! document with those variables replaced by concrete colors, so it renders the
+//! same in any browser.
+
+use regex::{Captures, Regex};
+
+use crate::walkthrough_parser::escape_html;
+
+/// Mermaid build loaded by exported documents, the same one the VSCode webview uses
+const MERMAID_SCRIPT_URL: &str = "https://cdn.jsdelivr.net/npm/mermaid@10.9.1/dist/mermaid.min.js";
+
+/// Concrete value for a VSCode theme variable (without the `--vscode-` prefix),
+/// following VSCode's default light theme
+fn theme_value(name: &str) -> &'static str {
+    match name {
+        "foreground" => "#3b3b3b",
+        "descriptionForeground" => "#717171",
+        "errorForeground" => "#f85149",
+        "textLink-foreground" => "#005fb8",
+        "editor-background" => "#ffffff",
+        "editor-font-family" => "Menlo, Monaco, Consolas, 'Courier New', monospace",
+        "panel-border" => "#e5e5e5",
+        "button-background" => "#005fb8",
+        "button-foreground" => "#ffffff",
+        "inputValidation-errorBackground" => "#f2dede",
+        "inputValidation-errorBorder" => "#be1100",
+        _ => "inherit",
+    }
+}
+
+/// Replace every `var(--vscode-*)` reference in `html` with a concrete value
+pub fn inline_theme_variables(html: &str) -> String {
+    let pattern = Regex::new(r"var\(--vscode-([A-Za-z0-9-]+)\)").unwrap();
+    pattern
+        .replace_all(html, |caps: &Captures| theme_value(&caps[1]))
+        .into_owned()
+}
+
+/// Wrap a rendered walkthrough fragment in a self-contained HTML document.
+///
+/// Mermaid diagrams are rendered in the browser by the mermaid script, which is
+/// only included when the walkthrough has diagrams.
+pub fn standalone_document(title: &str, body_html: &str) -> String {
+    let body = inline_theme_variables(body_html);
+    let mermaid = if body.contains("<mermaid") {
+        format!(
+            r#"
+    <script src="{MERMAID_SCRIPT_URL}"></script>
+    <script>
+        document.querySelectorAll('mermaid').forEach((element) => {{
+            const diagram = document.createElement('pre');
+            diagram.className = 'mermaid';
+            diagram.textContent = element.textContent;
+            element.replaceWith(diagram);
+        }});
+        mermaid.initialize({{ startOnLoad: false, theme: 'default' }});
+        mermaid.run();
+    </script>"#
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{title}</title>
+    <style>
+        body {{
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif;
+            color: {foreground};
+            background-color: {background};
+            max-width: 960px;
+            margin: 0 auto;
+            padding: 24px;
+            line-height: 1.5;
+        }}
+        a {{ color: {link}; }}
+        code, pre {{ font-family: {mono}; }}
+        pre {{ overflow-x: auto; }}
+        .mermaid {{ text-align: center; }}
+    </style>
+</head>
+<body>
+{body}{mermaid}
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        foreground = theme_value("foreground"),
+        background = theme_value("editor-background"),
+        link = theme_value("textLink-foreground"),
+        mono = theme_value("editor-font-family"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standalone_document() {
+        let fragment = r#"<h1>Auth</h1><div style="color: var(--vscode-foreground); border: 1px solid var(--vscode-panel-border);">Note</div><div style="color: var(--vscode-unknownColor);"></div>"#;
+        let html = standalone_document("Auth <flow>", fragment);
+
+        assert!(html.starts_with("<!DOCTYPE html>\n<html"));
+        assert!(html.trim_end().ends_with("</html>"));
+        assert!(html.contains("<title>Auth &lt;flow&gt;</title>"));
+        assert!(html.contains("<h1>Auth</h1>"));
+        assert!(html.contains("color: #3b3b3b; border: 1px solid #e5e5e5;"));
+        assert!(html.contains("color: inherit;"));
+        assert!(!html.contains("var(--vscode-"));
+
+        // No diagrams, no script
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_standalone_document_with_mermaid() {
+        let html = standalone_document("Flow", "<mermaid>graph TD\n  A --> B</mermaid>");
+        assert!(html.contains(MERMAID_SCRIPT_URL));
+        assert!(html.contains("mermaid.run()"));
+    }
+}
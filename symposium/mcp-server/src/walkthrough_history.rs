@@ -0,0 +1,253 @@
+//! Revision history of presented walkthroughs
+//!
+//! Every walkthrough passed to `present_walkthrough` is saved as a numbered
+//! markdown file, per taskspace, so that `diff_walkthroughs` can show the user
+//! what changed between the last two presentations.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::constants::TEMP_DIR;
+
+/// Number of revisions kept on disk; older ones are pruned
+const MAX_WALKTHROUGH_REVISIONS: u64 = 10;
+
+/// Title used for content that appears before the first heading
+const PREAMBLE_TITLE: &str = "(introduction)";
+
+/// Directory-backed store of walkthrough revisions
+#[derive(Debug, Clone)]
+pub struct WalkthroughHistory {
+    dir: PathBuf,
+}
+
+impl WalkthroughHistory {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// History for the current taskspace (`project.symposium/task-$UUID/walkthroughs`),
+    /// or a per-process directory under the temp dir when not running in a taskspace
+    pub fn for_current_taskspace() -> Self {
+        match crate::ipc::extract_project_info() {
+            Ok((project_path, taskspace_uuid)) => Self::new(
+                Path::new(&project_path)
+                    .join(format!("task-{taskspace_uuid}"))
+                    .join("walkthroughs"),
+            ),
+            Err(_) => Self::new(
+                Path::new(TEMP_DIR)
+                    .join("symposium-walkthroughs")
+                    .join(std::process::id().to_string()),
+            ),
+        }
+    }
+
+    /// Save a newly presented walkthrough, returning its revision number
+    pub fn record(&self, markdown: &str) -> anyhow::Result<u64> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+
+        let revisions = self.revisions()?;
+        let revision = revisions.last().map_or(1, |last| last + 1);
+        std::fs::write(self.revision_path(revision), markdown)?;
+
+        // Prune the oldest revisions
+        for old in revisions
+            .iter()
+            .filter(|&&old| old + MAX_WALKTHROUGH_REVISIONS <= revision)
+        {
+            let _ = std::fs::remove_file(self.revision_path(*old));
+        }
+
+        Ok(revision)
+    }
+
+    /// The two most recent revisions as `((number, markdown), (number, markdown))`, oldest first
+    pub fn last_two(&self) -> anyhow::Result<Option<((u64, String), (u64, String))>> {
+        let revisions = self.revisions()?;
+        let &[.., previous, latest] = revisions.as_slice() else {
+            return Ok(None);
+        };
+
+        let read = |revision| std::fs::read_to_string(self.revision_path(revision));
+        Ok(Some(((previous, read(previous)?), (latest, read(latest)?))))
+    }
+
+    /// Revision numbers on disk, ascending
+    fn revisions(&self) -> anyhow::Result<Vec<u64>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut revisions: Vec<u64> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                name.to_str()?.strip_suffix(".md")?.parse().ok()
+            })
+            .collect();
+        revisions.sort_unstable();
+        Ok(revisions)
+    }
+
+    fn revision_path(&self, revision: u64) -> PathBuf {
+        self.dir.join(format!("{revision}.md"))
+    }
+}
+
+/// A markdown section: a heading line (or the preamble) and the lines below it
+struct Section<'a> {
+    title: &'a str,
+    lines: Vec<&'a str>,
+}
+
+/// Split markdown into sections at ATX headings, ignoring `#` lines inside code blocks
+fn sections(markdown: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![Section {
+        title: PREAMBLE_TITLE,
+        lines: vec![],
+    }];
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if !in_code_block && line.starts_with('#') {
+            sections.push(Section {
+                title: line.trim(),
+                lines: vec![],
+            });
+        } else {
+            sections.last_mut().unwrap().lines.push(line);
+        }
+    }
+
+    // Drop an empty preamble
+    if sections[0].lines.iter().all(|line| line.trim().is_empty()) {
+        sections.remove(0);
+    }
+    sections
+}
+
+/// Line diff of two sections' bodies based on their longest common subsequence
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<String> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut output = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            output.push(format!("+ {}", new[j]));
+            j += 1;
+        } else {
+            output.push(format!("- {}", old[i]));
+            i += 1;
+        }
+    }
+    output
+}
+
+/// Describe how a walkthrough changed, section by section
+pub fn diff_walkthroughs(old: &str, new: &str) -> String {
+    let old_sections = sections(old);
+    let new_sections = sections(new);
+    let find = |sections: &[Section<'_>], title: &str| {
+        sections.iter().position(|section| section.title == title)
+    };
+
+    let mut added = vec![];
+    let mut changed = vec![];
+    for section in &new_sections {
+        match find(&old_sections, section.title) {
+            None => added.push(section.title),
+            Some(index) if old_sections[index].lines != section.lines => {
+                changed.push((section.title, diff_lines(&old_sections[index].lines, &section.lines)));
+            }
+            Some(_) => {}
+        }
+    }
+    let removed: Vec<&str> = old_sections
+        .iter()
+        .filter(|section| find(&new_sections, section.title).is_none())
+        .map(|section| section.title)
+        .collect();
+
+    if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        return "No changes between the two walkthroughs.".to_string();
+    }
+
+    let mut output = String::new();
+    if !added.is_empty() {
+        output.push_str("Added sections:\n");
+        for title in added {
+            output.push_str(&format!("  {title}\n"));
+        }
+    }
+    if !removed.is_empty() {
+        output.push_str("Removed sections:\n");
+        for title in removed {
+            output.push_str(&format!("  {title}\n"));
+        }
+    }
+    if !changed.is_empty() {
+        output.push_str("Changed sections:\n");
+        for (title, lines) in changed {
+            output.push_str(&format!("  {title}\n"));
+            for line in lines {
+                output.push_str(&format!("    {line}\n"));
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_prune_revisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = WalkthroughHistory::new(dir.path());
+        assert!(history.last_two().unwrap().is_none());
+
+        for i in 1..=MAX_WALKTHROUGH_REVISIONS + 2 {
+            assert_eq!(history.record(&format!("# Revision {i}")).unwrap(), i);
+        }
+
+        let ((previous, old), (latest, new)) = history.last_two().unwrap().unwrap();
+        assert_eq!((previous, latest), (MAX_WALKTHROUGH_REVISIONS + 1, MAX_WALKTHROUGH_REVISIONS + 2));
+        assert_eq!(old, format!("# Revision {previous}"));
+        assert_eq!(new, format!("# Revision {latest}"));
+        assert_eq!(history.revisions().unwrap().len() as u64, MAX_WALKTHROUGH_REVISIONS);
+    }
+
+    #[test]
+    fn test_diff_ignores_headings_in_code_blocks() {
+        let old = "# Intro\n```bash\n# not a heading\n```\n";
+        let new = "# Intro\n```bash\n# still not a heading\n```\n";
+
+        let diff = diff_walkthroughs(old, new);
+        assert!(!diff.contains("Added sections"), "{diff}");
+        assert!(diff.contains("- # not a heading"), "{diff}");
+        assert!(diff.contains("+ # still not a heading"), "{diff}");
+    }
+}
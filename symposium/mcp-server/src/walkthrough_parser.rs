@@ -1,12 +1,13 @@
 use anyhow::Result;
 use pulldown_cmark::{Event, Parser, Tag, TagEnd, html};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::path::Path;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use crate::dialect::DialectInterpreter;
-use crate::ide::{FileRange, IpcClient, SymbolDef};
+use crate::ide::{DocumentSymbol, FileLocation, FileRange, IpcClient, SymbolDef};
 
 /// Location data that can be either a symbol definition or a file range
 /// Uses untagged enum to automatically deserialize from different location formats
@@ -17,6 +18,8 @@ pub enum LocationData {
     SymbolDef(SymbolDef),
     /// File range (from search operations)  
     FileRange(FileRange),
+    /// Outline entry (from documentSymbols)
+    DocumentSymbol(DocumentSymbol),
 }
 
 /// Parsed XML element from walkthrough markdown
@@ -24,6 +27,8 @@ pub enum LocationData {
 pub enum XmlElement {
     Comment {
         location: String,
+        /// Literal `file:` and `lines:` parameters, used instead of `location` when present
+        file_lines: Option<(String, String)>,
         icon: Option<String>,
         content: String,
     },
@@ -31,12 +36,23 @@ pub enum XmlElement {
         range: String,
         exclude_unstaged: bool,
         exclude_staged: bool,
+        /// Also show untracked (but not ignored) files as added
+        include_untracked: bool,
     },
     Action {
         button: String,
         message: String,
+        /// URL or workspace file opened on click instead of messaging the agent
+        open: Option<String>,
+    },
+    ApplyDiff {
+        button: String,
+        /// Unified diff applied to the workspace when the button is clicked
+        patch: String,
     },
     Mermaid {
+        /// Optional `theme:` parameter (e.g., `dark`, `neutral`, `forest`)
+        theme: Option<String>,
         content: String,
     },
 }
@@ -50,11 +66,100 @@ pub struct ResolvedXmlElement {
     pub content: String,
 }
 
+/// A Dialect expression (or gitdiff range) that resolved during a parse
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedReference {
+    pub element_type: String,
+    pub expression: String,
+    /// Number of locations found (0 for scalar values and gitdiffs)
+    pub location_count: usize,
+}
+
+/// A Dialect expression (or gitdiff range) that failed to resolve during a parse
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailedReference {
+    pub element_type: String,
+    pub expression: String,
+    pub error: String,
+}
+
+/// What a parse resolved, for checking a walkthrough without presenting it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// Number of elements of each type (`comment`, `gitdiff`, `action`, ...)
+    pub element_counts: BTreeMap<String, usize>,
+    pub resolved: Vec<ResolvedReference>,
+    pub errors: Vec<FailedReference>,
+}
+
+impl ValidationReport {
+    /// Record the outcome of resolving one element
+    fn record(&mut self, resolved: &ResolvedXmlElement) {
+        let element_type = resolved.element_type.clone();
+        *self.element_counts.entry(element_type.clone()).or_default() += 1;
+
+        let data = &resolved.resolved_data;
+        let expression = data
+            .get("dialect_expression")
+            .or_else(|| data.get("range"))
+            .and_then(|v| v.as_str());
+        let Some(expression) = expression else {
+            // Nothing to resolve (actions, mermaid, comments without a location)
+            return;
+        };
+
+        match data.get("error").and_then(|v| v.as_str()) {
+            Some(error) => self.errors.push(FailedReference {
+                element_type,
+                expression: expression.to_string(),
+                error: error.to_string(),
+            }),
+            None => self.resolved.push(ResolvedReference {
+                element_type,
+                expression: expression.to_string(),
+                location_count: data
+                    .get("locations")
+                    .and_then(|v| v.as_array())
+                    .map_or(0, |locations| locations.len()),
+            }),
+        }
+    }
+}
+
+/// Destination for the HTML of a chunked parse, buffering until a chunk is big enough
+struct ChunkSink {
+    chunks: mpsc::Sender<String>,
+    chunk_bytes: usize,
+    /// Rendered HTML not yet sent
+    pending: String,
+}
+
+impl ChunkSink {
+    /// Send whatever is pending as one chunk
+    async fn flush(&mut self) -> Result<(), anyhow::Error> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.chunks
+            .send(std::mem::take(&mut self.pending))
+            .await
+            .map_err(|_| anyhow::anyhow!("walkthrough chunk receiver closed"))
+    }
+}
+
 /// Main walkthrough parser
 pub struct WalkthroughParser<T: IpcClient + Clone + 'static> {
     interpreter: DialectInterpreter<T>,
     uuid_generator: Box<dyn Fn() -> String + Send + Sync>,
     base_uri: Option<String>,
+    /// Resolved comment locations by Dialect expression, so repeated expressions
+    /// are evaluated once. Cleared at the start of every parse to avoid stale results.
+    location_cache: HashMap<String, serde_json::Value>,
+    /// How many comments in the current parse use each anchor id, so repeated
+    /// locations get distinct `id` attributes. Cleared at the start of every parse.
+    comment_anchors: HashMap<String, usize>,
+    /// Resolution outcomes of the current parse. Cleared at the start of every parse.
+    report: ValidationReport,
 }
 
 impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
@@ -63,6 +168,9 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
             interpreter,
             uuid_generator: Box::new(|| Uuid::new_v4().to_string()),
             base_uri: None,
+            location_cache: HashMap::new(),
+            comment_anchors: HashMap::new(),
+            report: ValidationReport::default(),
         }
     }
 
@@ -80,6 +188,9 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
             interpreter,
             uuid_generator: Box::new(generator),
             base_uri: None,
+            location_cache: HashMap::new(),
+            comment_anchors: HashMap::new(),
+            report: ValidationReport::default(),
         }
     }
 
@@ -89,30 +200,89 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
 
     /// Parse markdown with embedded XML elements and return normalized output
     pub async fn parse_and_normalize(&mut self, content: &str) -> Result<String, anyhow::Error> {
-        let processed_events = self.process_events_sequentially(content).await?;
+        self.location_cache.clear();
+        self.comment_anchors.clear();
+        self.report = ValidationReport::default();
+        let processed_events = self.process_events_sequentially(content, None).await?;
         Self::render_events_to_markdown(processed_events)
     }
 
-    /// Process pulldown-cmark event stream sequentially
+    /// Parse a walkthrough like [`Self::parse_and_normalize`], but report which
+    /// references resolved and which failed instead of returning the HTML
+    pub async fn validate(&mut self, content: &str) -> Result<ValidationReport, anyhow::Error> {
+        self.parse_and_normalize(content).await?;
+        Ok(std::mem::take(&mut self.report))
+    }
+
+    /// Like [`Self::parse_and_normalize`], but send the output to `chunks` as it is
+    /// produced instead of returning it at the end.
+    ///
+    /// Chunks are split between top-level blocks (never inside a paragraph, list, or
+    /// element) once at least `chunk_bytes` of HTML have accumulated, and arrive in
+    /// document order. Concatenated, they match the single-shot output up to
+    /// whitespace between blocks.
+    pub async fn parse_and_normalize_chunked(
+        &mut self,
+        content: &str,
+        chunk_bytes: usize,
+        chunks: mpsc::Sender<String>,
+    ) -> Result<(), anyhow::Error> {
+        self.location_cache.clear();
+        self.comment_anchors.clear();
+        self.report = ValidationReport::default();
+        let mut sink = ChunkSink {
+            chunks,
+            chunk_bytes,
+            pending: String::new(),
+        };
+        let remaining = self.process_events_sequentially(content, Some(&mut sink)).await?;
+        sink.pending.push_str(&Self::render_events_to_markdown(remaining)?);
+        sink.flush().await
+    }
+
+    /// Process pulldown-cmark event stream sequentially, handing each finished
+    /// top-level block to `sink` if there is one
     async fn process_events_sequentially<'a>(
         &mut self,
         content: &'a str,
+        mut sink: Option<&mut ChunkSink>,
     ) -> Result<Vec<Event<'a>>, anyhow::Error> {
         let mut input_events: VecDeque<Event<'a>> = Parser::new(content).collect();
         let mut output_events = Vec::new();
+        let mut depth = 0usize;
 
         while let Some(event) = input_events.pop_front() {
             match event {
                 Event::Start(Tag::CodeBlock(kind)) => {
                     if self.is_special_code_block(&kind) {
+                        // Consumes the whole block, through its end tag
                         self.process_code_block(kind, &mut input_events, &mut output_events)
                             .await?;
                     } else {
+                        depth += 1;
                         output_events.push(Event::Start(Tag::CodeBlock(kind)));
                     }
                 }
+                Event::Start(_) => {
+                    depth += 1;
+                    output_events.push(event);
+                }
+                Event::End(_) => {
+                    depth = depth.saturating_sub(1);
+                    output_events.push(event);
+                }
                 _ => output_events.push(event),
             }
+
+            if let Some(sink) = sink.as_deref_mut() {
+                if depth == 0 && !output_events.is_empty() {
+                    let block = std::mem::take(&mut output_events);
+                    sink.pending.push_str(&Self::render_events_to_markdown(block)?);
+                    if sink.pending.len() >= sink.chunk_bytes {
+                        sink.flush().await?;
+                    }
+                }
+            }
         }
 
         Ok(output_events)
@@ -122,7 +292,10 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
     fn is_special_code_block(&self, kind: &pulldown_cmark::CodeBlockKind) -> bool {
         match kind {
             pulldown_cmark::CodeBlockKind::Fenced(lang) => {
-                matches!(lang.trim(), "mermaid" | "comment" | "gitdiff" | "action")
+                matches!(
+                    lang.trim(),
+                    "mermaid" | "comment" | "gitdiff" | "action" | "applydiff" | "section"
+                )
             }
             _ => false,
         }
@@ -193,9 +366,15 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
             }
         }
 
-        // Parse YAML parameters from content (except for mermaid)
+        // Parse YAML parameters from content. Mermaid diagrams only accept a
+        // `theme:` parameter; anything else is diagram syntax and kept as-is.
         let (params, remaining_content) = if element_type == "mermaid" {
-            (HashMap::new(), content)
+            let (params, remaining_content) = self.parse_yaml_parameters(&content);
+            if !params.is_empty() && params.keys().all(|key| key == "theme") {
+                (params, remaining_content)
+            } else {
+                (HashMap::new(), content)
+            }
         } else {
             self.parse_yaml_parameters(&content)
         };
@@ -203,15 +382,17 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         // Create the appropriate XML element
         match element_type.as_str() {
             "mermaid" => {
-                let xml_element = XmlElement::Mermaid { content: remaining_content };
+                let theme = params.get("theme").cloned();
+                let xml_element = XmlElement::Mermaid { theme, content: remaining_content };
                 let resolved = self.resolve_single_element(xml_element).await?;
                 let html = self.create_mermaid_html(&resolved);
                 output_events.push(Event::InlineHtml(html.into()));
             }
             "comment" => {
                 let location = params.get("location").cloned().unwrap_or_default();
+                let file_lines = params.get("file").cloned().zip(params.get("lines").cloned());
                 let icon = params.get("icon").cloned();
-                let xml_element = XmlElement::Comment { location, icon, content: remaining_content };
+                let xml_element = XmlElement::Comment { location, file_lines, icon, content: remaining_content };
                 let resolved = self.resolve_single_element(xml_element).await?;
                 let html = self.create_comment_html(&resolved);
                 output_events.push(Event::InlineHtml(html.into()));
@@ -220,18 +401,48 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
                 let range = params.get("range").cloned().unwrap_or_default();
                 let exclude_unstaged = params.get("exclude-unstaged").is_some() || params.get("exclude_unstaged").is_some();
                 let exclude_staged = params.get("exclude-staged").is_some() || params.get("exclude_staged").is_some();
-                let xml_element = XmlElement::GitDiff { range, exclude_unstaged, exclude_staged };
+                let include_untracked = params.get("include-untracked").is_some() || params.get("include_untracked").is_some();
+                let xml_element = XmlElement::GitDiff { range, exclude_unstaged, exclude_staged, include_untracked };
                 let resolved = self.resolve_single_element(xml_element).await?;
                 let html = self.create_gitdiff_html(&resolved);
                 output_events.push(Event::InlineHtml(html.into()));
             }
             "action" => {
                 let button = params.get("button").cloned().unwrap_or("Action".to_string());
-                let xml_element = XmlElement::Action { button, message: remaining_content };
+                let open = params.get("open").cloned();
+                let xml_element = XmlElement::Action { button, message: remaining_content, open };
                 let resolved = self.resolve_single_element(xml_element).await?;
                 let html = self.create_action_html(&resolved);
                 output_events.push(Event::InlineHtml(html.into()));
             }
+            "applydiff" => {
+                let button = params.get("button").cloned().unwrap_or("Apply diff".to_string());
+                let mut patch = remaining_content;
+                if !patch.ends_with('\n') {
+                    patch.push('\n');
+                }
+                let xml_element = XmlElement::ApplyDiff { button, patch };
+                let resolved = self.resolve_single_element(xml_element).await?;
+                let html = self.create_applydiff_html(&resolved);
+                output_events.push(Event::InlineHtml(html.into()));
+            }
+            "section" => {
+                let title = params.get("title").cloned().unwrap_or("Details".to_string());
+                let open = params.get("open").is_some_and(|value| value == "true");
+                // The body is walkthrough markdown in its own right, so comments, diffs
+                // and further sections inside it are processed like top-level ones
+                let events = Box::pin(self.process_events_sequentially(&remaining_content, None)).await?;
+                let body = Self::render_events_to_markdown(events)?;
+                *self.report.element_counts.entry(element_type.clone()).or_default() += 1;
+                let html = format!(
+                    r#"<details class="walkthrough-section"{}><summary>{}</summary>
+{}</details>"#,
+                    if open { " open" } else { "" },
+                    escape_html(&title),
+                    body
+                );
+                output_events.push(Event::InlineHtml(html.into()));
+            }
             _ => {
                 // Unknown element type, shouldn't happen
                 return Ok(());
@@ -256,6 +467,7 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         let (element_type, attributes, resolved_data) = match &element {
             XmlElement::Comment {
                 location,
+                file_lines,
                 icon,
                 content: _,
             } => {
@@ -264,8 +476,23 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
                     attrs.insert("icon".to_string(), icon.clone());
                 }
 
-                // Resolve Dialect expression for location
-                let resolved_data = if !location.is_empty() {
+                // A literal file and line range resolves directly, without the interpreter
+                let resolved_data = if let Some((file, lines)) = file_lines {
+                    let expression = format!("{file}:{lines}");
+                    match parse_line_range(lines) {
+                        Some((start, end)) => serde_json::json!({
+                            "locations": [literal_file_range(file, start, end)],
+                            "dialect_expression": expression
+                        }),
+                        None => serde_json::json!({
+                            "error": format!("Invalid line range `{lines}`, expected `N` or `N-M`"),
+                            "dialect_expression": expression
+                        }),
+                    }
+                } else if let Some(cached) = self.location_cache.get(location) {
+                    cached.clone()
+                } else if !location.is_empty() {
+                    // Resolve Dialect expression for location
                     // Clone interpreter for thread safety
                     let mut interpreter = self.interpreter.clone();
                     let location_clone = location.clone();
@@ -277,7 +504,17 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
                     .await
                     .map_err(|e| anyhow::anyhow!("Task execution failed: {}", e))?;
 
-                    match result {
+                    let resolved_data = match result {
+                        // A scalar (e.g. from `count(...)`) is a value to show, not a location
+                        Ok(value @ (serde_json::Value::Number(_)
+                        | serde_json::Value::String(_)
+                        | serde_json::Value::Bool(_))) => {
+                            serde_json::json!({
+                                "locations": [],
+                                "value": value,
+                                "dialect_expression": location
+                            })
+                        }
                         Ok(result) => {
                             serde_json::json!({
                                 "locations": result,
@@ -290,7 +527,10 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
                                 "dialect_expression": location
                             })
                         }
-                    }
+                    };
+                    self.location_cache
+                        .insert(location.clone(), resolved_data.clone());
+                    resolved_data
                 } else {
                     serde_json::json!({
                         "locations": []
@@ -303,15 +543,23 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
                 range,
                 exclude_unstaged,
                 exclude_staged,
+                include_untracked,
             } => {
                 // Use GitService to generate actual file changes
                 use crate::git::GitService;
 
-                let resolved_data = match GitService::new(".") {
+                let resolved_data = match GitService::for_workspace() {
                     Ok(git_service) => {
-                        match git_service.parse_commit_range(range).and_then(
-                            |(base_oid, head_oid)| git_service.generate_diff(base_oid, head_oid),
-                        ) {
+                        match git_service.parse_commit_range(range).and_then(|(base_oid, head_oid)| {
+                            let mut file_changes = git_service.generate_diff(base_oid, head_oid)?;
+                            if *include_untracked {
+                                // Untracked files replace the bare entries a working tree diff has for them
+                                let untracked = git_service.untracked_file_changes()?;
+                                file_changes.retain(|change| !untracked.iter().any(|u| u.path == change.path));
+                                file_changes.extend(untracked);
+                            }
+                            Ok(file_changes)
+                        }) {
                             Ok(file_changes) => {
                                 serde_json::json!({
                                     "type": "gitdiff",
@@ -346,12 +594,18 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
                 if *exclude_staged {
                     attrs.insert("exclude-staged".to_string(), "true".to_string());
                 }
+                if *include_untracked {
+                    attrs.insert("include-untracked".to_string(), "true".to_string());
+                }
 
                 ("gitdiff".to_string(), attrs, resolved_data)
             }
-            XmlElement::Action { button, message: _ } => {
+            XmlElement::Action { button, message: _, open } => {
                 let mut attrs = HashMap::new();
                 attrs.insert("button".to_string(), button.clone());
+                if let Some(open) = open {
+                    attrs.insert("open".to_string(), open.clone());
+                }
 
                 let resolved_data = serde_json::json!({
                     "button_text": button
@@ -359,8 +613,27 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
 
                 ("action".to_string(), attrs, resolved_data)
             }
-            XmlElement::Mermaid { content: _ } => {
-                let attrs = HashMap::new();
+            XmlElement::ApplyDiff { button, patch: _ } => {
+                let mut attrs = HashMap::new();
+                attrs.insert("button".to_string(), button.clone());
+
+                // The extension echoes this back so only our server applies the patch
+                let workspace_root = crate::workspace_dir::workspace_root()
+                    .map(|root| root.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                attrs.insert("workspace-root".to_string(), workspace_root);
+
+                let resolved_data = serde_json::json!({
+                    "button_text": button
+                });
+
+                ("applydiff".to_string(), attrs, resolved_data)
+            }
+            XmlElement::Mermaid { theme, content: _ } => {
+                let mut attrs = HashMap::new();
+                if let Some(theme) = theme {
+                    attrs.insert("theme".to_string(), theme.clone());
+                }
                 let resolved_data = serde_json::json!({
                     "type": "mermaid",
                     "rendered": true
@@ -373,16 +646,19 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         let content = match &element {
             XmlElement::Comment { content, .. } => content.clone(),
             XmlElement::Action { message, .. } => message.clone(),
-            XmlElement::Mermaid { content } => content.clone(),
+            XmlElement::ApplyDiff { patch, .. } => patch.clone(),
+            XmlElement::Mermaid { content, .. } => content.clone(),
             XmlElement::GitDiff { .. } => String::new(),
         };
 
-        Ok(ResolvedXmlElement {
+        let resolved = ResolvedXmlElement {
             element_type,
             attributes,
             resolved_data,
             content,
-        })
+        };
+        self.report.record(&resolved);
+        Ok(resolved)
     }
 
     /// Generate HTML for comment elements
@@ -439,7 +715,7 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         dialect_expression.to_string()
     }
 
-    fn create_comment_html(&self, resolved: &ResolvedXmlElement) -> String {
+    fn create_comment_html(&mut self, resolved: &ResolvedXmlElement) -> String {
         // Extract and normalize locations from resolved data
         let empty_vec = vec![];
         let raw_locations = resolved
@@ -448,26 +724,28 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
             .and_then(|v| v.as_array())
             .unwrap_or(&empty_vec);
 
-        // Normalize locations to consistent format for webview consumption
-        let mut normalized_locations: Vec<FileRange> = raw_locations
+        // Normalize locations to consistent format for webview consumption,
+        // remembering the symbol kind (if any) of each for display
+        let (mut normalized_locations, location_kinds): (Vec<FileRange>, Vec<Option<String>>) = raw_locations
             .iter()
             .filter_map(|loc| {
                 // Try to deserialize as LocationData using untagged enum
                 match serde_json::from_value::<LocationData>(loc.clone()) {
-                    Ok(LocationData::FileRange(r)) => Some(r),
-                    Ok(LocationData::SymbolDef(d)) => Some(d.defined_at),
+                    Ok(LocationData::FileRange(r)) => Some((r, None)),
+                    Ok(LocationData::SymbolDef(d)) => Some((d.defined_at, d.kind)),
+                    Ok(LocationData::DocumentSymbol(s)) => Some((s.range, s.kind)),
 
                     // if deserialization files, ignore, but we should really do something else
                     Err(_) => None,
                 }
             })
-            .collect();
+            .unzip();
 
         // Convert paths to resolve if base-uri provided 
         if let Some(base_uri) = &self.base_uri {
-            if let Ok(base_uri) = Path::new(base_uri).canonicalize() {
+            if let Ok(base_uri) = crate::workspace_dir::absolute_path(Path::new(base_uri)) {
                 for l in &mut normalized_locations {
-                    if let Ok(abs_path) = std::path::Path::new(&l.path).canonicalize() {
+                    if let Ok(abs_path) = crate::workspace_dir::absolute_path(Path::new(&l.path)) {
                         if let Ok(rel_path) = abs_path.strip_prefix(&base_uri) {
                             l.path = rel_path.to_string_lossy().to_string();
                         }
@@ -476,11 +754,29 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
             }
         }
 
+        // Stable anchor so the webview can scroll to a comment by file and line;
+        // the random id in the comment data remains the click handler's key
+        let anchor_attr = match normalized_locations.first() {
+            Some(loc) => format!(r#" id="{}""#, self.comment_anchor(&loc.path, loc.start.line)),
+            None => String::new(),
+        };
+
+        // A location that resolved to a value is displayed, and interpolated
+        // into the comment wherever it says `{value}`
+        let value = resolved.resolved_data.get("value").map(|value| match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        });
+        let content = match &value {
+            Some(value) => resolved.content.replace("{value}", &escape_html(value)),
+            None => resolved.content.clone(),
+        };
+
         // Generate comment data for click handler with normalized locations
         let comment_data = serde_json::json!({
             "id": format!("comment-{}", self.generate_uuid()),
             "locations": normalized_locations,
-            "comment": [&resolved.content]
+            "comment": [&content]
         });
 
         // Get icon from attributes
@@ -504,17 +800,42 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         let formatted_dialect_expression = self.format_dialect_expression(raw_dialect_expression);
 
         // Generate location display using normalized locations
-        let location_display = if normalized_locations.len() == 1 {
+        let location_display = if let Some(value) = &value {
+            format!("= {}", escape_html(value))
+        } else if normalized_locations.len() == 1 {
             // Single location - show file:line with relative path
             let loc = &normalized_locations[0];
             format!("{}:{}", loc.path, loc.start.line)
         } else if normalized_locations.len() > 1 {
-            // Multiple locations - show count
-            format!("({} possible locations) 🔍", normalized_locations.len())
+            // Multiple locations - show count, then each location with its symbol kind
+            let mut display = format!("({} possible locations) 🔍", normalized_locations.len());
+            for (loc, kind) in normalized_locations.iter().zip(&location_kinds) {
+                display.push_str(&format!(
+                    r#"<div class="comment-location" title="{kind}">{icon} {path}:{line}</div>"#,
+                    kind = escape_html(kind.as_deref().unwrap_or("location")),
+                    icon = symbol_kind_icon(kind.as_deref()),
+                    path = escape_html(&loc.path),
+                    line = loc.start.line,
+                ));
+            }
+            display
         } else {
             "no location".to_string()
         };
 
+        // A location expression that failed to evaluate gets an error badge instead of
+        // silently rendering as "no location", so mistakes in the walkthrough are visible
+        let location_html = match resolved.resolved_data.get("error").and_then(|v| v.as_str()) {
+            Some(error) => format!(
+                r#"<div class="comment-error" style="color: var(--vscode-errorForeground); background-color: var(--vscode-inputValidation-errorBackground); border: 1px solid var(--vscode-inputValidation-errorBorder); border-radius: 3px; padding: 4px 6px; margin-bottom: 4px; font-size: 0.9em;">⚠️ {error}<div class="comment-error-expression" style="font-family: var(--vscode-editor-font-family); margin-top: 2px;">{expression}</div></div>"#,
+                error = escape_html(error),
+                expression = escape_html(raw_dialect_expression),
+            ),
+            None => format!(
+                r#"<div class="comment-locations" style="font-weight: 500; color: var(--vscode-textLink-foreground); margin-bottom: 4px; font-family: var(--vscode-editor-font-family); font-size: 0.9em;">{location_display}</div>"#
+            ),
+        };
+
         // Keep them separate for individual div rendering
 
         let comment_data_encoded = serde_json::to_string(&comment_data).unwrap_or_default();
@@ -523,24 +844,58 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         // Build the expression content for the inline div
 
         format!(
-            r#"<div class="comment-item" data-comment="{comment_data_escaped}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
+            r#"<div class="comment-item"{anchor_attr} data-comment="{comment_data_escaped}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
                 <div style="display: flex; align-items: flex-start;">
                     <div class="comment-icon" style="margin-right: 8px; font-size: 16px;">{icon_emoji}</div>
                     <div class="comment-content" style="flex: 1;">
                         <div class="comment-expression" style="display: block; color: var(--vscode-textLink-foreground); font-family: var(--vscode-editor-font-family); font-size: 1.0em; font-weight: 500; margin-bottom: 6px; text-decoration: underline;">{formatted_dialect_expression}</div>
-                        <div class="comment-locations" style="font-weight: 500; color: var(--vscode-textLink-foreground); margin-bottom: 4px; font-family: var(--vscode-editor-font-family); font-size: 0.9em;">{location_display}</div>
+                        {location_html}
                         <div class="comment-text" style="color: var(--vscode-foreground); font-size: 0.9em;">{resolved_content}</div>
                     </div>
                 </div>
             </div>"#,
-            resolved_content = resolved.content
+            resolved_content = content
         )
     }
 
+    /// Deterministic anchor id for a comment at `path:line`, e.g.
+    /// `comment-src-models-rs-10`. Later comments at the same location within
+    /// one walkthrough get a `-2`, `-3`, ... suffix so ids stay unique.
+    fn comment_anchor(&mut self, path: &str, line: u32) -> String {
+        let mut slug = String::new();
+        for c in path.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let anchor = format!("comment-{}-{line}", slug.trim_end_matches('-'));
+
+        let count = self.comment_anchors.entry(anchor.clone()).or_default();
+        *count += 1;
+        match *count {
+            1 => anchor,
+            n => format!("{anchor}-{n}"),
+        }
+    }
+
     /// Generate HTML for action elements
+    ///
+    /// Actions with an `open` param get a `data-open` target that the extension
+    /// opens (a URL externally, a path in the editor); all others message the agent.
     fn create_action_html(&self, resolved: &ResolvedXmlElement) -> String {
         let default_button = "Action".to_string();
         let button_text = resolved.attributes.get("button").unwrap_or(&default_button);
+
+        if let Some(open) = resolved.attributes.get("open") {
+            return format!(
+                r#"<button class="action-button" data-open="{}" style="background-color: var(--vscode-button-background); color: var(--vscode-button-foreground); border: none; padding: 8px 16px; border-radius: 4px; cursor: pointer; margin: 8px 0; font-size: 0.9em;">{}</button>"#,
+                escape_html(open),
+                escape_html(button_text)
+            );
+        }
+
         let tell_agent = resolved.content.replace('"', "&quot;");
 
         format!(
@@ -549,28 +904,150 @@ impl<T: IpcClient + Clone + 'static> WalkthroughParser<T> {
         )
     }
 
+    /// Generate HTML for applydiff elements: the diff itself plus a button that
+    /// asks the MCP server to apply it
+    fn create_applydiff_html(&self, resolved: &ResolvedXmlElement) -> String {
+        let default_button = "Apply diff".to_string();
+        let button_text = resolved.attributes.get("button").unwrap_or(&default_button);
+        let workspace_root = resolved
+            .attributes
+            .get("workspace-root")
+            .map(String::as_str)
+            .unwrap_or_default();
+        let patch = escape_html(&resolved.content);
+
+        format!(
+            r#"<div class="applydiff-container" style="border: 1px solid var(--vscode-panel-border); border-radius: 4px; margin: 8px 0; background-color: var(--vscode-editor-background);"><pre class="applydiff-patch" style="margin: 0; padding: 8px; overflow-x: auto; font-size: 0.85em;">{patch}</pre><button class="applydiff-button" data-patch="{patch}" data-workspace-root="{workspace_root}" style="background-color: var(--vscode-button-background); color: var(--vscode-button-foreground); border: none; padding: 8px 16px; border-radius: 4px; cursor: pointer; margin: 8px; font-size: 0.9em;">{button_text}</button></div>"#,
+            workspace_root = escape_html(workspace_root),
+            button_text = escape_html(button_text),
+        )
+    }
+
     /// Generate HTML for gitdiff elements
     fn create_gitdiff_html(&self, resolved: &ResolvedXmlElement) -> String {
+        // Renamed and binary files get a note: the former would otherwise read as a
+        // plain edit, and the latter have no hunks to render
+        let file_notes: String = resolved
+            .resolved_data
+            .get("files")
+            .cloned()
+            .and_then(|files| serde_json::from_value::<Vec<crate::git::FileChange>>(files).ok())
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|file| {
+                let origin = file.old_path.as_ref().map(|old_path| {
+                    let verb = match file.status {
+                        crate::git::ChangeStatus::Copied => "copied",
+                        _ => "renamed",
+                    };
+                    ("gitdiff-rename", format!("{} from {}", verb, old_path))
+                });
+                let binary = file
+                    .binary_summary()
+                    .map(|summary| ("gitdiff-binary", summary));
+                origin.into_iter().chain(binary).map(move |(class, note)| {
+                    format!(
+                        r#"
+                <div class="{}" style="padding: 4px 12px; color: var(--vscode-descriptionForeground);">{}: {}</div>"#,
+                        class,
+                        escape_html(&file.path),
+                        escape_html(&note)
+                    )
+                })
+            })
+            .collect();
+
         // For now, return a placeholder - we'll implement this properly later
         format!(
             r#"<div class="gitdiff-container" style="border: 1px solid var(--vscode-panel-border); border-radius: 4px; margin: 8px 0; background-color: var(--vscode-editor-background);">
-                <div style="padding: 12px; color: var(--vscode-descriptionForeground);">GitDiff rendering: {}</div>
+                <div style="padding: 12px; color: var(--vscode-descriptionForeground);">GitDiff rendering: {}</div>{}
             </div>"#,
             resolved
                 .resolved_data
                 .get("range")
                 .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
+                .unwrap_or("unknown"),
+            file_notes
         )
     }
 
     /// Generate HTML for mermaid elements
     fn create_mermaid_html(&self, resolved: &ResolvedXmlElement) -> String {
         // Keep mermaid elements as-is for client-side processing
-        format!("<mermaid>{}</mermaid>", resolved.content)
+        match resolved.attributes.get("theme") {
+            Some(theme) => format!(
+                r#"<mermaid data-theme="{}">{}</mermaid>"#,
+                escape_html(theme),
+                resolved.content
+            ),
+            None => format!("<mermaid>{}</mermaid>", resolved.content),
+        }
+    }
+}
+
+/// Parse a `lines:` value, either a single line (`40`) or an inclusive range (`40-55`)
+fn parse_line_range(lines: &str) -> Option<(u32, u32)> {
+    let (start, end) = match lines.split_once('-') {
+        Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+        None => {
+            let line = lines.trim().parse().ok()?;
+            (line, line)
+        }
+    };
+    (start >= 1 && start <= end).then_some((start, end))
+}
+
+/// Build the range for a literal `file:`/`lines:` location. The file is only read
+/// to fill in the range's content; a missing file still yields a usable location.
+fn literal_file_range(path: &str, start: u32, end: u32) -> FileRange {
+    let content = crate::workspace_dir::workspace_root()
+        .and_then(|root| std::fs::read_to_string(root.join(path)));
+    let selected: Option<Vec<String>> = content.ok().map(|content| {
+        content
+            .lines()
+            .skip(start as usize - 1)
+            .take((end - start + 1) as usize)
+            .map(str::to_string)
+            .collect()
+    });
+    let last_column = selected
+        .as_ref()
+        .and_then(|lines| lines.last())
+        .map_or(1, |line| line.len() as u32);
+
+    FileRange {
+        path: path.to_string(),
+        start: FileLocation { line: start, column: 1 },
+        end: FileLocation {
+            line: end,
+            column: last_column,
+        },
+        content: selected.map(|lines| lines.join("\n")),
+    }
+}
+
+/// Small icon for an LSP symbol kind (as reported by the IDE, e.g. "Function" or "struct")
+fn symbol_kind_icon(kind: Option<&str>) -> &'static str {
+    match kind.map(|k| k.to_ascii_lowercase()).as_deref() {
+        Some("function" | "method" | "constructor") => "ƒ",
+        Some("struct" | "class" | "object") => "◆",
+        Some("interface" | "trait") => "◇",
+        Some("enum" | "enummember") => "∈",
+        Some("module" | "namespace" | "package" | "file") => "▤",
+        Some("field" | "property" | "variable" | "constant") => "•",
+        Some("typeparameter") => "τ",
+        _ => "📍",
     }
 }
 
+/// Escape text for interpolation into generated HTML
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -601,7 +1078,7 @@ User struct
 ```
 "#,
             expect![[r#"
-                <div class="comment-item" data-comment="{&quot;comment&quot;:[&quot;User struct&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
+                <div class="comment-item" id="comment-src-models-rs-10" data-comment="{&quot;comment&quot;:[&quot;User struct&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
                                 <div style="display: flex; align-items: flex-start;">
                                     <div class="comment-icon" style="margin-right: 8px; font-size: 16px;">💬</div>
                                     <div class="comment-content" style="flex: 1;">
@@ -614,6 +1091,29 @@ User struct
         );
     }
 
+    #[test]
+    fn test_multiple_locations_show_symbol_kinds() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut parser = create_test_parser();
+        let result = rt
+            .block_on(parser.parse_and_normalize(
+                "```comment\nlocation: findDefinitions(`validateToken`)\n\nTwo candidates\n```\n",
+            ))
+            .unwrap();
+
+        assert!(result.contains("(2 possible locations) 🔍"));
+        assert!(result.contains(r#"<div class="comment-location" title="function">ƒ src/auth.rs:42</div>"#));
+        assert!(result.contains(r#"<div class="comment-location" title="function">ƒ src/utils.rs:15</div>"#));
+    }
+
+    #[test]
+    fn test_symbol_kind_icon() {
+        assert_eq!(symbol_kind_icon(Some("Function")), "ƒ");
+        assert_eq!(symbol_kind_icon(Some("struct")), "◆");
+        assert_eq!(symbol_kind_icon(Some("Trait")), "◇");
+        assert_eq!(symbol_kind_icon(None), "📍");
+    }
+
     #[test]
     fn test_self_closing_gitdiff() {
         check(
@@ -668,7 +1168,7 @@ What should we do next?
             expect![[r#"
                 <h1>My Walkthrough</h1>
                 <p>This is some markdown content.</p>
-                <div class="comment-item" data-comment="{&quot;comment&quot;:[&quot;This explains the User struct&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
+                <div class="comment-item" id="comment-src-models-rs-10" data-comment="{&quot;comment&quot;:[&quot;This explains the User struct&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
                                 <div style="display: flex; align-items: flex-start;">
                                     <div class="comment-icon" style="margin-right: 8px; font-size: 16px;">💡</div>
                                     <div class="comment-content" style="flex: 1;">
@@ -703,7 +1203,7 @@ More text"#,
             expect![[r#"
                 <h1>Title</h1>
                 <p>Some text before</p>
-                <div class="comment-item" data-comment="{&quot;comment&quot;:[&quot;User comment&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
+                <div class="comment-item" id="comment-src-models-rs-10" data-comment="{&quot;comment&quot;:[&quot;User comment&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
                                 <div style="display: flex; align-items: flex-start;">
                                     <div class="comment-icon" style="margin-right: 8px; font-size: 16px;">💬</div>
                                     <div class="comment-content" style="flex: 1;">
@@ -732,7 +1232,7 @@ location:findDefinitions(`User`)
 This has *emphasis* and **bold** text
 ```"#,
             expect![[r#"
-                <div class="comment-item" data-comment="{&quot;comment&quot;:[&quot;This has *emphasis* and **bold** text&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
+                <div class="comment-item" id="comment-src-models-rs-10" data-comment="{&quot;comment&quot;:[&quot;This has *emphasis* and **bold** text&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
                                 <div style="display: flex; align-items: flex-start;">
                                     <div class="comment-icon" style="margin-right: 8px; font-size: 16px;">💬</div>
                                     <div class="comment-content" style="flex: 1;">
@@ -757,11 +1257,12 @@ This has *emphasis* and **bold** text
         assert_eq!(remaining, "This is the content");
         
         // Test boolean flags
-        let content = "range: HEAD~2\nexclude_unstaged: true\nexclude_staged: true\n";
+        let content = "range: HEAD~2\nexclude_unstaged: true\nexclude_staged: true\ninclude-untracked: true\n";
         let (params, remaining) = parser.parse_yaml_parameters(content);
         assert_eq!(params.get("range").unwrap(), "HEAD~2");
         assert_eq!(params.get("exclude_unstaged").unwrap(), "true");
         assert_eq!(params.get("exclude_staged").unwrap(), "true");
+        assert_eq!(params.get("include-untracked").unwrap(), "true");
         assert_eq!(remaining, "");
         
         // Test content only (no parameters)
@@ -794,6 +1295,37 @@ More content here."#;
         assert!(result.contains("</mermaid>"));
     }
 
+    #[tokio::test]
+    async fn test_parse_mermaid_code_block_with_theme() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```mermaid
+theme: dark
+flowchart TD
+    A[Start] --> B[End]
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        assert!(result.contains(r#"<mermaid data-theme="dark">"#));
+        assert!(!result.contains("theme: dark"));
+        assert!(result.contains("flowchart TD"));
+        assert!(result.contains("A[Start] --> B[End]"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_mermaid_code_block_keeps_diagram_colons() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```mermaid
+sequenceDiagram
+    Alice->>Bob: Hello
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        assert!(result.contains("<mermaid>sequenceDiagram"));
+        assert!(result.contains("Alice->>Bob: Hello"));
+    }
+
     #[tokio::test]
     async fn test_parse_comment_code_block_yaml() {
         let mut parser = create_test_parser();
@@ -877,7 +1409,107 @@ More content here."#;
         // Should contain the action HTML element
         assert!(result.contains("action-button"));
         assert!(result.contains("Run Tests"));
-        assert!(result.contains("Should we run the test suite now?"));
+        assert!(result.contains(r#"data-tell-agent="Should we run the test suite now?""#));
+        assert!(!result.contains("data-open"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_action_code_block_open() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```action
+button: Read the docs
+open: https://docs.rs/serde?a=1&b=2
+```
+
+```action
+button: Show parser
+open: src/walkthrough_parser.rs
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        assert!(result.contains(r#"data-open="https://docs.rs/serde?a=1&amp;b=2""#));
+        assert!(result.contains(r#"data-open="src/walkthrough_parser.rs""#));
+        assert!(result.contains(">Read the docs</button>"));
+        assert!(result.contains(">Show parser</button>"));
+        assert!(!result.contains("data-tell-agent"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_applydiff_code_block() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```applydiff
+button: Use the new name
+
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-pub fn old() {}
++pub fn new() {}
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        assert!(result.contains(r#"class="applydiff-button""#));
+        assert!(result.contains(">Use the new name</button>"));
+        assert!(!result.contains("button: Use the new name"));
+        // The patch is shown to the user and carried, escaped, on the button
+        assert!(result.contains("-pub fn old() {}\n+pub fn new() {}\n</pre>"));
+        assert!(result.contains(r#"data-patch="--- a/src/lib.rs"#));
+        let workspace_root = crate::workspace_dir::workspace_root().unwrap();
+        assert!(result.contains(&format!(
+            r#"data-workspace-root="{}""#,
+            workspace_root.display()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_parse_and_normalize_chunked() {
+        let markdown = "# Overview\n\nFirst paragraph.\n\n- one\n- two\n\n```comment\nlocation: findDefinitions(`User`)\n\nUser struct\n```\n\nLast paragraph.\n";
+
+        let single = create_test_parser().parse_and_normalize(markdown).await.unwrap();
+
+        // A tiny chunk size sends each top-level block as soon as it is done
+        let (tx, mut rx) = mpsc::channel(16);
+        create_test_parser()
+            .parse_and_normalize_chunked(markdown, 1, tx)
+            .await
+            .unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            chunks.push(chunk);
+        }
+
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks[2].starts_with("<ul>") && chunks[2].contains("</ul>"), "{}", chunks[2]);
+        assert!(chunks[3].contains(r#"id="comment-src-models-rs-10""#));
+        assert_eq!(chunks.concat().replace('\n', ""), single.replace('\n', ""));
+
+        // A large chunk size sends everything at once
+        let (tx, mut rx) = mpsc::channel(16);
+        create_test_parser()
+            .parse_and_normalize_chunked(markdown, usize::MAX, tx)
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().replace('\n', ""), single.replace('\n', ""));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_parse_applydiff_default_button() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```applydiff
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-Hello
++Hello, world
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        assert!(result.contains(">Apply diff</button>"));
+        assert!(result.contains("+Hello, world\n</pre>"));
     }
 
     #[tokio::test]
@@ -1138,7 +1770,7 @@ The new system passes all core functionality tests and works seamlessly with the
                             </div>
                         </div>
             <p>Comments now use clean YAML parameters:</p>
-            <div class="comment-item" data-comment="{&quot;comment&quot;:[&quot;This explains the User struct&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
+            <div class="comment-item" id="comment-src-models-rs-10" data-comment="{&quot;comment&quot;:[&quot;This explains the User struct&quot;],&quot;id&quot;:&quot;comment-test-uuid&quot;,&quot;locations&quot;:[{&quot;content&quot;:&quot;struct User {&quot;,&quot;end&quot;:{&quot;column&quot;:4,&quot;line&quot;:10},&quot;path&quot;:&quot;src/models.rs&quot;,&quot;start&quot;:{&quot;column&quot;:0,&quot;line&quot;:10}}]}" style="cursor: pointer; border: 1px solid var(--vscode-panel-border); border-radius: 4px; padding: 8px; margin: 8px 0; background-color: var(--vscode-editor-background);">
                             <div style="display: flex; align-items: flex-start;">
                                 <div class="comment-icon" style="margin-right: 8px; font-size: 16px;">💬</div>
                                 <div class="comment-content" style="flex: 1;">
@@ -1170,4 +1802,269 @@ The new system passes all core functionality tests and works seamlessly with the
             <p>🎉 <strong>Triple-tickification is complete and working!</strong></p>
         "#]].assert_eq(&result);
     }
+
+    #[tokio::test]
+    async fn test_comment_location_error_badge() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```comment
+location: unknownFunction(`User`)
+
+This points nowhere
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        // The failed resolution renders as an error badge, not "no location"
+        assert!(result.contains(r#"class="comment-error""#));
+        assert!(result.contains("var(--vscode-errorForeground)"));
+        assert!(result.contains("Failed to resolve location: unknown function: unknownFunction"));
+        assert!(result.contains("unknownFunction(`User`)"));
+        assert!(!result.contains("no location"));
+        assert!(result.contains("This points nowhere"));
+    }
+
+    #[tokio::test]
+    async fn test_comment_value_interpolation() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```comment
+location: count(findReferences(`User`))
+
+`User` is referenced {value} times
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        // The count is displayed instead of a location, and interpolated into the text
+        assert!(result.contains(r#"font-size: 0.9em;">= 2</div>"#), "{result}");
+        assert!(result.contains("`User` is referenced 2 times"));
+        assert!(result.contains("&quot;locations&quot;:[]"));
+        assert!(!result.contains("no location"));
+        assert!(!result.contains("comment-error"));
+    }
+
+    #[tokio::test]
+    async fn test_comment_literal_line_range() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```comment
+file: src/nowhere.rs
+lines: 40-55
+
+This block needs no LSP
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        // Resolves without the interpreter, even though the file doesn't exist
+        assert!(result.contains(r#"class="comment-locations""#));
+        assert!(result.contains(">src/nowhere.rs:40</div>"));
+        assert!(result.contains("&quot;start&quot;:{&quot;column&quot;:1,&quot;line&quot;:40}"));
+        assert!(result.contains("&quot;line&quot;:55"));
+        assert!(result.contains("This block needs no LSP"));
+    }
+
+    #[tokio::test]
+    async fn test_comment_literal_single_line() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```comment
+file: src/nowhere.rs
+lines: 7
+
+Just one line
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+        assert!(result.contains(">src/nowhere.rs:7</div>"));
+        assert!(result.contains("&quot;end&quot;:{&quot;column&quot;:1,&quot;line&quot;:7}"));
+
+        let invalid = parser
+            .parse_and_normalize("```comment\nfile: src/nowhere.rs\nlines: 9-3\n\nBackwards\n```")
+            .await
+            .unwrap();
+        assert!(invalid.contains(r#"class="comment-error""#));
+        assert!(invalid.contains("Invalid line range `9-3`"));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_location_resolved_once() {
+        let client = MockIpcClient::new();
+        let mut interpreter = DialectInterpreter::new(client.clone());
+        interpreter.add_standard_ide_functions();
+        let mut parser = WalkthroughParser::with_uuid_generator(interpreter, || "test-uuid".to_string());
+
+        let markdown = r#"```comment
+location: findDefinitions(`User`)
+
+The User struct
+```
+
+```comment
+location: findDefinitions(`User`)
+
+User again
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+        assert_eq!(result.matches(">src/models.rs:10<").count(), 2, "{result}");
+        assert_eq!(client.resolve_call_count(), 1);
+
+        // The cache does not outlive a parse
+        parser.parse_and_normalize(markdown).await.unwrap();
+        assert_eq!(client.resolve_call_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_comment_anchor_ids() {
+        let mut parser = create_test_parser();
+        let markdown = r#"```comment
+location: findDefinitions(`User`)
+
+First
+```
+
+```comment
+location: findDefinitions(`User`)
+
+Second
+```
+
+```comment
+file: src/My File.rs
+lines: 3
+
+Third
+```"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+        assert!(result.contains(r#"id="comment-src-models-rs-10" data-comment="#));
+        assert!(result.contains(r#"id="comment-src-models-rs-10-2" data-comment="#));
+        assert!(result.contains(r#"id="comment-src-my-file-rs-3" data-comment="#));
+        // The random id used for click handling is still in the payload
+        assert!(result.contains("&quot;id&quot;:&quot;comment-test-uuid&quot;"));
+
+        // Anchors are deterministic across parses
+        let again = parser.parse_and_normalize(markdown).await.unwrap();
+        assert_eq!(result, again);
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_resolution_outcomes() {
+        let mut parser = create_test_parser();
+        let markdown = r#"# Auth
+
+```comment
+location: findDefinitions(`User`)
+
+User struct
+```
+
+```comment
+location: unknownFunction(`User`)
+
+Broken
+```
+
+```comment
+file: src/auth.rs
+lines: 5-2
+
+Backwards range
+```
+
+```action
+button: Next
+
+Show me more
+```
+
+```mermaid
+graph TD
+  A --> B
+```
+"#;
+
+        let report = parser.validate(markdown).await.unwrap();
+
+        let counts: Vec<_> = report.element_counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(counts, [("action", 1), ("comment", 3), ("mermaid", 1)]);
+        assert_eq!(
+            report.resolved,
+            [ResolvedReference {
+                element_type: "comment".to_string(),
+                expression: "findDefinitions(`User`)".to_string(),
+                location_count: 1,
+            }]
+        );
+
+        let errors: Vec<_> = report.errors.iter().map(|e| e.expression.as_str()).collect();
+        assert_eq!(errors, ["unknownFunction(`User`)", "src/auth.rs:5-2"]);
+        assert!(report.errors[0].error.contains("unknown function: unknownFunction"));
+        assert!(report.errors[1].error.contains("Invalid line range"));
+
+        // Each parse starts a fresh report
+        let report = parser.validate("Just text").await.unwrap();
+        assert_eq!(report, ValidationReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_section_with_comment() {
+        let mut parser = create_test_parser();
+        let markdown = r#"Intro
+
+````section
+title: Implementation <details>
+
+The hidden part
+
+```comment
+location: findDefinitions(`User`)
+
+The User struct
+```
+````
+
+Outro"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        assert!(
+            result.contains(
+                r#"<details class="walkthrough-section"><summary>Implementation &lt;details&gt;</summary>"#
+            ),
+            "{result}"
+        );
+        // The body is processed as markdown, comment included, inside the details element
+        let body = &result[result.find("<summary>").unwrap()..result.find("</details>").unwrap()];
+        assert!(body.contains("<p>The hidden part</p>"), "{result}");
+        assert!(body.contains(r#"id="comment-src-models-rs-10""#), "{result}");
+        assert!(body.contains("The User struct"));
+        assert!(result.contains("<p>Outro</p>"));
+
+        let report = parser.validate(markdown).await.unwrap();
+        let counts: Vec<_> = report.element_counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(counts, [("comment", 1), ("section", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_nested_sections() {
+        let mut parser = create_test_parser();
+        let markdown = r#"`````section
+title: Outer
+open: true
+
+Outer text
+
+````section
+title: Inner
+
+Inner text
+````
+`````"#;
+
+        let result = parser.parse_and_normalize(markdown).await.unwrap();
+
+        let outer = result.find(r#"<details class="walkthrough-section" open><summary>Outer</summary>"#);
+        let inner = result.find(r#"<details class="walkthrough-section"><summary>Inner</summary>"#);
+        assert!(outer.unwrap() < inner.unwrap(), "{result}");
+        assert!(result.contains("<p>Inner text</p>\n</details></details>"), "{result}");
+    }
 }
@@ -1,6 +1,53 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-/// Get the current working directory
+/// Directory given with `--workspace`, used instead of the process working directory
+static OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` as the working directory from now on, whatever the process CWD is.
+/// Fails if `dir` is not an existing directory or an override was already set.
+pub fn set_override(dir: &Path) -> std::io::Result<()> {
+    let dir = dir.canonicalize()?;
+    if !dir.is_dir() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotADirectory,
+            format!("{} is not a directory", dir.display()),
+        ));
+    }
+    OVERRIDE.set(dir).map_err(|_| {
+        std::io::Error::new(std::io::ErrorKind::AlreadyExists, "workspace override already set")
+    })
+}
+
+/// Get the current working directory, or the `--workspace` override if one was set
 pub fn current_dir() -> std::io::Result<PathBuf> {
-    std::env::current_dir()
+    match OVERRIDE.get() {
+        Some(dir) => Ok(dir.clone()),
+        None => std::env::current_dir(),
+    }
+}
+
+/// Get the canonical workspace root: the working directory of the enclosing git
+/// repository, or the current directory when not inside one
+pub fn workspace_root() -> std::io::Result<PathBuf> {
+    let current = current_dir()?;
+    let root = git2::Repository::discover(&current)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+        .unwrap_or(current);
+    root.canonicalize()
+}
+
+/// Canonicalize `path`, taking relative paths relative to [`current_dir`]
+pub fn absolute_path(path: &Path) -> std::io::Result<PathBuf> {
+    current_dir()?.join(path).canonicalize()
+}
+
+/// Convert a walkthrough `baseUri` to an absolute path, falling back to the
+/// current directory when it does not exist
+pub fn absolute_base_uri(base_uri: &str) -> String {
+    absolute_path(Path::new(base_uri))
+        .or_else(|_| current_dir())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| base_uri.to_string())
 }
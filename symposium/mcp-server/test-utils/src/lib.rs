@@ -14,8 +14,29 @@ enum RepoAction {
     Append { path: String, content: String },
     /// Add file changes to Git index
     Add { path: String },
+    /// Move a file and stage both sides of the move (like `git mv`)
+    Rename { from: String, to: String },
     /// Create a commit with current staged changes
-    Commit { message: String },
+    Commit {
+        message: String,
+        /// Author and committer, or None for the default test user at the current time
+        author: Option<CommitAuthor>,
+    },
+    /// Create a branch at HEAD without switching to it
+    Branch { name: String },
+    /// Switch HEAD and the working tree to a branch
+    Checkout { name: String },
+    /// Create a lightweight tag at HEAD
+    Tag { name: String },
+}
+
+/// Author identity and timestamp for a commit
+#[derive(Debug, Clone)]
+struct CommitAuthor {
+    name: String,
+    email: String,
+    /// Seconds since the Unix epoch
+    time: i64,
 }
 
 impl TestRepo {
@@ -76,10 +97,58 @@ impl TestRepo {
         self
     }
     
+    /// Move a file and stage the removal and the addition (like `git mv`)
+    pub fn rename_and_add(mut self, from: &str, to: &str) -> Self {
+        self.actions.push(RepoAction::Rename {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+        self
+    }
+    
     /// Create a commit with current staged changes
     pub fn commit(mut self, message: &str) -> Self {
         self.actions.push(RepoAction::Commit {
             message: message.to_string(),
+            author: None,
+        });
+        self
+    }
+    
+    /// Create a commit with current staged changes, authored (and committed) by
+    /// `name <email>` at `time` seconds since the Unix epoch
+    pub fn commit_as(mut self, message: &str, name: &str, email: &str, time: i64) -> Self {
+        self.actions.push(RepoAction::Commit {
+            message: message.to_string(),
+            author: Some(CommitAuthor {
+                name: name.to_string(),
+                email: email.to_string(),
+                time,
+            }),
+        });
+        self
+    }
+    
+    /// Create a branch at the current commit (like `git branch`)
+    pub fn branch(mut self, name: &str) -> Self {
+        self.actions.push(RepoAction::Branch {
+            name: name.to_string(),
+        });
+        self
+    }
+    
+    /// Switch to an existing branch, updating the working tree (like `git checkout`)
+    pub fn checkout(mut self, name: &str) -> Self {
+        self.actions.push(RepoAction::Checkout {
+            name: name.to_string(),
+        });
+        self
+    }
+    
+    /// Tag the current commit (like `git tag`)
+    pub fn tag(mut self, name: &str) -> Self {
+        self.actions.push(RepoAction::Tag {
+            name: name.to_string(),
         });
         self
     }
@@ -130,7 +199,19 @@ impl TestRepo {
                     index.add_path(std::path::Path::new(&path)).expect("Failed to add file to index");
                     index.write().expect("Failed to write index");
                 }
-                RepoAction::Commit { message } => {
+                RepoAction::Rename { from, to } => {
+                    let to_path = repo_path.join(&to);
+                    if let Some(parent) = to_path.parent() {
+                        fs::create_dir_all(parent).expect("Failed to create parent directories");
+                    }
+                    fs::rename(repo_path.join(&from), &to_path).expect("Failed to rename file");
+                    
+                    let mut index = repo.index().expect("Failed to get index");
+                    index.remove_path(std::path::Path::new(&from)).expect("Failed to remove file from index");
+                    index.add_path(std::path::Path::new(&to)).expect("Failed to add file to index");
+                    index.write().expect("Failed to write index");
+                }
+                RepoAction::Commit { message, author } => {
                     let mut index = repo.index().expect("Failed to get index");
                     let tree_id = index.write_tree().expect("Failed to write tree");
                     let tree = repo.find_tree(tree_id).expect("Failed to find tree");
@@ -146,6 +227,16 @@ impl TestRepo {
                         vec![]
                     };
                     
+                    let signature = match author {
+                        Some(author) => git2::Signature::new(
+                            &author.name,
+                            &author.email,
+                            &git2::Time::new(author.time, 0),
+                        )
+                        .expect("Failed to create signature"),
+                        None => signature.clone(),
+                    };
+                    
                     repo.commit(
                         Some("HEAD"),
                         &signature,
@@ -155,6 +246,24 @@ impl TestRepo {
                         &parents,
                     ).expect("Failed to create commit");
                 }
+                RepoAction::Branch { name } => {
+                    let head = repo.head()
+                        .and_then(|head| head.peel_to_commit())
+                        .expect("Cannot branch before the first commit");
+                    repo.branch(&name, &head, false).expect("Failed to create branch");
+                }
+                RepoAction::Checkout { name } => {
+                    let reference = format!("refs/heads/{}", name);
+                    repo.set_head(&reference).expect("Failed to switch branch");
+                    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                        .expect("Failed to check out branch");
+                }
+                RepoAction::Tag { name } => {
+                    let head = repo.head()
+                        .and_then(|head| head.peel(git2::ObjectType::Commit))
+                        .expect("Cannot tag before the first commit");
+                    repo.tag_lightweight(&name, &head, false).expect("Failed to create tag");
+                }
             }
         }
         
@@ -1,4 +1,4 @@
-use symposium_mcp::git::{GitService, CommentParser};
+use symposium_mcp::git::{GitService, CommentParser, CommentType, group_threads_by_file};
 use test_utils::TestRepo;
 
 #[test]
@@ -142,3 +142,32 @@ fn new_function() {}
     assert!(comment_contents.iter().any(|c| c.contains("added line")), 
             "Should find comments from added lines");
 }
+
+#[test]
+fn test_threads_grouped_by_file() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("README.md", "# Project\n")
+        .commit("Initial commit")
+        .overwrite_and_add("src/b.rs", "// TODO: second file\nfn b() {}\n")
+        .overwrite_and_add("src/a.rs", "// 💡 first comment\nfn a() {}\n// ❓ second comment\n")
+        .commit("Add files with comments")
+        .create();
+
+    let repo_path = temp_dir.path().to_str().unwrap();
+    let git_service = GitService::new(repo_path).unwrap();
+
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD~1..HEAD").unwrap();
+    let file_changes = git_service.generate_diff(base_oid, head_oid).unwrap();
+    let threads = CommentParser::new().parse_file_changes(&file_changes).unwrap();
+    let files = group_threads_by_file(threads);
+
+    assert_eq!(files.len(), 2);
+    let a = files.iter().find(|f| f.file_path == "src/a.rs").unwrap();
+    let lines: Vec<u32> = a.threads.iter().map(|t| t.line_number).collect();
+    assert_eq!(lines, vec![1, 3]);
+    assert_eq!(a.threads[1].comment_type, CommentType::Question);
+    assert!(a.threads.iter().all(|t| !t.resolved && t.responses.is_empty()));
+
+    let b = files.iter().find(|f| f.file_path == "src/b.rs").unwrap();
+    assert_eq!(b.threads[0].content, "second file");
+}
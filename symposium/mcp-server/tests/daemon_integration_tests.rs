@@ -32,7 +32,7 @@ async fn test_daemon_ensure_running_separate_process() {
     // Test the daemon spawning logic in isolation using the library function
     let test_id = Uuid::new_v4();
     let socket_prefix = format!("symposium-integration-test-{}", test_id);
-    let socket_path = format!("/tmp/{}.sock", socket_prefix);
+    let socket_path = symposium_mcp::constants::daemon_socket_path(&socket_prefix);
 
     // Clean up any existing socket
     let _ = std::fs::remove_file(&socket_path);
@@ -43,7 +43,13 @@ async fn test_daemon_ensure_running_separate_process() {
     // Start daemon with idle timeout (using library function, not separate process)
     let ready_barrier_clone = ready_barrier.clone();
     let daemon_handle = tokio::spawn(async move {
-        run_daemon_with_idle_timeout(&socket_prefix, 30, Some(ready_barrier_clone)).await
+        run_daemon_with_idle_timeout(
+            &socket_prefix,
+            30,
+            None,
+            symposium_mcp::constants::DEFAULT_MESSAGE_HISTORY_LIMIT,
+            Some(ready_barrier_clone),
+        ).await
     });
 
     // Wait for daemon to be ready
@@ -66,6 +72,78 @@ async fn test_daemon_ensure_running_separate_process() {
     daemon_handle.abort();
 }
 
+#[tokio::test]
+async fn test_daemon_sigterm_graceful_shutdown() {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::process::Command;
+    use tokio::time::{timeout, Duration};
+    use uuid::Uuid;
+
+    let socket_prefix = format!("symposium-sigterm-test-{}", Uuid::new_v4());
+    let socket_path = symposium_mcp::constants::daemon_socket_path(&socket_prefix);
+
+    // Run the real binary so the signal is delivered to a separate process
+    let mut daemon = Command::new(env!("CARGO_BIN_EXE_symposium-mcp"))
+        .args(["daemon", "--prefix", &socket_prefix])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("Failed to spawn daemon");
+
+    // Wait for the daemon to announce it is ready
+    let mut stdout = BufReader::new(daemon.stdout.take().unwrap()).lines();
+    timeout(Duration::from_secs(10), async {
+        while let Some(line) = stdout.next_line().await.unwrap() {
+            if line == "DAEMON_READY" {
+                return;
+            }
+        }
+        panic!("Daemon exited before becoming ready");
+    })
+    .await
+    .expect("Daemon did not become ready in time");
+
+    // Connect a client and round-trip a debug command so we know it is subscribed
+    let stream = tokio::net::UnixStream::connect(&socket_path)
+        .await
+        .expect("Should be able to connect to daemon");
+    let (reader, mut writer) = stream.into_split();
+    let mut client = BufReader::new(reader).lines();
+    writer.write_all(b"#debug_stats\n").await.unwrap();
+    timeout(Duration::from_secs(5), client.next_line())
+        .await
+        .expect("Daemon did not answer debug_stats")
+        .unwrap();
+
+    kill(Pid::from_raw(daemon.id().unwrap() as i32), Signal::SIGTERM).unwrap();
+
+    // Connected clients are told to reload before the daemon goes away
+    let notice = timeout(Duration::from_secs(5), client.next_line())
+        .await
+        .expect("No shutdown notice received")
+        .unwrap()
+        .expect("Connection closed without a shutdown notice");
+    assert!(
+        notice.contains("reload_window"),
+        "Expected reload_window notice, got: {}",
+        notice
+    );
+
+    let status = timeout(Duration::from_secs(5), daemon.wait())
+        .await
+        .expect("Daemon did not exit after SIGTERM")
+        .unwrap();
+    assert!(status.success(), "Daemon should exit cleanly, got {}", status);
+    assert!(
+        !std::path::Path::new(&socket_path).exists(),
+        "Daemon should remove its socket file on shutdown"
+    );
+}
+
 // Note: Testing separate process spawning requires more complex integration tests
 // that would need to be run with the actual binary. The above tests verify
 // the core daemon functionality works correctly.
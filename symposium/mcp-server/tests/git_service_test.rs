@@ -0,0 +1,620 @@
+//! Integration tests for GitService repository queries
+
+use symposium_mcp::git::{
+    ChangeStatus, CommentParser, CommentThread, CommentType, GitService, LogFilter,
+    language_for_path, parse_since,
+};
+use test_utils::TestRepo;
+
+#[test]
+fn test_uncommitted_file_count() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\n")
+        .overwrite_and_add("src/main.rs", "fn main() {}\n")
+        .commit("Initial commit")
+        // One staged modification
+        .append_and_add("src/lib.rs", "pub fn b() {}\n")
+        // One unstaged modification
+        .append("src/main.rs", "// unstaged\n")
+        // One untracked file
+        .overwrite("notes.txt", "scratch\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    assert_eq!(git_service.uncommitted_file_count().unwrap(), 3);
+}
+
+#[test]
+fn test_uncommitted_file_count_clean() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("README.md", "# Test\n")
+        .commit("Initial commit")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    assert_eq!(git_service.uncommitted_file_count().unwrap(), 0);
+}
+
+#[test]
+fn test_generate_patch_for_commit_range() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\npub fn b() {}\n")
+        .commit("Initial commit")
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\npub fn c() {}\n")
+        .commit("Replace b with c")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD~1..HEAD").unwrap();
+    let patch = git_service
+        .generate_patch(base_oid, head_oid, false, false)
+        .unwrap();
+
+    assert!(patch.contains("--- a/src/lib.rs"), "patch: {patch}");
+    assert!(patch.contains("+++ b/src/lib.rs"), "patch: {patch}");
+    assert!(patch.contains("@@ -1,2 +1,2 @@"), "patch: {patch}");
+    assert!(patch.contains("-pub fn b() {}\n"), "patch: {patch}");
+    assert!(patch.contains("+pub fn c() {}\n"), "patch: {patch}");
+    assert!(patch.contains(" pub fn a() {}\n"), "patch: {patch}");
+}
+
+#[test]
+fn test_generate_patch_respects_exclude_flags() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("staged.rs", "one\n")
+        .overwrite_and_add("unstaged.rs", "one\n")
+        .commit("Initial commit")
+        .append_and_add("staged.rs", "two\n")
+        .append("unstaged.rs", "two\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD").unwrap();
+
+    let all = git_service.generate_patch(base_oid, head_oid, false, false).unwrap();
+    assert!(all.contains("+++ b/staged.rs"));
+    assert!(all.contains("+++ b/unstaged.rs"));
+
+    let staged_only = git_service.generate_patch(base_oid, head_oid, true, false).unwrap();
+    assert!(staged_only.contains("+++ b/staged.rs"));
+    assert!(!staged_only.contains("unstaged.rs"));
+
+    let unstaged_only = git_service.generate_patch(base_oid, head_oid, false, true).unwrap();
+    assert!(!unstaged_only.contains("b/staged.rs"));
+    assert!(unstaged_only.contains("+++ b/unstaged.rs"));
+}
+
+#[test]
+fn test_generate_diff_detects_language() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("README", "Project\n")
+        .commit("Initial commit")
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\n")
+        .overwrite_and_add("web/app.tsx", "export const App = () => null;\n")
+        .append_and_add("README", "More\n")
+        .commit("Add sources")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD~1..HEAD").unwrap();
+    let file_changes = git_service.generate_diff(base_oid, head_oid).unwrap();
+
+    let language = |path: &str| {
+        file_changes
+            .iter()
+            .find(|change| change.path == path)
+            .unwrap()
+            .language
+            .clone()
+    };
+    assert_eq!(language("src/lib.rs").as_deref(), Some("rust"));
+    assert_eq!(language("web/app.tsx").as_deref(), Some("typescriptreact"));
+    assert_eq!(language("README"), None);
+
+    // The language is part of the JSON handed to walkthrough consumers
+    let json = serde_json::to_value(&file_changes).unwrap();
+    assert!(json.as_array().unwrap().iter().any(|file| file["language"] == "rust"));
+
+    assert_eq!(language_for_path("Cargo.TOML"), Some("toml"));
+    assert_eq!(language_for_path("notes.unknown"), None);
+}
+
+#[test]
+fn test_generate_diff_binary_file() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("logo.png", "\u{89}PNG\0\0\0\rIHDR")
+        .overwrite_and_add("README", "Project\n")
+        .commit("Initial commit")
+        .overwrite_and_add("logo.png", "\u{89}PNG\0\0\0\rIHDR\0\0\0\x10\0\0\0\x10")
+        .append_and_add("README", "More\n")
+        .commit("Grow logo")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD~1..HEAD").unwrap();
+    let file_changes = git_service.generate_diff(base_oid, head_oid).unwrap();
+
+    let logo = file_changes.iter().find(|c| c.path == "logo.png").unwrap();
+    assert!(logo.binary);
+    assert!(logo.hunks.is_empty());
+    assert_eq!((logo.additions, logo.deletions), (0, 0));
+    assert_eq!(logo.size_delta, Some(8));
+    assert_eq!(
+        logo.binary_summary().as_deref(),
+        Some("Binary file changed (+8 bytes)")
+    );
+
+    // Text files alongside it are still diffed line by line
+    let readme = file_changes.iter().find(|c| c.path == "README").unwrap();
+    assert!(!readme.binary);
+    assert_eq!(readme.size_delta, None);
+    assert_eq!(readme.additions, 1);
+}
+
+#[test]
+fn test_generate_diff_detects_renames() {
+    let source = "pub fn alpha() -> u32 {\n    1\n}\n\npub fn beta() -> u32 {\n    2\n}\n\npub fn gamma() -> u32 {\n    3\n}\n";
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a/foo.rs", source)
+        .overwrite_and_add("a/bar.rs", &source.replace("pub fn", "fn"))
+        .commit("Initial commit")
+        .rename_and_add("a/foo.rs", "b/foo.rs")
+        .rename_and_add("a/bar.rs", "b/bar.rs")
+        .append_and_add("b/bar.rs", "\npub fn delta() -> u32 {\n    4\n}\n")
+        .commit("Move sources")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD~1..HEAD").unwrap();
+    let file_changes = git_service.generate_diff(base_oid, head_oid).unwrap();
+
+    // Each move is one change, not a delete plus an add
+    assert_eq!(file_changes.len(), 2, "{:#?}", file_changes);
+
+    let foo = file_changes.iter().find(|c| c.path == "b/foo.rs").unwrap();
+    assert_eq!(foo.status, ChangeStatus::Renamed);
+    assert_eq!(foo.old_path.as_deref(), Some("a/foo.rs"));
+    assert_eq!(foo.similarity, Some(100));
+    assert!(foo.hunks.is_empty());
+
+    // A rename with edits still pairs up, and carries the edit as hunks
+    let bar = file_changes.iter().find(|c| c.path == "b/bar.rs").unwrap();
+    assert_eq!(bar.status, ChangeStatus::Renamed);
+    assert_eq!(bar.old_path.as_deref(), Some("a/bar.rs"));
+    let similarity = bar.similarity.unwrap();
+    assert!((50..100).contains(&similarity), "similarity {similarity}");
+    assert_eq!((bar.additions, bar.deletions), (4, 0));
+}
+
+#[test]
+fn test_commit_staged_changes() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .overwrite_and_add("b.rs", "one\n")
+        .commit("Initial commit")
+        .append_and_add("a.rs", "two\n")
+        .append("b.rs", "two\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let oid = git_service.commit("Checkpoint a", None).unwrap();
+
+    let (head_oid, _) = git_service.parse_commit_range("HEAD").unwrap();
+    assert_eq!(oid, head_oid);
+
+    // Only the staged file is committed; the unstaged edit stays in the working tree
+    let (base_oid, head_oid) = git_service.parse_commit_range("HEAD~1..HEAD").unwrap();
+    let paths: Vec<String> = git_service
+        .generate_diff(base_oid, head_oid)
+        .unwrap()
+        .into_iter()
+        .map(|c| c.path)
+        .collect();
+    assert_eq!(paths, vec!["a.rs"]);
+    assert_eq!(git_service.uncommitted_file_count().unwrap(), 1);
+}
+
+#[test]
+fn test_commit_given_paths() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .commit("Initial commit")
+        .append("a.rs", "two\n")
+        .overwrite("new.rs", "fresh\n")
+        .overwrite("scratch.txt", "notes\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    git_service
+        .commit("Checkpoint", Some(&["a.rs".to_string(), "new.rs".to_string()]))
+        .unwrap();
+
+    assert_eq!(git_service.file_at_revision("a.rs", "HEAD").unwrap(), "one\ntwo\n");
+    assert_eq!(git_service.file_at_revision("new.rs", "HEAD").unwrap(), "fresh\n");
+    assert!(git_service.file_at_revision("scratch.txt", "HEAD").is_err());
+}
+
+#[test]
+fn test_working_tree_file() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .commit("Initial commit")
+        .overwrite("src/new.rs", "fresh\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let file = git_service.working_tree_file("src/new.rs").unwrap();
+    assert_eq!(std::fs::read_to_string(file).unwrap(), "fresh\n");
+    assert!(git_service.working_tree_file("a.rs").is_ok());
+    assert!(git_service.working_tree_file("missing.rs").is_err());
+    assert!(git_service.working_tree_file("src").is_err());
+    assert!(git_service.working_tree_file("../outside.rs").is_err());
+    assert!(git_service.working_tree_file("src/../a.rs").is_err());
+    let absolute = temp_dir.path().join("a.rs");
+    assert!(git_service.working_tree_file(absolute.to_str().unwrap()).is_err());
+}
+
+#[test]
+fn test_commit_refuses_when_nothing_staged() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .commit("Initial commit")
+        .append("a.rs", "unstaged\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let error = git_service.commit("Empty", None).unwrap_err();
+    assert_eq!(error.message(), "Nothing staged to commit");
+
+    // No commit was created
+    assert!(git_service.parse_commit_range("HEAD~1").is_err());
+}
+
+fn thread(file_path: &str, line_number: u32, content: &str, resolved: bool) -> CommentThread {
+    CommentThread {
+        thread_id: format!("{file_path}:{line_number}"),
+        file_path: file_path.to_string(),
+        line_number,
+        comment_type: CommentType::Question,
+        content: content.to_string(),
+        responses: vec![],
+        resolved,
+    }
+}
+
+#[test]
+fn test_todo_patch_writes_language_appropriate_markers() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn a() {\n    let x = 1;\n    x\n}\n")
+        .overwrite_and_add("tool.py", "def main():\n    return 1\n")
+        .overwrite_and_add("data.json", "{}\n")
+        .commit("Initial commit")
+        .create();
+
+    let repo_path = temp_dir.path().to_str().unwrap();
+    let git_service = GitService::new(repo_path).unwrap();
+
+    let todo_patch = git_service
+        .todo_patch(
+            &[
+                thread("src/lib.rs", 3, "Should this be checked?", false),
+                thread("src/lib.rs", 1, "Already settled", true),
+                thread("tool.py", 2, "Return an exit code", false),
+                thread("data.json", 1, "No comments in JSON", false),
+            ],
+            &CommentParser::new(),
+        )
+        .unwrap();
+
+    assert_eq!(todo_patch.files, vec!["src/lib.rs", "tool.py"]);
+    assert_eq!(todo_patch.skipped, vec!["data.json:1"]);
+    git_service.check_patch(&todo_patch.patch).unwrap();
+
+    // Apply the patch as the extension would after the user confirms
+    let repo = git2::Repository::open(repo_path).unwrap();
+    let diff = git2::Diff::from_buffer(todo_patch.patch.as_bytes()).unwrap();
+    repo.apply(&diff, git2::ApplyLocation::WorkDir, None).unwrap();
+
+    let lib = std::fs::read_to_string(temp_dir.path().join("src/lib.rs")).unwrap();
+    assert_eq!(
+        lib,
+        "pub fn a() {\n    let x = 1;\n    // TODO: Should this be checked?\n    x\n}\n"
+    );
+    let tool = std::fs::read_to_string(temp_dir.path().join("tool.py")).unwrap();
+    assert_eq!(tool, "def main():\n    # TODO: Return an exit code\n    return 1\n");
+
+    // The written markers are picked up again as TODO comments at the commented line
+    let threads = CommentParser::new()
+        .parse_file(temp_dir.path().join("src/lib.rs").to_str().unwrap())
+        .unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].line_number, 3);
+    assert_eq!(threads[0].comment_type, CommentType::Todo);
+}
+
+#[test]
+fn test_todo_patch_skips_paths_outside_the_repository() {
+    let parent = tempfile::tempdir().unwrap();
+    std::fs::write(parent.path().join("outside.rs"), "fn outside() {}\n").unwrap();
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\n")
+        .commit("Initial commit")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let outside = parent.path().join("outside.rs");
+    let todo_patch = git_service
+        .todo_patch(
+            &[
+                thread(outside.to_str().unwrap(), 1, "Absolute path", false),
+                thread("../outside.rs", 1, "Parent directory", false),
+                thread("src/../../outside.rs", 1, "Sneaky parent directory", false),
+                thread("src/lib.rs", 1, "Inside", false),
+            ],
+            &CommentParser::new(),
+        )
+        .unwrap();
+
+    assert_eq!(todo_patch.files, vec!["src/lib.rs"]);
+    assert_eq!(todo_patch.skipped.len(), 3);
+}
+
+#[test]
+fn test_file_at_revision() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn old() {}\n")
+        .commit("First version")
+        .overwrite_and_add("src/lib.rs", "pub fn new() {}\n")
+        .overwrite_and_add("src/added.rs", "pub fn later() {}\n")
+        .commit("Second version")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+
+    assert_eq!(git_service.file_at_revision("src/lib.rs", "HEAD~1").unwrap(), "pub fn old() {}\n");
+    assert_eq!(git_service.file_at_revision("src/lib.rs", "HEAD").unwrap(), "pub fn new() {}\n");
+
+    // The file did not exist yet in the first commit
+    let error = git_service.file_at_revision("src/added.rs", "HEAD~1").unwrap_err();
+    assert_eq!(error.message(), "Path 'src/added.rs' does not exist at revision 'HEAD~1'");
+
+    // Directories are not files
+    assert!(git_service.file_at_revision("src", "HEAD").is_err());
+    assert!(git_service.file_at_revision("src/lib.rs", "no-such-ref").is_err());
+}
+
+#[test]
+fn test_apply_patch() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn a() {\n    1\n}\n")
+        .commit("Initial commit")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ pub fn a() {
+-    1
++    2
+ }
+";
+
+    let files = git_service.apply_patch(patch).unwrap();
+    assert_eq!(files, vec!["src/lib.rs"]);
+    let lib = std::fs::read_to_string(temp_dir.path().join("src/lib.rs")).unwrap();
+    assert_eq!(lib, "pub fn a() {\n    2\n}\n");
+
+    // Applying it again conflicts and leaves the working tree untouched
+    assert!(git_service.apply_patch(patch).is_err());
+    let lib = std::fs::read_to_string(temp_dir.path().join("src/lib.rs")).unwrap();
+    assert_eq!(lib, "pub fn a() {\n    2\n}\n");
+}
+
+#[test]
+fn test_parse_commit_range_syntaxes() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .commit("Initial commit")
+        .append_and_add("a.rs", "two\n")
+        .commit("Second commit")
+        .append_and_add("a.rs", "three\n")
+        .commit("Third commit")
+        .create();
+
+    // Branch `feature` off the second commit, diverging from HEAD
+    let repo = git2::Repository::open(temp_dir.path()).unwrap();
+    let oid = |spec: &str| repo.revparse_single(spec).unwrap().peel_to_commit().unwrap().id();
+    let second = repo.find_commit(oid("HEAD~1")).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let feature = repo
+        .commit(None, &signature, &signature, "Feature work", &second.tree().unwrap(), &[&second])
+        .unwrap();
+    repo.branch("feature", &repo.find_commit(feature).unwrap(), false).unwrap();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let resolve = |range: &str| {
+        let (base, head) = git_service.parse_commit_range(range).unwrap();
+        let commits = git_service.commits_in_range(base, head).unwrap();
+        let summaries: Vec<String> = commits.into_iter().map(|c| c.summary).collect();
+        (base, head, summaries)
+    };
+
+    // Two-dot ranges compare the endpoints directly; an empty side is HEAD
+    assert_eq!(
+        resolve("HEAD~2..HEAD"),
+        (oid("HEAD~2"), Some(oid("HEAD")), vec!["Second commit".to_string(), "Third commit".to_string()])
+    );
+    assert_eq!(resolve("HEAD~1.."), (oid("HEAD~1"), Some(oid("HEAD")), vec!["Third commit".to_string()]));
+
+    // Three-dot ranges start from the merge base
+    assert_eq!(resolve("feature..."), (oid("HEAD~1"), Some(oid("HEAD")), vec!["Third commit".to_string()]));
+    assert_eq!(resolve("HEAD...feature"), (oid("HEAD~1"), Some(feature), vec!["Feature work".to_string()]));
+
+    // WORKING is the working tree; its commits end at HEAD
+    assert_eq!(resolve("HEAD~1..WORKING"), (oid("HEAD~1"), None, vec!["Third commit".to_string()]));
+    assert_eq!(resolve("feature...WORKING"), (oid("HEAD~1"), None, vec!["Third commit".to_string()]));
+    assert_eq!(resolve("WORKING"), (oid("HEAD"), None, vec![]));
+    assert_eq!(resolve("HEAD~2"), (oid("HEAD~2"), None, vec!["Second commit".to_string(), "Third commit".to_string()]));
+
+    // WORKING can't be a base, and unknown revisions are reported
+    let error = git_service.parse_commit_range("WORKING..HEAD").unwrap_err();
+    assert!(error.message().contains("can only be the head"), "{error}");
+    assert!(git_service.parse_commit_range("nope..HEAD").is_err());
+    assert!(git_service.parse_commit_range("a..b..c").is_err());
+}
+
+#[test]
+fn test_untracked_file_changes() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add(".gitignore", "target/\n")
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\n")
+        .commit("Initial commit")
+        .append("src/lib.rs", "pub fn b() {}\n")
+        .overwrite("notes/todo.md", "# TODO\n- write docs\n")
+        .overwrite("target/debug.log", "ignored\n")
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let untracked = git_service.untracked_file_changes().unwrap();
+
+    // Untracked directories are listed file by file; ignored and tracked files are left out
+    let paths: Vec<_> = untracked.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, ["notes/todo.md"]);
+
+    let todo = &untracked[0];
+    assert_eq!(todo.status, ChangeStatus::Added);
+    assert_eq!((todo.additions, todo.deletions), (2, 0));
+    assert_eq!(todo.hunks.len(), 1);
+}
+
+/// 2025-01-01T00:00:00Z
+const JAN_1_2025: i64 = 1_735_689_600;
+const DAY: i64 = 24 * 60 * 60;
+
+#[test]
+fn test_log_filters_by_since_and_author() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("src/lib.rs", "pub fn a() {}\n")
+        .commit_as("Add a", "Alice Smith", "alice@example.com", JAN_1_2025)
+        .append_and_add("src/lib.rs", "pub fn b() {}\n")
+        .commit_as("Add b", "Bob Jones", "bob@work.example", JAN_1_2025 + 2 * DAY)
+        .append_and_add("src/lib.rs", "pub fn c() {}\n")
+        .commit_as("Add c", "Alice Smith", "alice@example.com", JAN_1_2025 + 5 * DAY)
+        .create();
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let summaries = |filter: &LogFilter, max_count: usize| -> Vec<String> {
+        git_service
+            .log(filter, max_count)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.summary)
+            .collect()
+    };
+
+    // Unfiltered: newest first, limited by max_count
+    assert_eq!(summaries(&LogFilter::default(), 10), ["Add c", "Add b", "Add a"]);
+    assert_eq!(summaries(&LogFilter::default(), 2), ["Add c", "Add b"]);
+
+    // Since is inclusive
+    let since = LogFilter {
+        since: Some(JAN_1_2025 + 2 * DAY),
+        ..Default::default()
+    };
+    assert_eq!(summaries(&since, 10), ["Add c", "Add b"]);
+
+    // Author matches name or email, ignoring case
+    let by_name = LogFilter {
+        author: Some("alice".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(summaries(&by_name, 10), ["Add c", "Add a"]);
+    let by_email = LogFilter {
+        author: Some("@WORK.".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(summaries(&by_email, 10), ["Add b"]);
+
+    // Filters combine, and max_count counts matches only
+    let combined = LogFilter {
+        since: Some(JAN_1_2025 + DAY),
+        author: Some("alice".to_string()),
+    };
+    assert_eq!(summaries(&combined, 10), ["Add c"]);
+    assert_eq!(summaries(&by_name, 1), ["Add c"]);
+
+    let entry = &git_service.log(&by_email, 1).unwrap()[0];
+    assert_eq!(entry.author, "Bob Jones");
+    assert_eq!(entry.email, "bob@work.example");
+    assert_eq!(entry.date, "2025-01-03T00:00:00+00:00");
+}
+
+#[test]
+fn test_parse_since() {
+    let now = chrono::DateTime::from_timestamp(JAN_1_2025 + 10 * DAY, 0).unwrap();
+
+    assert_eq!(parse_since("2 days ago", now).unwrap(), JAN_1_2025 + 8 * DAY);
+    assert_eq!(parse_since("1 week ago", now).unwrap(), JAN_1_2025 + 3 * DAY);
+    assert_eq!(parse_since("3 hours ago", now).unwrap(), JAN_1_2025 + 10 * DAY - 3 * 60 * 60);
+    assert_eq!(parse_since("yesterday", now).unwrap(), JAN_1_2025 + 9 * DAY);
+    assert_eq!(parse_since("2025-01-03", now).unwrap(), JAN_1_2025 + 2 * DAY);
+    assert_eq!(
+        parse_since("2025-01-03T01:00:00+01:00", now).unwrap(),
+        JAN_1_2025 + 2 * DAY
+    );
+
+    for value in ["last tuesday", "two days ago", "2 fortnights ago", "2025-13-01", ""] {
+        let error = parse_since(value, now).unwrap_err();
+        assert!(error.message().contains("Cannot parse since value"), "{error}");
+    }
+}
+
+#[test]
+fn test_commit_as_sets_author_and_time() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .commit_as("Initial commit", "Alice", "alice@example.com", 1_700_000_000)
+        .create();
+
+    let repo = git2::Repository::open(temp_dir.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.author().name(), Some("Alice"));
+    assert_eq!(head.author().email(), Some("alice@example.com"));
+    assert_eq!(head.committer().name(), Some("Alice"));
+    assert_eq!(head.time().seconds(), 1_700_000_000);
+}
+
+#[test]
+fn test_ranges_between_branches_and_tags() {
+    let temp_dir = TestRepo::new()
+        .overwrite_and_add("a.rs", "one\n")
+        .commit("Initial commit")
+        .tag("v1")
+        .branch("trunk")
+        .branch("feature")
+        .checkout("feature")
+        .overwrite_and_add("feature.rs", "feature\n")
+        .commit("Feature work")
+        .checkout("trunk")
+        .append_and_add("a.rs", "two\n")
+        .commit("Trunk work")
+        .create();
+
+    // Checking out trunk removed the feature branch's file from the working tree
+    assert!(!temp_dir.path().join("feature.rs").exists());
+
+    let git_service = GitService::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let summaries = |range: &str| -> Vec<String> {
+        let (base, head) = git_service.parse_commit_range(range).unwrap();
+        let commits = git_service.commits_in_range(base, head).unwrap();
+        commits.into_iter().map(|c| c.summary).collect()
+    };
+
+    assert_eq!(summaries("v1..feature"), ["Feature work"]);
+    assert_eq!(summaries("v1..trunk"), ["Trunk work"]);
+    assert_eq!(summaries("trunk...feature"), ["Feature work"]);
+    assert_eq!(summaries("feature..HEAD"), ["Trunk work"]);
+}
@@ -28,6 +28,7 @@ fn test_spawn_taskspace_payload_serialization() {
         task_description: "Test task description".to_string(),
         initial_prompt: "Test initial prompt".to_string(),
         collaborator: Some("sparkle".to_string()),
+        tags: vec![],
     };
 
     // Should serialize and deserialize correctly
@@ -41,6 +42,45 @@ fn test_spawn_taskspace_payload_serialization() {
     assert_eq!(payload.initial_prompt, deserialized.initial_prompt);
 }
 
+#[test]
+fn test_taskspace_tags_serialization() {
+    let payload = SpawnTaskspacePayload {
+        project_path: "/path/to/project".to_string(),
+        taskspace_uuid: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+        name: "login-ui".to_string(),
+        task_description: "Build the login form".to_string(),
+        initial_prompt: "Start with the form layout".to_string(),
+        collaborator: None,
+        tags: vec!["oauth-rewrite".to_string()],
+    };
+    let json = serde_json::to_value(&payload).unwrap();
+    assert_eq!(json["tags"], serde_json::json!(["oauth-rewrite"]));
+
+    // Untagged spawns and reads leave the field out, so older apps see the same messages
+    let untagged = SpawnTaskspacePayload { tags: vec![], ..payload };
+    assert!(serde_json::to_value(&untagged).unwrap().get("tags").is_none());
+
+    let request = TaskspaceStateRequest {
+        project_path: "/path/to/project".to_string(),
+        taskspace_uuid: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+        name: None,
+        description: None,
+        collaborator: None,
+        tags: None,
+    };
+    assert!(serde_json::to_value(&request).unwrap().get("tags").is_none());
+
+    // Responses from apps that predate tags have none
+    let response: TaskspaceStateResponse = serde_json::from_value(serde_json::json!({
+        "name": "login-ui",
+        "description": "Build the login form",
+        "initial_prompt": null,
+        "collaborator": null
+    }))
+    .unwrap();
+    assert!(response.tags.is_empty());
+}
+
 #[test]
 fn test_delete_taskspace_payload_serialization() {
     let payload = DeleteTaskspacePayload {
@@ -56,6 +96,32 @@ fn test_delete_taskspace_payload_serialization() {
     assert_eq!(payload.taskspace_uuid, deserialized.taskspace_uuid);
 }
 
+#[test]
+fn test_prepare_for_deletion_serialization() {
+    let payload = PrepareForDeletionPayload {
+        project_path: "/path/to/project".to_string(),
+        taskspace_uuid: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+        uncommitted_file_count: 3,
+    };
+
+    let json = serde_json::to_string(&payload).expect("Failed to serialize");
+    let deserialized: PrepareForDeletionPayload = serde_json::from_str(&json).expect("Failed to deserialize");
+    assert_eq!(payload.uncommitted_file_count, deserialized.uncommitted_file_count);
+
+    // Warnings are optional in the reply
+    let response: PrepareForDeletionResponse = serde_json::from_str(
+        r#"{"safe_to_delete": false, "uncommitted_file_count": 2}"#,
+    ).expect("Failed to deserialize");
+    assert!(!response.safe_to_delete);
+    assert!(response.warnings.is_empty());
+    assert_eq!(response.uncommitted_file_count, 2);
+
+    assert_eq!(
+        serde_json::to_string(&IPCMessageType::PrepareForDeletion).unwrap(),
+        "\"prepare_for_deletion\""
+    );
+}
+
 #[test]
 fn test_log_progress_payload_serialization() {
     let payload = LogProgressPayload {
@@ -63,6 +129,7 @@ fn test_log_progress_payload_serialization() {
         taskspace_uuid: "550e8400-e29b-41d4-a716-446655440000".to_string(),
         message: "Test progress message".to_string(),
         category: ProgressCategory::Milestone,
+        fields: Default::default(),
     };
 
     // Should serialize and deserialize correctly
@@ -73,6 +140,36 @@ fn test_log_progress_payload_serialization() {
     assert_eq!(payload.taskspace_uuid, deserialized.taskspace_uuid);
     assert_eq!(payload.message, deserialized.message);
     assert!(matches!(deserialized.category, ProgressCategory::Milestone));
+
+    // Empty fields are left off the wire
+    assert!(!json.contains("fields"));
+}
+
+#[test]
+fn test_log_progress_payload_fields() {
+    let payload = LogProgressPayload {
+        project_path: "/path/to/project".to_string(),
+        taskspace_uuid: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+        message: "Migrating modules".to_string(),
+        category: ProgressCategory::Info,
+        fields: [("completed", "3"), ("total", "10")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    };
+
+    let json = serde_json::to_value(&payload).expect("Failed to serialize");
+    assert_eq!(json["fields"], serde_json::json!({"completed": "3", "total": "10"}));
+
+    // Messages from senders that predate fields still deserialize
+    let legacy: LogProgressPayload = serde_json::from_value(serde_json::json!({
+        "project_path": "/path/to/project",
+        "taskspace_uuid": "550e8400-e29b-41d4-a716-446655440000",
+        "message": "Working",
+        "category": "info"
+    }))
+    .expect("Failed to deserialize");
+    assert!(legacy.fields.is_empty());
 }
 
 #[test]
@@ -0,0 +1,31 @@
+//! Integration test for the `--workspace` override. The override is process-wide,
+//! so it lives in its own test binary where it cannot affect other tests.
+
+use symposium_mcp::workspace_dir::{absolute_base_uri, current_dir, set_override, workspace_root};
+
+#[test]
+fn test_workspace_override_flows_into_base_uri() {
+    let workspace = tempfile::tempdir().unwrap();
+    std::fs::create_dir(workspace.path().join("docs")).unwrap();
+    std::fs::write(workspace.path().join("README.md"), "# Project\n").unwrap();
+    let root = workspace.path().canonicalize().unwrap();
+
+    // Only existing directories are accepted
+    assert!(set_override(&workspace.path().join("missing")).is_err());
+    assert!(set_override(&workspace.path().join("README.md")).is_err());
+    assert_ne!(current_dir().unwrap(), root);
+
+    set_override(workspace.path()).unwrap();
+    assert_eq!(current_dir().unwrap(), root);
+    assert_eq!(workspace_root().unwrap(), root);
+
+    // Relative base URIs resolve against the workspace, not the process CWD
+    let root_uri = root.to_string_lossy().to_string();
+    assert_eq!(absolute_base_uri("."), root_uri);
+    assert_eq!(absolute_base_uri("docs"), root.join("docs").to_string_lossy());
+    assert_eq!(absolute_base_uri("no/such/dir"), root_uri);
+
+    // The override is fixed once set
+    assert!(set_override(&root.join("docs")).is_err());
+    assert_eq!(current_dir().unwrap(), root);
+}
@@ -0,0 +1,59 @@
+//! Integration test running tools through an MCP client with the `--workspace`
+//! override set. The override is process-wide, so it lives in its own test binary.
+
+use rmcp::model::CallToolRequestParam;
+use rmcp::service::{RoleClient, RunningService};
+use rmcp::ServiceExt;
+use symposium_mcp::SymposiumServer;
+use test_utils::TestRepo;
+
+/// Call `tool` and parse its text result as JSON
+async fn call_tool(
+    client: &RunningService<RoleClient, ()>,
+    tool: &'static str,
+    arguments: serde_json::Value,
+) -> serde_json::Value {
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: tool.into(),
+            arguments: arguments.as_object().cloned(),
+        })
+        .await
+        .unwrap();
+    let text = &result.content[0].as_text().unwrap().text;
+    serde_json::from_str(text).unwrap()
+}
+
+#[tokio::test]
+async fn test_tools_use_the_workspace_override() {
+    let workspace = TestRepo::new()
+        .overwrite_and_add("README.md", "# Override workspace\n")
+        .commit("Commit in the override workspace")
+        .create();
+    symposium_mcp::workspace_dir::set_override(workspace.path()).unwrap();
+
+    let (server_transport, client_transport) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        SymposiumServer::new_test().serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await.unwrap();
+
+    // The process runs in this crate, so the log only shows the override's commit
+    // if the tool opens the repository at the workspace root rather than "."
+    let log = call_tool(&client, "get_git_log", serde_json::json!({})).await;
+    assert_eq!(log["commit_count"], 1);
+    assert_eq!(log["commits"][0]["summary"], "Commit in the override workspace");
+
+    // Dialect reads workspace-relative files from the override too
+    let range = call_tool(
+        &client,
+        "ide_operation",
+        serde_json::json!({ "program": r#"lines("README.md", 1, 1)"# }),
+    )
+    .await;
+    assert_eq!(range["path"], "README.md");
+    assert_eq!(range["content"], "# Override workspace");
+
+    client.cancel().await.unwrap();
+}
@@ -146,9 +146,13 @@ interface FileRange {
 interface FileChange {
     path: string;
     status: string;
+    old_path?: string;
+    similarity?: number;
     additions: number;
     deletions: number;
     hunks: DiffHunk[];
+    binary?: boolean;
+    size_delta?: number;
 }
 
 interface DiffHunk {
@@ -37,6 +37,19 @@ interface FindReferencesPayload {
     symbol: SymbolDef;
 }
 
+interface RenamePreviewPayload {
+    symbol: SymbolDef;
+    new_name: string;
+}
+
+interface DocumentSymbolsPayload {
+    path: string;
+}
+
+interface DefinitionAtPayload {
+    location: FileRange;
+}
+
 interface ResponsePayload {
     success: boolean;
     data?: any;
@@ -53,6 +66,72 @@ interface PresentWalkthroughPayload {
     baseUri: string;
 }
 
+interface PresentWalkthroughBeginPayload {
+    walkthrough_id: string;
+    baseUri: string;
+}
+
+interface PresentWalkthroughChunkPayload {
+    walkthrough_id: string;
+    index: number;
+    content: string;
+}
+
+interface PresentWalkthroughEndPayload {
+    walkthrough_id: string;
+    chunk_count: number;
+    error?: string;
+}
+
+interface RevealLocationPayload {
+    location: {
+        path: string;
+        start: { line: number; column: number };
+        end: { line: number; column: number };
+    };
+}
+
+interface OpenDiffPayload {
+    path: string;
+    base_revision: string;
+    base_content: string;
+    head_revision: string;
+    head_content: string | null;
+}
+
+interface PrepareForDeletionPayload {
+    project_path: string;
+    taskspace_uuid: string;
+    uncommitted_file_count: number;
+}
+
+interface PrepareForDeletionResponse {
+    safe_to_delete: boolean;
+    warnings: string[];
+    uncommitted_file_count: number;
+}
+
+interface ApplyPatchPayload {
+    project_path: string;
+    taskspace_uuid: string;
+    patch: string;
+    description: string;
+    require_confirmation: boolean;
+}
+
+interface ApplyPatchResponse {
+    status: 'applied' | 'conflict' | 'declined';
+    conflicts: string[];
+    message?: string;
+}
+
+interface OpenEditorInfo {
+    path: string;
+    is_active: boolean;
+    is_dirty: boolean;
+    language: string | null;
+}
+
 interface TaskspaceRollCallPayload {
     taskspace_uuid: string;
 }
@@ -84,6 +163,23 @@ interface SymbolDef {
     definedAt: FileRange;
 }
 
+interface DocumentSymbolDef {
+    name: string;
+    kind?: string;
+    range: FileRange;
+    children: DocumentSymbolDef[];
+}
+
+interface FileEdits {
+    path: string;
+    edits: TextEdit[];
+}
+
+interface TextEdit {
+    range: { start: Position; end: Position };
+    new_text: string;
+}
+
 interface FileRange {
     path: string;
     start: Position;
@@ -95,6 +191,26 @@ interface Position {
     column: number;
 }
 
+/**
+ * Serves file contents at a git revision for `open_diff`, read-only under the
+ * `symposium-revision` scheme (like the walkthrough's `walkthrough-diff` scheme).
+ */
+class RevisionContentProvider implements vscode.TextDocumentContentProvider {
+    private contentMap = new Map<string, string>();
+    private changeEmitter = new vscode.EventEmitter<vscode.Uri>();
+    readonly onDidChange = this.changeEmitter.event;
+
+    setContent(uri: vscode.Uri, content: string): void {
+        this.contentMap.set(uri.toString(), content);
+        // A revision like HEAD may have moved since the document was last opened
+        this.changeEmitter.fire(uri);
+    }
+
+    provideTextDocumentContent(uri: vscode.Uri): string | undefined {
+        return this.contentMap.get(uri.toString());
+    }
+}
+
 export class DaemonClient implements vscode.Disposable {
     private clientProcess: any = null;
     private reconnectTimer: NodeJS.Timeout | null = null;
@@ -111,11 +227,20 @@ export class DaemonClient implements vscode.Disposable {
     // General request-response handling
     private pendingRequestResolvers: Map<string, (response: any) => void> = new Map();
 
+    // Chunked walkthroughs being received: walkthrough_id -> index of the next expected chunk
+    private chunkedWalkthroughs: Map<string, number> = new Map();
+
+    // File contents at the revisions shown by open_diff
+    private revisionContentProvider = new RevisionContentProvider();
+
     constructor(
         private context: vscode.ExtensionContext,
         private walkthroughProvider: WalkthroughWebviewProvider,
         private logger: StructuredLogger,
     ) {
+        this.context.subscriptions.push(
+            vscode.workspace.registerTextDocumentContentProvider('symposium-revision', this.revisionContentProvider)
+        );
     }
 
     start(): void {
@@ -260,6 +385,57 @@ export class DaemonClient implements vscode.Disposable {
                     error: error instanceof Error ? error.message : String(error)
                 });
             }
+        } else if (message.type === 'present_walkthrough_begin'
+            || message.type === 'present_walkthrough_chunk'
+            || message.type === 'present_walkthrough_end') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            try {
+                this.handleChunkedWalkthrough(message);
+                this.sendResponse(message.id, { success: true });
+            } catch (error) {
+                this.logger.error(`Error handling ${message.type}: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'reveal_location') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            try {
+                const { location } = message.payload as RevealLocationPayload;
+                await this.revealLocation(location);
+                this.sendResponse(message.id, { success: true });
+            } catch (error) {
+                this.logger.error(`Error handling reveal_location: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'open_diff') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            try {
+                await this.openDiff(message.payload as OpenDiffPayload);
+                this.sendResponse(message.id, { success: true });
+            } catch (error) {
+                this.logger.error(`Error handling open_diff: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
         } else if (message.type === 'get_selection') {
             if (!await this.isMessageForOurWindow(message.sender)) {
                 debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
@@ -279,6 +455,65 @@ export class DaemonClient implements vscode.Disposable {
                     error: error instanceof Error ? error.message : String(error)
                 });
             }
+        } else if (message.type === 'list_open_editors') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            try {
+                this.sendResponse(message.id, {
+                    success: true,
+                    data: this.listOpenEditors()
+                });
+            } catch (error) {
+                this.logger.error(`Error handling list_open_editors: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'prepare_for_deletion') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            try {
+                this.sendResponse(message.id, {
+                    success: true,
+                    data: this.prepareForDeletion(message.payload as PrepareForDeletionPayload)
+                });
+            } catch (error) {
+                this.logger.error(`Error handling prepare_for_deletion: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'apply_patch') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            try {
+                const patchPayload = message.payload as ApplyPatchPayload;
+                const response = await this.applyPatch(patchPayload, message.sender.workingDirectory);
+                this.sendResponse(message.id, {
+                    success: true,
+                    data: response
+                });
+            } catch (error) {
+                this.logger.error(`Error handling apply_patch: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'marco') {
+            // Answer discovery so MCP servers can list us among their peers (`list_peers`)
+            this.sendPolo();
         } else if (message.type === 'polo') {
             if (!await this.isMessageForOurWindow(message.sender)) {
                 debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
@@ -352,6 +587,85 @@ export class DaemonClient implements vscode.Disposable {
                     error: error instanceof Error ? error.message : String(error)
                 });
             }
+        } else if (message.type === 'rename_preview') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            // Handle rename preview requests from MCP server
+            try {
+                const renamePayload = message.payload as RenamePreviewPayload;
+
+                debugLog(`[LSP] Previewing rename of ${renamePayload.symbol.name} to ${renamePayload.new_name}`);
+
+                // Ask VSCode's LSP for the edits without applying them
+                const edits = await this.renamePreview(renamePayload.symbol, renamePayload.new_name);
+
+                this.sendResponse(message.id, {
+                    success: true,
+                    data: edits
+                });
+            } catch (error) {
+                debugLog(`Error handling rename_preview: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'document_symbols') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            // Handle file outline requests from MCP server
+            try {
+                const outlinePayload = message.payload as DocumentSymbolsPayload;
+
+                debugLog(`[LSP] Outlining document: ${outlinePayload.path}`);
+
+                // Call VSCode's LSP to get the document symbol tree
+                const symbols = await this.documentSymbols(outlinePayload.path);
+
+                this.sendResponse(message.id, {
+                    success: true,
+                    data: symbols
+                });
+            } catch (error) {
+                debugLog(`Error handling document_symbols: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
+        } else if (message.type === 'definition_at') {
+            if (!await this.isMessageForOurWindow(message.sender)) {
+                debugLog(`Ignoring ${message.type} request: not for our window`, { local: true });
+                return; // Silently ignore messages for other windows
+            }
+
+            // Handle go-to-definition requests from MCP server
+            try {
+                const definitionPayload = message.payload as DefinitionAtPayload;
+                const { path: filePath, start } = definitionPayload.location;
+
+                debugLog(`[LSP] Finding definition at ${filePath}:${start.line}:${start.column}`);
+
+                // Call VSCode's LSP to find the definition of the symbol at the position
+                const definitions = await this.definitionAt(definitionPayload.location);
+
+                this.sendResponse(message.id, {
+                    success: true,
+                    data: definitions
+                });
+            } catch (error) {
+                debugLog(`Error handling definition_at: ${error}`);
+                this.sendResponse(message.id, {
+                    success: false,
+                    error: error instanceof Error ? error.message : String(error)
+                });
+            }
         } else if (message.type === 'reload_window') {
             // Handle reload window signal from daemon (on shutdown)
             vscode.commands.executeCommand('workbench.action.reloadWindow');
@@ -441,6 +755,179 @@ export class DaemonClient implements vscode.Disposable {
     }
     // ANCHOR_END: is_message_for_our_window
 
+    /**
+     * Assemble a walkthrough sent in chunks, showing it as it arrives.
+     * The MCP server waits for each acknowledgement before sending the next
+     * message, so chunks arrive in order; the index check catches anything else.
+     */
+    private handleChunkedWalkthrough(message: IPCMessage): void {
+        if (message.type === 'present_walkthrough_begin') {
+            const { walkthrough_id, baseUri } = message.payload as PresentWalkthroughBeginPayload;
+            this.chunkedWalkthroughs.set(walkthrough_id, 0);
+            if (baseUri) {
+                this.walkthroughProvider.setBaseUri(baseUri);
+            }
+            this.walkthroughProvider.showWalkthroughHtml('');
+            vscode.commands.executeCommand('symposium.walkthrough.focus');
+        } else if (message.type === 'present_walkthrough_chunk') {
+            const { walkthrough_id, index, content } = message.payload as PresentWalkthroughChunkPayload;
+            const expected = this.chunkedWalkthroughs.get(walkthrough_id);
+            if (expected === undefined) {
+                throw new Error(`Unknown walkthrough ${walkthrough_id}`);
+            }
+            if (index !== expected) {
+                throw new Error(`Walkthrough chunk ${index} arrived, expected ${expected}`);
+            }
+            this.chunkedWalkthroughs.set(walkthrough_id, expected + 1);
+            this.walkthroughProvider.appendWalkthroughHtml(content);
+        } else {
+            const { walkthrough_id, chunk_count, error } = message.payload as PresentWalkthroughEndPayload;
+            const received = this.chunkedWalkthroughs.get(walkthrough_id);
+            this.chunkedWalkthroughs.delete(walkthrough_id);
+            if (error) {
+                this.logger.error(`Walkthrough ${walkthrough_id} ended early: ${error}`);
+            } else if (received !== chunk_count) {
+                throw new Error(`Walkthrough ${walkthrough_id} has ${received ?? 0} of ${chunk_count} chunks`);
+            }
+        }
+    }
+
+    private listOpenEditors(): OpenEditorInfo[] {
+        // A file open in several editor groups is listed once
+        const editors = new Map<string, OpenEditorInfo>();
+        for (const group of vscode.window.tabGroups.all) {
+            for (const tab of group.tabs) {
+                if (!(tab.input instanceof vscode.TabInputText)) {
+                    continue; // Diff views, webviews, notebooks, ...
+                }
+                const uri = tab.input.uri;
+                const document = vscode.workspace.textDocuments.find(doc => doc.uri.toString() === uri.toString());
+                const path = vscode.workspace.asRelativePath(uri, false);
+                const existing = editors.get(path);
+                editors.set(path, {
+                    path,
+                    is_active: (existing?.is_active ?? false) || (group.isActive && tab.isActive),
+                    is_dirty: (existing?.is_dirty ?? false) || tab.isDirty,
+                    language: document?.languageId ?? null,
+                });
+            }
+        }
+        return [...editors.values()];
+    }
+
+    /**
+     * Decide whether the taskspace can be deleted without asking the user.
+     * Unsaved editors would be lost along with the worktree, so they block
+     * deletion just like the uncommitted files the MCP server counted.
+     */
+    private prepareForDeletion(payload: PrepareForDeletionPayload): PrepareForDeletionResponse {
+        const unsaved = vscode.workspace.textDocuments
+            .filter(doc => doc.isDirty && !doc.isUntitled)
+            .map(doc => vscode.workspace.asRelativePath(doc.uri, false));
+
+        const warnings = unsaved.map(path => `Unsaved changes in editor: ${path}`);
+        return {
+            safe_to_delete: payload.uncommitted_file_count === 0 && unsaved.length === 0,
+            warnings,
+            uncommitted_file_count: payload.uncommitted_file_count,
+        };
+    }
+
+    /**
+     * Show a patch proposed by the agent and apply it with `git apply` once
+     * the user confirms. Confirmation is always required, whatever the payload says.
+     */
+    private async applyPatch(payload: ApplyPatchPayload, workingDirectory: string): Promise<ApplyPatchResponse> {
+        // Let the user read the patch before deciding
+        const patchDoc = await vscode.workspace.openTextDocument({ content: payload.patch, language: 'diff' });
+        await vscode.window.showTextDocument(patchDoc, { preview: true });
+
+        const choice = await vscode.window.showWarningMessage(
+            `Apply the agent's patch? ${payload.description}`,
+            { modal: true, detail: `The patch will be applied to ${workingDirectory} with \`git apply\`.` },
+            'Apply'
+        );
+        if (choice !== 'Apply') {
+            return { status: 'declined', conflicts: [], message: 'User declined to apply the patch' };
+        }
+
+        const { code, stderr } = await this.runGitApply(payload.patch, workingDirectory);
+        if (code !== 0) {
+            // The working tree may have changed since the MCP server checked the patch
+            return {
+                status: 'conflict',
+                conflicts: stderr.split('\n').filter(line => line.trim()),
+                message: 'Patch no longer applies to the working tree; nothing was changed',
+            };
+        }
+        return { status: 'applied', conflicts: [], message: 'Patch applied' };
+    }
+
+    /**
+     * Run `git apply` with the patch on stdin. `git apply` checks every hunk
+     * before writing anything, so a failure leaves the working tree untouched.
+     */
+    private runGitApply(patch: string, cwd: string): Promise<{ code: number | null; stderr: string }> {
+        const { spawn } = require('child_process');
+        return new Promise((resolve, reject) => {
+            const git = spawn('git', ['apply', '-'], { cwd });
+            let stderr = '';
+            git.stderr.on('data', (data: Buffer) => {
+                stderr += data.toString();
+            });
+            git.on('error', reject);
+            git.on('close', (code: number | null) => resolve({ code, stderr }));
+            git.stdin.end(patch);
+        });
+    }
+
+    /**
+     * Open a workspace-relative file and select the given 1-based range
+     */
+    private async revealLocation(location: RevealLocationPayload['location']): Promise<void> {
+        const workspaceFolder = vscode.workspace.workspaceFolders?.[0];
+        if (!workspaceFolder) {
+            throw new Error('No workspace folder found');
+        }
+
+        const uri = vscode.Uri.joinPath(workspaceFolder.uri, location.path);
+        const range = new vscode.Range(
+            location.start.line - 1, Math.max(location.start.column - 1, 0),
+            location.end.line - 1, Math.max(location.end.column - 1, 0)
+        );
+        const editor = await vscode.window.showTextDocument(uri, { selection: range });
+        editor.revealRange(range, vscode.TextEditorRevealType.InCenterIfOutsideViewport);
+    }
+
+    private async openDiff(payload: OpenDiffPayload): Promise<void> {
+        const workspaceFolder = vscode.workspace.workspaceFolders?.[0];
+        if (!workspaceFolder) {
+            throw new Error('No workspace folder found');
+        }
+
+        const fileUri = vscode.Uri.joinPath(workspaceFolder.uri, payload.path);
+
+        // Revisions are served read-only by the content provider; the URI keeps the file's
+        // extension so the language is detected. The working tree is the file itself.
+        const revisionUri = (revision: string, content: string) => {
+            const uri = vscode.Uri.from({ scheme: 'symposium-revision', path: `/${payload.path}`, query: revision });
+            this.revisionContentProvider.setContent(uri, content);
+            return uri;
+        };
+        const baseUri = revisionUri(payload.base_revision, payload.base_content);
+        const headUri = payload.head_content === null
+            ? fileUri
+            : revisionUri(payload.head_revision, payload.head_content);
+
+        const fileName = payload.path.split('/').pop() ?? payload.path;
+        await vscode.commands.executeCommand(
+            'vscode.diff',
+            baseUri,
+            headUri,
+            `${fileName} (${payload.base_revision} ↔ ${payload.head_revision})`
+        );
+    }
+
     private getCurrentSelection(): any {
         const activeEditor = vscode.window.activeTextEditor;
 
@@ -822,6 +1309,115 @@ export class DaemonClient implements vscode.Disposable {
         }
     }
 
+    /**
+     * Compute the edits a rename would make. The WorkspaceEdit is only read,
+     * never applied, so no file changes.
+     */
+    private async renamePreview(symbol: SymbolDef, newName: string): Promise<FileEdits[]> {
+        try {
+            const workspaceFolder = vscode.workspace.workspaceFolders?.[0];
+            if (!workspaceFolder) {
+                throw new Error('No workspace folder found');
+            }
+
+            const workspaceEdit = await vscode.commands.executeCommand<vscode.WorkspaceEdit | undefined>(
+                'vscode.executeDocumentRenameProvider',
+                vscode.Uri.file(path.isAbsolute(symbol.definedAt.path)
+                    ? symbol.definedAt.path
+                    : path.resolve(workspaceFolder.uri.fsPath, symbol.definedAt.path)),
+                new vscode.Position(symbol.definedAt.start.line - 1, symbol.definedAt.start.column - 1),
+                newName
+            );
+            if (!workspaceEdit) {
+                throw new Error(`No rename provider could rename '${symbol.name}'`);
+            }
+
+            return workspaceEdit.entries().map(([uri, edits]) => ({
+                path: uri.fsPath,
+                edits: edits.map(edit => ({
+                    range: {
+                        start: { line: edit.range.start.line + 1, column: edit.range.start.character + 1 },
+                        end: { line: edit.range.end.line + 1, column: edit.range.end.character + 1 },
+                    },
+                    new_text: edit.newText,
+                })),
+            }));
+        } catch (error) {
+            debugLog(`Error in renamePreview: ${error}`);
+            throw error;
+        }
+    }
+
+    /**
+     * Outline a file as a tree of symbols using VSCode's LSP
+     */
+    private async documentSymbols(filePath: string): Promise<DocumentSymbolDef[]> {
+        try {
+            const workspaceFolder = vscode.workspace.workspaceFolders?.[0];
+            if (!workspaceFolder) {
+                throw new Error('No workspace folder found');
+            }
+
+            const uri = vscode.Uri.file(path.isAbsolute(filePath)
+                ? filePath
+                : path.resolve(workspaceFolder.uri.fsPath, filePath));
+            const symbols = await vscode.commands.executeCommand<vscode.DocumentSymbol[]>(
+                'vscode.executeDocumentSymbolProvider',
+                uri
+            );
+
+            const convert = (symbol: vscode.DocumentSymbol): DocumentSymbolDef => ({
+                name: symbol.name,
+                kind: vscode.SymbolKind[symbol.kind],
+                range: this.vscodeLocationToRange(new vscode.Location(uri, symbol.range)),
+                children: (symbol.children || []).map(convert),
+            });
+
+            return (symbols || []).map(convert);
+        } catch (error) {
+            debugLog(`Error in documentSymbols: ${error}`);
+            throw error;
+        }
+    }
+
+    /**
+     * Find the definition of the symbol at the start of a range using VSCode's LSP
+     */
+    private async definitionAt(location: FileRange): Promise<SymbolDef[]> {
+        try {
+            const workspaceFolder = vscode.workspace.workspaceFolders?.[0];
+            if (!workspaceFolder) {
+                throw new Error('No workspace folder found');
+            }
+
+            const uri = vscode.Uri.file(path.isAbsolute(location.path)
+                ? location.path
+                : path.resolve(workspaceFolder.uri.fsPath, location.path));
+            const targets = await vscode.commands.executeCommand<(vscode.Location | vscode.LocationLink)[]>(
+                'vscode.executeDefinitionProvider',
+                uri,
+                new vscode.Position(location.start.line - 1, location.start.column - 1)
+            );
+
+            const definitions: SymbolDef[] = [];
+            for (const target of targets || []) {
+                // Providers answer with either plain locations or links; links know the name's own range
+                const targetLocation = 'targetUri' in target
+                    ? new vscode.Location(target.targetUri, target.targetSelectionRange ?? target.targetRange)
+                    : target;
+                const document = await vscode.workspace.openTextDocument(targetLocation.uri);
+                definitions.push({
+                    name: document.getText(targetLocation.range),
+                    definedAt: this.vscodeLocationToRange(targetLocation),
+                });
+            }
+            return definitions;
+        } catch (error) {
+            debugLog(`Error in definitionAt: ${error}`);
+            throw error;
+        }
+    }
+
     /**
      * Send an IPC request; does not expect any response, returns the message id
      */
@@ -941,4 +1537,30 @@ export class DaemonClient implements vscode.Disposable {
         }
     }
 
+    private sendPolo(): void {
+        if (!this.clientProcess || this.clientProcess.stdin?.destroyed) {
+            debugLog(`Cannot send POLO - client not connected`);
+            return;
+        }
+
+        // Our shellPid is the extension host, never a terminal, so other windows'
+        // MCP server discovery ignores this reply
+        const poloMessage: IPCMessage = {
+            type: 'polo',
+            id: crypto.randomUUID(),
+            sender: {
+                workingDirectory: vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? process.cwd(),
+                taskspaceUuid: undefined,
+                shellPid: process.pid
+            },
+            payload: { role: 'ide' }
+        };
+
+        try {
+            this.clientProcess.stdin.write(JSON.stringify(poloMessage) + '\n');
+        } catch (error) {
+            debugLog(`Error sending POLO: ${error}`);
+        }
+    }
+
 } 
\ No newline at end of file
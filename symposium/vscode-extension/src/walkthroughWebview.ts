@@ -16,9 +16,13 @@ interface PlacementState {
 interface FileChange {
     path: string;
     status: string;
+    old_path?: string;
+    similarity?: number;
     additions: number;
     deletions: number;
     hunks: DiffHunk[];
+    binary?: boolean;
+    size_delta?: number;
 }
 
 interface DiffHunk {
@@ -196,6 +200,14 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                 // Send message to active AI terminal using Bus method
                 await this.bus.sendTextToActiveTerminal(message.message);
                 break;
+            case 'openActionTarget':
+                console.log('Walkthrough: openActionTarget received:', message.target);
+                await this.openActionTarget(message.target);
+                break;
+            case 'applyDiff':
+                console.log('Walkthrough: applyDiff received');
+                await this.applyDiff(message.patch, message.workspaceRoot);
+                break;
             case 'showDiff':
                 console.log('Walkthrough: showDiff command received:', message.filePath);
                 await this.showFileDiff(message.filePath);
@@ -596,6 +608,12 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
 
         console.log(`[WALKTHROUGH DIFF] Found file change: ${fileChange.status}, ${fileChange.additions}+/${fileChange.deletions}-, ${fileChange.hunks.length} hunks`);
 
+        if (fileChange.binary) {
+            // Binary files have no hunks to reverse-apply, so there is nothing to diff
+            vscode.window.showInformationMessage(`Binary file changed: ${filePath}`);
+            return;
+        }
+
         try {
             // Get workspace folder
             const workspaceFolder = vscode.workspace.workspaceFolders?.[0];
@@ -628,10 +646,11 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
 
             // Show diff using VSCode's native diff viewer with automatic highlighting
             console.log('[WALKTHROUGH DIFF] Calling vscode.diff command...');
+            const title = fileChange.old_path ? `${fileChange.old_path} → ${filePath}` : filePath;
             await vscode.commands.executeCommand('vscode.diff',
                 originalUri,
                 modifiedUri,
-                `${filePath} (Walkthrough Diff)`
+                `${title} (Walkthrough Diff)`
             );
             console.log('[WALKTHROUGH DIFF] vscode.diff command completed successfully');
 
@@ -771,6 +790,25 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
         }
     }
 
+    /**
+     * Append a chunk of a walkthrough that is still arriving. Only the new
+     * chunk is posted; the webview adds it after the content it already shows.
+     */
+    public appendWalkthroughHtml(htmlChunk: string) {
+        this.bus.log(`[WALKTHROUGH] appendWalkthroughHtml called with ${htmlChunk.length} chars`);
+
+        // Keep the full content so it can be restored if the webview is recreated
+        this.offscreenHtmlContent = (this.offscreenHtmlContent ?? '') + htmlChunk;
+
+        // If the webview is not ready, the ready handler sends the full content
+        if (this._view && this.webviewReady) {
+            this._view.webview.postMessage({
+                type: 'appendWalkthroughHtml',
+                content: htmlChunk
+            });
+        }
+    }
+
     public showWalkthrough(walkthrough: WalkthroughData) {
         console.log('WalkthroughWebviewProvider.showWalkthrough called with:', walkthrough);
 
@@ -825,6 +863,51 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
         await this.sendCommentToShell(reply.text, reply.thread);
     }
 
+    /**
+     * Ask the MCP server that rendered an `applydiff` block to apply its patch.
+     * Conflicting patches are rejected by the server without touching any files.
+     */
+    private async applyDiff(patch: string, workspaceRoot: string): Promise<void> {
+        this.bus.log(`[WALKTHROUGH] Applying diff in ${workspaceRoot}`);
+        const response = await this.bus.daemonClient.sendRequest<{ status: string; message?: string }>(
+            'apply_diff',
+            { workspace_root: workspaceRoot, patch },
+        );
+
+        if (!response) {
+            vscode.window.showErrorMessage('Failed to apply diff: no response from the MCP server');
+        } else if (response.status === 'applied') {
+            vscode.window.showInformationMessage(response.message || 'Diff applied');
+        } else {
+            vscode.window.showErrorMessage(`Diff not applied: ${response.message || response.status}`);
+        }
+    }
+
+    /**
+     * Open the target of an `action` block with an `open:` param: URLs go to the
+     * system browser, anything else is treated as a file relative to the walkthrough base.
+     */
+    private async openActionTarget(target: string): Promise<void> {
+        this.bus.log(`[WALKTHROUGH] Opening action target: ${target}`);
+        if (/^[a-z][a-z0-9+.-]*:\/\//i.test(target)) {
+            await vscode.env.openExternal(vscode.Uri.parse(target));
+            return;
+        }
+
+        const baseDir = this.baseUri?.fsPath ?? vscode.workspace.workspaceFolders?.[0]?.uri.fsPath;
+        if (!baseDir) {
+            vscode.window.showErrorMessage(`Cannot open ${target}: no workspace folder`);
+            return;
+        }
+
+        try {
+            const uri = vscode.Uri.file(path.resolve(baseDir, target));
+            await vscode.window.showTextDocument(uri, { preview: false });
+        } catch (error) {
+            vscode.window.showErrorMessage(`Failed to open ${target}: ${error}`);
+        }
+    }
+
     /**
      * Send comment reply to active AI shell with context
      */
@@ -1008,6 +1091,16 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                         margin-bottom: 8px;
                         padding: 4px 0;
                     }
+                    .walkthrough-section {
+                        margin: 8px 0;
+                        padding-left: 12px;
+                        border-left: 2px solid var(--vscode-panel-border);
+                    }
+                    .walkthrough-section > summary {
+                        cursor: pointer;
+                        font-weight: 600;
+                        margin-left: -12px;
+                    }
                     .action-button {
                         background-color: var(--vscode-button-background);
                         color: var(--vscode-button-foreground);
@@ -1076,6 +1169,10 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                     .clickable-file:hover {
                         color: var(--vscode-textLink-activeForeground);
                     }
+                    .file-origin {
+                        color: var(--vscode-descriptionForeground);
+                        font-size: 0.85em;
+                    }
                     .file-stats {
                         margin: 0 12px;
                         color: var(--vscode-descriptionForeground);
@@ -1299,7 +1396,11 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                                 // Create mermaid div
                                 const mermaidDiv = document.createElement('div');
                                 mermaidDiv.className = 'mermaid';
-                                mermaidDiv.textContent = cleanContent;
+                                // Honor a per-diagram theme via a mermaid init directive
+                                const theme = element.getAttribute('data-theme');
+                                mermaidDiv.textContent = theme
+                                    ? "%%{init: {'theme': '" + theme + "'}}%%\\n" + cleanContent
+                                    : cleanContent;
                                 
                                 container.appendChild(mermaidDiv);
                                 
@@ -1515,7 +1616,16 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                                     html += '<div class="file-diff">';
                                     html += '<div class="file-header">';
                                     html += '<span class="file-path clickable-file" data-file-path="' + fileChange.path + '">' + fileChange.path + '</span>';
-                                    html += '<span class="file-stats">+' + fileChange.additions + ' -' + fileChange.deletions + '</span>';
+                                    if (fileChange.old_path) {
+                                        const verb = fileChange.status === 'Copied' ? 'copied' : 'renamed';
+                                        html += '<span class="file-origin"> (' + verb + ' from ' + fileChange.old_path + ')</span>';
+                                    }
+                                    if (fileChange.binary) {
+                                        const delta = fileChange.size_delta || 0;
+                                        html += '<span class="file-stats">binary ' + (delta >= 0 ? '+' : '') + delta + ' bytes</span>';
+                                    } else {
+                                        html += '<span class="file-stats">+' + fileChange.additions + ' -' + fileChange.deletions + '</span>';
+                                    }
                                     html += '</div>';
                                     html += '</div>';
                                 });
@@ -1565,10 +1675,25 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                             vscode.postMessage({
                                 type: 'clearWalkthrough'
                             });
+                        } else if (event.target.tagName === 'BUTTON' &&
+                            event.target.classList.contains('action-button') &&
+                            event.target.dataset.open) {
+                            vscode.postMessage({
+                                type: 'openActionTarget',
+                                target: event.target.dataset.open
+                            });
                         } else if (event.target.tagName === 'BUTTON' && 
                             event.target.classList.contains('action-button') && 
                             event.target.dataset.tellAgent) {
                             handleAction(event.target.dataset.tellAgent);
+                        } else if (event.target.tagName === 'BUTTON' &&
+                            event.target.classList.contains('applydiff-button') &&
+                            event.target.dataset.patch) {
+                            vscode.postMessage({
+                                type: 'applyDiff',
+                                patch: event.target.dataset.patch,
+                                workspaceRoot: event.target.dataset.workspaceRoot
+                            });
                         } else if (event.target.classList.contains('clickable-file') && 
                                    event.target.dataset.filePath) {
                             vscode.postMessage({
@@ -1653,6 +1778,20 @@ export class WalkthroughWebviewProvider implements vscode.WebviewViewProvider {
                             } else {
                                 console.error('[ERROR] Content element not found!');
                             }
+                        } else if (message.type === 'appendWalkthroughHtml') {
+                            console.log('[HTML] Appending walkthrough HTML chunk, length:', message.content.length);
+                            
+                            const contentElement = document.getElementById('content');
+                            if (contentElement) {
+                                // Chunks end between top-level blocks, so each one parses on its own
+                                contentElement.insertAdjacentHTML('beforeend', message.content);
+                                
+                                // Both skip the links and diagrams handled for earlier chunks
+                                addPlacementIcons();
+                                processMermaidDiagrams();
+                            } else {
+                                console.error('[ERROR] Content element not found!');
+                            }
                         } else {
                             console.log('[IGNORE] Ignoring message type:', message.type);
                         }